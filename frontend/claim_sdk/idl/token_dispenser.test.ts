@@ -12,17 +12,30 @@ test('Anchor sanity check', (done) => {
   buffer = coder.types.encode('ClaimCertificate', {
     amount: new anchor.BN(1),
     proofOfIdentity: { discord: { username: '1234' } },
-    proofOfInclusion: [Buffer.alloc(32, 1), Buffer.alloc(32, 2)],
+    proofOfInclusion: {
+      keccak160: { '0': [Buffer.alloc(20, 1), Buffer.alloc(20, 2)] },
+    },
+    expiresAt: null,
+    nonce: new anchor.BN(2),
   })
 
   expect(buffer).toStrictEqual(
     Buffer.concat([
       Buffer.from([
-        1, 0, 0, 0, 0, 0, 0, 0, 0, 4, 0, 0, 0, 49, 50, 51, 52, 0, 2, 0, 0, 0,
+        1, 0, 0, 0, 0, 0, 0, 0, 0, 4, 0, 0, 0, 49, 50, 51, 52, 0, 0, 2, 0, 0,
+        0,
       ]),
-      Buffer.alloc(32, 1),
-      Buffer.alloc(32, 2),
+      Buffer.alloc(20, 1),
+      Buffer.alloc(20, 2),
+      Buffer.from([0, 1, 2, 0, 0, 0, 0, 0, 0, 0]),
     ])
   )
+
+  buffer = coder.types.encode('DispenserMerkleRoot', {
+    keccak256: { '0': Buffer.alloc(32, 3) },
+  })
+  expect(buffer).toStrictEqual(
+    Buffer.concat([Buffer.from([1]), Buffer.alloc(32, 3)])
+  )
   done()
 })
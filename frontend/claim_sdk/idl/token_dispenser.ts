@@ -6,7 +6,17 @@ export type TokenDispenser =
     {
       "name": "initialize",
       "docs": [
-        "This can only be called once and should be called right after the program is deployed."
+        "This can only be called once per campaign. Each campaign has its own tree, mint, treasury and",
+        "guard and claims from different campaigns are independent.",
+        "The variant of `merkle_root` is the hasher of the tree of the campaign, see `DispenserMerkleRoot`.",
+        "If `escrow` is set, the treasury is the associated token account of the config, created here and",
+        "funded with `deposit`. Otherwise it is an external token account that delegates to the config.",
+        "If `global_cap` is set, claims fail once the total claimed would exceed it.",
+        "If `max_per_claimant` is set, claims fail once the total claimed by a claimant across all their",
+        "identities would exceed it.",
+        "The payer becomes the admin of the campaign, who can adjust its rate limit and guard signature policy",
+        "and manage its denylist. Campaigns other than the default one can only be initialized by the upgrade",
+        "authority of the program, so that their ids can't be squatted."
       ],
       "accounts": [
         {
@@ -22,19 +32,17 @@ export type TokenDispenser =
         {
           "name": "mint",
           "isMut": false,
-          "isSigner": false,
-          "docs": [
-            "Mint of the treasury"
-          ]
+          "isSigner": false
         },
         {
           "name": "treasury",
-          "isMut": false,
+          "isMut": true,
           "isSigner": false,
           "docs": [
-            "Treasury token account. This is an externally owned token account and",
             "the owner of this account will approve the config as a delegate using the",
-            "solana CLI command `spl-token approve <treasury_account_address> <approve_amount> <config_address>`"
+            "solana CLI command `spl-token approve <treasury_account_address> <approve_amount> <config_address>`",
+            "In escrow mode, it is instead the associated token account of the config and gets created here.",
+            "It is unpacked and checked against the mint in the instruction."
           ]
         },
         {
@@ -46,16 +54,32 @@ export type TokenDispenser =
           "name": "addressLookupTable",
           "isMut": false,
           "isSigner": false
+        },
+        {
+          "name": "tokenProgram",
+          "isMut": false,
+          "isSigner": false
+        },
+        {
+          "name": "associatedTokenProgram",
+          "isMut": false,
+          "isSigner": false
+        },
+        {
+          "name": "programData",
+          "isMut": false,
+          "isSigner": false
         }
       ],
       "args": [
+        {
+          "name": "campaignId",
+          "type": "u64"
+        },
         {
           "name": "merkleRoot",
           "type": {
-            "array": [
-              "u8",
-              20
-            ]
+            "defined": "DispenserMerkleRoot"
           }
         },
         {
@@ -69,499 +93,1240 @@ export type TokenDispenser =
         {
           "name": "maxTransfer",
           "type": "u64"
+        },
+        {
+          "name": "escrow",
+          "type": "bool"
+        },
+        {
+          "name": "globalCap",
+          "type": {
+            "option": "u64"
+          }
+        },
+        {
+          "name": "maxPerClaimant",
+          "type": {
+            "option": "u64"
+          }
         }
       ]
     },
     {
-      "name": "claim",
+      "name": "migrateConfig",
       "docs": [
-        "* Claim a claimant's tokens. This instructions needs to enforce :\n     * - The dispenser guard has signed the transaction - DONE\n     * - The claimant is claiming no more than once per ecosystem - DONE\n     * - The claimant has provided a valid proof of identity (is the owner of the wallet\n     *   entitled to the tokens)\n     * - The claimant has provided a valid proof of inclusion (this confirm that the claimant --\n     *   DONE\n     * - The claimant has not already claimed tokens -- DONE"
+        "* Upgrades the config of a campaign created before configs were versioned, whose layout is `LegacyConfig`.\n     * The account is reallocated to `Config::LEN` and the new fields get the values of a config initialized without\n     * escrow, claimant cap, rate limit, denylist or guard signature policy. The upgrade authority of the program signs\n     * the migration, pays the rent of the new space and becomes the admin of the campaign.\n     *\n     * The legacy program kept no statistics, so the claims made before the migration are seeded from\n     * `claimed_per_identity` and `claim_count`, e.g. the totals of the `CampaignClaimIndex` of the campaign.\n     * `global_cap` then counts these claims too. Each of these claims created a legacy receipt, so `claim_count` is\n     * also the number of legacy receipts that must be migrated before the tree can be amended."
       ],
       "accounts": [
         {
-          "name": "funder",
+          "name": "authority",
           "isMut": true,
           "isSigner": true
         },
         {
-          "name": "claimant",
+          "name": "config",
+          "isMut": true,
+          "isSigner": false
+        },
+        {
+          "name": "programData",
+          "isMut": false,
+          "isSigner": false
+        },
+        {
+          "name": "systemProgram",
+          "isMut": false,
+          "isSigner": false
+        }
+      ],
+      "args": [
+        {
+          "name": "campaignId",
+          "type": "u64"
+        },
+        {
+          "name": "claimedPerIdentity",
+          "type": {
+            "array": [
+              "u64",
+              10
+            ]
+          }
+        },
+        {
+          "name": "claimCount",
+          "type": "u64"
+        },
+        {
+          "name": "globalCap",
+          "type": {
+            "option": "u64"
+          }
+        }
+      ]
+    },
+    {
+      "name": "setRateLimit",
+      "docs": [
+        "* Sets the maximum amount that claims can transfer out of the treasury per window of `window_seconds`,\n     * or removes the limit if `max_amount` is None. The amounts claimed in the current and previous windows are kept\n     * as long as `window_seconds` doesn't change."
+      ],
+      "accounts": [
+        {
+          "name": "admin",
           "isMut": false,
           "isSigner": true
         },
         {
-          "name": "claimantFund",
+          "name": "config",
           "isMut": true,
-          "isSigner": false,
-          "docs": [
-            "Claimant's associated token account to receive the tokens",
-            "Should be initialized outside of this program."
-          ]
+          "isSigner": false
+        }
+      ],
+      "args": [
+        {
+          "name": "campaignId",
+          "type": "u64"
+        },
+        {
+          "name": "maxAmount",
+          "type": {
+            "option": "u64"
+          }
+        },
+        {
+          "name": "windowSeconds",
+          "type": "i64"
+        }
+      ]
+    },
+    {
+      "name": "setGuardSignaturePolicy",
+      "docs": [
+        "* Sets the ecosystems whose claims must be signed by the dispenser guard, as a bitmask indexed by\n     * `Identity::get_variant_index`. This lets the guard gate claims with off-chain checks for any ecosystem."
+      ],
+      "accounts": [
+        {
+          "name": "admin",
+          "isMut": false,
+          "isSigner": true
         },
         {
           "name": "config",
+          "isMut": true,
+          "isSigner": false
+        }
+      ],
+      "args": [
+        {
+          "name": "campaignId",
+          "type": "u64"
+        },
+        {
+          "name": "guardPolicy",
+          "type": "u16"
+        }
+      ]
+    },
+    {
+      "name": "updateMerkleRoot",
+      "docs": [
+        "* Replaces the tree of the campaign, e.g. to top up allocations. Leaves hold cumulative amounts and receipts are\n     * keyed by identity, so identities that already claimed are only paid the difference.\n     * Legacy receipts of the default campaign, see `migrate_receipt`, only match the leaves they were created for,\n     * so the tree can't be replaced until they have all been migrated."
+      ],
+      "accounts": [
+        {
+          "name": "admin",
           "isMut": false,
+          "isSigner": true
+        },
+        {
+          "name": "config",
+          "isMut": true,
           "isSigner": false
+        }
+      ],
+      "args": [
+        {
+          "name": "campaignId",
+          "type": "u64"
         },
         {
-          "name": "mint",
+          "name": "merkleRoot",
+          "type": {
+            "defined": "DispenserMerkleRoot"
+          }
+        }
+      ]
+    },
+    {
+      "name": "migrateReceipt",
+      "docs": [
+        "* Copies a legacy receipt of the default campaign into the receipt of its identity. Before receipts were keyed by\n     * identity, a claim created an empty receipt keyed by the hash of the leaf, see `get_legacy_receipt_pda`.\n     * `claim_info` is the leaf of the legacy receipt. Anyone can migrate a receipt, since it only records a claim that\n     * happened. Until the tree is amended, the receipt of an identity with a legacy receipt can only be created here,\n     * since its claims fail with `AlreadyClaimed`, so creating it counts the legacy receipt as migrated."
+      ],
+      "accounts": [
+        {
+          "name": "payer",
+          "isMut": true,
+          "isSigner": true
+        },
+        {
+          "name": "legacyReceipt",
           "isMut": false,
           "isSigner": false
         },
         {
-          "name": "treasury",
+          "name": "receipt",
           "isMut": true,
           "isSigner": false
         },
         {
-          "name": "tokenProgram",
-          "isMut": false,
+          "name": "config",
+          "isMut": true,
           "isSigner": false
         },
         {
           "name": "systemProgram",
           "isMut": false,
           "isSigner": false
+        }
+      ],
+      "args": [
+        {
+          "name": "claimInfo",
+          "type": {
+            "defined": "ClaimInfo"
+          }
+        }
+      ]
+    },
+    {
+      "name": "block",
+      "docs": [
+        "* Adds an identity or a claimant to the denylist of the campaign, their claims fail with `IdentityBlocked`\n     * until they are removed with `unblock`. Identities are keyed like their receipts, so they stay blocked\n     * when the tree is amended."
+      ],
+      "accounts": [
+        {
+          "name": "admin",
+          "isMut": true,
+          "isSigner": true
+        },
+        {
+          "name": "config",
+          "isMut": true,
+          "isSigner": false
         },
         {
-          "name": "sysvarInstruction",
-          "isMut": false,
-          "isSigner": false,
-          "docs": [
-            "CHECK : Anchor wants me to write this comment because I'm using AccountInfo which doesn't check for ownership and doesn't deserialize the account automatically. But it's fine because I check the address and I load it using load_instruction_at_checked."
-          ]
+          "name": "denylistEntry",
+          "isMut": true,
+          "isSigner": false
         },
         {
-          "name": "associatedTokenProgram",
+          "name": "systemProgram",
           "isMut": false,
           "isSigner": false
         }
       ],
       "args": [
         {
-          "name": "claimCertificate",
+          "name": "campaignId",
+          "type": "u64"
+        },
+        {
+          "name": "denylistKey",
           "type": {
-            "defined": "ClaimCertificate"
+            "defined": "DenylistKey"
           }
         }
       ]
-    }
-  ],
-  "accounts": [
-    {
-      "name": "Config",
-      "type": {
-        "kind": "struct",
-        "fields": [
-          {
-            "name": "bump",
-            "type": "u8"
-          },
-          {
-            "name": "merkleRoot",
-            "type": {
-              "array": [
-                "u8",
-                20
-              ]
-            }
-          },
-          {
-            "name": "dispenserGuard",
-            "type": "publicKey"
-          },
-          {
-            "name": "mint",
-            "type": "publicKey"
-          },
-          {
-            "name": "treasury",
-            "type": "publicKey"
-          },
-          {
-            "name": "addressLookupTable",
-            "type": "publicKey"
-          },
-          {
-            "name": "funder",
-            "type": "publicKey"
-          },
-          {
-            "name": "maxTransfer",
-            "type": "u64"
-          }
-        ]
-      }
     },
     {
-      "name": "Receipt",
-      "type": {
-        "kind": "struct",
-        "fields": []
-      }
-    }
-  ],
-  "types": [
-    {
-      "name": "CosmosMessage",
+      "name": "unblock",
       "docs": [
-        "* An ADR036 message used in Cosmos. ADR036 is a standard for signing arbitrary data.\n* Only the message payload is stored in this struct.\n* The message signed for Cosmos is a JSON serialized CosmosStdSignDoc containing the payload and ADR036 compliant parameters.\n* The message also contains the bech32 address of the signer. We check that the signer corresponds to the public key."
+        "* Removes an identity or a claimant from the denylist of the campaign, the rent of the entry goes back to the admin."
       ],
-      "type": {
-        "kind": "struct",
-        "fields": [
-          {
-            "name": "payload",
-            "type": "bytes"
-          },
-          {
-            "name": "signer",
-            "type": "string"
+      "accounts": [
+        {
+          "name": "admin",
+          "isMut": true,
+          "isSigner": true
+        },
+        {
+          "name": "config",
+          "isMut": true,
+          "isSigner": false
+        },
+        {
+          "name": "denylistEntry",
+          "isMut": true,
+          "isSigner": false
+        }
+      ],
+      "args": [
+        {
+          "name": "campaignId",
+          "type": "u64"
+        },
+        {
+          "name": "denylistKey",
+          "type": {
+            "defined": "DenylistKey"
           }
-        ]
-      }
+        }
+      ]
     },
     {
-      "name": "DiscordMessage",
+      "name": "deposit",
       "docs": [
-        "* This message (borsh-serialized) needs to be signed by the dispenser guard after\n * verifying the claimant's pubkey controls the discord account.\n * The dispenser guard key should not be used for anything else."
+        "* Deposit tokens into the escrow treasury of a campaign. Anyone can deposit, the amount received by\n     * the treasury is added to `total_deposited`."
+      ],
+      "accounts": [
+        {
+          "name": "depositor",
+          "isMut": false,
+          "isSigner": true
+        },
+        {
+          "name": "depositorFund",
+          "isMut": true,
+          "isSigner": false
+        },
+        {
+          "name": "config",
+          "isMut": true,
+          "isSigner": false
+        },
+        {
+          "name": "mint",
+          "isMut": false,
+          "isSigner": false
+        },
+        {
+          "name": "treasury",
+          "isMut": true,
+          "isSigner": false
+        },
+        {
+          "name": "tokenProgram",
+          "isMut": false,
+          "isSigner": false
+        }
+      ],
+      "args": [
+        {
+          "name": "campaignId",
+          "type": "u64"
+        },
+        {
+          "name": "amount",
+          "type": "u64"
+        }
+      ]
+    },
+    {
+      "name": "claim",
+      "docs": [
+        "* Claim a claimant's tokens. This instructions needs to enforce :\n     * - The dispenser guard has signed the transaction, if the guard signature policy of the campaign\n     *   requires it for the ecosystem of the identity - DONE\n     * - The claimant is claiming no more than once per ecosystem - DONE\n     * - The claimant has provided a valid proof of identity (is the owner of the wallet\n     *   entitled to the tokens)\n     * - The claimant has provided a valid proof of inclusion (this confirm that the claimant --\n     *   DONE\n     * - The claimant has not already claimed tokens -- DONE\n     * `bumps` are the canonical bumps of the PDAs in `remaining_accounts`, so that the program doesn't search for them.\n     * Without them the program searches for the bumps, which costs more compute units."
+      ],
+      "accounts": [
+        {
+          "name": "funder",
+          "isMut": true,
+          "isSigner": true
+        },
+        {
+          "name": "claimant",
+          "isMut": false,
+          "isSigner": true
+        },
+        {
+          "name": "claimantFund",
+          "isMut": true,
+          "isSigner": false,
+          "docs": [
+            "if needed and checked in `checked_create_associated_token_account`."
+          ]
+        },
+        {
+          "name": "config",
+          "isMut": true,
+          "isSigner": false
+        },
+        {
+          "name": "mint",
+          "isMut": false,
+          "isSigner": false
+        },
+        {
+          "name": "treasury",
+          "isMut": true,
+          "isSigner": false
+        },
+        {
+          "name": "tokenProgram",
+          "isMut": false,
+          "isSigner": false
+        },
+        {
+          "name": "systemProgram",
+          "isMut": false,
+          "isSigner": false
+        },
+        {
+          "name": "sysvarInstruction",
+          "isMut": false,
+          "isSigner": false,
+          "docs": [
+            "CHECK : Anchor wants me to write this comment because I'm using AccountInfo which doesn't check for ownership and doesn't deserialize the account automatically. But it's fine because I check the address and I load it using load_instruction_at_checked."
+          ]
+        },
+        {
+          "name": "associatedTokenProgram",
+          "isMut": false,
+          "isSigner": false
+        }
       ],
+      "args": [
+        {
+          "name": "campaignId",
+          "type": "u64"
+        },
+        {
+          "name": "claimCertificate",
+          "type": {
+            "defined": "ClaimCertificate"
+          }
+        },
+        {
+          "name": "bumps",
+          "type": {
+            "option": {
+              "defined": "ClaimBumps"
+            }
+          }
+        }
+      ]
+    }
+  ],
+  "accounts": [
+    {
+      "name": "Config",
       "type": {
         "kind": "struct",
         "fields": [
           {
-            "name": "username",
-            "type": "string"
+            "name": "bump",
+            "type": "u8"
           },
           {
-            "name": "claimant",
+            "name": "merkleRoot",
+            "type": {
+              "defined": "DispenserMerkleRoot"
+            }
+          },
+          {
+            "name": "dispenserGuard",
             "type": "publicKey"
-          }
-        ]
-      }
-    },
-    {
-      "name": "Ed25519InstructionHeader",
-      "type": {
-        "kind": "struct",
-        "fields": [
+          },
           {
-            "name": "numSignatures",
-            "type": "u8"
+            "name": "mint",
+            "type": "publicKey"
           },
           {
-            "name": "padding",
-            "type": "u8"
+            "name": "treasury",
+            "type": "publicKey"
           },
           {
-            "name": "signatureOffset",
-            "type": "u16"
+            "name": "addressLookupTable",
+            "type": "publicKey"
           },
           {
-            "name": "signatureInstructionIndex",
-            "type": "u16"
+            "name": "funder",
+            "type": "publicKey"
           },
           {
-            "name": "publicKeyOffset",
-            "type": "u16"
+            "name": "maxTransfer",
+            "type": "u64"
           },
           {
-            "name": "publicKeyInstructionIndex",
-            "type": "u16"
+            "name": "escrow",
+            "type": "bool"
           },
           {
-            "name": "messageDataOffset",
-            "type": "u16"
+            "name": "totalDeposited",
+            "type": "u64"
           },
           {
-            "name": "messageDataSize",
-            "type": "u16"
+            "name": "totalClaimed",
+            "type": "u64"
           },
           {
-            "name": "messageInstructionIndex",
-            "type": "u16"
-          }
-        ]
-      }
-    },
-    {
-      "name": "Secp256k1InstructionHeader",
-      "type": {
-        "kind": "struct",
-        "fields": [
+            "name": "claimCount",
+            "type": "u64"
+          },
           {
-            "name": "numSignatures",
-            "type": "u8"
+            "name": "claimedPerIdentity",
+            "type": {
+              "array": [
+                "u64",
+                10
+              ]
+            }
           },
           {
-            "name": "signatureOffset",
-            "type": "u16"
+            "name": "globalCap",
+            "type": {
+              "option": "u64"
+            }
           },
           {
-            "name": "signatureInstructionIndex",
-            "type": "u8"
+            "name": "maxPerClaimant",
+            "type": {
+              "option": "u64"
+            }
           },
           {
-            "name": "ethAddressOffset",
-            "type": "u16"
+            "name": "admin",
+            "type": "publicKey"
           },
           {
-            "name": "ethAddressInstructionIndex",
-            "type": "u8"
+            "name": "rateLimit",
+            "type": {
+              "option": {
+                "defined": "RateLimit"
+              }
+            }
           },
           {
-            "name": "messageDataOffset",
-            "type": "u16"
+            "name": "denylistLen",
+            "type": "u64"
           },
           {
-            "name": "messageDataSize",
+            "name": "guardPolicy",
             "type": "u16"
           },
           {
-            "name": "messageInstructionIndex",
+            "name": "legacyReceipts",
+            "type": "u64"
+          },
+          {
+            "name": "version",
             "type": "u8"
           }
         ]
       }
     },
     {
-      "name": "ClaimInfo",
+      "name": "Receipt",
+      "docs": [
+        "* The claim receipt of an identity, it records the cumulative amount paid to the identity so that a\n * leaf of an amended tree only pays the difference. It also records its canonical bump, which claims\n * provide to derive its address without a bump search.\n * `nonce` is the number of claims made by the identity. A signed payload that includes a nonce is only\n * accepted while it is equal to the nonce of the receipt, so it can't be replayed for a later claim."
+      ],
       "type": {
         "kind": "struct",
         "fields": [
           {
-            "name": "identity",
-            "type": {
-              "defined": "Identity"
-            }
+            "name": "amountClaimed",
+            "type": "u64"
           },
           {
-            "name": "amount",
-            "type": "u64"
-          }
-        ]
+            "name": "bump",
+            "type": "u8"
+          },
+          {
+            "name": "nonce",
+            "type": "u64"
+          }
+        ]
       }
     },
     {
-      "name": "ClaimCertificate",
+      "name": "ClaimantTotal",
+      "docs": [
+        "* The total claimed by a claimant across all the identities that authorized it. It only exists for campaigns\n * with a `max_per_claimant`, since a single claimant can aggregate many identities."
+      ],
       "type": {
         "kind": "struct",
         "fields": [
           {
-            "name": "amount",
+            "name": "amountClaimed",
             "type": "u64"
+          }
+        ]
+      }
+    },
+    {
+      "name": "DenylistEntry",
+      "docs": [
+        "* A blocked identity or claimant, see `DenylistKey`. The entry only holds the discriminator, it is blocking as\n * long as it exists."
+      ],
+      "type": {
+        "kind": "struct",
+        "fields": []
+      }
+    }
+  ],
+  "types": [
+    {
+      "name": "CardanoMessage",
+      "docs": [
+        "* A CIP-8 message signed by a Cardano wallet through CIP-30 `signData`.\n* Only the payload and the address from the protected header are stored in this struct.\n* The message signed is the CBOR-encoded COSE `Sig_structure`:\n* `[\"Signature1\", protected header, external aad (empty), payload]`."
+      ],
+      "type": {
+        "kind": "struct",
+        "fields": [
+          {
+            "name": "address",
+            "type": "bytes"
           },
           {
-            "name": "proofOfIdentity",
-            "type": {
-              "defined": "IdentityCertificate"
-            }
+            "name": "payload",
+            "type": "bytes"
+          }
+        ]
+      }
+    },
+    {
+      "name": "CosmosMessage",
+      "docs": [
+        "* An ADR036 message used in Cosmos. ADR036 is a standard for signing arbitrary data.\n* Only the message payload is stored in this struct.\n* The message signed for Cosmos is a JSON serialized CosmosStdSignDoc containing the payload and ADR036 compliant parameters.\n* The message also contains the bech32 address of the signer. We check that the signer corresponds to the public key."
+      ],
+      "type": {
+        "kind": "struct",
+        "fields": [
+          {
+            "name": "payload",
+            "type": "bytes"
           },
           {
-            "name": "proofOfInclusion",
-            "type": {
-              "vec": {
-                "array": [
-                  "u8",
-                  20
-                ]
-              }
-            }
+            "name": "signer",
+            "type": "string"
           }
         ]
       }
     },
     {
-      "name": "Identity",
+      "name": "DiscordMessage",
       "docs": [
-        "* This is the identity that the claimant will use to claim tokens.\n * A claimant can claim tokens for 1 identity on each ecosystem.\n * Typically for a blockchain it is a public key in the blockchain's address space."
+        "* This message (borsh-serialized) needs to be signed by the dispenser guard after\n * verifying the claimant's pubkey controls the discord account.\n * The dispenser guard key should not be used for anything else."
       ],
       "type": {
-        "kind": "enum",
-        "variants": [
+        "kind": "struct",
+        "fields": [
           {
-            "name": "Discord",
-            "fields": [
-              {
-                "name": "username",
-                "type": "string"
-              }
-            ]
+            "name": "username",
+            "type": "string"
           },
           {
-            "name": "Solana",
-            "fields": [
-              {
-                "name": "pubkey",
-                "type": {
-                  "array": [
-                    "u8",
-                    32
-                  ]
-                }
-              }
-            ]
+            "name": "claimant",
+            "type": "publicKey"
+          }
+        ]
+      }
+    },
+    {
+      "name": "Ed25519InstructionHeader",
+      "type": {
+        "kind": "struct",
+        "fields": [
+          {
+            "name": "numSignatures",
+            "type": "u8"
           },
           {
-            "name": "Evm",
-            "fields": [
-              {
-                "name": "pubkey",
-                "type": {
-                  "array": [
-                    "u8",
-                    20
-                  ]
-                }
-              }
-            ]
+            "name": "padding",
+            "type": "u8"
           },
           {
-            "name": "Sui",
-            "fields": [
-              {
-                "name": "address",
-                "type": {
-                  "array": [
-                    "u8",
-                    32
-                  ]
-                }
-              }
-            ]
+            "name": "signatureOffset",
+            "type": "u16"
           },
           {
-            "name": "Aptos",
-            "fields": [
-              {
-                "name": "address",
-                "type": {
-                  "array": [
-                    "u8",
-                    32
-                  ]
-                }
-              }
-            ]
+            "name": "signatureInstructionIndex",
+            "type": "u16"
           },
           {
-            "name": "Cosmwasm",
-            "fields": [
-              {
-                "name": "address",
-                "type": "string"
-              }
-            ]
+            "name": "publicKeyOffset",
+            "type": "u16"
           },
           {
-            "name": "Injective",
-            "fields": [
-              {
-                "name": "address",
-                "type": "string"
-              }
-            ]
+            "name": "publicKeyInstructionIndex",
+            "type": "u16"
+          },
+          {
+            "name": "messageDataOffset",
+            "type": "u16"
+          },
+          {
+            "name": "messageDataSize",
+            "type": "u16"
+          },
+          {
+            "name": "messageInstructionIndex",
+            "type": "u16"
           }
         ]
       }
     },
     {
-      "name": "IdentityCertificate",
+      "name": "WebAuthnMessage",
+      "docs": [
+        "* A WebAuthn assertion produced by a passkey.\n* The authenticator signs `authenticatorData || sha256(clientDataJSON)` and the payload is\n* committed to through the `challenge` field of `clientDataJSON`. Since the challenge has to be\n* short, it is the sha256 hash of the authorization payload rather than the payload itself."
+      ],
       "type": {
-        "kind": "enum",
-        "variants": [
+        "kind": "struct",
+        "fields": [
           {
-            "name": "Discord",
-            "fields": [
-              {
-                "name": "username",
-                "type": "string"
-              },
-              {
-                "name": "verification_instruction_index",
-                "type": "u8"
-              }
-            ]
+            "name": "authenticatorData",
+            "type": "bytes"
           },
           {
-            "name": "Evm",
-            "fields": [
-              {
-                "name": "pubkey",
-                "type": {
-                  "array": [
-                    "u8",
-                    20
-                  ]
-                }
-              },
-              {
-                "name": "verification_instruction_index",
-                "type": "u8"
-              }
-            ]
+            "name": "clientDataJson",
+            "type": "bytes"
+          }
+        ]
+      }
+    },
+    {
+      "name": "Secp256k1InstructionHeader",
+      "type": {
+        "kind": "struct",
+        "fields": [
+          {
+            "name": "numSignatures",
+            "type": "u8"
           },
           {
-            "name": "Solana"
+            "name": "signatureOffset",
+            "type": "u16"
           },
           {
-            "name": "Sui",
-            "fields": [
-              {
-                "name": "pubkey",
-                "type": {
-                  "array": [
-                    "u8",
-                    32
-                  ]
-                }
-              },
-              {
-                "name": "verification_instruction_index",
-                "type": "u8"
-              }
-            ]
+            "name": "signatureInstructionIndex",
+            "type": "u8"
           },
           {
-            "name": "Aptos",
-            "fields": [
-              {
-                "name": "pubkey",
-                "type": {
-                  "array": [
-                    "u8",
-                    32
-                  ]
-                }
-              },
-              {
-                "name": "verification_instruction_index",
-                "type": "u8"
-              }
-            ]
+            "name": "ethAddressOffset",
+            "type": "u16"
           },
           {
-            "name": "Cosmwasm",
+            "name": "ethAddressInstructionIndex",
+            "type": "u8"
+          },
+          {
+            "name": "messageDataOffset",
+            "type": "u16"
+          },
+          {
+            "name": "messageDataSize",
+            "type": "u16"
+          },
+          {
+            "name": "messageInstructionIndex",
+            "type": "u8"
+          }
+        ]
+      }
+    },
+    {
+      "name": "Secp256r1InstructionHeader",
+      "docs": [
+        "The secp256r1 precompile uses the same offsets layout as the ed25519 precompile"
+      ],
+      "type": {
+        "kind": "struct",
+        "fields": [
+          {
+            "name": "numSignatures",
+            "type": "u8"
+          },
+          {
+            "name": "padding",
+            "type": "u8"
+          },
+          {
+            "name": "signatureOffset",
+            "type": "u16"
+          },
+          {
+            "name": "signatureInstructionIndex",
+            "type": "u16"
+          },
+          {
+            "name": "publicKeyOffset",
+            "type": "u16"
+          },
+          {
+            "name": "publicKeyInstructionIndex",
+            "type": "u16"
+          },
+          {
+            "name": "messageDataOffset",
+            "type": "u16"
+          },
+          {
+            "name": "messageDataSize",
+            "type": "u16"
+          },
+          {
+            "name": "messageInstructionIndex",
+            "type": "u16"
+          }
+        ]
+      }
+    },
+    {
+      "name": "StarknetMessage",
+      "docs": [
+        "* Starknet accounts are contracts and their signatures (on the STARK curve) can't be checked on Solana.\n * Instead, the dispenser guard verifies off-chain the SNIP-12 typed data signature of the\n * authorization payload by the Starknet account and then signs this message (prefixed with\n * `STARKNET_MESSAGE_PREFIX` and borsh-serialized).\n * These attestations are issued by the `starknet_signed_message` endpoint of `token-dispenser-guard`.\n * The dispenser guard key should not be used for anything else."
+      ],
+      "type": {
+        "kind": "struct",
+        "fields": [
+          {
+            "name": "address",
+            "type": {
+              "array": [
+                "u8",
+                32
+              ]
+            }
+          },
+          {
+            "name": "claimant",
+            "type": "publicKey"
+          }
+        ]
+      }
+    },
+    {
+      "name": "ClaimInfo",
+      "type": {
+        "kind": "struct",
+        "fields": [
+          {
+            "name": "identity",
+            "type": {
+              "defined": "Identity"
+            }
+          },
+          {
+            "name": "amount",
+            "type": "u64"
+          }
+        ]
+      }
+    },
+    {
+      "name": "ClaimCertificate",
+      "type": {
+        "kind": "struct",
+        "fields": [
+          {
+            "name": "amount",
+            "type": "u64"
+          },
+          {
+            "name": "proofOfIdentity",
+            "type": {
+              "defined": "IdentityCertificate"
+            }
+          },
+          {
+            "name": "proofOfInclusion",
+            "type": {
+              "defined": "DispenserMerklePath"
+            }
+          },
+          {
+            "name": "expiresAt",
+            "type": {
+              "option": "i64"
+            }
+          },
+          {
+            "name": "nonce",
+            "type": {
+              "option": "u64"
+            }
+          }
+        ]
+      }
+    },
+    {
+      "name": "ClaimBumps",
+      "docs": [
+        "* The bumps of the PDAs that a claim passes in `remaining_accounts`, the bumps of the accounts that the claim doesn't\n * use are ignored. They are checked with `create_program_address` only, see `check_pda`."
+      ],
+      "type": {
+        "kind": "struct",
+        "fields": [
+          {
+            "name": "receipt",
+            "type": "u8"
+          },
+          {
+            "name": "claimantTotal",
+            "type": "u8"
+          },
+          {
+            "name": "denylistIdentity",
+            "type": "u8"
+          },
+          {
+            "name": "denylistClaimant",
+            "type": "u8"
+          },
+          {
+            "name": "legacyReceipt",
+            "type": "u8"
+          }
+        ]
+      }
+    },
+    {
+      "name": "LegacyConfig",
+      "docs": [
+        "* The layout of the config before it was versioned, when a deployment had a single campaign. `migrate_config`\n * converts it to a `Config`."
+      ],
+      "type": {
+        "kind": "struct",
+        "fields": [
+          {
+            "name": "bump",
+            "type": "u8"
+          },
+          {
+            "name": "merkleRoot",
+            "type": {
+              "array": [
+                "u8",
+                20
+              ]
+            }
+          },
+          {
+            "name": "dispenserGuard",
+            "type": "publicKey"
+          },
+          {
+            "name": "mint",
+            "type": "publicKey"
+          },
+          {
+            "name": "treasury",
+            "type": "publicKey"
+          },
+          {
+            "name": "addressLookupTable",
+            "type": "publicKey"
+          },
+          {
+            "name": "funder",
+            "type": "publicKey"
+          },
+          {
+            "name": "maxTransfer",
+            "type": "u64"
+          }
+        ]
+      }
+    },
+    {
+      "name": "RateLimit",
+      "docs": [
+        "* A circuit breaker on the outflows of the treasury, claims fail once the amount claimed over the last\n * `window_seconds` would exceed `max_amount`. That amount is approximated with two windows aligned on multiples\n * of `window_seconds`: the current one counts in full and the previous one in proportion to its overlap with the\n * sliding window, so a burst right before a boundary still counts right after it."
+      ],
+      "type": {
+        "kind": "struct",
+        "fields": [
+          {
+            "name": "maxAmount",
+            "type": "u64"
+          },
+          {
+            "name": "windowSeconds",
+            "type": "i64"
+          },
+          {
+            "name": "windowStart",
+            "type": "i64"
+          },
+          {
+            "name": "windowClaimed",
+            "type": "u64"
+          },
+          {
+            "name": "previousWindowClaimed",
+            "type": "u64"
+          }
+        ]
+      }
+    },
+    {
+      "name": "DenylistKey",
+      "docs": [
+        "* An entry of the denylist of a campaign, either an identity, by the hash that seeds its receipt, see\n * `get_identity_hash`, or a claimant."
+      ],
+      "type": {
+        "kind": "enum",
+        "variants": [
+          {
+            "name": "Identity",
             "fields": [
               {
-                "name": "chain_id",
-                "type": "string"
-              },
-              {
-                "name": "signature",
+                "name": "identity_hash",
                 "type": {
                   "array": [
                     "u8",
-                    64
+                    32
                   ]
                 }
-              },
-              {
-                "name": "recovery_id",
-                "type": "u8"
-              },
+              }
+            ]
+          },
+          {
+            "name": "Claimant",
+            "fields": [
               {
                 "name": "pubkey",
-                "type": {
+                "type": "publicKey"
+              }
+            ]
+          }
+        ]
+      }
+    },
+    {
+      "name": "Identity",
+      "docs": [
+        "* This is the identity that the claimant will use to claim tokens.\n * A claimant can claim tokens for 1 identity on each ecosystem.\n * Typically for a blockchain it is a public key in the blockchain's address space."
+      ],
+      "type": {
+        "kind": "enum",
+        "variants": [
+          {
+            "name": "Discord",
+            "fields": [
+              {
+                "name": "username",
+                "type": "string"
+              }
+            ]
+          },
+          {
+            "name": "Solana",
+            "fields": [
+              {
+                "name": "pubkey",
+                "type": {
+                  "array": [
+                    "u8",
+                    32
+                  ]
+                }
+              }
+            ]
+          },
+          {
+            "name": "Evm",
+            "fields": [
+              {
+                "name": "pubkey",
+                "type": {
+                  "array": [
+                    "u8",
+                    20
+                  ]
+                }
+              }
+            ]
+          },
+          {
+            "name": "Sui",
+            "fields": [
+              {
+                "name": "address",
+                "type": {
+                  "array": [
+                    "u8",
+                    32
+                  ]
+                }
+              }
+            ]
+          },
+          {
+            "name": "Aptos",
+            "fields": [
+              {
+                "name": "address",
+                "type": {
+                  "array": [
+                    "u8",
+                    32
+                  ]
+                }
+              }
+            ]
+          },
+          {
+            "name": "Cosmwasm",
+            "fields": [
+              {
+                "name": "address",
+                "type": "string"
+              }
+            ]
+          },
+          {
+            "name": "Injective",
+            "fields": [
+              {
+                "name": "address",
+                "type": "string"
+              }
+            ]
+          },
+          {
+            "name": "Passkey",
+            "fields": [
+              {
+                "name": "pubkey",
+                "type": {
+                  "array": [
+                    "u8",
+                    33
+                  ]
+                }
+              }
+            ]
+          },
+          {
+            "name": "Starknet",
+            "fields": [
+              {
+                "name": "address",
+                "type": {
+                  "array": [
+                    "u8",
+                    32
+                  ]
+                }
+              }
+            ]
+          },
+          {
+            "name": "Cardano",
+            "fields": [
+              {
+                "name": "key_hash",
+                "type": {
+                  "array": [
+                    "u8",
+                    28
+                  ]
+                }
+              }
+            ]
+          }
+        ]
+      }
+    },
+    {
+      "name": "IdentityCertificate",
+      "type": {
+        "kind": "enum",
+        "variants": [
+          {
+            "name": "Discord",
+            "fields": [
+              {
+                "name": "username",
+                "type": "string"
+              },
+              {
+                "name": "verification_instruction_index",
+                "type": "u8"
+              }
+            ]
+          },
+          {
+            "name": "Evm",
+            "fields": [
+              {
+                "name": "pubkey",
+                "type": {
+                  "array": [
+                    "u8",
+                    20
+                  ]
+                }
+              },
+              {
+                "name": "verification_instruction_index",
+                "type": "u8"
+              }
+            ]
+          },
+          {
+            "name": "Solana"
+          },
+          {
+            "name": "Sui",
+            "fields": [
+              {
+                "name": "pubkey",
+                "type": {
+                  "array": [
+                    "u8",
+                    32
+                  ]
+                }
+              },
+              {
+                "name": "verification_instruction_index",
+                "type": "u8"
+              }
+            ]
+          },
+          {
+            "name": "Aptos",
+            "fields": [
+              {
+                "name": "pubkey",
+                "type": {
+                  "array": [
+                    "u8",
+                    32
+                  ]
+                }
+              },
+              {
+                "name": "verification_instruction_index",
+                "type": "u8"
+              }
+            ]
+          },
+          {
+            "name": "Cosmwasm",
+            "fields": [
+              {
+                "name": "chain_id",
+                "type": "string"
+              },
+              {
+                "name": "signature",
+                "type": {
+                  "array": [
+                    "u8",
+                    64
+                  ]
+                }
+              },
+              {
+                "name": "recovery_id",
+                "type": "u8"
+              },
+              {
+                "name": "pubkey",
+                "type": {
                   "array": [
                     "u8",
                     65
@@ -591,96 +1356,761 @@ export type TokenDispenser =
                 "type": "u8"
               }
             ]
-          }
-        ]
-      }
-    }
-  ],
-  "events": [
-    {
-      "name": "ClaimEvent",
-      "fields": [
+          },
+          {
+            "name": "CosmwasmSignDoc",
+            "fields": [
+              {
+                "name": "chain_id",
+                "type": "string"
+              },
+              {
+                "name": "signature",
+                "type": {
+                  "array": [
+                    "u8",
+                    64
+                  ]
+                }
+              },
+              {
+                "name": "recovery_id",
+                "type": "u8"
+              },
+              {
+                "name": "pubkey",
+                "type": {
+                  "array": [
+                    "u8",
+                    65
+                  ]
+                }
+              },
+              {
+                "name": "sign_doc",
+                "type": "bytes"
+              }
+            ]
+          },
+          {
+            "name": "Passkey",
+            "fields": [
+              {
+                "name": "pubkey",
+                "type": {
+                  "array": [
+                    "u8",
+                    33
+                  ]
+                }
+              },
+              {
+                "name": "authenticator_data",
+                "type": "bytes"
+              },
+              {
+                "name": "client_data_json",
+                "type": "bytes"
+              },
+              {
+                "name": "verification_instruction_index",
+                "type": "u8"
+              }
+            ]
+          },
+          {
+            "name": "Starknet",
+            "fields": [
+              {
+                "name": "address",
+                "type": {
+                  "array": [
+                    "u8",
+                    32
+                  ]
+                }
+              },
+              {
+                "name": "verification_instruction_index",
+                "type": "u8"
+              }
+            ]
+          },
+          {
+            "name": "Cardano",
+            "fields": [
+              {
+                "name": "pubkey",
+                "type": {
+                  "array": [
+                    "u8",
+                    32
+                  ]
+                }
+              },
+              {
+                "name": "verification_instruction_index",
+                "type": "u8"
+              }
+            ]
+          }
+        ]
+      }
+    },
+    {
+      "name": "DispenserMerkleRoot",
+      "docs": [
+        "* The root of the distribution tree of a campaign. The variant is the hasher of the tree, so that a single\n * deployment can verify campaigns of both hash sizes."
+      ],
+      "type": {
+        "kind": "enum",
+        "variants": [
+          {
+            "name": "Keccak160",
+            "fields": [
+              {
+                "name": "0",
+                "type": {
+                  "array": [
+                    "u8",
+                    20
+                  ]
+                }
+              }
+            ]
+          },
+          {
+            "name": "Keccak256",
+            "fields": [
+              {
+                "name": "0",
+                "type": {
+                  "array": [
+                    "u8",
+                    32
+                  ]
+                }
+              }
+            ]
+          }
+        ]
+      }
+    },
+    {
+      "name": "DispenserMerklePath",
+      "docs": [
+        "* A proof of inclusion in a `DispenserMerkleRoot`, it only verifies against a root of the same hasher."
+      ],
+      "type": {
+        "kind": "enum",
+        "variants": [
+          {
+            "name": "Keccak160",
+            "fields": [
+              {
+                "name": "0",
+                "type": {
+                  "vec": {
+                    "array": [
+                      "u8",
+                      20
+                    ]
+                  }
+                }
+              }
+            ]
+          },
+          {
+            "name": "Keccak256",
+            "fields": [
+              {
+                "name": "0",
+                "type": {
+                  "vec": {
+                    "array": [
+                      "u8",
+                      32
+                    ]
+                  }
+                }
+              }
+            ]
+          }
+        ]
+      }
+    }
+  ],
+  "events": [
+    {
+      "name": "ClaimEvent",
+      "fields": [
+        {
+          "name": "remainingBalance",
+          "type": "u64",
+          "index": false
+        },
+        {
+          "name": "claimant",
+          "type": "publicKey",
+          "index": false
+        },
+        {
+          "name": "claimInfo",
+          "type": {
+            "defined": "ClaimInfo"
+          },
+          "index": false
+        },
+        {
+          "name": "amountReceived",
+          "type": "u64",
+          "index": false
+        }
+      ]
+    },
+    {
+      "name": "ClaimEventV2",
+      "fields": [
+        {
+          "name": "campaignId",
+          "type": "u64",
+          "index": false
+        },
+        {
+          "name": "claimant",
+          "type": "publicKey",
+          "index": false
+        },
+        {
+          "name": "claimantFund",
+          "type": "publicKey",
+          "index": false
+        },
+        {
+          "name": "ecosystem",
+          "type": "u8",
+          "index": false
+        },
+        {
+          "name": "identityHash",
+          "type": {
+            "array": [
+              "u8",
+              32
+            ]
+          },
+          "index": false
+        },
+        {
+          "name": "leafHash",
+          "type": {
+            "array": [
+              "u8",
+              32
+            ]
+          },
+          "index": false
+        },
+        {
+          "name": "receipt",
+          "type": "publicKey",
+          "index": false
+        },
+        {
+          "name": "merkleRoot",
+          "type": {
+            "array": [
+              "u8",
+              32
+            ]
+          },
+          "index": false
+        },
+        {
+          "name": "amount",
+          "type": "u64",
+          "index": false
+        },
+        {
+          "name": "amountTransferred",
+          "type": "u64",
+          "index": false
+        },
+        {
+          "name": "amountReceived",
+          "type": "u64",
+          "index": false
+        },
+        {
+          "name": "remainingBalance",
+          "type": "u64",
+          "index": false
+        },
+        {
+          "name": "slot",
+          "type": "u64",
+          "index": false
+        },
+        {
+          "name": "unixTimestamp",
+          "type": "i64",
+          "index": false
+        }
+      ]
+    }
+  ],
+  "errors": [
+    {
+      "code": 6000,
+      "name": "AlreadyClaimed"
+    },
+    {
+      "code": 6001,
+      "name": "InvalidInclusionProof"
+    },
+    {
+      "code": 6002,
+      "name": "WrongPda"
+    },
+    {
+      "code": 6003,
+      "name": "SignatureVerificationWrongProgram"
+    },
+    {
+      "code": 6004,
+      "name": "SignatureVerificationWrongAccounts"
+    },
+    {
+      "code": 6005,
+      "name": "SignatureVerificationWrongHeader"
+    },
+    {
+      "code": 6006,
+      "name": "SignatureVerificationWrongPayload"
+    },
+    {
+      "code": 6007,
+      "name": "SignatureVerificationWrongPayloadMetadata"
+    },
+    {
+      "code": 6008,
+      "name": "SignatureVerificationWrongSigner"
+    },
+    {
+      "code": 6009,
+      "name": "UnauthorizedCosmosChainId"
+    },
+    {
+      "code": 6010,
+      "name": "TransferExceedsMax"
+    },
+    {
+      "code": 6011,
+      "name": "AuthorizationExpired"
+    },
+    {
+      "code": 6012,
+      "name": "TreasuryNotEscrowed"
+    },
+    {
+      "code": 6013,
+      "name": "GlobalCapExceeded"
+    },
+    {
+      "code": 6014,
+      "name": "ClaimantCapExceeded"
+    },
+    {
+      "code": 6015,
+      "name": "RateLimitExceeded"
+    },
+    {
+      "code": 6016,
+      "name": "InvalidRateLimit"
+    },
+    {
+      "code": 6017,
+      "name": "IdentityBlocked"
+    },
+    {
+      "code": 6018,
+      "name": "GuardSignatureRequired"
+    },
+    {
+      "code": 6019,
+      "name": "LegacyReceiptNotFound"
+    },
+    {
+      "code": 6020,
+      "name": "ConfigAlreadyMigrated"
+    },
+    {
+      "code": 6021,
+      "name": "ArithmeticOverflow"
+    },
+    {
+      "code": 6022,
+      "name": "UpgradeAuthorityRequired"
+    },
+    {
+      "code": 6023,
+      "name": "NonceMismatch"
+    },
+    {
+      "code": 6024,
+      "name": "LegacyReceiptsNotMigrated"
+    }
+  ]
+}
+;
+export const IDL: TokenDispenser =
+{
+  "version": "0.1.0",
+  "name": "token_dispenser",
+  "instructions": [
+    {
+      "name": "initialize",
+      "docs": [
+        "This can only be called once per campaign. Each campaign has its own tree, mint, treasury and",
+        "guard and claims from different campaigns are independent.",
+        "The variant of `merkle_root` is the hasher of the tree of the campaign, see `DispenserMerkleRoot`.",
+        "If `escrow` is set, the treasury is the associated token account of the config, created here and",
+        "funded with `deposit`. Otherwise it is an external token account that delegates to the config.",
+        "If `global_cap` is set, claims fail once the total claimed would exceed it.",
+        "If `max_per_claimant` is set, claims fail once the total claimed by a claimant across all their",
+        "identities would exceed it.",
+        "The payer becomes the admin of the campaign, who can adjust its rate limit and guard signature policy",
+        "and manage its denylist. Campaigns other than the default one can only be initialized by the upgrade",
+        "authority of the program, so that their ids can't be squatted."
+      ],
+      "accounts": [
+        {
+          "name": "payer",
+          "isMut": true,
+          "isSigner": true
+        },
+        {
+          "name": "config",
+          "isMut": true,
+          "isSigner": false
+        },
+        {
+          "name": "mint",
+          "isMut": false,
+          "isSigner": false
+        },
+        {
+          "name": "treasury",
+          "isMut": true,
+          "isSigner": false,
+          "docs": [
+            "the owner of this account will approve the config as a delegate using the",
+            "solana CLI command `spl-token approve <treasury_account_address> <approve_amount> <config_address>`",
+            "In escrow mode, it is instead the associated token account of the config and gets created here.",
+            "It is unpacked and checked against the mint in the instruction."
+          ]
+        },
+        {
+          "name": "systemProgram",
+          "isMut": false,
+          "isSigner": false
+        },
+        {
+          "name": "addressLookupTable",
+          "isMut": false,
+          "isSigner": false
+        },
+        {
+          "name": "tokenProgram",
+          "isMut": false,
+          "isSigner": false
+        },
+        {
+          "name": "associatedTokenProgram",
+          "isMut": false,
+          "isSigner": false
+        },
+        {
+          "name": "programData",
+          "isMut": false,
+          "isSigner": false
+        }
+      ],
+      "args": [
+        {
+          "name": "campaignId",
+          "type": "u64"
+        },
+        {
+          "name": "merkleRoot",
+          "type": {
+            "defined": "DispenserMerkleRoot"
+          }
+        },
+        {
+          "name": "dispenserGuard",
+          "type": "publicKey"
+        },
+        {
+          "name": "funder",
+          "type": "publicKey"
+        },
+        {
+          "name": "maxTransfer",
+          "type": "u64"
+        },
+        {
+          "name": "escrow",
+          "type": "bool"
+        },
+        {
+          "name": "globalCap",
+          "type": {
+            "option": "u64"
+          }
+        },
+        {
+          "name": "maxPerClaimant",
+          "type": {
+            "option": "u64"
+          }
+        }
+      ]
+    },
+    {
+      "name": "migrateConfig",
+      "docs": [
+        "* Upgrades the config of a campaign created before configs were versioned, whose layout is `LegacyConfig`.\n     * The account is reallocated to `Config::LEN` and the new fields get the values of a config initialized without\n     * escrow, claimant cap, rate limit, denylist or guard signature policy. The upgrade authority of the program signs\n     * the migration, pays the rent of the new space and becomes the admin of the campaign.\n     *\n     * The legacy program kept no statistics, so the claims made before the migration are seeded from\n     * `claimed_per_identity` and `claim_count`, e.g. the totals of the `CampaignClaimIndex` of the campaign.\n     * `global_cap` then counts these claims too. Each of these claims created a legacy receipt, so `claim_count` is\n     * also the number of legacy receipts that must be migrated before the tree can be amended."
+      ],
+      "accounts": [
+        {
+          "name": "authority",
+          "isMut": true,
+          "isSigner": true
+        },
+        {
+          "name": "config",
+          "isMut": true,
+          "isSigner": false
+        },
+        {
+          "name": "programData",
+          "isMut": false,
+          "isSigner": false
+        },
+        {
+          "name": "systemProgram",
+          "isMut": false,
+          "isSigner": false
+        }
+      ],
+      "args": [
+        {
+          "name": "campaignId",
+          "type": "u64"
+        },
+        {
+          "name": "claimedPerIdentity",
+          "type": {
+            "array": [
+              "u64",
+              10
+            ]
+          }
+        },
+        {
+          "name": "claimCount",
+          "type": "u64"
+        },
+        {
+          "name": "globalCap",
+          "type": {
+            "option": "u64"
+          }
+        }
+      ]
+    },
+    {
+      "name": "setRateLimit",
+      "docs": [
+        "* Sets the maximum amount that claims can transfer out of the treasury per window of `window_seconds`,\n     * or removes the limit if `max_amount` is None. The amounts claimed in the current and previous windows are kept\n     * as long as `window_seconds` doesn't change."
+      ],
+      "accounts": [
+        {
+          "name": "admin",
+          "isMut": false,
+          "isSigner": true
+        },
+        {
+          "name": "config",
+          "isMut": true,
+          "isSigner": false
+        }
+      ],
+      "args": [
+        {
+          "name": "campaignId",
+          "type": "u64"
+        },
+        {
+          "name": "maxAmount",
+          "type": {
+            "option": "u64"
+          }
+        },
+        {
+          "name": "windowSeconds",
+          "type": "i64"
+        }
+      ]
+    },
+    {
+      "name": "setGuardSignaturePolicy",
+      "docs": [
+        "* Sets the ecosystems whose claims must be signed by the dispenser guard, as a bitmask indexed by\n     * `Identity::get_variant_index`. This lets the guard gate claims with off-chain checks for any ecosystem."
+      ],
+      "accounts": [
+        {
+          "name": "admin",
+          "isMut": false,
+          "isSigner": true
+        },
+        {
+          "name": "config",
+          "isMut": true,
+          "isSigner": false
+        }
+      ],
+      "args": [
+        {
+          "name": "campaignId",
+          "type": "u64"
+        },
+        {
+          "name": "guardPolicy",
+          "type": "u16"
+        }
+      ]
+    },
+    {
+      "name": "updateMerkleRoot",
+      "docs": [
+        "* Replaces the tree of the campaign, e.g. to top up allocations. Leaves hold cumulative amounts and receipts are\n     * keyed by identity, so identities that already claimed are only paid the difference.\n     * Legacy receipts of the default campaign, see `migrate_receipt`, only match the leaves they were created for,\n     * so the tree can't be replaced until they have all been migrated."
+      ],
+      "accounts": [
+        {
+          "name": "admin",
+          "isMut": false,
+          "isSigner": true
+        },
+        {
+          "name": "config",
+          "isMut": true,
+          "isSigner": false
+        }
+      ],
+      "args": [
+        {
+          "name": "campaignId",
+          "type": "u64"
+        },
+        {
+          "name": "merkleRoot",
+          "type": {
+            "defined": "DispenserMerkleRoot"
+          }
+        }
+      ]
+    },
+    {
+      "name": "migrateReceipt",
+      "docs": [
+        "* Copies a legacy receipt of the default campaign into the receipt of its identity. Before receipts were keyed by\n     * identity, a claim created an empty receipt keyed by the hash of the leaf, see `get_legacy_receipt_pda`.\n     * `claim_info` is the leaf of the legacy receipt. Anyone can migrate a receipt, since it only records a claim that\n     * happened. Until the tree is amended, the receipt of an identity with a legacy receipt can only be created here,\n     * since its claims fail with `AlreadyClaimed`, so creating it counts the legacy receipt as migrated."
+      ],
+      "accounts": [
+        {
+          "name": "payer",
+          "isMut": true,
+          "isSigner": true
+        },
+        {
+          "name": "legacyReceipt",
+          "isMut": false,
+          "isSigner": false
+        },
+        {
+          "name": "receipt",
+          "isMut": true,
+          "isSigner": false
+        },
+        {
+          "name": "config",
+          "isMut": true,
+          "isSigner": false
+        },
+        {
+          "name": "systemProgram",
+          "isMut": false,
+          "isSigner": false
+        }
+      ],
+      "args": [
+        {
+          "name": "claimInfo",
+          "type": {
+            "defined": "ClaimInfo"
+          }
+        }
+      ]
+    },
+    {
+      "name": "block",
+      "docs": [
+        "* Adds an identity or a claimant to the denylist of the campaign, their claims fail with `IdentityBlocked`\n     * until they are removed with `unblock`. Identities are keyed like their receipts, so they stay blocked\n     * when the tree is amended."
+      ],
+      "accounts": [
+        {
+          "name": "admin",
+          "isMut": true,
+          "isSigner": true
+        },
+        {
+          "name": "config",
+          "isMut": true,
+          "isSigner": false
+        },
         {
-          "name": "remainingBalance",
-          "type": "u64",
-          "index": false
+          "name": "denylistEntry",
+          "isMut": true,
+          "isSigner": false
         },
         {
-          "name": "claimant",
-          "type": "publicKey",
-          "index": false
+          "name": "systemProgram",
+          "isMut": false,
+          "isSigner": false
+        }
+      ],
+      "args": [
+        {
+          "name": "campaignId",
+          "type": "u64"
         },
         {
-          "name": "claimInfo",
+          "name": "denylistKey",
           "type": {
-            "defined": "ClaimInfo"
-          },
-          "index": false
+            "defined": "DenylistKey"
+          }
         }
       ]
-    }
-  ],
-  "errors": [
-    {
-      "code": 6000,
-      "name": "AlreadyClaimed"
-    },
-    {
-      "code": 6001,
-      "name": "InvalidInclusionProof"
-    },
-    {
-      "code": 6002,
-      "name": "WrongPda"
-    },
-    {
-      "code": 6003,
-      "name": "SignatureVerificationWrongProgram"
-    },
-    {
-      "code": 6004,
-      "name": "SignatureVerificationWrongAccounts"
-    },
-    {
-      "code": 6005,
-      "name": "SignatureVerificationWrongHeader"
-    },
-    {
-      "code": 6006,
-      "name": "SignatureVerificationWrongPayload"
-    },
-    {
-      "code": 6007,
-      "name": "SignatureVerificationWrongPayloadMetadata"
-    },
-    {
-      "code": 6008,
-      "name": "SignatureVerificationWrongSigner"
-    },
-    {
-      "code": 6009,
-      "name": "UnauthorizedCosmosChainId"
     },
     {
-      "code": 6010,
-      "name": "TransferExceedsMax"
-    }
-  ]
-}
-;
-export const IDL: TokenDispenser =
-{
-  "version": "0.1.0",
-  "name": "token_dispenser",
-  "instructions": [
-    {
-      "name": "initialize",
+      "name": "unblock",
       "docs": [
-        "This can only be called once and should be called right after the program is deployed."
+        "* Removes an identity or a claimant from the denylist of the campaign, the rent of the entry goes back to the admin."
       ],
       "accounts": [
         {
-          "name": "payer",
+          "name": "admin",
           "isMut": true,
           "isSigner": true
         },
@@ -690,54 +2120,68 @@ export const IDL: TokenDispenser =
           "isSigner": false
         },
         {
-          "name": "mint",
-          "isMut": false,
-          "isSigner": false,
-          "docs": [
-            "Mint of the treasury"
-          ]
+          "name": "denylistEntry",
+          "isMut": true,
+          "isSigner": false
+        }
+      ],
+      "args": [
+        {
+          "name": "campaignId",
+          "type": "u64"
         },
         {
-          "name": "treasury",
+          "name": "denylistKey",
+          "type": {
+            "defined": "DenylistKey"
+          }
+        }
+      ]
+    },
+    {
+      "name": "deposit",
+      "docs": [
+        "* Deposit tokens into the escrow treasury of a campaign. Anyone can deposit, the amount received by\n     * the treasury is added to `total_deposited`."
+      ],
+      "accounts": [
+        {
+          "name": "depositor",
           "isMut": false,
-          "isSigner": false,
-          "docs": [
-            "Treasury token account. This is an externally owned token account and",
-            "the owner of this account will approve the config as a delegate using the",
-            "solana CLI command `spl-token approve <treasury_account_address> <approve_amount> <config_address>`"
-          ]
+          "isSigner": true
         },
         {
-          "name": "systemProgram",
+          "name": "depositorFund",
+          "isMut": true,
+          "isSigner": false
+        },
+        {
+          "name": "config",
+          "isMut": true,
+          "isSigner": false
+        },
+        {
+          "name": "mint",
           "isMut": false,
           "isSigner": false
         },
         {
-          "name": "addressLookupTable",
+          "name": "treasury",
+          "isMut": true,
+          "isSigner": false
+        },
+        {
+          "name": "tokenProgram",
           "isMut": false,
           "isSigner": false
         }
       ],
       "args": [
         {
-          "name": "merkleRoot",
-          "type": {
-            "array": [
-              "u8",
-              20
-            ]
-          }
-        },
-        {
-          "name": "dispenserGuard",
-          "type": "publicKey"
-        },
-        {
-          "name": "funder",
-          "type": "publicKey"
+          "name": "campaignId",
+          "type": "u64"
         },
         {
-          "name": "maxTransfer",
+          "name": "amount",
           "type": "u64"
         }
       ]
@@ -745,7 +2189,7 @@ export const IDL: TokenDispenser =
     {
       "name": "claim",
       "docs": [
-        "* Claim a claimant's tokens. This instructions needs to enforce :\n     * - The dispenser guard has signed the transaction - DONE\n     * - The claimant is claiming no more than once per ecosystem - DONE\n     * - The claimant has provided a valid proof of identity (is the owner of the wallet\n     *   entitled to the tokens)\n     * - The claimant has provided a valid proof of inclusion (this confirm that the claimant --\n     *   DONE\n     * - The claimant has not already claimed tokens -- DONE"
+        "* Claim a claimant's tokens. This instructions needs to enforce :\n     * - The dispenser guard has signed the transaction, if the guard signature policy of the campaign\n     *   requires it for the ecosystem of the identity - DONE\n     * - The claimant is claiming no more than once per ecosystem - DONE\n     * - The claimant has provided a valid proof of identity (is the owner of the wallet\n     *   entitled to the tokens)\n     * - The claimant has provided a valid proof of inclusion (this confirm that the claimant --\n     *   DONE\n     * - The claimant has not already claimed tokens -- DONE\n     * `bumps` are the canonical bumps of the PDAs in `remaining_accounts`, so that the program doesn't search for them.\n     * Without them the program searches for the bumps, which costs more compute units."
       ],
       "accounts": [
         {
@@ -763,13 +2207,12 @@ export const IDL: TokenDispenser =
           "isMut": true,
           "isSigner": false,
           "docs": [
-            "Claimant's associated token account to receive the tokens",
-            "Should be initialized outside of this program."
+            "if needed and checked in `checked_create_associated_token_account`."
           ]
         },
         {
           "name": "config",
-          "isMut": false,
+          "isMut": true,
           "isSigner": false
         },
         {
@@ -807,11 +2250,23 @@ export const IDL: TokenDispenser =
         }
       ],
       "args": [
+        {
+          "name": "campaignId",
+          "type": "u64"
+        },
         {
           "name": "claimCertificate",
           "type": {
             "defined": "ClaimCertificate"
           }
+        },
+        {
+          "name": "bumps",
+          "type": {
+            "option": {
+              "defined": "ClaimBumps"
+            }
+          }
         }
       ]
     }
@@ -829,10 +2284,7 @@ export const IDL: TokenDispenser =
           {
             "name": "merkleRoot",
             "type": {
-              "array": [
-                "u8",
-                20
-              ]
+              "defined": "DispenserMerkleRoot"
             }
           },
           {
@@ -856,21 +2308,146 @@ export const IDL: TokenDispenser =
             "type": "publicKey"
           },
           {
-            "name": "maxTransfer",
-            "type": "u64"
+            "name": "maxTransfer",
+            "type": "u64"
+          },
+          {
+            "name": "escrow",
+            "type": "bool"
+          },
+          {
+            "name": "totalDeposited",
+            "type": "u64"
+          },
+          {
+            "name": "totalClaimed",
+            "type": "u64"
+          },
+          {
+            "name": "claimCount",
+            "type": "u64"
+          },
+          {
+            "name": "claimedPerIdentity",
+            "type": {
+              "array": [
+                "u64",
+                10
+              ]
+            }
+          },
+          {
+            "name": "globalCap",
+            "type": {
+              "option": "u64"
+            }
+          },
+          {
+            "name": "maxPerClaimant",
+            "type": {
+              "option": "u64"
+            }
+          },
+          {
+            "name": "admin",
+            "type": "publicKey"
+          },
+          {
+            "name": "rateLimit",
+            "type": {
+              "option": {
+                "defined": "RateLimit"
+              }
+            }
+          },
+          {
+            "name": "denylistLen",
+            "type": "u64"
+          },
+          {
+            "name": "guardPolicy",
+            "type": "u16"
+          },
+          {
+            "name": "legacyReceipts",
+            "type": "u64"
+          },
+          {
+            "name": "version",
+            "type": "u8"
+          }
+        ]
+      }
+    },
+    {
+      "name": "Receipt",
+      "docs": [
+        "* The claim receipt of an identity, it records the cumulative amount paid to the identity so that a\n * leaf of an amended tree only pays the difference. It also records its canonical bump, which claims\n * provide to derive its address without a bump search.\n * `nonce` is the number of claims made by the identity. A signed payload that includes a nonce is only\n * accepted while it is equal to the nonce of the receipt, so it can't be replayed for a later claim."
+      ],
+      "type": {
+        "kind": "struct",
+        "fields": [
+          {
+            "name": "amountClaimed",
+            "type": "u64"
+          },
+          {
+            "name": "bump",
+            "type": "u8"
+          },
+          {
+            "name": "nonce",
+            "type": "u64"
+          }
+        ]
+      }
+    },
+    {
+      "name": "ClaimantTotal",
+      "docs": [
+        "* The total claimed by a claimant across all the identities that authorized it. It only exists for campaigns\n * with a `max_per_claimant`, since a single claimant can aggregate many identities."
+      ],
+      "type": {
+        "kind": "struct",
+        "fields": [
+          {
+            "name": "amountClaimed",
+            "type": "u64"
+          }
+        ]
+      }
+    },
+    {
+      "name": "DenylistEntry",
+      "docs": [
+        "* A blocked identity or claimant, see `DenylistKey`. The entry only holds the discriminator, it is blocking as\n * long as it exists."
+      ],
+      "type": {
+        "kind": "struct",
+        "fields": []
+      }
+    }
+  ],
+  "types": [
+    {
+      "name": "CardanoMessage",
+      "docs": [
+        "* A CIP-8 message signed by a Cardano wallet through CIP-30 `signData`.\n* Only the payload and the address from the protected header are stored in this struct.\n* The message signed is the CBOR-encoded COSE `Sig_structure`:\n* `[\"Signature1\", protected header, external aad (empty), payload]`."
+      ],
+      "type": {
+        "kind": "struct",
+        "fields": [
+          {
+            "name": "address",
+            "type": "bytes"
+          },
+          {
+            "name": "payload",
+            "type": "bytes"
           }
         ]
       }
     },
-    {
-      "name": "Receipt",
-      "type": {
-        "kind": "struct",
-        "fields": []
-      }
-    }
-  ],
-  "types": [
     {
       "name": "CosmosMessage",
       "docs": [
@@ -953,6 +2530,25 @@ export const IDL: TokenDispenser =
         ]
       }
     },
+    {
+      "name": "WebAuthnMessage",
+      "docs": [
+        "* A WebAuthn assertion produced by a passkey.\n* The authenticator signs `authenticatorData || sha256(clientDataJSON)` and the payload is\n* committed to through the `challenge` field of `clientDataJSON`. Since the challenge has to be\n* short, it is the sha256 hash of the authorization payload rather than the payload itself."
+      ],
+      "type": {
+        "kind": "struct",
+        "fields": [
+          {
+            "name": "authenticatorData",
+            "type": "bytes"
+          },
+          {
+            "name": "clientDataJson",
+            "type": "bytes"
+          }
+        ]
+      }
+    },
     {
       "name": "Secp256k1InstructionHeader",
       "type": {
@@ -993,6 +2589,77 @@ export const IDL: TokenDispenser =
         ]
       }
     },
+    {
+      "name": "Secp256r1InstructionHeader",
+      "docs": [
+        "The secp256r1 precompile uses the same offsets layout as the ed25519 precompile"
+      ],
+      "type": {
+        "kind": "struct",
+        "fields": [
+          {
+            "name": "numSignatures",
+            "type": "u8"
+          },
+          {
+            "name": "padding",
+            "type": "u8"
+          },
+          {
+            "name": "signatureOffset",
+            "type": "u16"
+          },
+          {
+            "name": "signatureInstructionIndex",
+            "type": "u16"
+          },
+          {
+            "name": "publicKeyOffset",
+            "type": "u16"
+          },
+          {
+            "name": "publicKeyInstructionIndex",
+            "type": "u16"
+          },
+          {
+            "name": "messageDataOffset",
+            "type": "u16"
+          },
+          {
+            "name": "messageDataSize",
+            "type": "u16"
+          },
+          {
+            "name": "messageInstructionIndex",
+            "type": "u16"
+          }
+        ]
+      }
+    },
+    {
+      "name": "StarknetMessage",
+      "docs": [
+        "* Starknet accounts are contracts and their signatures (on the STARK curve) can't be checked on Solana.\n * Instead, the dispenser guard verifies off-chain the SNIP-12 typed data signature of the\n * authorization payload by the Starknet account and then signs this message (prefixed with\n * `STARKNET_MESSAGE_PREFIX` and borsh-serialized).\n * These attestations are issued by the `starknet_signed_message` endpoint of `token-dispenser-guard`.\n * The dispenser guard key should not be used for anything else."
+      ],
+      "type": {
+        "kind": "struct",
+        "fields": [
+          {
+            "name": "address",
+            "type": {
+              "array": [
+                "u8",
+                32
+              ]
+            }
+          },
+          {
+            "name": "claimant",
+            "type": "publicKey"
+          }
+        ]
+      }
+    },
     {
       "name": "ClaimInfo",
       "type": {
@@ -1029,13 +2696,164 @@ export const IDL: TokenDispenser =
           {
             "name": "proofOfInclusion",
             "type": {
-              "vec": {
-                "array": [
-                  "u8",
-                  20
-                ]
-              }
+              "defined": "DispenserMerklePath"
+            }
+          },
+          {
+            "name": "expiresAt",
+            "type": {
+              "option": "i64"
+            }
+          },
+          {
+            "name": "nonce",
+            "type": {
+              "option": "u64"
+            }
+          }
+        ]
+      }
+    },
+    {
+      "name": "ClaimBumps",
+      "docs": [
+        "* The bumps of the PDAs that a claim passes in `remaining_accounts`, the bumps of the accounts that the claim doesn't\n * use are ignored. They are checked with `create_program_address` only, see `check_pda`."
+      ],
+      "type": {
+        "kind": "struct",
+        "fields": [
+          {
+            "name": "receipt",
+            "type": "u8"
+          },
+          {
+            "name": "claimantTotal",
+            "type": "u8"
+          },
+          {
+            "name": "denylistIdentity",
+            "type": "u8"
+          },
+          {
+            "name": "denylistClaimant",
+            "type": "u8"
+          },
+          {
+            "name": "legacyReceipt",
+            "type": "u8"
+          }
+        ]
+      }
+    },
+    {
+      "name": "LegacyConfig",
+      "docs": [
+        "* The layout of the config before it was versioned, when a deployment had a single campaign. `migrate_config`\n * converts it to a `Config`."
+      ],
+      "type": {
+        "kind": "struct",
+        "fields": [
+          {
+            "name": "bump",
+            "type": "u8"
+          },
+          {
+            "name": "merkleRoot",
+            "type": {
+              "array": [
+                "u8",
+                20
+              ]
             }
+          },
+          {
+            "name": "dispenserGuard",
+            "type": "publicKey"
+          },
+          {
+            "name": "mint",
+            "type": "publicKey"
+          },
+          {
+            "name": "treasury",
+            "type": "publicKey"
+          },
+          {
+            "name": "addressLookupTable",
+            "type": "publicKey"
+          },
+          {
+            "name": "funder",
+            "type": "publicKey"
+          },
+          {
+            "name": "maxTransfer",
+            "type": "u64"
+          }
+        ]
+      }
+    },
+    {
+      "name": "RateLimit",
+      "docs": [
+        "* A circuit breaker on the outflows of the treasury, claims fail once the amount claimed over the last\n * `window_seconds` would exceed `max_amount`. That amount is approximated with two windows aligned on multiples\n * of `window_seconds`: the current one counts in full and the previous one in proportion to its overlap with the\n * sliding window, so a burst right before a boundary still counts right after it."
+      ],
+      "type": {
+        "kind": "struct",
+        "fields": [
+          {
+            "name": "maxAmount",
+            "type": "u64"
+          },
+          {
+            "name": "windowSeconds",
+            "type": "i64"
+          },
+          {
+            "name": "windowStart",
+            "type": "i64"
+          },
+          {
+            "name": "windowClaimed",
+            "type": "u64"
+          },
+          {
+            "name": "previousWindowClaimed",
+            "type": "u64"
+          }
+        ]
+      }
+    },
+    {
+      "name": "DenylistKey",
+      "docs": [
+        "* An entry of the denylist of a campaign, either an identity, by the hash that seeds its receipt, see\n * `get_identity_hash`, or a claimant."
+      ],
+      "type": {
+        "kind": "enum",
+        "variants": [
+          {
+            "name": "Identity",
+            "fields": [
+              {
+                "name": "identity_hash",
+                "type": {
+                  "array": [
+                    "u8",
+                    32
+                  ]
+                }
+              }
+            ]
+          },
+          {
+            "name": "Claimant",
+            "fields": [
+              {
+                "name": "pubkey",
+                "type": "publicKey"
+              }
+            ]
           }
         ]
       }
@@ -1130,6 +2948,48 @@ export const IDL: TokenDispenser =
                 "type": "string"
               }
             ]
+          },
+          {
+            "name": "Passkey",
+            "fields": [
+              {
+                "name": "pubkey",
+                "type": {
+                  "array": [
+                    "u8",
+                    33
+                  ]
+                }
+              }
+            ]
+          },
+          {
+            "name": "Starknet",
+            "fields": [
+              {
+                "name": "address",
+                "type": {
+                  "array": [
+                    "u8",
+                    32
+                  ]
+                }
+              }
+            ]
+          },
+          {
+            "name": "Cardano",
+            "fields": [
+              {
+                "name": "key_hash",
+                "type": {
+                  "array": [
+                    "u8",
+                    28
+                  ]
+                }
+              }
+            ]
           }
         ]
       }
@@ -1261,6 +3121,185 @@ export const IDL: TokenDispenser =
                 "type": "u8"
               }
             ]
+          },
+          {
+            "name": "CosmwasmSignDoc",
+            "fields": [
+              {
+                "name": "chain_id",
+                "type": "string"
+              },
+              {
+                "name": "signature",
+                "type": {
+                  "array": [
+                    "u8",
+                    64
+                  ]
+                }
+              },
+              {
+                "name": "recovery_id",
+                "type": "u8"
+              },
+              {
+                "name": "pubkey",
+                "type": {
+                  "array": [
+                    "u8",
+                    65
+                  ]
+                }
+              },
+              {
+                "name": "sign_doc",
+                "type": "bytes"
+              }
+            ]
+          },
+          {
+            "name": "Passkey",
+            "fields": [
+              {
+                "name": "pubkey",
+                "type": {
+                  "array": [
+                    "u8",
+                    33
+                  ]
+                }
+              },
+              {
+                "name": "authenticator_data",
+                "type": "bytes"
+              },
+              {
+                "name": "client_data_json",
+                "type": "bytes"
+              },
+              {
+                "name": "verification_instruction_index",
+                "type": "u8"
+              }
+            ]
+          },
+          {
+            "name": "Starknet",
+            "fields": [
+              {
+                "name": "address",
+                "type": {
+                  "array": [
+                    "u8",
+                    32
+                  ]
+                }
+              },
+              {
+                "name": "verification_instruction_index",
+                "type": "u8"
+              }
+            ]
+          },
+          {
+            "name": "Cardano",
+            "fields": [
+              {
+                "name": "pubkey",
+                "type": {
+                  "array": [
+                    "u8",
+                    32
+                  ]
+                }
+              },
+              {
+                "name": "verification_instruction_index",
+                "type": "u8"
+              }
+            ]
+          }
+        ]
+      }
+    },
+    {
+      "name": "DispenserMerkleRoot",
+      "docs": [
+        "* The root of the distribution tree of a campaign. The variant is the hasher of the tree, so that a single\n * deployment can verify campaigns of both hash sizes."
+      ],
+      "type": {
+        "kind": "enum",
+        "variants": [
+          {
+            "name": "Keccak160",
+            "fields": [
+              {
+                "name": "0",
+                "type": {
+                  "array": [
+                    "u8",
+                    20
+                  ]
+                }
+              }
+            ]
+          },
+          {
+            "name": "Keccak256",
+            "fields": [
+              {
+                "name": "0",
+                "type": {
+                  "array": [
+                    "u8",
+                    32
+                  ]
+                }
+              }
+            ]
+          }
+        ]
+      }
+    },
+    {
+      "name": "DispenserMerklePath",
+      "docs": [
+        "* A proof of inclusion in a `DispenserMerkleRoot`, it only verifies against a root of the same hasher."
+      ],
+      "type": {
+        "kind": "enum",
+        "variants": [
+          {
+            "name": "Keccak160",
+            "fields": [
+              {
+                "name": "0",
+                "type": {
+                  "vec": {
+                    "array": [
+                      "u8",
+                      20
+                    ]
+                  }
+                }
+              }
+            ]
+          },
+          {
+            "name": "Keccak256",
+            "fields": [
+              {
+                "name": "0",
+                "type": {
+                  "vec": {
+                    "array": [
+                      "u8",
+                      32
+                    ]
+                  }
+                }
+              }
+            ]
           }
         ]
       }
@@ -1286,6 +3325,101 @@ export const IDL: TokenDispenser =
             "defined": "ClaimInfo"
           },
           "index": false
+        },
+        {
+          "name": "amountReceived",
+          "type": "u64",
+          "index": false
+        }
+      ]
+    },
+    {
+      "name": "ClaimEventV2",
+      "fields": [
+        {
+          "name": "campaignId",
+          "type": "u64",
+          "index": false
+        },
+        {
+          "name": "claimant",
+          "type": "publicKey",
+          "index": false
+        },
+        {
+          "name": "claimantFund",
+          "type": "publicKey",
+          "index": false
+        },
+        {
+          "name": "ecosystem",
+          "type": "u8",
+          "index": false
+        },
+        {
+          "name": "identityHash",
+          "type": {
+            "array": [
+              "u8",
+              32
+            ]
+          },
+          "index": false
+        },
+        {
+          "name": "leafHash",
+          "type": {
+            "array": [
+              "u8",
+              32
+            ]
+          },
+          "index": false
+        },
+        {
+          "name": "receipt",
+          "type": "publicKey",
+          "index": false
+        },
+        {
+          "name": "merkleRoot",
+          "type": {
+            "array": [
+              "u8",
+              32
+            ]
+          },
+          "index": false
+        },
+        {
+          "name": "amount",
+          "type": "u64",
+          "index": false
+        },
+        {
+          "name": "amountTransferred",
+          "type": "u64",
+          "index": false
+        },
+        {
+          "name": "amountReceived",
+          "type": "u64",
+          "index": false
+        },
+        {
+          "name": "remainingBalance",
+          "type": "u64",
+          "index": false
+        },
+        {
+          "name": "slot",
+          "type": "u64",
+          "index": false
+        },
+        {
+          "name": "unixTimestamp",
+          "type": "i64",
+          "index": false
         }
       ]
     }
@@ -1334,6 +3468,62 @@ export const IDL: TokenDispenser =
     {
       "code": 6010,
       "name": "TransferExceedsMax"
+    },
+    {
+      "code": 6011,
+      "name": "AuthorizationExpired"
+    },
+    {
+      "code": 6012,
+      "name": "TreasuryNotEscrowed"
+    },
+    {
+      "code": 6013,
+      "name": "GlobalCapExceeded"
+    },
+    {
+      "code": 6014,
+      "name": "ClaimantCapExceeded"
+    },
+    {
+      "code": 6015,
+      "name": "RateLimitExceeded"
+    },
+    {
+      "code": 6016,
+      "name": "InvalidRateLimit"
+    },
+    {
+      "code": 6017,
+      "name": "IdentityBlocked"
+    },
+    {
+      "code": 6018,
+      "name": "GuardSignatureRequired"
+    },
+    {
+      "code": 6019,
+      "name": "LegacyReceiptNotFound"
+    },
+    {
+      "code": 6020,
+      "name": "ConfigAlreadyMigrated"
+    },
+    {
+      "code": 6021,
+      "name": "ArithmeticOverflow"
+    },
+    {
+      "code": 6022,
+      "name": "UpgradeAuthorityRequired"
+    },
+    {
+      "code": 6023,
+      "name": "NonceMismatch"
+    },
+    {
+      "code": 6024,
+      "name": "LegacyReceiptsNotMigrated"
     }
   ]
 }
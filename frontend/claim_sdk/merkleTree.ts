@@ -6,6 +6,13 @@ const NULL_PREFIX = Buffer.from('02', 'hex')
 
 export const HASH_SIZE = 20
 
+// The hasher of a tree, named after the variants of `DispenserMerkleRoot` in the program
+export type MerkleHasher = 'keccak160' | 'keccak256'
+
+export function getHashSize(hasher: MerkleHasher): number {
+  return hasher === 'keccak160' ? HASH_SIZE : 32
+}
+
 export class MerkleTree {
   public nodes: Buffer[]
   public indices = new Map<string, number>()
@@ -0,0 +1,203 @@
+import * as anchor from '@coral-xyz/anchor'
+import { Keypair, PublicKey } from '@solana/web3.js'
+import { expect, jest } from '@jest/globals'
+import { TokenDispenserProvider } from './solana'
+import { ClaimInfo } from './claim'
+import keccak256 from 'keccak256'
+import authorizationPayload from './idl/authorization_payload.json'
+
+const programId = new PublicKey('Fg6PaFpoGXkYsidMpWTK6W2BeZ7FEfcYkg476zPFsLnS')
+const wallet = new anchor.Wallet(Keypair.generate())
+
+function getProvider(campaignId?: anchor.BN): TokenDispenserProvider {
+  return new TokenDispenserProvider(
+    'http://127.0.0.1:8899',
+    wallet,
+    programId,
+    undefined,
+    campaignId
+  )
+}
+
+/** The accounts and the payload of the default campaign are the ones from before campaigns were introduced. */
+test('Default campaign', () => {
+  const provider = getProvider()
+
+  expect(provider.getConfigPda()).toEqual(
+    PublicKey.findProgramAddressSync([Buffer.from('config')], programId)
+  )
+  expect(provider.generateAuthorizationPayload()).toEqual(
+    `Pyth Grant PID:\n${programId}\nI authorize Solana wallet\n${wallet.publicKey}\nto claim my token grant.\n`
+  )
+})
+
+test('Other campaigns', () => {
+  const provider = getProvider(new anchor.BN(258))
+
+  expect(provider.getConfigPda()).toEqual(
+    PublicKey.findProgramAddressSync(
+      [Buffer.from('config'), Buffer.from([2, 1, 0, 0, 0, 0, 0, 0])],
+      programId
+    )
+  )
+  expect(provider.getConfigPda()).not.toEqual(getProvider().getConfigPda())
+  expect(provider.generateAuthorizationPayload()).toEqual(
+    `Pyth Grant PID:\n${programId}\nCampaign: 258\nI authorize Solana wallet\n${wallet.publicKey}\nto claim my token grant.\n`
+  )
+})
+
+test('Program data of the upgradeable loader', () => {
+  expect(getProvider().getProgramDataPda()).toEqual(
+    PublicKey.findProgramAddressSync(
+      [programId.toBuffer()],
+      new PublicKey('BPFLoaderUpgradeab1e11111111111111111111111')
+    )[0]
+  )
+})
+
+test('Expiring payload', () => {
+  expect(getProvider().generateAuthorizationPayload(1700000000)).toEqual(
+    `Pyth Grant PID:\n${programId}\nI authorize Solana wallet\n${wallet.publicKey}\nto claim my token grant.\nExpires at: 1700000000\n`
+  )
+})
+
+test('Payload with a nonce', () => {
+  expect(
+    getProvider().generateAuthorizationPayload(1700000000, undefined, 2)
+  ).toEqual(
+    `Pyth Grant PID:\n${programId}\nI authorize Solana wallet\n${wallet.publicKey}\nto claim my token grant.\nExpires at: 1700000000\nNonce: 2\n`
+  )
+  expect(
+    getProvider().generateAuthorizationPayload(undefined, undefined, 0)
+  ).toEqual(
+    getProvider().generateAuthorizationPayload() +
+      authorizationPayload.nonce[0] +
+      '0' +
+      authorizationPayload.nonce[1]
+  )
+})
+
+test('Payload with the amount and the merkle root', async () => {
+  const provider = getProvider(new anchor.BN(258))
+  const merkleRoot = Buffer.alloc(20, 0xab)
+  provider.config = {
+    merkleRoot: { keccak160: { '0': Array.from(merkleRoot) } },
+  } as any
+
+  const payload = await provider.generateAuthorizationPayloadV2(
+    new anchor.BN(1000),
+    1700000000
+  )
+  expect(payload).toEqual(
+    provider.generateAuthorizationPayload().concat(
+      authorizationPayload.claim[0],
+      '1000',
+      authorizationPayload.claim[1],
+      'ab'.repeat(20),
+      authorizationPayload.claim[2],
+      authorizationPayload.expiry[0],
+      '1700000000',
+      authorizationPayload.expiry[1]
+    )
+  )
+  expect(payload).toEqual(
+    `Pyth Grant PID:\n${programId}\nCampaign: 258\nI authorize Solana wallet\n${wallet.publicKey}\nto claim my token grant.\nAmount: 1000\nMerkle root: ${'ab'.repeat(
+      20
+    )}\nExpires at: 1700000000\n`
+  )
+
+  provider.config = {
+    merkleRoot: { keccak256: { '0': Array.from(Buffer.alloc(32, 1)) } },
+  } as any
+  expect(
+    await provider.generateAuthorizationPayloadV2(new anchor.BN(5))
+  ).toEqual(
+    provider.generateAuthorizationPayload() +
+      `Amount: 5\nMerkle root: ${'01'.repeat(32)}\n`
+  )
+})
+
+test('Merkle path of the hasher of the campaign', async () => {
+  const provider = getProvider()
+  const proof = Buffer.concat([Buffer.alloc(32, 1), Buffer.alloc(32, 2)])
+
+  provider.config = {
+    merkleRoot: { keccak256: { '0': Array.from(Buffer.alloc(32)) } },
+  } as any
+  expect(await provider.getMerklePath([proof])).toEqual({
+    keccak256: {
+      '0': [Array.from(Buffer.alloc(32, 1)), Array.from(Buffer.alloc(32, 2))],
+    },
+  })
+
+  provider.config = {
+    merkleRoot: { keccak160: { '0': Array.from(Buffer.alloc(20)) } },
+  } as any
+  await expect(provider.getMerklePath([proof])).rejects.toThrow()
+  expect(
+    await provider.getMerklePath([Buffer.alloc(20, 1), Buffer.alloc(20, 2)])
+  ).toEqual({
+    keccak160: {
+      '0': [Array.from(Buffer.alloc(20, 1)), Array.from(Buffer.alloc(20, 2))],
+    },
+  })
+})
+
+test('Token program of the mint', async () => {
+  const provider = getProvider()
+  const mint = PublicKey.unique()
+  const token2022 = new PublicKey('TokenzQdBNbLqP5VEhdkAS6EPFLC1PHnBqCXEpPxuEb')
+  const getAccountInfo = jest
+    .spyOn(provider.provider.connection, 'getAccountInfo')
+    .mockResolvedValue({
+      owner: token2022,
+      data: Buffer.alloc(0),
+      executable: false,
+      lamports: 0,
+    })
+
+  expect(await provider.getMintOwner(mint)).toEqual(token2022)
+  expect(getAccountInfo).toHaveBeenCalledWith(mint)
+
+  getAccountInfo.mockResolvedValue(null)
+  await expect(provider.getMintOwner(mint)).rejects.toThrow(
+    'mint account not found'
+  )
+})
+
+test('Receipts and denylist entries are keyed by identity', () => {
+  const provider = getProvider(new anchor.BN(258))
+  const claimInfo = new ClaimInfo('discord', 'alice', new anchor.BN(100))
+  const amended = new ClaimInfo('discord', 'alice', new anchor.BN(200))
+  // Borsh serialization of `Identity::Discord { username: "alice" }`
+  const identityHash = keccak256(
+    Buffer.concat([Buffer.from([0, 5, 0, 0, 0]), Buffer.from('alice')])
+  )
+
+  expect(provider.getIdentityHash(claimInfo)).toEqual(identityHash)
+  expect(provider.getIdentityHash(amended)).toEqual(identityHash)
+  expect(provider.getReceiptPda(amended)).toEqual(
+    provider.getReceiptPda(claimInfo)
+  )
+  expect(provider.getDenylistPda('identity', identityHash)).toEqual(
+    PublicKey.findProgramAddressSync(
+      [
+        Buffer.from('denylist'),
+        Buffer.from([2, 1, 0, 0, 0, 0, 0, 0]),
+        Buffer.from('identity'),
+        identityHash,
+      ],
+      programId
+    )
+  )
+  expect(provider.getClaimBumps(amended)).toEqual({
+    receipt: provider.getReceiptPda(claimInfo)[1],
+    claimantTotal: provider.getClaimantTotalPda()[1],
+    denylistIdentity: provider.getDenylistPda('identity', identityHash)[1],
+    denylistClaimant: provider.getDenylistPda(
+      'claimant',
+      provider.claimant.toBuffer()
+    )[1],
+    legacyReceipt: provider.getLegacyReceiptPda(amended)[1],
+  })
+})
@@ -1,10 +1,16 @@
 import * as anchor from '@coral-xyz/anchor'
 import { Wallet } from '@coral-xyz/anchor/dist/cjs/provider'
 import tokenDispenser from './idl/token_dispenser.json'
+import authorizationPayload from './idl/authorization_payload.json'
 import type { TokenDispenser } from './idl/token_dispenser'
 import { Idl, IdlAccounts, IdlTypes, Program } from '@coral-xyz/anchor'
 import { Buffer } from 'buffer'
-import { MerkleTree } from './merkleTree'
+import {
+  getHashSize,
+  MerkleHasher,
+  MerkleTree,
+} from './merkleTree'
+import keccak256 from 'keccak256'
 import {
   AddressLookupTableAccount,
   AddressLookupTableProgram,
@@ -23,7 +29,7 @@ import {
 } from '@solana/web3.js'
 import * as splToken from '@solana/spl-token'
 import { ClaimInfo, Ecosystem } from './claim'
-import { TOKEN_PROGRAM_ID, Token } from '@solana/spl-token'
+import { Token } from '@solana/spl-token'
 import { SignedMessage } from './ecosystems/signatures'
 import { extractChainId } from './ecosystems/cosmos'
 import { fetchFundTransaction } from '../utils/api'
@@ -34,16 +40,23 @@ export const ERROR_RPC_CONNECTION = 'error: rpc connection'
 export const ERROR_CRAFTING_TX = 'error: crafting transaction'
 
 type bump = number
-// NOTE: This must be kept in sync with the on-chain program
-const AUTHORIZATION_PAYLOAD = [
-  'Pyth Grant PID:\n',
-  '\nI authorize Solana wallet\n',
-  '\nto claim my token grant.\n',
-]
+// Shared with the on-chain program, see `authorization_payload.json`
+const AUTHORIZATION_PAYLOAD = authorizationPayload.payload
+const CAMPAIGN_PAYLOAD = authorizationPayload.campaign
+const CLAIM_PAYLOAD = authorizationPayload.claim
+const EXPIRY_PAYLOAD = authorizationPayload.expiry
+const NONCE_PAYLOAD = authorizationPayload.nonce
+
+const BPF_LOADER_UPGRADEABLE_PROGRAM_ID = new PublicKey(
+  'BPFLoaderUpgradeab1e11111111111111111111111'
+)
+
+// The campaign of the initial distribution, its accounts and authorization payload don't mention the campaign id
+export const DEFAULT_CAMPAIGN_ID = new anchor.BN(0)
 
 /**
  * This class wraps the interaction with the TokenDispenser
- * program for a specific claimant. The claimant will be the
+ * program for a specific claimant and campaign. The claimant will be the
  * solana pubkey of the wallet used in the constructor.
  *
  * TODO: add more documentation
@@ -52,13 +65,17 @@ export class TokenDispenserProvider {
   tokenDispenserProgram: anchor.Program<TokenDispenser>
   configPda: [anchor.web3.PublicKey, bump]
   config: IdlAccounts<TokenDispenser>['Config'] | undefined
+  tokenProgramId: PublicKey | undefined
+  campaignId: anchor.BN
 
   constructor(
     endpoint: string,
     wallet: Wallet,
     programId: anchor.web3.PublicKey,
-    confirmOpts?: anchor.web3.ConfirmOptions
+    confirmOpts?: anchor.web3.ConfirmOptions,
+    campaignId: anchor.BN = DEFAULT_CAMPAIGN_ID
   ) {
+    this.campaignId = campaignId
     confirmOpts = confirmOpts ?? anchor.AnchorProvider.defaultOptions()
     const provider = new anchor.AnchorProvider(
       new anchor.web3.Connection(endpoint, confirmOpts.preflightCommitment),
@@ -90,18 +107,61 @@ export class TokenDispenserProvider {
     return this.tokenDispenserProgram.provider
   }
 
+  get isDefaultCampaign(): boolean {
+    return this.campaignId.eq(DEFAULT_CAMPAIGN_ID)
+  }
+
+  // Seed of the accounts of the campaign, empty for the default campaign
+  public getCampaignSeed(): Buffer {
+    return this.isDefaultCampaign
+      ? Buffer.alloc(0)
+      : this.campaignId.toArrayLike(Buffer, 'le', 8)
+  }
+
   public getConfigPda(): [anchor.web3.PublicKey, bump] {
     return (
       this.configPda ??
       anchor.web3.PublicKey.findProgramAddressSync(
-        [Buffer.from('config')],
+        [Buffer.from('config'), this.getCampaignSeed()],
         this.programId
       )
     )
   }
 
+  // The hasher of the tree of the campaign, see `DispenserMerkleRoot`
+  public async getMerkleHasher(): Promise<MerkleHasher> {
+    return 'keccak256' in (await this.getConfig()).merkleRoot
+      ? 'keccak256'
+      : 'keccak160'
+  }
+
+  /**
+   * Builds the proof of inclusion of the hasher of the campaign from the sibling hashes in `proofOfInclusion`,
+   * which may be split in any way, e.g. as a single buffer.
+   */
+  public async getMerklePath(
+    proofOfInclusion: Uint8Array[]
+  ): Promise<IdlTypes<TokenDispenser>['DispenserMerklePath']> {
+    const hasher = await this.getMerkleHasher()
+    const hashSize = getHashSize(hasher)
+    const proof = Buffer.concat(proofOfInclusion)
+    if (proof.length % hashSize !== 0) {
+      throw new Error(
+        `Proof of inclusion must be a multiple of ${hashSize} bytes`
+      )
+    }
+    const siblings = []
+    for (let i = 0; i < proof.length; i += hashSize) {
+      siblings.push(Array.from(proof.subarray(i, i + hashSize)))
+    }
+    return {
+      [hasher]: { '0': siblings },
+    } as IdlTypes<TokenDispenser>['DispenserMerklePath']
+  }
+
   public async getConfig(): Promise<IdlAccounts<TokenDispenser>['Config']> {
-    // config is immutable once its been initialized so this is safe.
+    // Only the admin can update the config, e.g. the merkle root with `update_merkle_root`. Create a new provider
+    // to read the updated config.
     if (this.config === undefined) {
       this.config = await this.fetchConfigData()
     }
@@ -123,21 +183,130 @@ export class TokenDispenserProvider {
     )
   }
 
+  // Keccak256 hash of the identity, which is the serialized claim info without the amount
+  public getIdentityHash(claimInfo: ClaimInfo): Buffer {
+    return keccak256(claimInfo.toBuffer().subarray(0, -8))
+  }
+
+  // Receipts are keyed by the hash of the identity
   public getReceiptPda(claimInfo: ClaimInfo): [anchor.web3.PublicKey, bump] {
+    return anchor.web3.PublicKey.findProgramAddressSync(
+      [
+        Buffer.from('receipt'),
+        this.getCampaignSeed(),
+        this.getIdentityHash(claimInfo),
+      ],
+      this.programId
+    )
+  }
+
+  // Receipt of the default campaign created before receipts were keyed by identity, keyed by the hash of the leaf
+  public getLegacyReceiptPda(
+    claimInfo: ClaimInfo
+  ): [anchor.web3.PublicKey, bump] {
     return anchor.web3.PublicKey.findProgramAddressSync(
       [Buffer.from('receipt'), MerkleTree.hashLeaf(claimInfo.toBuffer())],
       this.programId
     )
   }
 
+  public getClaimantTotalPda(): [anchor.web3.PublicKey, bump] {
+    return anchor.web3.PublicKey.findProgramAddressSync(
+      [
+        Buffer.from('claimant_total'),
+        this.getCampaignSeed(),
+        this.claimant.toBuffer(),
+      ],
+      this.programId
+    )
+  }
+
+  // Denylist entries are keyed by the hash of the identity, like receipts, or by the claimant
+  public getDenylistPda(
+    kind: 'identity' | 'claimant',
+    key: Buffer
+  ): [anchor.web3.PublicKey, bump] {
+    return anchor.web3.PublicKey.findProgramAddressSync(
+      [Buffer.from('denylist'), this.getCampaignSeed(), Buffer.from(kind), key],
+      this.programId
+    )
+  }
+
+  /**
+   * The bumps of the accounts of a claim of `claimInfo`, so that the program doesn't search for them.
+   */
+  public getClaimBumps(
+    claimInfo: ClaimInfo
+  ): IdlTypes<TokenDispenser>['ClaimBumps'] {
+    return {
+      receipt: this.getReceiptPda(claimInfo)[1],
+      claimantTotal: this.getClaimantTotalPda()[1],
+      denylistIdentity: this.getDenylistPda(
+        'identity',
+        this.getIdentityHash(claimInfo)
+      )[1],
+      denylistClaimant: this.getDenylistPda(
+        'claimant',
+        this.claimant.toBuffer()
+      )[1],
+      legacyReceipt: this.getLegacyReceiptPda(claimInfo)[1],
+    }
+  }
+
+  /**
+   * The receipt of the identity of `claimInfo`, `null` if it hasn't claimed yet.
+   */
+  public async fetchReceipt(
+    claimInfo: ClaimInfo
+  ): Promise<IdlAccounts<TokenDispenser>['Receipt'] | null> {
+    const receiptAccountInfo = await this.connection.getAccountInfo(
+      this.getReceiptPda(claimInfo)[0]
+    )
+    if (
+      receiptAccountInfo === null ||
+      !receiptAccountInfo.owner.equals(this.programId)
+    ) {
+      return null
+    }
+    return this.tokenDispenserProgram.coder.accounts.decode(
+      'Receipt',
+      receiptAccountInfo.data
+    )
+  }
+
+  /**
+   * The nonce to sign in the payload of the next claim of the identity of `claimInfo`,
+   * see `generateAuthorizationPayload`.
+   */
+  public async getReceiptNonce(claimInfo: ClaimInfo): Promise<number> {
+    return (await this.fetchReceipt(claimInfo))?.nonce.toNumber() ?? 0
+  }
+
+  /**
+   * Whether the amount of `claimInfo` has been claimed. The receipt of the identity records the cumulative amount
+   * claimed, so a leaf of an amended tree with a larger amount can still be claimed. Leaves of the default campaign
+   * may also have been claimed before receipts were keyed by identity, which created their legacy receipt.
+   */
   public async isClaimAlreadySubmitted(claimInfo: ClaimInfo): Promise<boolean> {
+    const receipt = await this.fetchReceipt(claimInfo)
+    if (receipt !== null) {
+      return receipt.amountClaimed.gte(claimInfo.amount)
+    }
     return (
-      (
-        await this.connection.getAccountInfo(this.getReceiptPda(claimInfo)[0])
-      )?.owner.equals(this.programId) ?? false
+      this.isDefaultCampaign &&
+      ((
+        await this.connection.getAccountInfo(
+          this.getLegacyReceiptPda(claimInfo)[0]
+        )
+      )?.owner.equals(this.programId) ??
+        false)
     )
   }
 
+  /**
+   * The hasher of the tree is the one of the size of `root`, 20 bytes for keccak160 and 32 bytes for keccak256.
+   * The token program is the owner of `mint`, either the token program or token-2022.
+   */
   public async initialize(
     root: Buffer,
     mint: anchor.web3.PublicKey,
@@ -146,28 +315,59 @@ export class TokenDispenserProvider {
     funder: anchor.web3.PublicKey,
     maxTransfer: anchor.BN
   ): Promise<TransactionSignature> {
+    const tokenProgram = await this.getMintOwner(mint)
     const addressLookupTable = await this.initAddressLookupTable(
       mint,
       treasury,
-      funder
+      funder,
+      tokenProgram
     )
 
     return this.tokenDispenserProgram.methods
-      .initialize(Array.from(root), dispenserGuard, funder, maxTransfer)
+      .initialize(
+        this.campaignId,
+        {
+          [root.length === getHashSize('keccak160')
+            ? 'keccak160'
+            : 'keccak256']: { '0': Array.from(root) },
+        } as IdlTypes<TokenDispenser>['DispenserMerkleRoot'],
+        dispenserGuard,
+        funder,
+        maxTransfer,
+        false,
+        null,
+        null
+      )
       .accounts({
+        payer: this.provider.publicKey!,
         config: this.getConfigPda()[0],
         mint,
         treasury,
         systemProgram: anchor.web3.SystemProgram.programId,
         addressLookupTable,
+        tokenProgram,
+        associatedTokenProgram: splToken.ASSOCIATED_TOKEN_PROGRAM_ID,
+        programData: this.getProgramDataPda(),
       })
       .rpc()
   }
 
+  /**
+   * The `ProgramData` account of the program. Campaigns other than the default one can only be initialized
+   * by its upgrade authority.
+   */
+  public getProgramDataPda(): anchor.web3.PublicKey {
+    return PublicKey.findProgramAddressSync(
+      [this.programId.toBuffer()],
+      BPF_LOADER_UPGRADEABLE_PROGRAM_ID
+    )[0]
+  }
+
   private async initAddressLookupTable(
     mint: anchor.web3.PublicKey,
     treasury: anchor.web3.PublicKey,
-    funder: anchor.web3.PublicKey
+    funder: anchor.web3.PublicKey,
+    tokenProgram: anchor.web3.PublicKey
   ): Promise<anchor.web3.PublicKey> {
     const recentSlot = await this.provider.connection.getSlot()
     const [loookupTableInstruction, lookupTableAddress] =
@@ -184,7 +384,7 @@ export class TokenDispenserProvider {
         this.configPda[0],
         mint,
         treasury,
-        TOKEN_PROGRAM_ID,
+        tokenProgram,
         SystemProgram.programId,
         SYSVAR_INSTRUCTIONS_PUBKEY,
         splToken.ASSOCIATED_TOKEN_PROGRAM_ID,
@@ -213,13 +413,77 @@ export class TokenDispenserProvider {
     return resp.value
   }
 
-  public generateAuthorizationPayload(): string {
-    return AUTHORIZATION_PAYLOAD[0].concat(
+  /**
+   * `expiresAt` is an optional unix timestamp after which the signature can't be used to claim,
+   * it must be submitted with the claim. If `claim` is provided, this is the v2 payload, see
+   * `generateAuthorizationPayloadV2`. `nonce` is optional too, if provided the signature can only be used for the
+   * claim of the identity with this nonce, see `getReceiptNonce`, and it must also be submitted with the claim.
+   */
+  public generateAuthorizationPayload(
+    expiresAt?: number,
+    claim?: { amount: anchor.BN; merkleRoot: Uint8Array },
+    nonce?: number
+  ): string {
+    let payload = AUTHORIZATION_PAYLOAD[0].concat(
       this.programId.toString(),
+      this.isDefaultCampaign
+        ? ''
+        : CAMPAIGN_PAYLOAD.concat(this.campaignId.toString()),
       AUTHORIZATION_PAYLOAD[1],
       this.claimant.toString(),
       AUTHORIZATION_PAYLOAD[2]
     )
+    if (claim !== undefined) {
+      payload = payload.concat(
+        CLAIM_PAYLOAD[0],
+        claim.amount.toString(),
+        CLAIM_PAYLOAD[1],
+        Buffer.from(claim.merkleRoot).toString('hex'),
+        CLAIM_PAYLOAD[2]
+      )
+    }
+    if (expiresAt !== undefined) {
+      payload = payload.concat(
+        EXPIRY_PAYLOAD[0],
+        expiresAt.toString(),
+        EXPIRY_PAYLOAD[1]
+      )
+    }
+    if (nonce !== undefined) {
+      payload = payload.concat(
+        NONCE_PAYLOAD[0],
+        nonce.toString(),
+        NONCE_PAYLOAD[1]
+      )
+    }
+    return payload
+  }
+
+  /**
+   * The v2 payload, which also shows the amount of the claim and the merkle root of the campaign to the signer,
+   * so that the signature can't be used with another tree. The program accepts both payloads.
+   */
+  public async generateAuthorizationPayloadV2(
+    amount: anchor.BN,
+    expiresAt?: number,
+    nonce?: number
+  ): Promise<string> {
+    return this.generateAuthorizationPayload(
+      expiresAt,
+      {
+        amount,
+        merkleRoot: await this.getMerkleRoot(),
+      },
+      nonce
+    )
+  }
+
+  public async getMerkleRoot(): Promise<Buffer> {
+    const merkleRoot = (await this.getConfig()).merkleRoot as Record<
+      MerkleHasher,
+      { '0': number[] }
+    >
+    return Buffer.from(merkleRoot[await this.getMerkleHasher()]['0'])
   }
 
   public async submitClaims(
@@ -227,6 +491,8 @@ export class TokenDispenserProvider {
       claimInfo: ClaimInfo
       proofOfInclusion: Uint8Array[]
       signedMessage: SignedMessage | undefined
+      expiresAt?: number
+      nonce?: number
     }[],
     fetchFundTransactionFunction: (
       transactions: VersionedTransaction[]
@@ -240,7 +506,9 @@ export class TokenDispenserProvider {
           await this.generateClaimTransaction(
             claim.claimInfo,
             claim.proofOfInclusion,
-            claim.signedMessage
+            claim.signedMessage,
+            claim.expiresAt,
+            claim.nonce
           )
         )
       }
@@ -289,10 +557,16 @@ export class TokenDispenserProvider {
     return sendTxs
   }
 
+  /**
+   * `expiresAt` and `nonce` must be the deadline and the nonce of the payload signed in `signedMessage`,
+   * if it has them, see `generateAuthorizationPayload`.
+   */
   public async generateClaimTransaction(
     claimInfo: ClaimInfo,
     proofOfInclusion: Uint8Array[],
-    signedMessage: SignedMessage | undefined
+    signedMessage: SignedMessage | undefined,
+    expiresAt?: number,
+    nonce?: number
   ): Promise<VersionedTransaction> {
     // 1. generate claim certificate
     //    a. create proofOfIdentity
@@ -301,7 +575,9 @@ export class TokenDispenserProvider {
     const claimCert: IdlTypes<TokenDispenser>['ClaimCertificate'] = {
       amount: claimInfo.amount,
       proofOfIdentity,
-      proofOfInclusion,
+      proofOfInclusion: await this.getMerklePath(proofOfInclusion),
+      expiresAt: expiresAt === undefined ? null : new anchor.BN(expiresAt),
+      nonce: nonce === undefined ? null : new anchor.BN(nonce),
     }
 
     // 2. generate signature verification instruction if needed
@@ -312,13 +588,13 @@ export class TokenDispenserProvider {
       )
 
     // 3. derive receipt pda
-    const receiptPda = this.getReceiptPda(claimInfo)[0]
+    const [receiptPda] = this.getReceiptPda(claimInfo)
 
     const lookupTableAccount = await this.getLookupTableAccount()
 
     const ixs = signatureVerificationIx ? [signatureVerificationIx] : []
     const claim_ix = await this.tokenDispenserProgram.methods
-      .claim(claimCert)
+      .claim(this.campaignId, claimCert, this.getClaimBumps(claimInfo))
       .accounts({
         funder: (await this.getConfig()).funder,
         claimant: this.claimant,
@@ -326,18 +602,14 @@ export class TokenDispenserProvider {
         config: this.getConfigPda()[0],
         mint: (await this.getConfig()).mint,
         treasury: (await this.getConfig()).treasury,
-        tokenProgram: TOKEN_PROGRAM_ID,
+        tokenProgram: await this.getTokenProgramId(),
         systemProgram: anchor.web3.SystemProgram.programId,
         sysvarInstruction: anchor.web3.SYSVAR_INSTRUCTIONS_PUBKEY,
         associatedTokenProgram: splToken.ASSOCIATED_TOKEN_PROGRAM_ID,
       })
-      .remainingAccounts([
-        {
-          pubkey: receiptPda,
-          isWritable: true,
-          isSigner: false,
-        },
-      ])
+      .remainingAccounts(
+        await this.getClaimRemainingAccounts(claimInfo, receiptPda)
+      )
       .instruction()
     ixs.push(claim_ix)
     ixs.push(ComputeBudgetProgram.setComputeUnitLimit({ units: 200_000 }))
@@ -353,6 +625,52 @@ export class TokenDispenserProvider {
     return claimTx
   }
 
+  /**
+   * The remaining accounts of a claim in the order of `get_claim_instruction` of the program: the receipt,
+   * the claimant total if the campaign has a `maxPerClaimant`, the denylist entries of the identity and
+   * the claimant, and the legacy receipt of the leaf for the default campaign.
+   * Campaigns that require the signature of the dispenser guard can't be claimed with this class.
+   */
+  private async getClaimRemainingAccounts(
+    claimInfo: ClaimInfo,
+    receiptPda: PublicKey
+  ): Promise<anchor.web3.AccountMeta[]> {
+    const config = await this.getConfig()
+    const accounts: anchor.web3.AccountMeta[] = [
+      { pubkey: receiptPda, isWritable: true, isSigner: false },
+    ]
+    if (config.maxPerClaimant !== null) {
+      accounts.push({
+        pubkey: this.getClaimantTotalPda()[0],
+        isWritable: true,
+        isSigner: false,
+      })
+    }
+    accounts.push(
+      {
+        pubkey: this.getDenylistPda(
+          'identity',
+          this.getIdentityHash(claimInfo)
+        )[0],
+        isWritable: false,
+        isSigner: false,
+      },
+      {
+        pubkey: this.getDenylistPda('claimant', this.claimant.toBuffer())[0],
+        isWritable: false,
+        isSigner: false,
+      }
+    )
+    if (this.isDefaultCampaign) {
+      accounts.push({
+        pubkey: this.getLegacyReceiptPda(claimInfo)[0],
+        isWritable: false,
+        isSigner: false,
+      })
+    }
+    return accounts
+  }
+
   private createProofOfIdentity(
     claimInfo: ClaimInfo,
     signedMessage: SignedMessage | undefined
@@ -450,12 +768,30 @@ export class TokenDispenserProvider {
       )
     }
   }
+  // The mint can belong to the token program or to token-2022, the claimant fund is created under the same program
+  public async getTokenProgramId(): Promise<PublicKey> {
+    if (this.tokenProgramId === undefined) {
+      this.tokenProgramId = await this.getMintOwner(
+        (await this.getConfig()).mint
+      )
+    }
+    return this.tokenProgramId
+  }
+
+  public async getMintOwner(mint: PublicKey): Promise<PublicKey> {
+    const mintAccountInfo = await this.provider.connection.getAccountInfo(mint)
+    if (mintAccountInfo === null) {
+      throw new Error('mint account not found')
+    }
+    return mintAccountInfo.owner
+  }
+
   public async getClaimantFundAddress(): Promise<PublicKey> {
     const config = await this.getConfig()
     const associatedTokenAccount =
       await splToken.Token.getAssociatedTokenAddress(
         splToken.ASSOCIATED_TOKEN_PROGRAM_ID,
-        splToken.TOKEN_PROGRAM_ID,
+        await this.getTokenProgramId(),
         config.mint,
         this.claimant
       )
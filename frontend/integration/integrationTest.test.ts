@@ -130,7 +130,9 @@ describe('integration test', () => {
       const configAccount = await deployerTokenDispenserProvider.getConfig()
 
       expect(configAccount.bump).toEqual(configBump)
-      expect(configAccount.merkleRoot).toEqual(Array.from(root))
+      expect(configAccount.merkleRoot).toEqual({
+        keccak160: { '0': Array.from(root) },
+      })
       expect(configAccount.mint).toEqual(mint.publicKey)
       expect(configAccount.treasury).toEqual(treasury)
       expect(configAccount.dispenserGuard).toEqual(dispenserGuard)
@@ -233,8 +235,16 @@ describe('integration test', () => {
         testWallets.cosmwasm[0].address()
       ))!
 
+      // The signature can only be used for an hour and for the next claim of the identity
+      const expiresAt = Math.floor(Date.now() / 1000) + 60 * 60
+      const nonce = await tokenDispenserProvider.getReceiptNonce(claimInfo)
+      expect(nonce).toEqual(0)
       const signedMessage = await testWallets.cosmwasm[0].signMessage(
-        tokenDispenserProvider.generateAuthorizationPayload()
+        tokenDispenserProvider.generateAuthorizationPayload(
+          expiresAt,
+          undefined,
+          nonce
+        )
       )
 
       await Promise.all(
@@ -244,6 +254,8 @@ describe('integration test', () => {
               claimInfo,
               proofOfInclusion,
               signedMessage,
+              expiresAt,
+              nonce,
             },
           ],
           mockfetchFundTransaction
@@ -253,6 +265,7 @@ describe('integration test', () => {
       expect(
         await tokenDispenserProvider.isClaimAlreadySubmitted(claimInfo)
       ).toBeTruthy()
+      expect(await tokenDispenserProvider.getReceiptNonce(claimInfo)).toEqual(1)
 
       const claimantFundPubkey =
         await tokenDispenserProvider.getClaimantFundAddress()
@@ -1,23 +1,21 @@
 import BN from 'bn.js'
 import { ClaimInfo, Ecosystem } from '../claim_sdk/claim'
-import { HASH_SIZE } from '../claim_sdk/merkleTree'
+import { getHashSize } from '../claim_sdk/merkleTree'
 import { PublicKey, VersionedTransaction } from '@solana/web3.js'
 import { SignedMessage } from '../claim_sdk/ecosystems/signatures'
 import { EvmChains, SOLANA_SOURCES } from './db'
 
+// The proof is kept in one piece, the claim sdk splits it by the hash size of the tree of the campaign
 function parseProof(proof: string) {
   const buffer = Buffer.from(proof, 'hex')
-  const chunks = []
 
-  if (buffer.length % HASH_SIZE !== 0) {
-    throw new Error('Proof of inclusion must be a multiple of 32 bytes')
+  if (
+    buffer.length % getHashSize('keccak160') !== 0 &&
+    buffer.length % getHashSize('keccak256') !== 0
+  ) {
+    throw new Error('Proof of inclusion must be a multiple of 20 or 32 bytes')
   }
-
-  for (let i = 0; i < buffer.length; i += HASH_SIZE) {
-    const chunk = Uint8Array.prototype.slice.call(buffer, i, i + HASH_SIZE)
-    chunks.push(chunk)
-  }
-  return chunks
+  return [Uint8Array.prototype.slice.call(buffer)]
 }
 
 export function getAmountAndProofRoute(
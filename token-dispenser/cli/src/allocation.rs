@@ -0,0 +1,497 @@
+use {
+    anchor_lang::{
+        prelude::Pubkey,
+        AnchorSerialize,
+    },
+    anyhow::{
+        anyhow,
+        bail,
+        ensure,
+        Context,
+        Result,
+    },
+    bech32::FromBase32,
+    serde::{
+        Deserialize,
+        Serialize,
+    },
+    std::{
+        collections::HashSet,
+        fmt,
+        fs,
+        path::Path,
+        str::FromStr,
+    },
+    token_dispenser::{
+        ecosystems::{
+            aptos::AptosAddress,
+            cardano::CardanoKeyHash,
+            cosmos::{
+                CosmosBech32Address,
+                ADMISSIBLE_CHAIN_IDS,
+                INJECTIVE_CHAIN_ID,
+            },
+            ed25519::Ed25519Pubkey,
+            secp256k1::EvmPubkey,
+            secp256r1::{
+                Secp256r1Pubkey,
+                SECP256R1_EVEN_PREFIX,
+                SECP256R1_ODD_PREFIX,
+            },
+            starknet::StarknetAddress,
+            sui::SuiAddress,
+        },
+        ClaimInfo,
+        Identity,
+        IDENTITY_VARIANTS,
+    },
+};
+
+pub const CSV_HEADER: [&str; 3] = ["ecosystem", "identity", "amount"];
+
+/**
+ * The ecosystems of the `Identity` enum, named like in the typescript sdk and the database.
+ */
+#[derive(Serialize, Deserialize, Clone, Copy, PartialEq, Eq, Hash, Debug)]
+#[serde(rename_all = "lowercase")]
+pub enum Ecosystem {
+    Discord,
+    Solana,
+    Evm,
+    Sui,
+    Aptos,
+    Cosmwasm,
+    Injective,
+    Passkey,
+    Starknet,
+    Cardano,
+}
+
+impl Ecosystem {
+    /// In the order of the variants of `Identity`, so that `ecosystem as usize` is `Identity::get_variant_index`
+    pub const ALL: [Ecosystem; IDENTITY_VARIANTS] = [
+        Ecosystem::Discord,
+        Ecosystem::Solana,
+        Ecosystem::Evm,
+        Ecosystem::Sui,
+        Ecosystem::Aptos,
+        Ecosystem::Cosmwasm,
+        Ecosystem::Injective,
+        Ecosystem::Passkey,
+        Ecosystem::Starknet,
+        Ecosystem::Cardano,
+    ];
+}
+
+impl FromStr for Ecosystem {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> Result<Self> {
+        serde_json::from_value(serde_json::Value::String(s.to_string()))
+            .map_err(|_| anyhow!("Unknown ecosystem {}", s))
+    }
+}
+
+impl fmt::Display for Ecosystem {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match serde_json::to_value(self) {
+            Ok(serde_json::Value::String(name)) => write!(f, "{}", name),
+            _ => Err(fmt::Error),
+        }
+    }
+}
+
+/**
+ * A row of the allocation file, as provided by the user.
+ */
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub struct AllocationRow {
+    pub ecosystem: Ecosystem,
+    pub identity:  String,
+    pub amount:    u64,
+}
+
+/**
+ * An allocation whose identity has been validated and converted to the on-chain `Identity`.
+ * `identity` is the normalized string representation of the identity, it is used to key the proofs file.
+ */
+#[derive(Clone)]
+pub struct Allocation {
+    pub ecosystem:  Ecosystem,
+    pub identity:   String,
+    pub claim_info: ClaimInfo,
+}
+
+impl Allocation {
+    pub fn key(&self) -> String {
+        get_allocation_key(self.ecosystem, &self.identity)
+    }
+
+    /// The leaf of the merkle tree is the borsh-serialized `ClaimInfo`
+    pub fn leaf(&self) -> Vec<u8> {
+        self.claim_info.try_to_vec().unwrap()
+    }
+}
+
+pub fn get_allocation_key(ecosystem: Ecosystem, identity: &str) -> String {
+    format!("{}:{}", ecosystem, identity)
+}
+
+/**
+ * Reads an allocation file. JSON files contain an array of `AllocationRow`, every other file is
+ * treated as a CSV file with the `ecosystem,identity,amount` header.
+ */
+pub fn read_allocation_file(path: &Path) -> Result<Vec<AllocationRow>> {
+    let content = fs::read_to_string(path)
+        .with_context(|| format!("Failed to read allocation file {}", path.display()))?;
+    if path
+        .extension()
+        .map_or(false, |extension| extension == "json")
+    {
+        serde_json::from_str(&content)
+            .with_context(|| format!("Failed to parse allocation file {}", path.display()))
+    } else {
+        parse_allocation_csv(&content)
+    }
+}
+
+pub fn parse_allocation_csv(content: &str) -> Result<Vec<AllocationRow>> {
+    let mut reader = csv::ReaderBuilder::new()
+        .trim(csv::Trim::All)
+        .from_reader(content.as_bytes());
+    ensure!(
+        reader.headers()?.iter().eq(CSV_HEADER),
+        "The allocation file header should be {}",
+        CSV_HEADER.join(",")
+    );
+
+    reader
+        .deserialize()
+        .map(|row| row.context("Invalid allocation row"))
+        .collect()
+}
+
+/**
+ * Validates and normalizes every row, duplicated identities are rejected since each
+ * identity can only claim once.
+ */
+pub fn normalize_allocations(rows: &[AllocationRow]) -> Result<Vec<Allocation>> {
+    let mut keys = HashSet::new();
+    rows.iter()
+        .map(|row| {
+            let allocation = normalize_allocation(row)
+                .with_context(|| format!("Invalid {} identity {}", row.ecosystem, row.identity))?;
+            ensure!(
+                keys.insert(allocation.key()),
+                "Duplicate identity {}",
+                allocation.key()
+            );
+            Ok(allocation)
+        })
+        .collect()
+}
+
+pub fn normalize_allocation(row: &AllocationRow) -> Result<Allocation> {
+    let (identity, normalized_identity) = normalize_identity(row.ecosystem, &row.identity)?;
+    Ok(Allocation {
+        ecosystem:  row.ecosystem,
+        identity:   normalized_identity,
+        claim_info: ClaimInfo {
+            identity,
+            amount: row.amount,
+        },
+    })
+}
+
+/**
+ * Converts the user provided identity to the on-chain `Identity` and its normalized string representation.
+ * Addresses that are hex encoded are normalized to lowercase with a `0x` prefix and are zero padded.
+ */
+pub fn normalize_identity(ecosystem: Ecosystem, identity: &str) -> Result<(Identity, String)> {
+    match ecosystem {
+        Ecosystem::Discord => {
+            ensure!(!identity.is_empty(), "Empty username");
+            Ok((
+                Identity::Discord {
+                    username: identity.to_string(),
+                },
+                identity.to_string(),
+            ))
+        }
+        Ecosystem::Solana => {
+            let pubkey = Pubkey::from_str(identity)?;
+            Ok((
+                Identity::Solana {
+                    pubkey: Ed25519Pubkey::from(pubkey),
+                },
+                pubkey.to_string(),
+            ))
+        }
+        Ecosystem::Evm => {
+            let bytes: [u8; EvmPubkey::LEN] = parse_hex(identity, false)?;
+            Ok((
+                Identity::Evm {
+                    pubkey: EvmPubkey::from(bytes),
+                },
+                format_hex(&bytes),
+            ))
+        }
+        Ecosystem::Sui => {
+            let bytes: [u8; SuiAddress::LEN] = parse_hex(identity, true)?;
+            Ok((
+                Identity::Sui {
+                    address: SuiAddress::from(bytes),
+                },
+                format_hex(&bytes),
+            ))
+        }
+        Ecosystem::Aptos => {
+            let bytes: [u8; AptosAddress::LEN] = parse_hex(identity, true)?;
+            Ok((
+                Identity::Aptos {
+                    address: AptosAddress::from(bytes),
+                },
+                format_hex(&bytes),
+            ))
+        }
+        Ecosystem::Cosmwasm => {
+            let (chain_id, data) = parse_bech32(identity)?;
+            ensure!(
+                ADMISSIBLE_CHAIN_IDS.contains(&chain_id.as_str()),
+                "Unauthorized chain id {}",
+                chain_id
+            );
+            let address = bech32::encode(
+                &chain_id,
+                bech32::ToBase32::to_base32(&data),
+                bech32::Variant::Bech32,
+            )?;
+            Ok((
+                Identity::Cosmwasm {
+                    address: CosmosBech32Address::from(address.as_str()),
+                },
+                address,
+            ))
+        }
+        Ecosystem::Injective => {
+            let (chain_id, data) = parse_bech32(identity)?;
+            ensure!(
+                chain_id == INJECTIVE_CHAIN_ID,
+                "Expected a {} address",
+                INJECTIVE_CHAIN_ID
+            );
+            let bytes: [u8; EvmPubkey::LEN] = data
+                .try_into()
+                .map_err(|_| anyhow!("Expected {} bytes", EvmPubkey::LEN))?;
+            let address = bech32::encode(
+                INJECTIVE_CHAIN_ID,
+                bech32::ToBase32::to_base32(&bytes),
+                bech32::Variant::Bech32,
+            )?;
+            Ok((
+                Identity::Injective {
+                    address: CosmosBech32Address::from(EvmPubkey::from(bytes)),
+                },
+                address,
+            ))
+        }
+        Ecosystem::Passkey => {
+            let bytes: [u8; Secp256r1Pubkey::LEN] = parse_hex(identity, false)?;
+            ensure!(
+                bytes[0] == SECP256R1_EVEN_PREFIX || bytes[0] == SECP256R1_ODD_PREFIX,
+                "Expected a compressed public key"
+            );
+            Ok((
+                Identity::Passkey {
+                    pubkey: Secp256r1Pubkey::from(bytes),
+                },
+                format_hex(&bytes),
+            ))
+        }
+        Ecosystem::Starknet => {
+            let bytes: [u8; StarknetAddress::LEN] = parse_hex(identity, true)?;
+            let address = StarknetAddress::from(bytes);
+            ensure!(
+                address.is_field_element(),
+                "Starknet address is not a field element"
+            );
+            Ok((Identity::Starknet { address }, format_hex(&bytes)))
+        }
+        Ecosystem::Cardano => {
+            let bytes: [u8; CardanoKeyHash::LEN] = parse_hex(identity, false)?;
+            Ok((
+                Identity::Cardano {
+                    key_hash: CardanoKeyHash::from(bytes),
+                },
+                format_hex(&bytes),
+            ))
+        }
+    }
+}
+
+/// Parses a hex string with an optional `0x` prefix. Short inputs are left-padded with zeros if `pad` is set.
+fn parse_hex<const N: usize>(identity: &str, pad: bool) -> Result<[u8; N]> {
+    let digits = identity
+        .strip_prefix("0x")
+        .unwrap_or(identity)
+        .to_lowercase();
+    ensure!(
+        digits.len() == 2 * N || (pad && !digits.is_empty() && digits.len() < 2 * N),
+        "Expected {} bytes",
+        N
+    );
+    let mut bytes = [0u8; N];
+    let padded_digits = format!("{:0>width$}", digits, width = 2 * N);
+    hex::decode_to_slice(padded_digits, &mut bytes)?;
+    Ok(bytes)
+}
+
+fn format_hex(bytes: &[u8]) -> String {
+    format!("0x{}", hex::encode(bytes))
+}
+
+fn parse_bech32(identity: &str) -> Result<(String, Vec<u8>)> {
+    let (chain_id, data, variant) = bech32::decode(identity)?;
+    if variant != bech32::Variant::Bech32 {
+        bail!("Expected a bech32 address");
+    }
+    Ok((chain_id, Vec::<u8>::from_base32(&data)?))
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_parse_allocation_csv() {
+        let rows = parse_allocation_csv(
+            "ecosystem,identity,amount\nevm,0xf3f9225A2166861e745742509CED164183a626d7,2000\n\ndiscord, pepito ,1000\n",
+        )
+        .unwrap();
+        assert_eq!(rows.len(), 2);
+        assert_eq!(rows[0].ecosystem, Ecosystem::Evm);
+        assert_eq!(rows[0].amount, 2000);
+        assert_eq!(rows[1].ecosystem, Ecosystem::Discord);
+        assert_eq!(rows[1].identity, "pepito");
+
+        let rows = parse_allocation_csv(
+            "ecosystem,identity,amount\ndiscord,\"pepito, \"\"el\"\"\",1000\n",
+        )
+        .unwrap();
+        assert_eq!(rows[0].identity, "pepito, \"el\"");
+
+        assert!(parse_allocation_csv("identity,amount\npepito,1000").is_err());
+        assert!(parse_allocation_csv("ecosystem,identity,amount\nbitcoin,pepito,1000").is_err());
+        assert!(parse_allocation_csv("ecosystem,identity,amount\ndiscord,pepito,-1").is_err());
+    }
+
+    #[test]
+    fn test_normalize_identity() {
+        assert_eq!(
+            normalize_identity(Ecosystem::Evm, "0xf3f9225A2166861e745742509CED164183a626d7")
+                .unwrap()
+                .1,
+            "0xf3f9225a2166861e745742509ced164183a626d7"
+        );
+        assert!(
+            normalize_identity(Ecosystem::Evm, "0xf3f9225A2166861e745742509CED164183a626").is_err()
+        );
+
+        assert_eq!(
+            normalize_identity(Ecosystem::Aptos, "0x1").unwrap().1,
+            format!("0x{:0>64}", "1")
+        );
+
+        let passkey = format!(
+            "0x{}{}",
+            hex::encode([SECP256R1_EVEN_PREFIX]),
+            "11".repeat(32)
+        );
+        assert_eq!(
+            normalize_identity(Ecosystem::Passkey, &passkey).unwrap().1,
+            passkey
+        );
+        // Uncompressed points aren't accepted by the precompile
+        assert!(
+            normalize_identity(Ecosystem::Passkey, &passkey.replacen("0x02", "0x04", 1)).is_err()
+        );
+
+        assert_eq!(
+            normalize_identity(Ecosystem::Starknet, "0x1").unwrap().1,
+            format!("0x{:0>64}", "1")
+        );
+        // The prime of the STARK field
+        assert!(normalize_identity(
+            Ecosystem::Starknet,
+            "0x800000000000011000000000000000000000000000000000000000000000001"
+        )
+        .is_err());
+
+        assert_eq!(
+            normalize_identity(
+                Ecosystem::Solana,
+                "3kzAHeiucNConBwKQVHyLcG3soaMzSZkvs4y14fmMgKL"
+            )
+            .unwrap()
+            .1,
+            "3kzAHeiucNConBwKQVHyLcG3soaMzSZkvs4y14fmMgKL"
+        );
+
+        let injective_address = bech32::encode(
+            INJECTIVE_CHAIN_ID,
+            bech32::ToBase32::to_base32(&[1u8; EvmPubkey::LEN]),
+            bech32::Variant::Bech32,
+        )
+        .unwrap();
+        assert_eq!(
+            normalize_identity(Ecosystem::Injective, &injective_address.to_uppercase())
+                .unwrap()
+                .1,
+            injective_address
+        );
+        assert!(normalize_identity(Ecosystem::Cosmwasm, &injective_address).is_err());
+    }
+
+    #[test]
+    fn test_ecosystem_order() {
+        for (i, ecosystem) in Ecosystem::ALL.iter().enumerate() {
+            assert_eq!(*ecosystem as usize, i);
+        }
+        let identities = [
+            (Ecosystem::Discord, "pepito"),
+            (
+                Ecosystem::Solana,
+                "3kzAHeiucNConBwKQVHyLcG3soaMzSZkvs4y14fmMgKL",
+            ),
+            (Ecosystem::Evm, "0xf3f9225A2166861e745742509CED164183a626d7"),
+            (Ecosystem::Aptos, "0x1"),
+        ];
+        for (ecosystem, identity) in identities {
+            assert_eq!(
+                normalize_identity(ecosystem, identity)
+                    .unwrap()
+                    .0
+                    .get_variant_index(),
+                ecosystem as usize
+            );
+        }
+    }
+
+    #[test]
+    fn test_normalize_allocations_rejects_duplicates() {
+        let rows = vec![
+            AllocationRow {
+                ecosystem: Ecosystem::Evm,
+                identity:  "0xf3f9225A2166861e745742509CED164183a626d7".to_string(),
+                amount:    1000,
+            },
+            AllocationRow {
+                ecosystem: Ecosystem::Evm,
+                identity:  "0xf3f9225a2166861e745742509ced164183a626d7".to_string(),
+                amount:    2000,
+            },
+        ];
+        assert!(normalize_allocations(&rows[..1]).is_ok());
+        assert!(normalize_allocations(&rows).is_err());
+    }
+}
@@ -0,0 +1,253 @@
+use {
+    crate::allocation::{
+        Allocation,
+        Ecosystem,
+    },
+    anyhow::{
+        Context,
+        Result,
+    },
+    std::{
+        collections::HashSet,
+        fmt,
+        fs::File,
+        io::BufReader,
+        path::Path,
+    },
+    token_dispenser::{
+        client::indexer::{
+            CampaignClaimIndex,
+            ClaimIndex,
+        },
+        get_identity_hash,
+    },
+};
+
+/**
+ * Builds the claim index from a JSONL dump of transaction logs, see `ClaimIndex::ingest_jsonl`.
+ */
+pub fn read_claim_index(path: &Path) -> Result<ClaimIndex> {
+    let file = File::open(path).with_context(|| format!("Failed to read {}", path.display()))?;
+    let mut claim_index = ClaimIndex::default();
+    claim_index
+        .ingest_jsonl(BufReader::new(file))
+        .with_context(|| format!("Failed to parse the logs in {}", path.display()))?;
+    Ok(claim_index)
+}
+
+pub struct UnclaimedAllocation {
+    pub key:              String,
+    pub allocated_amount: u64,
+    pub claimed_amount:   u64,
+}
+
+/**
+ * The claims found in the logs, cross-checked against the allocation file of the campaign.
+ */
+pub struct IndexReport {
+    pub claimed_per_ecosystem: Vec<(Ecosystem, u64)>,
+    pub total_claimed:         u64,
+    pub claim_count:           u64,
+    pub remaining_balance:     Option<u64>,
+    pub unclaimed:             Vec<UnclaimedAllocation>,
+    /// Identities that claimed but are not in the allocation file, a sign that the file is from another campaign
+    pub unknown_claims:        usize,
+    /// Program data logs that aren't a known event, in any campaign
+    pub undecodable_events:    usize,
+}
+
+pub fn build_index_report(
+    claim_index: &ClaimIndex,
+    campaign_id: u64,
+    allocations: &[Allocation],
+) -> IndexReport {
+    let undecodable_events = claim_index.undecodable_events.len();
+    let empty_claim_index = CampaignClaimIndex::default();
+    let claim_index = claim_index
+        .get_campaign(campaign_id)
+        .unwrap_or(&empty_claim_index);
+    let unclaimed = allocations
+        .iter()
+        .filter_map(|allocation| {
+            let claimed_amount = claim_index.get_amount_claimed(&allocation.claim_info.identity);
+            (allocation.claim_info.amount > claimed_amount).then(|| UnclaimedAllocation {
+                key: allocation.key(),
+                allocated_amount: allocation.claim_info.amount,
+                claimed_amount,
+            })
+        })
+        .collect();
+
+    let allocated_identities: HashSet<_> = allocations
+        .iter()
+        .map(|allocation| get_identity_hash(&allocation.claim_info.identity))
+        .collect();
+    let unknown_claims = claim_index
+        .claims()
+        .filter(|indexed_claim| {
+            !allocated_identities.contains(&get_identity_hash(&indexed_claim.identity))
+        })
+        .count();
+
+    IndexReport {
+        claimed_per_ecosystem: Ecosystem::ALL
+            .iter()
+            .map(|ecosystem| {
+                (
+                    *ecosystem,
+                    claim_index.claimed_per_ecosystem[*ecosystem as usize],
+                )
+            })
+            .collect(),
+        total_claimed: claim_index.total_claimed,
+        claim_count: claim_index.claim_count,
+        remaining_balance: claim_index.remaining_balance,
+        unclaimed,
+        unknown_claims,
+        undecodable_events,
+    }
+}
+
+impl fmt::Display for IndexReport {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        writeln!(f, "Claims: {}", self.claim_count)?;
+        writeln!(f, "Total claimed: {}", self.total_claimed)?;
+        for (ecosystem, amount) in &self.claimed_per_ecosystem {
+            writeln!(f, "  {}: {}", ecosystem, amount)?;
+        }
+        match self.remaining_balance {
+            Some(remaining_balance) => writeln!(f, "Remaining balance: {}", remaining_balance)?,
+            None => writeln!(f, "Remaining balance: unknown (no claims)")?,
+        }
+        if self.undecodable_events > 0 {
+            writeln!(
+                f,
+                "Warning: {} events in the logs could not be decoded, their claims are missing",
+                self.undecodable_events
+            )?;
+        }
+        if self.unknown_claims > 0 {
+            writeln!(
+                f,
+                "Warning: {} claimed identities are not in the allocation file",
+                self.unknown_claims
+            )?;
+        }
+        writeln!(f, "Unclaimed allocations: {}", self.unclaimed.len())?;
+        for unclaimed in &self.unclaimed {
+            writeln!(
+                f,
+                "  {} {} (claimed {})",
+                unclaimed.key, unclaimed.allocated_amount, unclaimed.claimed_amount
+            )?;
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use {
+        super::*,
+        crate::allocation::{
+            normalize_allocations,
+            AllocationRow,
+        },
+        anchor_lang::prelude::Pubkey,
+        token_dispenser::{
+            ClaimEvent,
+            DEFAULT_CAMPAIGN_ID,
+        },
+    };
+
+    #[test]
+    fn test_build_index_report() {
+        let rows = vec![
+            AllocationRow {
+                ecosystem: Ecosystem::Discord,
+                identity:  "pepito".to_string(),
+                amount:    1000,
+            },
+            AllocationRow {
+                ecosystem: Ecosystem::Evm,
+                identity:  "0xf3f9225A2166861e745742509CED164183a626d7".to_string(),
+                amount:    2000,
+            },
+            AllocationRow {
+                ecosystem: Ecosystem::Discord,
+                identity:  "pepita".to_string(),
+                amount:    3000,
+            },
+        ];
+        let allocations = normalize_allocations(&rows).unwrap();
+        let unknown_allocation = normalize_allocations(&[AllocationRow {
+            ecosystem: Ecosystem::Discord,
+            identity:  "stranger".to_string(),
+            amount:    10,
+        }])
+        .unwrap();
+
+        let mut claim_index = ClaimIndex::default();
+        for (claim_info, remaining_balance) in [
+            (allocations[0].claim_info.clone(), 5000),
+            (allocations[1].claim_info.clone(), 3000),
+            (unknown_allocation[0].claim_info.clone(), 2990),
+        ] {
+            claim_index.ingest_claim_event(
+                DEFAULT_CAMPAIGN_ID,
+                &ClaimEvent {
+                    remaining_balance,
+                    claimant: Pubkey::new_unique(),
+                    amount_received: claim_info.amount,
+                    claim_info,
+                },
+            );
+        }
+
+        // Claims of another campaign
+        claim_index.ingest_claim_event(
+            1,
+            &ClaimEvent {
+                remaining_balance: 0,
+                claimant:          Pubkey::new_unique(),
+                amount_received:   3000,
+                claim_info:        allocations[2].claim_info.clone(),
+            },
+        );
+        claim_index.ingest_logs(&[
+            format!("Program {} invoke [1]", token_dispenser::id()),
+            "Program data: bm90IGFuIGV2ZW50".to_string(),
+            format!("Program {} success", token_dispenser::id()),
+        ]);
+
+        let report = build_index_report(&claim_index, DEFAULT_CAMPAIGN_ID, &allocations);
+        assert_eq!(report.claim_count, 3);
+        assert_eq!(report.total_claimed, 3010);
+        assert_eq!(
+            report.claimed_per_ecosystem[Ecosystem::Discord as usize],
+            (Ecosystem::Discord, 1010)
+        );
+        assert_eq!(
+            report.claimed_per_ecosystem[Ecosystem::Evm as usize],
+            (Ecosystem::Evm, 2000)
+        );
+        assert_eq!(report.remaining_balance, Some(2990));
+        assert_eq!(report.unknown_claims, 1);
+        assert_eq!(report.unclaimed.len(), 1);
+        assert_eq!(report.unclaimed[0].key, "discord:pepita");
+        assert_eq!(report.unclaimed[0].allocated_amount, 3000);
+        assert_eq!(report.unclaimed[0].claimed_amount, 0);
+        assert_eq!(report.undecodable_events, 1);
+        assert!(report
+            .to_string()
+            .contains("1 events in the logs could not be decoded"));
+
+        let report = build_index_report(&claim_index, 1, &allocations);
+        assert_eq!(report.total_claimed, 3000);
+        assert_eq!(report.unclaimed.len(), 2);
+        assert_eq!(
+            build_index_report(&claim_index, 2, &allocations).claim_count,
+            0
+        );
+    }
+}
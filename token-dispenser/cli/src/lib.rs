@@ -0,0 +1,4 @@
+pub mod allocation;
+pub mod index;
+pub mod proofs;
+pub mod verify;
@@ -0,0 +1,164 @@
+use {
+    anyhow::Result,
+    clap::Parser,
+    std::path::PathBuf,
+    token_dispenser::DEFAULT_CAMPAIGN_ID,
+    token_dispenser_cli::{
+        allocation::{
+            normalize_allocation,
+            normalize_allocations,
+            read_allocation_file,
+            AllocationRow,
+            Ecosystem,
+        },
+        index::{
+            build_index_report,
+            read_claim_index,
+        },
+        proofs::{
+            build_merkle_tree,
+            decode_merkle_root,
+            MerkleHasher,
+            ProofsFile,
+        },
+        verify::{
+            check_leaf,
+            AccountDump,
+        },
+    },
+};
+
+#[derive(Parser)]
+#[clap(author, version, about = "Off-chain tooling for the token dispenser")]
+struct Cli {
+    #[clap(subcommand)]
+    command: Command,
+}
+
+#[derive(Parser)]
+enum Command {
+    /// Builds the merkle tree from an allocation file, prints the root and writes the proofs file
+    BuildTree {
+        /// Allocation file, either a JSON array or a CSV file with the `ecosystem,identity,amount` header
+        #[clap(long)]
+        allocations: PathBuf,
+        /// Output file for the proofs of inclusion, keyed by `ecosystem:identity`
+        #[clap(long, default_value = "proofs.json")]
+        proofs:      PathBuf,
+        /// Hasher of the tree, keccak160 (20-byte hashes) or keccak256 (32-byte hashes)
+        #[clap(long, default_value = "keccak160")]
+        hasher:      MerkleHasher,
+    },
+    /// Checks whether an identity and amount are included in the merkle tree and whether they have been claimed
+    CheckProof {
+        /// Proofs file written by `build-tree`
+        #[clap(long)]
+        proofs:    PathBuf,
+        /// One of discord, solana, evm, sui, aptos, cosmwasm, injective, passkey, starknet, cardano
+        #[clap(long)]
+        ecosystem: Ecosystem,
+        #[clap(long)]
+        identity:  String,
+        #[clap(long)]
+        amount:    u64,
+        /// Hex encoded merkle root stored in the on-chain config, defaults to the root of the proofs file.
+        /// It is decoded with the hasher of the proofs file
+        #[clap(long)]
+        root:      Option<String>,
+        /// JSON dump of the program accounts, used to check whether the receipt and the legacy receipt exist
+        #[clap(long)]
+        accounts:  Option<PathBuf>,
+        /// Campaign of the tree, receipts are scoped to it
+        #[clap(long, default_value_t = DEFAULT_CAMPAIGN_ID)]
+        campaign:  u64,
+    },
+    /// Rebuilds the claims of a campaign from its transaction logs and lists the unclaimed allocations
+    Index {
+        /// JSONL file with one transaction per line, either its array of log messages or the `getTransaction` response
+        #[clap(long)]
+        logs:        PathBuf,
+        /// Allocation file of the campaign
+        #[clap(long)]
+        allocations: PathBuf,
+        /// Campaign whose claims are reported, the logs may contain the claims of other campaigns
+        #[clap(long, default_value_t = DEFAULT_CAMPAIGN_ID)]
+        campaign:    u64,
+    },
+}
+
+fn main() -> Result<()> {
+    match Cli::parse().command {
+        Command::BuildTree {
+            allocations,
+            proofs,
+            hasher,
+        } => {
+            let allocations = normalize_allocations(&read_allocation_file(&allocations)?)?;
+            let (_, proofs_file) = build_merkle_tree(&allocations, hasher)?;
+            proofs_file.write(&proofs)?;
+
+            let total = allocations
+                .iter()
+                .try_fold(0u64, |acc, allocation| {
+                    acc.checked_add(allocation.claim_info.amount)
+                })
+                .ok_or_else(|| anyhow::anyhow!("Total allocation overflows"))?;
+            let max_transfer = allocations
+                .iter()
+                .map(|allocation| allocation.claim_info.amount)
+                .max()
+                .unwrap_or_default();
+
+            println!("Merkle root: {}", proofs_file.root);
+            println!("Leaves: {}", allocations.len());
+            println!("Total amount: {}", total);
+            println!("Max transfer: {}", max_transfer);
+            println!("Proofs written to {}", proofs.display());
+        }
+        Command::CheckProof {
+            proofs,
+            ecosystem,
+            identity,
+            amount,
+            root,
+            accounts,
+            campaign,
+        } => {
+            let proofs_file = ProofsFile::read(&proofs)?;
+            let merkle_root = match root {
+                Some(root) => decode_merkle_root(proofs_file.hasher, &root)?,
+                None => proofs_file.get_merkle_root()?,
+            };
+            let account_dump = accounts.map(|path| AccountDump::read(&path)).transpose()?;
+            let allocation = normalize_allocation(&AllocationRow {
+                ecosystem,
+                identity,
+                amount,
+            })?;
+
+            print!(
+                "{}",
+                check_leaf(
+                    &merkle_root,
+                    campaign,
+                    &allocation,
+                    &proofs_file,
+                    account_dump.as_ref()
+                )?
+            );
+        }
+        Command::Index {
+            logs,
+            allocations,
+            campaign,
+        } => {
+            let allocations = normalize_allocations(&read_allocation_file(&allocations)?)?;
+            let claim_index = read_claim_index(&logs)?;
+            print!(
+                "{}",
+                build_index_report(&claim_index, campaign, &allocations)
+            );
+        }
+    }
+    Ok(())
+}
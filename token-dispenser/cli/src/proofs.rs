@@ -0,0 +1,310 @@
+use {
+    crate::allocation::{
+        get_allocation_key,
+        Allocation,
+        Ecosystem,
+    },
+    anchor_lang::{
+        AnchorDeserialize,
+        AnchorSerialize,
+    },
+    anyhow::{
+        anyhow,
+        ensure,
+        Context,
+        Result,
+    },
+    pythnet_sdk::{
+        accumulators::{
+            merkle::{
+                MerklePath,
+                MerkleRoot,
+                MerkleTree,
+            },
+            Accumulator,
+        },
+        hashers::Hasher,
+    },
+    serde::{
+        Deserialize,
+        Serialize,
+    },
+    std::{
+        collections::BTreeMap,
+        fs,
+        path::Path,
+        str::FromStr,
+    },
+    token_dispenser::{
+        DispenserMerklePath,
+        DispenserMerkleRoot,
+        SolanaHasher,
+        SolanaHasher32,
+    },
+};
+
+/**
+ * The hasher of the tree, named after the variants of `DispenserMerkleRoot`. Trees are 20-byte by default,
+ * like the ones built before the hasher could be picked.
+ */
+#[derive(Serialize, Deserialize, Clone, Copy, PartialEq, Eq, Debug, Default)]
+#[serde(rename_all = "lowercase")]
+pub enum MerkleHasher {
+    #[default]
+    Keccak160,
+    Keccak256,
+}
+
+impl MerkleHasher {
+    pub fn get_hash_size(&self) -> usize {
+        match self {
+            MerkleHasher::Keccak160 => 20,
+            MerkleHasher::Keccak256 => 32,
+        }
+    }
+}
+
+impl FromStr for MerkleHasher {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> Result<Self> {
+        serde_json::from_value(serde_json::Value::String(s.to_string()))
+            .map_err(|_| anyhow!("Unknown hasher {}", s))
+    }
+}
+
+/**
+ * The proof of inclusion of a single allocation.
+ * `proof` contains the hex encoded sibling hashes, from the leaf to the root.
+ */
+#[derive(Serialize, Deserialize, Clone, PartialEq, Eq, Debug)]
+pub struct LeafProof {
+    pub ecosystem: Ecosystem,
+    pub identity:  String,
+    pub amount:    u64,
+    pub proof:     Vec<String>,
+}
+
+/**
+ * The file distributed to the frontend and the backend, it contains the proof of inclusion of every allocation
+ * keyed by `ecosystem:identity`.
+ */
+#[derive(Serialize, Deserialize, Clone, PartialEq, Eq, Debug)]
+pub struct ProofsFile {
+    #[serde(default)]
+    pub hasher: MerkleHasher,
+    pub root:   String,
+    pub proofs: BTreeMap<String, LeafProof>,
+}
+
+impl ProofsFile {
+    pub fn read(path: &Path) -> Result<Self> {
+        let content = fs::read_to_string(path)
+            .with_context(|| format!("Failed to read proofs file {}", path.display()))?;
+        serde_json::from_str(&content)
+            .with_context(|| format!("Failed to parse proofs file {}", path.display()))
+    }
+
+    pub fn write(&self, path: &Path) -> Result<()> {
+        fs::write(path, serde_json::to_string_pretty(self)?)
+            .with_context(|| format!("Failed to write proofs file {}", path.display()))
+    }
+
+    pub fn get_merkle_root(&self) -> Result<DispenserMerkleRoot> {
+        decode_merkle_root(self.hasher, &self.root)
+    }
+
+    pub fn get(&self, ecosystem: Ecosystem, identity: &str) -> Option<&LeafProof> {
+        self.proofs.get(&get_allocation_key(ecosystem, identity))
+    }
+}
+
+impl LeafProof {
+    pub fn get_merkle_path(&self, hasher: MerkleHasher) -> Result<DispenserMerklePath> {
+        // The borsh serialization of a `DispenserMerklePath` is the variant, a u32 length and the hashes
+        let mut bytes = vec![hasher as u8];
+        bytes.extend_from_slice(&(self.proof.len() as u32).to_le_bytes());
+        for node in self.proof.iter() {
+            bytes.extend_from_slice(&decode_hash(hasher, node)?);
+        }
+        Ok(DispenserMerklePath::try_from_slice(&bytes)?)
+    }
+}
+
+/**
+ * Builds the merkle tree whose leaves are the borsh-serialized `ClaimInfo`s of the allocations
+ * and the proofs of inclusion of every leaf.
+ */
+pub fn build_merkle_tree(
+    allocations: &[Allocation],
+    hasher: MerkleHasher,
+) -> Result<(DispenserMerkleRoot, ProofsFile)> {
+    ensure!(!allocations.is_empty(), "No allocations");
+    let leaves = allocations
+        .iter()
+        .map(|allocation| allocation.leaf())
+        .collect::<Vec<Vec<u8>>>();
+
+    let (merkle_root, merkle_paths): (DispenserMerkleRoot, Vec<Option<DispenserMerklePath>>) =
+        match hasher {
+            MerkleHasher::Keccak160 => {
+                let (merkle_root, merkle_paths) = prove_leaves::<SolanaHasher>(&leaves)?;
+                (
+                    merkle_root.into(),
+                    merkle_paths
+                        .into_iter()
+                        .map(|merkle_path| merkle_path.map(Into::into))
+                        .collect(),
+                )
+            }
+            MerkleHasher::Keccak256 => {
+                let (merkle_root, merkle_paths) = prove_leaves::<SolanaHasher32>(&leaves)?;
+                (
+                    merkle_root.into(),
+                    merkle_paths
+                        .into_iter()
+                        .map(|merkle_path| merkle_path.map(Into::into))
+                        .collect(),
+                )
+            }
+        };
+
+    let proofs = allocations
+        .iter()
+        .zip(merkle_paths.iter())
+        .map(|(allocation, merkle_path)| {
+            let merkle_path = merkle_path
+                .as_ref()
+                .ok_or_else(|| anyhow!("Failed to prove {}", allocation.key()))?;
+            Ok((
+                allocation.key(),
+                LeafProof {
+                    ecosystem: allocation.ecosystem,
+                    identity:  allocation.identity.clone(),
+                    amount:    allocation.claim_info.amount,
+                    proof:     encode_merkle_path(hasher, merkle_path),
+                },
+            ))
+        })
+        .collect::<Result<BTreeMap<String, LeafProof>>>()?;
+
+    let proofs_file = ProofsFile {
+        hasher,
+        root: hex::encode(merkle_root.as_bytes()),
+        proofs,
+    };
+    Ok((merkle_root, proofs_file))
+}
+
+fn prove_leaves<H: Hasher>(
+    leaves: &[Vec<u8>],
+) -> Result<(MerkleRoot<H>, Vec<Option<MerklePath<H>>>)> {
+    let merkle_tree: MerkleTree<H> = MerkleTree::new(
+        leaves
+            .iter()
+            .map(|leaf| leaf.as_slice())
+            .collect::<Vec<&[u8]>>()
+            .as_slice(),
+    )
+    .ok_or_else(|| anyhow!("Failed to build the merkle tree"))?;
+    let merkle_paths = leaves.iter().map(|leaf| merkle_tree.prove(leaf)).collect();
+    Ok((merkle_tree.root, merkle_paths))
+}
+
+pub fn encode_merkle_path(hasher: MerkleHasher, merkle_path: &DispenserMerklePath) -> Vec<String> {
+    // Skip the variant and the u32 length that precede the hashes
+    merkle_path.try_to_vec().unwrap()[5..]
+        .chunks(hasher.get_hash_size())
+        .map(hex::encode)
+        .collect()
+}
+
+pub fn decode_merkle_root(hasher: MerkleHasher, root: &str) -> Result<DispenserMerkleRoot> {
+    let mut bytes = vec![hasher as u8];
+    bytes.extend_from_slice(&decode_hash(hasher, root)?);
+    Ok(DispenserMerkleRoot::try_from_slice(&bytes)?)
+}
+
+fn decode_hash(hasher: MerkleHasher, hash: &str) -> Result<Vec<u8>> {
+    let mut bytes = vec![0u8; hasher.get_hash_size()];
+    hex::decode_to_slice(hash.strip_prefix("0x").unwrap_or(hash), &mut bytes)
+        .with_context(|| format!("Invalid hash {}", hash))?;
+    Ok(bytes)
+}
+
+#[cfg(test)]
+mod test {
+    use {
+        super::*,
+        crate::allocation::{
+            normalize_allocations,
+            AllocationRow,
+        },
+    };
+
+    #[test]
+    fn test_build_merkle_tree() {
+        let rows = (0..5)
+            .map(|i| AllocationRow {
+                ecosystem: Ecosystem::Discord,
+                identity:  format!("user{}", i),
+                amount:    1000 * (i + 1),
+            })
+            .chain(std::iter::once(AllocationRow {
+                ecosystem: Ecosystem::Evm,
+                identity:  "0xf3f9225A2166861e745742509CED164183a626d7".to_string(),
+                amount:    3000,
+            }))
+            .collect::<Vec<AllocationRow>>();
+        let allocations = normalize_allocations(&rows).unwrap();
+
+        for hasher in [MerkleHasher::Keccak160, MerkleHasher::Keccak256] {
+            let (merkle_root, proofs_file) = build_merkle_tree(&allocations, hasher).unwrap();
+            assert_eq!(proofs_file.root.len(), 2 * hasher.get_hash_size());
+
+            let round_tripped: ProofsFile =
+                serde_json::from_str(&serde_json::to_string(&proofs_file).unwrap()).unwrap();
+            assert_eq!(round_tripped, proofs_file);
+            assert_eq!(proofs_file.get_merkle_root().unwrap(), merkle_root);
+
+            for allocation in allocations.iter() {
+                let leaf_proof = proofs_file
+                    .get(allocation.ecosystem, &allocation.identity)
+                    .unwrap();
+                assert_eq!(leaf_proof.amount, allocation.claim_info.amount);
+                assert!(merkle_root.check(
+                    leaf_proof.get_merkle_path(hasher).unwrap(),
+                    &allocation.leaf()
+                ));
+            }
+
+            let leaf_proof = proofs_file
+                .get(Ecosystem::Evm, "0xf3f9225a2166861e745742509ced164183a626d7")
+                .unwrap();
+            assert!(!merkle_root.check(
+                leaf_proof.get_merkle_path(hasher).unwrap(),
+                &allocations[0].leaf()
+            ));
+        }
+    }
+
+    #[test]
+    fn test_proofs_file_without_hasher() {
+        // Proofs files written before the hasher could be picked are 20-byte trees
+        let proofs_file: ProofsFile = serde_json::from_str(&format!(
+            r#"{{"root": "{}", "proofs": {{}}}}"#,
+            "ab".repeat(20)
+        ))
+        .unwrap();
+        assert_eq!(proofs_file.hasher, MerkleHasher::Keccak160);
+        assert_eq!(
+            proofs_file.get_merkle_root().unwrap().as_bytes(),
+            [0xab; 20]
+        );
+        assert!(decode_merkle_root(MerkleHasher::Keccak256, &proofs_file.root).is_err());
+        assert_eq!(
+            "keccak256".parse::<MerkleHasher>().unwrap(),
+            MerkleHasher::Keccak256
+        );
+    }
+}
@@ -0,0 +1,299 @@
+use {
+    crate::{
+        allocation::Allocation,
+        proofs::ProofsFile,
+    },
+    anchor_lang::prelude::Pubkey,
+    anyhow::{
+        Context,
+        Result,
+    },
+    serde::Deserialize,
+    std::{
+        collections::HashMap,
+        fmt,
+        fs,
+        path::Path,
+        str::FromStr,
+    },
+    token_dispenser::{
+        get_legacy_receipt_pda,
+        get_receipt_pda,
+        DispenserMerkleRoot,
+        DEFAULT_CAMPAIGN_ID,
+        HASH_SIZE,
+    },
+};
+
+/**
+ * The owners of a set of accounts, keyed by address.
+ * It is read from a JSON array of `{ "pubkey": ..., "account": { "owner": ..., ... } }`, which is the format
+ * returned by the `getProgramAccounts` and `getMultipleAccounts` RPC methods with `jsonParsed` or `base64` encoding.
+ */
+pub struct AccountDump(HashMap<Pubkey, Pubkey>);
+
+#[derive(Deserialize)]
+struct KeyedAccount {
+    pubkey:  String,
+    account: AccountOwner,
+}
+
+#[derive(Deserialize)]
+struct AccountOwner {
+    owner: String,
+}
+
+impl AccountDump {
+    pub fn read(path: &Path) -> Result<Self> {
+        let content = fs::read_to_string(path)
+            .with_context(|| format!("Failed to read account dump {}", path.display()))?;
+        Self::parse(&content)
+            .with_context(|| format!("Failed to parse account dump {}", path.display()))
+    }
+
+    pub fn parse(content: &str) -> Result<Self> {
+        let keyed_accounts: Vec<KeyedAccount> = serde_json::from_str(content)?;
+        Ok(AccountDump(
+            keyed_accounts
+                .iter()
+                .map(|keyed_account| {
+                    Ok((
+                        Pubkey::from_str(&keyed_account.pubkey)?,
+                        Pubkey::from_str(&keyed_account.account.owner)?,
+                    ))
+                })
+                .collect::<Result<HashMap<Pubkey, Pubkey>>>()?,
+        ))
+    }
+
+    /// The receipt of an identity is owned by the program once the identity has claimed
+    pub fn is_claim_receipt_initialized(&self, receipt: &Pubkey) -> bool {
+        self.0
+            .get(receipt)
+            .map_or(false, |owner| *owner == token_dispenser::id())
+    }
+}
+
+/**
+ * Everything support needs to know about why a claim would fail the inclusion or the receipt check.
+ */
+pub struct LeafReport {
+    pub key:                   String,
+    /// Zero-padded to `HASH_SIZE` like the leaf hashes of the denylist and of the claim events
+    pub leaf_hash:             [u8; HASH_SIZE],
+    /// The amount allocated to this identity in the proofs file, if any
+    pub allocated_amount:      Option<u64>,
+    pub root:                  String,
+    pub proofs_file_root:      String,
+    /// Whether the proof of the proofs file passes `MerkleRoot::check` for the requested root and amount
+    pub proof_valid:           bool,
+    pub receipt:               Pubkey,
+    /// Whether the receipt exists, `None` if no account dump was supplied
+    pub receipt_exists:        Option<bool>,
+    /// Receipt of the leaf created before receipts were keyed by identity, only for the default campaign
+    pub legacy_receipt:        Option<Pubkey>,
+    /// Whether the legacy receipt exists, `None` if there is no legacy receipt or no account dump was supplied
+    pub legacy_receipt_exists: Option<bool>,
+}
+
+pub fn check_leaf(
+    merkle_root: &DispenserMerkleRoot,
+    campaign_id: u64,
+    allocation: &Allocation,
+    proofs_file: &ProofsFile,
+    account_dump: Option<&AccountDump>,
+) -> Result<LeafReport> {
+    let leaf = allocation.leaf();
+    let leaf_proof = proofs_file.get(allocation.ecosystem, &allocation.identity);
+    let proof_valid = match leaf_proof {
+        Some(leaf_proof) => {
+            merkle_root.check(leaf_proof.get_merkle_path(proofs_file.hasher)?, &leaf)
+        }
+        None => false,
+    };
+    let receipt = get_receipt_pda(campaign_id, &allocation.claim_info.identity).0;
+    let legacy_receipt =
+        (campaign_id == DEFAULT_CAMPAIGN_ID).then(|| get_legacy_receipt_pda(&leaf).0);
+
+    Ok(LeafReport {
+        key: allocation.key(),
+        leaf_hash: merkle_root.hash_leaf(&leaf),
+        allocated_amount: leaf_proof.map(|leaf_proof| leaf_proof.amount),
+        root: hex::encode(merkle_root.as_bytes()),
+        proofs_file_root: proofs_file.root.clone(),
+        proof_valid,
+        receipt,
+        receipt_exists: account_dump
+            .map(|account_dump| account_dump.is_claim_receipt_initialized(&receipt)),
+        legacy_receipt,
+        legacy_receipt_exists: legacy_receipt.zip(account_dump).map(
+            |(legacy_receipt, account_dump)| {
+                account_dump.is_claim_receipt_initialized(&legacy_receipt)
+            },
+        ),
+    })
+}
+
+impl fmt::Display for LeafReport {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        writeln!(f, "Leaf: {}", self.key)?;
+        writeln!(f, "Leaf hash: {}", hex::encode(self.leaf_hash))?;
+        match self.allocated_amount {
+            Some(amount) => writeln!(f, "Found in proofs file with amount {}", amount)?,
+            None => writeln!(f, "Not found in proofs file")?,
+        }
+        writeln!(f, "Root: {}", self.root)?;
+        if self.root != self.proofs_file_root {
+            writeln!(
+                f,
+                "Warning: the proofs file was built for root {}",
+                self.proofs_file_root
+            )?;
+        }
+        writeln!(f, "Proof of inclusion valid: {}", self.proof_valid)?;
+        writeln!(f, "Receipt: {}", self.receipt)?;
+        match self.receipt_exists {
+            Some(true) => writeln!(
+                f,
+                "Receipt exists: already claimed, only an amended larger amount pays the difference"
+            ),
+            Some(false) => writeln!(f, "Receipt exists: no")?,
+            None => writeln!(f, "Receipt exists: unknown (no account dump)")?,
+        }
+        if let Some(legacy_receipt) = self.legacy_receipt {
+            writeln!(f, "Legacy receipt: {}", legacy_receipt)?;
+            match (self.legacy_receipt_exists, self.receipt_exists) {
+                (Some(true), Some(false)) => writeln!(
+                    f,
+                    "Legacy receipt exists: already claimed, migrate_receipt must run before the leaf is amended"
+                )?,
+                (Some(true), _) => writeln!(f, "Legacy receipt exists: yes")?,
+                (Some(false), _) => writeln!(f, "Legacy receipt exists: no")?,
+                (None, _) => writeln!(f, "Legacy receipt exists: unknown (no account dump)")?,
+            }
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use {
+        super::*,
+        crate::{
+            allocation::{
+                normalize_allocation,
+                normalize_allocations,
+                AllocationRow,
+                Ecosystem,
+            },
+            proofs::{
+                build_merkle_tree,
+                MerkleHasher,
+            },
+        },
+    };
+
+    #[test]
+    fn test_check_leaf() {
+        let rows = (0..4)
+            .map(|i| AllocationRow {
+                ecosystem: Ecosystem::Discord,
+                identity:  format!("user{}", i),
+                amount:    1000,
+            })
+            .collect::<Vec<AllocationRow>>();
+        let allocations = normalize_allocations(&rows).unwrap();
+        let (merkle_root, proofs_file) =
+            build_merkle_tree(&allocations, MerkleHasher::Keccak160).unwrap();
+        let claimed_receipt =
+            get_receipt_pda(DEFAULT_CAMPAIGN_ID, &allocations[0].claim_info.identity).0;
+        // The second leaf was claimed before receipts were keyed by identity
+        let legacy_receipt = get_legacy_receipt_pda(&allocations[1].leaf()).0;
+        let account_dump = AccountDump::parse(&format!(
+            r#"[{{"pubkey": "{}", "account": {{"owner": "{}", "lamports": 890880}}}},
+                {{"pubkey": "{}", "account": {{"owner": "{}", "lamports": 890880}}}}]"#,
+            claimed_receipt,
+            token_dispenser::id(),
+            legacy_receipt,
+            token_dispenser::id()
+        ))
+        .unwrap();
+
+        let report = check_leaf(
+            &merkle_root,
+            DEFAULT_CAMPAIGN_ID,
+            &allocations[0],
+            &proofs_file,
+            Some(&account_dump),
+        )
+        .unwrap();
+        assert!(report.proof_valid);
+        assert_eq!(report.allocated_amount, Some(1000));
+        assert_eq!(report.receipt, claimed_receipt);
+        assert_eq!(report.receipt_exists, Some(true));
+        assert_eq!(report.legacy_receipt_exists, Some(false));
+
+        let report = check_leaf(
+            &merkle_root,
+            DEFAULT_CAMPAIGN_ID,
+            &allocations[1],
+            &proofs_file,
+            Some(&account_dump),
+        )
+        .unwrap();
+        assert!(report.proof_valid);
+        assert_eq!(report.receipt_exists, Some(false));
+        assert_eq!(report.legacy_receipt, Some(legacy_receipt));
+        assert_eq!(report.legacy_receipt_exists, Some(true));
+        assert!(report.to_string().contains("migrate_receipt"));
+
+        // Legacy receipts only exist in the default campaign
+        let report = check_leaf(
+            &merkle_root,
+            1,
+            &allocations[1],
+            &proofs_file,
+            Some(&account_dump),
+        )
+        .unwrap();
+        assert_eq!(report.legacy_receipt, None);
+        assert_eq!(report.legacy_receipt_exists, None);
+
+        // Wrong amount
+        let wrong_amount = normalize_allocation(&AllocationRow {
+            amount: 2000,
+            ..rows[0].clone()
+        })
+        .unwrap();
+        let report = check_leaf(
+            &merkle_root,
+            DEFAULT_CAMPAIGN_ID,
+            &wrong_amount,
+            &proofs_file,
+            None,
+        )
+        .unwrap();
+        assert!(!report.proof_valid);
+        assert_eq!(report.allocated_amount, Some(1000));
+        assert_eq!(report.receipt_exists, None);
+        assert_eq!(report.legacy_receipt_exists, None);
+
+        // Unknown identity
+        let unknown = normalize_allocation(&AllocationRow {
+            identity: "pepito".to_string(),
+            ..rows[0].clone()
+        })
+        .unwrap();
+        let report = check_leaf(
+            &merkle_root,
+            DEFAULT_CAMPAIGN_ID,
+            &unknown,
+            &proofs_file,
+            None,
+        )
+        .unwrap();
+        assert!(!report.proof_valid);
+        assert_eq!(report.allocated_amount, None);
+    }
+}
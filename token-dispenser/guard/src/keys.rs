@@ -0,0 +1,147 @@
+use {
+    anchor_lang::prelude::Pubkey,
+    anyhow::{
+        anyhow,
+        Context,
+        Result,
+    },
+    ed25519_dalek::{
+        Keypair,
+        Signer,
+    },
+    std::{
+        fs,
+        path::PathBuf,
+    },
+    token_dispenser::ecosystems::ed25519::Ed25519Signature,
+};
+
+/**
+ * The dispenser guard key. Implementations should only ever sign `DiscordMessage`s and `StarknetMessage`s,
+ * the program trusts anything signed by this key.
+ */
+pub trait GuardSigner: Send + Sync {
+    fn pubkey(&self) -> Pubkey;
+    fn sign(&self, message: &[u8]) -> Result<Ed25519Signature>;
+}
+
+/**
+ * Where to load the dispenser guard key from.
+ * `File` and `Env` contain a Solana keypair serialized as a JSON array of 64 bytes, like `solana-keygen` outputs.
+ */
+pub enum KeySource {
+    File(PathBuf),
+    Env(String),
+}
+
+pub fn load_signer(source: &KeySource) -> Result<Box<dyn GuardSigner>> {
+    let keypair_json = match source {
+        KeySource::File(path) => fs::read_to_string(path)
+            .with_context(|| format!("Failed to read keypair file {}", path.display()))?,
+        KeySource::Env(variable) => std::env::var(variable)
+            .with_context(|| format!("Environment variable {} is not set", variable))?,
+    };
+    Ok(Box::new(KeypairSigner::from_json(&keypair_json)?))
+}
+
+pub struct KeypairSigner(Keypair);
+
+impl KeypairSigner {
+    pub fn new(keypair: Keypair) -> Self {
+        KeypairSigner(keypair)
+    }
+
+    pub fn from_json(keypair_json: &str) -> Result<Self> {
+        let bytes: Vec<u8> = serde_json::from_str(keypair_json).context("Invalid keypair")?;
+        Ok(KeypairSigner(
+            Keypair::from_bytes(&bytes).map_err(|_| anyhow!("Invalid keypair"))?,
+        ))
+    }
+}
+
+impl GuardSigner for KeypairSigner {
+    fn pubkey(&self) -> Pubkey {
+        Pubkey::new_from_array(self.0.public.to_bytes())
+    }
+
+    fn sign(&self, message: &[u8]) -> Result<Ed25519Signature> {
+        Ok(self.0.sign(message).to_bytes().into())
+    }
+}
+
+/**
+ * A key management service holding the dispenser guard key, so that the key never touches the guard's memory.
+ * No implementation is provided here, deployments plug in their own provider.
+ */
+pub trait KmsClient: Send + Sync {
+    fn get_public_key(&self, key_id: &str) -> Result<Pubkey>;
+    fn sign_ed25519(&self, key_id: &str, message: &[u8]) -> Result<[u8; Ed25519Signature::LEN]>;
+}
+
+pub struct KmsSigner<T: KmsClient> {
+    client: T,
+    key_id: String,
+    pubkey: Pubkey,
+}
+
+impl<T: KmsClient> KmsSigner<T> {
+    pub fn new(client: T, key_id: &str) -> Result<Self> {
+        let pubkey = client.get_public_key(key_id)?;
+        Ok(KmsSigner {
+            client,
+            key_id: key_id.to_string(),
+            pubkey,
+        })
+    }
+}
+
+impl<T: KmsClient> GuardSigner for KmsSigner<T> {
+    fn pubkey(&self) -> Pubkey {
+        self.pubkey
+    }
+
+    fn sign(&self, message: &[u8]) -> Result<Ed25519Signature> {
+        Ok(self.client.sign_ed25519(&self.key_id, message)?.into())
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use {
+        super::*,
+        anchor_lang::AnchorSerialize,
+        ed25519_dalek::Verifier,
+    };
+
+    #[test]
+    fn test_load_signer() {
+        let keypair = Keypair::generate(&mut rand::rngs::OsRng {});
+        let keypair_json = serde_json::to_string(&keypair.to_bytes().to_vec()).unwrap();
+        let path =
+            std::env::temp_dir().join(format!("guard-{}.json", keypair.public.to_bytes()[0]));
+        fs::write(&path, &keypair_json).unwrap();
+
+        let signer = load_signer(&KeySource::File(path.clone())).unwrap();
+        fs::remove_file(path).unwrap();
+        assert_eq!(signer.pubkey().to_bytes(), keypair.public.to_bytes());
+
+        let signature = signer.sign(b"hello").unwrap();
+        assert!(keypair
+            .public
+            .verify(
+                b"hello",
+                &ed25519_dalek::Signature::from_bytes(&signature.try_to_vec().unwrap()).unwrap()
+            )
+            .is_ok());
+
+        std::env::set_var("TEST_GUARD_KEYPAIR", &keypair_json);
+        assert_eq!(
+            load_signer(&KeySource::Env("TEST_GUARD_KEYPAIR".to_string()))
+                .unwrap()
+                .pubkey(),
+            signer.pubkey()
+        );
+        assert!(load_signer(&KeySource::Env("TEST_GUARD_MISSING".to_string())).is_err());
+        assert!(KeypairSigner::from_json("[1, 2, 3]").is_err());
+    }
+}
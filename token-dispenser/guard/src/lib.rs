@@ -0,0 +1,4 @@
+pub mod keys;
+pub mod oauth;
+pub mod server;
+pub mod starknet;
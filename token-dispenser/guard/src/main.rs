@@ -0,0 +1,81 @@
+use {
+    anyhow::{
+        bail,
+        Context,
+        Result,
+    },
+    clap::Parser,
+    std::{
+        net::SocketAddr,
+        path::PathBuf,
+    },
+    token_dispenser_guard::{
+        keys::{
+            load_signer,
+            KeySource,
+        },
+        oauth::DISCORD_API_URL,
+        server::{
+            bind,
+            GuardService,
+        },
+    },
+};
+
+#[derive(Parser)]
+#[clap(
+    author,
+    version,
+    about = "Signs Discord and Starknet attestations with the dispenser guard key"
+)]
+struct Cli {
+    #[clap(long, default_value = "127.0.0.1:8080")]
+    listen:           SocketAddr,
+    /// Base url of the Discord API, used to resolve the user owning the OAuth token
+    #[clap(long, default_value = DISCORD_API_URL)]
+    oauth_api_url:    String,
+    /// Environment variable containing the salt used to hash Discord user ids, as a JSON array of bytes
+    #[clap(long, default_value = "DISCORD_HASH_SALT")]
+    salt_env:         String,
+    /// File containing the dispenser guard keypair
+    #[clap(long)]
+    key_file:         Option<PathBuf>,
+    /// Environment variable containing the dispenser guard keypair
+    #[clap(long)]
+    key_env:          Option<String>,
+    /// Starknet JSON-RPC node used to check the signatures of Starknet accounts, Starknet attestations are disabled if not provided
+    #[clap(long)]
+    starknet_rpc_url: Option<String>,
+}
+
+#[tokio::main]
+async fn main() -> Result<()> {
+    let cli = Cli::parse();
+    let key_source = match (cli.key_file, cli.key_env) {
+        (Some(path), None) => KeySource::File(path),
+        (None, Some(variable)) => KeySource::Env(variable),
+        _ => bail!("Exactly one of --key-file and --key-env must be provided"),
+    };
+    let salt: Vec<u8> = serde_json::from_str(
+        &std::env::var(&cli.salt_env)
+            .with_context(|| format!("Environment variable {} is not set", cli.salt_env))?,
+    )
+    .context("Invalid salt")?;
+
+    let signer = load_signer(&key_source)?;
+    println!("Dispenser guard: {}", signer.pubkey());
+
+    let (address, server) = bind(
+        &cli.listen,
+        GuardService {
+            signer,
+            oauth_api_url: cli.oauth_api_url,
+            salt,
+            http_client: reqwest::Client::new(),
+            starknet_rpc_url: cli.starknet_rpc_url,
+        },
+    )?;
+    println!("Listening on {}", address);
+    server.await?;
+    Ok(())
+}
@@ -0,0 +1,66 @@
+use {
+    anyhow::Result,
+    serde::Deserialize,
+    sha3::{
+        Digest,
+        Keccak256,
+    },
+};
+
+pub const DISCORD_API_URL: &str = "https://discord.com/api";
+
+#[derive(Deserialize)]
+struct DiscordUser {
+    id: String,
+}
+
+/**
+ * Resolves the Discord user id of the owner of an OAuth access token with the `identify` scope.
+ * Returns `None` if the token is rejected.
+ */
+pub async fn get_discord_user_id(
+    client: &reqwest::Client,
+    api_url: &str,
+    access_token: &str,
+) -> Result<Option<String>> {
+    let response = client
+        .get(format!("{}/users/@me", api_url.trim_end_matches('/')))
+        .bearer_auth(access_token)
+        .send()
+        .await?;
+    if response.status() == reqwest::StatusCode::UNAUTHORIZED {
+        return Ok(None);
+    }
+    Ok(Some(
+        response.error_for_status()?.json::<DiscordUser>().await?.id,
+    ))
+}
+
+/**
+ * The username in the merkle tree is the salted hash of the Discord user id, so that the allocation
+ * file doesn't leak Discord ids. This must match `hashDiscordUserId` in the frontend.
+ */
+pub fn hash_discord_user_id(salt: &[u8], discord_user_id: &str) -> String {
+    let mut hasher = Keccak256::new();
+    hasher.update(salt);
+    hasher.update(discord_user_id.as_bytes());
+    hex::encode(hasher.finalize())
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_hash_discord_user_id() {
+        // keccak256 of the empty string
+        assert_eq!(
+            hash_discord_user_id(b"", ""),
+            "c5d2460186f7233c927e7db2dcc703c0e500b653ca82273b7bfad8045d85a470"
+        );
+        assert_eq!(
+            hash_discord_user_id(b"salt", "1234"),
+            hash_discord_user_id(b"", "salt1234")
+        );
+    }
+}
@@ -0,0 +1,333 @@
+use {
+    crate::{
+        keys::GuardSigner,
+        oauth::{
+            get_discord_user_id,
+            hash_discord_user_id,
+        },
+        starknet::{
+            get_message_hash,
+            is_valid_signature,
+        },
+    },
+    anchor_lang::{
+        prelude::Pubkey,
+        AnchorSerialize,
+    },
+    anyhow::Result,
+    hyper::{
+        header,
+        service::{
+            make_service_fn,
+            service_fn,
+        },
+        Body,
+        Method,
+        Request,
+        Response,
+        Server,
+        StatusCode,
+    },
+    serde::Serialize,
+    starknet_crypto::FieldElement,
+    std::{
+        convert::Infallible,
+        future::Future,
+        net::SocketAddr,
+        str::FromStr,
+        sync::Arc,
+    },
+    token_dispenser::{
+        client::get_ed25519_verification_instruction,
+        ecosystems::{
+            discord::DiscordMessage,
+            ed25519::Ed25519Pubkey,
+            get_expected_payload,
+            starknet::{
+                StarknetAddress,
+                StarknetMessage,
+            },
+        },
+        DEFAULT_CAMPAIGN_ID,
+    },
+};
+
+pub const DISCORD_SIGNED_MESSAGE_PATH: &str = "/api/grant/v1/discord_signed_message";
+pub const STARKNET_SIGNED_MESSAGE_PATH: &str = "/api/grant/v1/starknet_signed_message";
+
+pub struct GuardService {
+    pub signer:           Box<dyn GuardSigner>,
+    pub oauth_api_url:    String,
+    pub salt:             Vec<u8>,
+    pub http_client:      reqwest::Client,
+    /// Starknet JSON-RPC node used to check the signatures of Starknet accounts, Starknet attestations are disabled if `None`
+    pub starknet_rpc_url: Option<String>,
+}
+
+/**
+ * Same fields as the frontend `discord_signed_message` endpoint, plus the username and the data of the
+ * ed25519 precompile instruction, which expects to be the first instruction of the transaction.
+ */
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct DiscordSignedMessage {
+    pub username:         String,
+    pub signature:        String,
+    pub public_key:       String,
+    pub full_message:     String,
+    pub instruction_data: String,
+}
+
+/**
+ * The Starknet attestation `StarknetMessage { address, claimant }` signed by the dispenser guard, with the data
+ * of the ed25519 precompile instruction, which expects to be the first instruction of the transaction.
+ */
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct StarknetSignedMessage {
+    pub address:          String,
+    pub signature:        String,
+    pub public_key:       String,
+    pub full_message:     String,
+    pub instruction_data: String,
+}
+
+impl GuardService {
+    /**
+     * Signs `DiscordMessage { username, claimant }` where `username` is derived from the Discord account
+     * owning the bearer token. Returns `None` if the token is missing or rejected by the OAuth server.
+     */
+    pub async fn sign_discord_message(
+        &self,
+        access_token: Option<&str>,
+        claimant: &Pubkey,
+    ) -> Result<Option<DiscordSignedMessage>> {
+        let discord_user_id = match access_token {
+            Some(access_token) => {
+                get_discord_user_id(&self.http_client, &self.oauth_api_url, access_token).await?
+            }
+            None => None,
+        };
+        let Some(discord_user_id) = discord_user_id else {
+            return Ok(None);
+        };
+
+        let username = hash_discord_user_id(&self.salt, &discord_user_id);
+        let full_message = DiscordMessage::new(&username, claimant).try_to_vec()?;
+        let signature = self.signer.sign(&full_message)?;
+        let instruction = get_ed25519_verification_instruction(
+            &Ed25519Pubkey::from(self.signer.pubkey()),
+            &signature,
+            &full_message,
+            0,
+        )
+        .map_err(|err| anyhow::anyhow!("{}", err))?;
+
+        Ok(Some(DiscordSignedMessage {
+            username,
+            signature: hex::encode(signature.try_to_vec()?),
+            public_key: hex::encode(self.signer.pubkey().to_bytes()),
+            full_message: hex::encode(full_message),
+            instruction_data: hex::encode(instruction.data),
+        }))
+    }
+
+    /**
+     * Signs `StarknetMessage { address, claimant }` if `signature` is a valid signature by the Starknet account
+     * `address` of the SNIP-12 typed data of the authorization payload of `claimant` for `campaign_id`.
+     * Returns `None` if Starknet attestations are disabled or if the signature is invalid.
+     */
+    pub async fn sign_starknet_message(
+        &self,
+        campaign_id: u64,
+        address: &StarknetAddress,
+        claimant: &Pubkey,
+        signature: &[FieldElement],
+    ) -> Result<Option<StarknetSignedMessage>> {
+        let Some(starknet_rpc_url) = &self.starknet_rpc_url else {
+            return Ok(None);
+        };
+        let message_hash = get_message_hash(address, &get_expected_payload(campaign_id, claimant));
+        if !is_valid_signature(
+            &self.http_client,
+            starknet_rpc_url,
+            address,
+            &message_hash,
+            signature,
+        )
+        .await?
+        {
+            return Ok(None);
+        }
+
+        let full_message = StarknetMessage::new(address, claimant).get_signed_message();
+        let signature = self.signer.sign(&full_message)?;
+        let instruction = get_ed25519_verification_instruction(
+            &Ed25519Pubkey::from(self.signer.pubkey()),
+            &signature,
+            &full_message,
+            0,
+        )
+        .map_err(|err| anyhow::anyhow!("{}", err))?;
+
+        Ok(Some(StarknetSignedMessage {
+            address:          hex::encode(address.as_bytes()),
+            signature:        hex::encode(signature.try_to_vec()?),
+            public_key:       hex::encode(self.signer.pubkey().to_bytes()),
+            full_message:     hex::encode(full_message),
+            instruction_data: hex::encode(instruction.data),
+        }))
+    }
+
+    pub async fn handle(&self, request: Request<Body>) -> Response<Body> {
+        if request.method() != Method::GET {
+            return json_error(StatusCode::NOT_FOUND, "Not found");
+        }
+        match request.uri().path() {
+            DISCORD_SIGNED_MESSAGE_PATH => self.handle_discord_signed_message(&request).await,
+            STARKNET_SIGNED_MESSAGE_PATH if self.starknet_rpc_url.is_some() => {
+                self.handle_starknet_signed_message(&request).await
+            }
+            _ => json_error(StatusCode::NOT_FOUND, "Not found"),
+        }
+    }
+
+    async fn handle_discord_signed_message(&self, request: &Request<Body>) -> Response<Body> {
+        let claimant = match get_claimant(request) {
+            Ok(claimant) => claimant,
+            Err(response) => return response,
+        };
+
+        let access_token = request
+            .headers()
+            .get(header::AUTHORIZATION)
+            .and_then(|value| value.to_str().ok())
+            .and_then(|value| value.strip_prefix("Bearer "));
+
+        match self.sign_discord_message(access_token, &claimant).await {
+            Ok(Some(signed_message)) => Response::builder()
+                .status(StatusCode::OK)
+                .header(header::CONTENT_TYPE, "application/json")
+                .body(Body::from(serde_json::to_vec(&signed_message).unwrap()))
+                .unwrap(),
+            Ok(None) => json_error(
+                StatusCode::FORBIDDEN,
+                "You must be logged in with Discord to access this endpoint",
+            ),
+            Err(_) => json_error(StatusCode::INTERNAL_SERVER_ERROR, "Internal server error"),
+        }
+    }
+
+    async fn handle_starknet_signed_message(&self, request: &Request<Body>) -> Response<Body> {
+        let claimant = match get_claimant(request) {
+            Ok(claimant) => claimant,
+            Err(response) => return response,
+        };
+        let address = match get_query_param(request, "address")
+            .and_then(|address| FieldElement::from_hex_be(&address).ok())
+        {
+            Some(address) => StarknetAddress::from(address.to_bytes_be()),
+            None => {
+                return json_error(
+                    StatusCode::BAD_REQUEST,
+                    "Must provide a valid 'address' query parameter",
+                )
+            }
+        };
+        let signature = match get_query_param(request, "signature").and_then(|signature| {
+            signature
+                .split(',')
+                .map(|element| FieldElement::from_hex_be(element).ok())
+                .collect::<Option<Vec<FieldElement>>>()
+        }) {
+            Some(signature) => signature,
+            None => {
+                return json_error(
+                    StatusCode::BAD_REQUEST,
+                    "Must provide a valid 'signature' query parameter",
+                )
+            }
+        };
+        let campaign_id = match get_query_param(request, "campaignId")
+            .map(|campaign_id| campaign_id.parse::<u64>())
+        {
+            Some(Ok(campaign_id)) => campaign_id,
+            Some(Err(_)) => {
+                return json_error(
+                    StatusCode::BAD_REQUEST,
+                    "Invalid 'campaignId' query parameter",
+                )
+            }
+            None => DEFAULT_CAMPAIGN_ID,
+        };
+
+        match self
+            .sign_starknet_message(campaign_id, &address, &claimant, &signature)
+            .await
+        {
+            Ok(Some(signed_message)) => Response::builder()
+                .status(StatusCode::OK)
+                .header(header::CONTENT_TYPE, "application/json")
+                .body(Body::from(serde_json::to_vec(&signed_message).unwrap()))
+                .unwrap(),
+            Ok(None) => json_error(StatusCode::FORBIDDEN, "Invalid Starknet signature"),
+            Err(_) => json_error(StatusCode::INTERNAL_SERVER_ERROR, "Internal server error"),
+        }
+    }
+}
+
+fn get_query_param(request: &Request<Body>, name: &str) -> Option<String> {
+    request.uri().query().and_then(|query| {
+        url::form_urlencoded::parse(query.as_bytes())
+            .find(|(key, _)| key == name)
+            .map(|(_, value)| value.into_owned())
+    })
+}
+
+/**
+ * Parses the `publicKey` query parameter, or returns the error response.
+ */
+fn get_claimant(request: &Request<Body>) -> std::result::Result<Pubkey, Response<Body>> {
+    match get_query_param(request, "publicKey").map(|public_key| Pubkey::from_str(&public_key)) {
+        Some(Ok(claimant)) => Ok(claimant),
+        Some(Err(_)) => Err(json_error(
+            StatusCode::BAD_REQUEST,
+            "Invalid 'publicKey' query parameter",
+        )),
+        None => Err(json_error(
+            StatusCode::BAD_REQUEST,
+            "Must provide the 'publicKey' query parameter",
+        )),
+    }
+}
+
+fn json_error(status: StatusCode, error: &str) -> Response<Body> {
+    Response::builder()
+        .status(status)
+        .header(header::CONTENT_TYPE, "application/json")
+        .body(Body::from(
+            serde_json::json!({ "error": error }).to_string(),
+        ))
+        .unwrap()
+}
+
+/**
+ * Binds the service to `address` and returns the bound address, which is useful when binding to port 0,
+ * and the future running the server.
+ */
+pub fn bind(
+    address: &SocketAddr,
+    service: GuardService,
+) -> Result<(SocketAddr, impl Future<Output = hyper::Result<()>>)> {
+    let service = Arc::new(service);
+    let server = Server::try_bind(address)?.serve(make_service_fn(move |_| {
+        let service = service.clone();
+        async move {
+            Ok::<_, Infallible>(service_fn(move |request| {
+                let service = service.clone();
+                async move { Ok::<_, Infallible>(service.handle(request).await) }
+            }))
+        }
+    }));
+    Ok((server.local_addr(), server))
+}
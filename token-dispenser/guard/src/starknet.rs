@@ -0,0 +1,169 @@
+use {
+    anyhow::Result,
+    serde::Deserialize,
+    sha3::{
+        Digest,
+        Keccak256,
+    },
+    starknet_crypto::{
+        pedersen_hash,
+        FieldElement,
+    },
+    token_dispenser::ecosystems::starknet::StarknetAddress,
+};
+
+/**
+ * Starknet accounts sign the authorization payload as SNIP-12 (revision 0) typed data :
+ * - the domain is `StarkNetDomain { name: "Pyth Grant", version: "1", chainId: "SN_MAIN" }`
+ * - the message is `Authorization { payload }` where `payload` is the payload split in chunks of
+ *   `SHORT_STRING_MAX_LEN` bytes, each encoded as a short string.
+ */
+pub const STARKNET_DOMAIN_TYPE: &str = "StarkNetDomain(name:felt,version:felt,chainId:felt)";
+pub const STARKNET_DOMAIN_NAME: &str = "Pyth Grant";
+pub const STARKNET_DOMAIN_VERSION: &str = "1";
+pub const STARKNET_CHAIN_ID: &str = "SN_MAIN";
+pub const AUTHORIZATION_TYPE: &str = "Authorization(payload:felt*)";
+pub const SNIP12_MESSAGE_PREFIX: &str = "StarkNet Message";
+pub const SHORT_STRING_MAX_LEN: usize = 31;
+
+/// Returned by Cairo 1 accounts for valid signatures, Cairo 0 accounts return 1
+pub const VALID_SIGNATURE: &str = "VALID";
+
+/**
+ * The `sn_keccak` hash used for type hashes and entry point selectors, keccak256 truncated to 250 bits.
+ */
+pub fn starknet_keccak(data: &[u8]) -> FieldElement {
+    let mut hash: [u8; 32] = Keccak256::digest(data).into();
+    hash[0] &= 0x03;
+    FieldElement::from_bytes_be(&hash).unwrap()
+}
+
+/**
+ * Encodes up to `SHORT_STRING_MAX_LEN` ASCII bytes as a big endian field element.
+ */
+fn get_short_string(data: &[u8]) -> FieldElement {
+    FieldElement::from_byte_slice_be(data).unwrap()
+}
+
+/**
+ * The pedersen hash chain over `elements` followed by their count, called `compute_hash_on_elements`
+ * in the Starknet tooling.
+ */
+fn compute_hash_on_elements(elements: &[FieldElement]) -> FieldElement {
+    let hash = elements.iter().fold(FieldElement::ZERO, |hash, element| {
+        pedersen_hash(&hash, element)
+    });
+    pedersen_hash(&hash, &FieldElement::from(elements.len() as u64))
+}
+
+/**
+ * The hash of the SNIP-12 typed data of `payload` that `address` signs.
+ */
+pub fn get_message_hash(address: &StarknetAddress, payload: &str) -> FieldElement {
+    let domain_hash = compute_hash_on_elements(&[
+        starknet_keccak(STARKNET_DOMAIN_TYPE.as_bytes()),
+        get_short_string(STARKNET_DOMAIN_NAME.as_bytes()),
+        get_short_string(STARKNET_DOMAIN_VERSION.as_bytes()),
+        get_short_string(STARKNET_CHAIN_ID.as_bytes()),
+    ]);
+    let payload_hash = compute_hash_on_elements(
+        &payload
+            .as_bytes()
+            .chunks(SHORT_STRING_MAX_LEN)
+            .map(get_short_string)
+            .collect::<Vec<FieldElement>>(),
+    );
+    let message_hash =
+        compute_hash_on_elements(&[starknet_keccak(AUTHORIZATION_TYPE.as_bytes()), payload_hash]);
+    compute_hash_on_elements(&[
+        get_short_string(SNIP12_MESSAGE_PREFIX.as_bytes()),
+        domain_hash,
+        FieldElement::from_bytes_be(&address.as_bytes()).unwrap(),
+        message_hash,
+    ])
+}
+
+#[derive(Deserialize)]
+struct StarknetCallResponse {
+    result: Option<Vec<String>>,
+}
+
+/**
+ * Starknet accounts are contracts that define their own signature scheme, so the signature is checked
+ * by calling `is_valid_signature` on the account through a Starknet JSON-RPC node.
+ * Some accounts revert instead of returning when the signature is invalid, errors of the call are
+ * treated as invalid signatures.
+ */
+pub async fn is_valid_signature(
+    client: &reqwest::Client,
+    rpc_url: &str,
+    address: &StarknetAddress,
+    message_hash: &FieldElement,
+    signature: &[FieldElement],
+) -> Result<bool> {
+    let mut calldata = vec![*message_hash, FieldElement::from(signature.len() as u64)];
+    calldata.extend_from_slice(signature);
+    let contract_address = FieldElement::from_bytes_be(&address.as_bytes()).unwrap();
+
+    let response = client
+        .post(rpc_url)
+        .json(&serde_json::json!({
+            "jsonrpc": "2.0",
+            "id": 1,
+            "method": "starknet_call",
+            "params": {
+                "request": {
+                    "contract_address": format!("{:#x}", contract_address),
+                    "entry_point_selector": format!("{:#x}", starknet_keccak(b"is_valid_signature")),
+                    "calldata": calldata
+                        .iter()
+                        .map(|element| format!("{:#x}", element))
+                        .collect::<Vec<String>>(),
+                },
+                "block_id": "latest",
+            },
+        }))
+        .send()
+        .await?
+        .error_for_status()?
+        .json::<StarknetCallResponse>()
+        .await?;
+
+    Ok(response.result.map_or(false, |result| {
+        result.len() == 1
+            && FieldElement::from_hex_be(&result[0]).map_or(false, |value| {
+                value == get_short_string(VALID_SIGNATURE.as_bytes()) || value == FieldElement::ONE
+            })
+    }))
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_starknet_keccak() {
+        assert_eq!(
+            format!("{:#x}", starknet_keccak(b"transfer")),
+            "0x83afd3f4caedc6eebf44246fe54e38c95e3179a5ec9ea81740eca5b482d12e"
+        );
+    }
+
+    #[test]
+    fn test_get_message_hash() {
+        let address = StarknetAddress::from([1; StarknetAddress::LEN]);
+        let payload = "a".repeat(SHORT_STRING_MAX_LEN + 1);
+        assert_eq!(
+            get_message_hash(&address, &payload),
+            get_message_hash(&address, &payload)
+        );
+        assert_ne!(
+            get_message_hash(&address, &payload),
+            get_message_hash(&address, &payload[1..])
+        );
+        assert_ne!(
+            get_message_hash(&address, &payload),
+            get_message_hash(&StarknetAddress::from([2; StarknetAddress::LEN]), &payload)
+        );
+    }
+}
@@ -0,0 +1,185 @@
+use {
+    anchor_lang::{
+        prelude::Pubkey,
+        solana_program::{
+            ed25519_program::ID as ED25519_ID,
+            instruction::Instruction,
+        },
+    },
+    hyper::{
+        header,
+        service::{
+            make_service_fn,
+            service_fn,
+        },
+        Body,
+        Request,
+        Response,
+        Server,
+        StatusCode,
+    },
+    serde_json::Value,
+    std::{
+        convert::Infallible,
+        net::SocketAddr,
+    },
+    token_dispenser::ecosystems::{
+        discord::DiscordMessage,
+        ed25519::{
+            Ed25519InstructionData,
+            Ed25519Pubkey,
+        },
+    },
+    token_dispenser_guard::{
+        keys::KeypairSigner,
+        oauth::hash_discord_user_id,
+        server::{
+            bind,
+            GuardService,
+            DISCORD_SIGNED_MESSAGE_PATH,
+        },
+    },
+};
+
+const VALID_ACCESS_TOKEN: &str = "valid-access-token";
+const DISCORD_USER_ID: &str = "80351110224678912";
+const SALT: &[u8] = b"salt";
+
+/// Mocks the `users/@me` endpoint of the Discord API
+async fn mock_discord_api(request: Request<Body>) -> Result<Response<Body>, Infallible> {
+    let authorized = request
+        .headers()
+        .get(header::AUTHORIZATION)
+        .map_or(false, |value| {
+            value.as_bytes() == format!("Bearer {}", VALID_ACCESS_TOKEN).as_bytes()
+        });
+    let response = match (request.uri().path(), authorized) {
+        ("/users/@me", true) => Response::new(Body::from(
+            serde_json::json!({ "id": DISCORD_USER_ID, "username": "nelly" }).to_string(),
+        )),
+        ("/users/@me", false) => Response::builder()
+            .status(StatusCode::UNAUTHORIZED)
+            .body(Body::from(r#"{"message": "401: Unauthorized", "code": 0}"#))
+            .unwrap(),
+        _ => Response::builder()
+            .status(StatusCode::NOT_FOUND)
+            .body(Body::empty())
+            .unwrap(),
+    };
+    Ok(response)
+}
+
+async fn start_servers() -> (SocketAddr, Pubkey) {
+    let oauth_server =
+        Server::bind(&"127.0.0.1:0".parse().unwrap()).serve(make_service_fn(|_| async {
+            Ok::<_, Infallible>(service_fn(mock_discord_api))
+        }));
+    let oauth_address = oauth_server.local_addr();
+    tokio::spawn(oauth_server);
+
+    let signer = KeypairSigner::new(ed25519_dalek::Keypair::generate(&mut rand::rngs::OsRng {}));
+    let dispenser_guard = token_dispenser_guard::keys::GuardSigner::pubkey(&signer);
+    let (guard_address, guard_server) = bind(
+        &"127.0.0.1:0".parse().unwrap(),
+        GuardService {
+            signer:           Box::new(signer),
+            oauth_api_url:    format!("http://{}", oauth_address),
+            salt:             SALT.to_vec(),
+            http_client:      reqwest::Client::new(),
+            starknet_rpc_url: None,
+        },
+    )
+    .unwrap();
+    tokio::spawn(guard_server);
+    (guard_address, dispenser_guard)
+}
+
+async fn get_discord_signed_message(
+    guard_address: &SocketAddr,
+    public_key: &str,
+    access_token: Option<&str>,
+) -> (StatusCode, Value) {
+    let mut request = reqwest::Client::new()
+        .get(format!(
+            "http://{}{}",
+            guard_address, DISCORD_SIGNED_MESSAGE_PATH
+        ))
+        .query(&[("publicKey", public_key)]);
+    if let Some(access_token) = access_token {
+        request = request.bearer_auth(access_token);
+    }
+    let response = request.send().await.unwrap();
+    (
+        StatusCode::from_u16(response.status().as_u16()).unwrap(),
+        response.json::<Value>().await.unwrap(),
+    )
+}
+
+#[tokio::test]
+async fn test_discord_signed_message() {
+    let (guard_address, dispenser_guard) = start_servers().await;
+    let claimant = Pubkey::new_unique();
+
+    let (status, body) = get_discord_signed_message(
+        &guard_address,
+        &claimant.to_string(),
+        Some(VALID_ACCESS_TOKEN),
+    )
+    .await;
+    assert_eq!(status, StatusCode::OK);
+
+    let username = hash_discord_user_id(SALT, DISCORD_USER_ID);
+    assert_eq!(body["username"], username.as_str());
+    assert_eq!(
+        body["publicKey"],
+        hex::encode(dispenser_guard.to_bytes()).as_str()
+    );
+
+    // The instruction data is accepted by the program
+    let instruction = Instruction {
+        program_id: ED25519_ID,
+        accounts:   vec![],
+        data:       hex::decode(body["instructionData"].as_str().unwrap()).unwrap(),
+    };
+    let message = Ed25519InstructionData::extract_message_and_check_signature(
+        &instruction,
+        &Ed25519Pubkey::from(dispenser_guard),
+        &0,
+    )
+    .unwrap();
+    assert_eq!(hex::encode(&message), body["fullMessage"].as_str().unwrap());
+    DiscordMessage::parse_and_check_claimant_and_username(&message, &username, &claimant).unwrap();
+
+    // And the signature is valid
+    let public_key = ed25519_dalek::PublicKey::from_bytes(&dispenser_guard.to_bytes()).unwrap();
+    let signature = ed25519_dalek::Signature::from_bytes(
+        &hex::decode(body["signature"].as_str().unwrap()).unwrap(),
+    )
+    .unwrap();
+    assert!(public_key.verify_strict(&message, &signature).is_ok());
+}
+
+#[tokio::test]
+async fn test_discord_signed_message_rejects_invalid_requests() {
+    let (guard_address, _) = start_servers().await;
+    let claimant = Pubkey::new_unique().to_string();
+
+    assert_eq!(
+        get_discord_signed_message(&guard_address, &claimant, None)
+            .await
+            .0,
+        StatusCode::FORBIDDEN
+    );
+    assert_eq!(
+        get_discord_signed_message(&guard_address, &claimant, Some("invalid-access-token"))
+            .await
+            .0,
+        StatusCode::FORBIDDEN
+    );
+    assert_eq!(
+        get_discord_signed_message(&guard_address, "not-a-pubkey", Some(VALID_ACCESS_TOKEN))
+            .await
+            .0,
+        StatusCode::BAD_REQUEST
+    );
+}
@@ -0,0 +1,234 @@
+use {
+    anchor_lang::{
+        prelude::Pubkey,
+        solana_program::{
+            ed25519_program::ID as ED25519_ID,
+            instruction::Instruction,
+        },
+    },
+    hyper::{
+        body::to_bytes,
+        service::{
+            make_service_fn,
+            service_fn,
+        },
+        Body,
+        Request,
+        Response,
+        Server,
+        StatusCode,
+    },
+    serde_json::Value,
+    std::{
+        convert::Infallible,
+        net::SocketAddr,
+    },
+    token_dispenser::ecosystems::{
+        ed25519::{
+            Ed25519InstructionData,
+            Ed25519Pubkey,
+        },
+        get_expected_payload,
+        starknet::{
+            StarknetAddress,
+            StarknetMessage,
+        },
+    },
+    token_dispenser_guard::{
+        keys::KeypairSigner,
+        server::{
+            bind,
+            GuardService,
+            STARKNET_SIGNED_MESSAGE_PATH,
+        },
+        starknet::{
+            get_message_hash,
+            starknet_keccak,
+        },
+    },
+};
+
+const ADDRESS: &str = "0x7e00d496e324876bbc8531f2d9a82bf154d1a04a50218ee74cdd372f75a551a";
+const CAMPAIGN_ID: u64 = 0;
+/// The signature that the mock account accepts
+const VALID_SIGNATURE: [&str; 2] = ["0x1", "0x2"];
+
+fn get_address() -> StarknetAddress {
+    let mut address = [0u8; StarknetAddress::LEN];
+    hex::decode_to_slice(
+        format!("{:0>64}", ADDRESS.trim_start_matches("0x")),
+        &mut address,
+    )
+    .unwrap();
+    StarknetAddress::from(address)
+}
+
+/// Mocks `starknet_call` of `is_valid_signature` on the account `ADDRESS` of a Starknet JSON-RPC node
+async fn mock_starknet_rpc(
+    request: Request<Body>,
+    claimant: Pubkey,
+) -> Result<Response<Body>, Infallible> {
+    let body: Value =
+        serde_json::from_slice(&to_bytes(request.into_body()).await.unwrap()).unwrap();
+    assert_eq!(body["method"], "starknet_call");
+    let call = &body["params"]["request"];
+    assert_eq!(call["contract_address"], ADDRESS);
+    assert_eq!(
+        call["entry_point_selector"],
+        format!("{:#x}", starknet_keccak(b"is_valid_signature")).as_str()
+    );
+
+    let calldata: Vec<&str> = call["calldata"]
+        .as_array()
+        .unwrap()
+        .iter()
+        .map(|element| element.as_str().unwrap())
+        .collect();
+    let message_hash = format!(
+        "{:#x}",
+        get_message_hash(
+            &get_address(),
+            &get_expected_payload(CAMPAIGN_ID, &claimant)
+        )
+    );
+    let response = if calldata[0] == message_hash
+        && calldata[1] == "0x2"
+        && calldata[2..] == VALID_SIGNATURE
+    {
+        serde_json::json!({ "jsonrpc": "2.0", "id": 1, "result": ["0x56414c4944"] })
+    } else {
+        serde_json::json!({ "jsonrpc": "2.0", "id": 1, "error": { "code": 40, "message": "Contract error" } })
+    };
+    Ok(Response::new(Body::from(response.to_string())))
+}
+
+async fn start_servers(claimant: Pubkey) -> (SocketAddr, Pubkey) {
+    let rpc_server =
+        Server::bind(&"127.0.0.1:0".parse().unwrap()).serve(make_service_fn(move |_| async move {
+            Ok::<_, Infallible>(service_fn(move |request| {
+                mock_starknet_rpc(request, claimant)
+            }))
+        }));
+    let rpc_address = rpc_server.local_addr();
+    tokio::spawn(rpc_server);
+
+    let signer = KeypairSigner::new(ed25519_dalek::Keypair::generate(&mut rand::rngs::OsRng {}));
+    let dispenser_guard = token_dispenser_guard::keys::GuardSigner::pubkey(&signer);
+    let (guard_address, guard_server) = bind(
+        &"127.0.0.1:0".parse().unwrap(),
+        GuardService {
+            signer:           Box::new(signer),
+            oauth_api_url:    String::new(),
+            salt:             vec![],
+            http_client:      reqwest::Client::new(),
+            starknet_rpc_url: Some(format!("http://{}", rpc_address)),
+        },
+    )
+    .unwrap();
+    tokio::spawn(guard_server);
+    (guard_address, dispenser_guard)
+}
+
+async fn get_starknet_signed_message(
+    guard_address: &SocketAddr,
+    public_key: &str,
+    address: &str,
+    signature: &str,
+) -> (StatusCode, Value) {
+    let response = reqwest::Client::new()
+        .get(format!(
+            "http://{}{}",
+            guard_address, STARKNET_SIGNED_MESSAGE_PATH
+        ))
+        .query(&[
+            ("publicKey", public_key),
+            ("address", address),
+            ("signature", signature),
+        ])
+        .send()
+        .await
+        .unwrap();
+    (
+        StatusCode::from_u16(response.status().as_u16()).unwrap(),
+        response.json::<Value>().await.unwrap(),
+    )
+}
+
+#[tokio::test]
+async fn test_starknet_signed_message() {
+    let claimant = Pubkey::new_unique();
+    let (guard_address, dispenser_guard) = start_servers(claimant).await;
+
+    let (status, body) = get_starknet_signed_message(
+        &guard_address,
+        &claimant.to_string(),
+        ADDRESS,
+        &VALID_SIGNATURE.join(","),
+    )
+    .await;
+    assert_eq!(status, StatusCode::OK);
+
+    // The instruction data is accepted by the program
+    let instruction = Instruction {
+        program_id: ED25519_ID,
+        accounts:   vec![],
+        data:       hex::decode(body["instructionData"].as_str().unwrap()).unwrap(),
+    };
+    let message = Ed25519InstructionData::extract_message_and_check_signature(
+        &instruction,
+        &Ed25519Pubkey::from(dispenser_guard),
+        &0,
+    )
+    .unwrap();
+    assert_eq!(hex::encode(&message), body["fullMessage"].as_str().unwrap());
+    assert_eq!(
+        message,
+        StarknetMessage::new(&get_address(), &claimant).get_signed_message()
+    );
+    StarknetMessage::parse_and_check_claimant_and_address(&message, &get_address(), &claimant)
+        .unwrap();
+}
+
+#[tokio::test]
+async fn test_starknet_signed_message_rejects_invalid_requests() {
+    let claimant = Pubkey::new_unique();
+    let (guard_address, _) = start_servers(claimant).await;
+
+    // rejected by the account
+    assert_eq!(
+        get_starknet_signed_message(&guard_address, &claimant.to_string(), ADDRESS, "0x1,0x3")
+            .await
+            .0,
+        StatusCode::FORBIDDEN
+    );
+    // signed for another claimant
+    assert_eq!(
+        get_starknet_signed_message(
+            &guard_address,
+            &Pubkey::new_unique().to_string(),
+            ADDRESS,
+            &VALID_SIGNATURE.join(",")
+        )
+        .await
+        .0,
+        StatusCode::FORBIDDEN
+    );
+    // the prime of the STARK field
+    assert_eq!(
+        get_starknet_signed_message(
+            &guard_address,
+            &claimant.to_string(),
+            "0x800000000000011000000000000000000000000000000000000000000000001",
+            &VALID_SIGNATURE.join(",")
+        )
+        .await
+        .0,
+        StatusCode::BAD_REQUEST
+    );
+    assert_eq!(
+        get_starknet_signed_message(&guard_address, &claimant.to_string(), ADDRESS, "0xz")
+            .await
+            .0,
+        StatusCode::BAD_REQUEST
+    );
+}
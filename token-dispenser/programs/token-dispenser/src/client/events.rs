@@ -0,0 +1,214 @@
+#[cfg(test)]
+use crate::{
+    Identity,
+    HASH_SIZE,
+};
+use {
+    crate::{
+        ClaimEvent,
+        ClaimEventV2,
+        ClaimInfo,
+    },
+    anchor_lang::{
+        prelude::*,
+        Discriminator,
+    },
+    base64::{
+        engine::general_purpose::STANDARD as base64_standard_engine,
+        Engine,
+    },
+};
+
+const PROGRAM_DATA_PREFIX: &str = "Program data: ";
+
+pub enum DispenserEvent {
+    Claim(ClaimEvent),
+    ClaimV2(ClaimEventV2),
+}
+
+/**
+ * Data logged by the token dispenser that isn't any known layout of its events, e.g. an event of a newer
+ * version of the program. `data` is the base64 of the `Program data: ` log line.
+ */
+#[derive(Debug, PartialEq)]
+pub struct UndecodableEvent {
+    pub data: String,
+}
+
+/**
+ * Layout of `ClaimEvent` before `amount_received` was added. The program only supported the token program
+ * then, so the claimant fund received the whole amount of the leaf.
+ */
+#[derive(AnchorDeserialize)]
+struct LegacyClaimEvent {
+    remaining_balance: u64,
+    claimant:          Pubkey,
+    claim_info:        ClaimInfo,
+}
+
+impl From<LegacyClaimEvent> for ClaimEvent {
+    fn from(legacy_claim_event: LegacyClaimEvent) -> Self {
+        ClaimEvent {
+            remaining_balance: legacy_claim_event.remaining_balance,
+            claimant:          legacy_claim_event.claimant,
+            amount_received:   legacy_claim_event.claim_info.amount,
+            claim_info:        legacy_claim_event.claim_info,
+        }
+    }
+}
+
+/**
+ * Decodes the events emitted by the token dispenser from the log messages of transactions, in order.
+ * Anchor logs an event as `Program data: ` followed by the base64 of its discriminator and its borsh
+ * serialization. The invocation stack is tracked so that data logged by other programs is ignored, while
+ * data logged by the token dispenser that can't be decoded is returned as an `UndecodableEvent`.
+ */
+pub fn decode_events<S: AsRef<str>>(
+    log_messages: &[S],
+) -> Vec<std::result::Result<DispenserEvent, UndecodableEvent>> {
+    let program_id = crate::id().to_string();
+    let mut invocation_stack: Vec<&str> = vec![];
+    let mut events = vec![];
+    for log_message in log_messages {
+        let log_message = log_message.as_ref();
+        if let Some(data) = log_message.strip_prefix(PROGRAM_DATA_PREFIX) {
+            if invocation_stack.last() == Some(&program_id.as_str()) {
+                events.push(decode_event(data));
+            }
+            continue;
+        }
+
+        let words: Vec<&str> = log_message.split(' ').collect();
+        match words.as_slice() {
+            ["Program", program, "invoke", _] => invocation_stack.push(*program),
+            ["Program", _, "success"] => {
+                invocation_stack.pop();
+            }
+            ["Program", _, failed, ..] if failed.starts_with("failed") => {
+                invocation_stack.pop();
+            }
+            _ => {}
+        }
+    }
+    events
+}
+
+/**
+ * Decodes the base64 data of a `Program data: ` log line of the token dispenser.
+ * `ClaimEvent`s emitted before `amount_received` was added are decoded with the legacy layout.
+ */
+pub fn decode_event(data: &str) -> std::result::Result<DispenserEvent, UndecodableEvent> {
+    let undecodable_event = || UndecodableEvent {
+        data: data.to_string(),
+    };
+    let bytes = base64_standard_engine
+        .decode(data)
+        .map_err(|_| undecodable_event())?;
+    if bytes.len() < 8 {
+        return Err(undecodable_event());
+    }
+    let (discriminator, event_data) = bytes.split_at(8);
+    if discriminator == ClaimEvent::DISCRIMINATOR {
+        ClaimEvent::try_from_slice(event_data)
+            .or_else(|_| LegacyClaimEvent::try_from_slice(event_data).map(ClaimEvent::from))
+            .map(DispenserEvent::Claim)
+            .map_err(|_| undecodable_event())
+    } else if discriminator == ClaimEventV2::DISCRIMINATOR {
+        ClaimEventV2::try_from_slice(event_data)
+            .map(DispenserEvent::ClaimV2)
+            .map_err(|_| undecodable_event())
+    } else {
+        Err(undecodable_event())
+    }
+}
+
+#[cfg(test)]
+fn encode_event_log(discriminator: &[u8], event_data: Vec<u8>) -> String {
+    format!(
+        "{}{}",
+        PROGRAM_DATA_PREFIX,
+        base64_standard_engine.encode([discriminator, &event_data].concat())
+    )
+}
+
+#[test]
+pub fn test_decode_events() {
+    let claim_event_v2 = ClaimEventV2 {
+        campaign_id:        1,
+        claimant:           Pubkey::new_unique(),
+        claimant_fund:      Pubkey::new_unique(),
+        ecosystem:          2,
+        identity_hash:      [1; HASH_SIZE],
+        leaf_hash:          [2; HASH_SIZE],
+        receipt:            Pubkey::new_unique(),
+        merkle_root:        [3; HASH_SIZE],
+        amount:             100,
+        amount_transferred: 100,
+        amount_received:    99,
+        remaining_balance:  1000,
+        slot:               10,
+        unix_timestamp:     1_700_000_000,
+    };
+    let claim_event_v2_log = encode_event_log(
+        &ClaimEventV2::DISCRIMINATOR,
+        claim_event_v2.try_to_vec().unwrap(),
+    );
+    let claim_info = ClaimInfo {
+        identity: Identity::Discord {
+            username: "pepito".to_string(),
+        },
+        amount:   100,
+    };
+    let claimant = Pubkey::new_unique();
+    // `ClaimEvent` without `amount_received`
+    let legacy_claim_event_log = encode_event_log(
+        &ClaimEvent::DISCRIMINATOR,
+        (1000u64, claimant, claim_info.clone())
+            .try_to_vec()
+            .unwrap(),
+    );
+    let other_program = Pubkey::new_unique();
+    let log_messages = vec![
+        format!("Program {} invoke [1]", crate::id()),
+        "Program log: Instruction: Claim".to_string(),
+        format!("Program {} invoke [2]", other_program),
+        // Data logged by other programs is ignored
+        claim_event_v2_log.clone(),
+        "Program data: bm90IGFuIGV2ZW50".to_string(),
+        format!("Program {} success", other_program),
+        legacy_claim_event_log,
+        claim_event_v2_log.clone(),
+        "Program data: bm90IGFuIGV2ZW50".to_string(),
+        format!(
+            "Program {} consumed 1000 of 200000 compute units",
+            crate::id()
+        ),
+        format!("Program {} success", crate::id()),
+        claim_event_v2_log,
+    ];
+
+    let mut events = decode_events(&log_messages).into_iter();
+    match events.next().unwrap().unwrap() {
+        DispenserEvent::Claim(event) => {
+            assert_eq!(event.remaining_balance, 1000);
+            assert_eq!(event.claimant, claimant);
+            assert_eq!(
+                event.claim_info.try_to_vec().unwrap(),
+                claim_info.try_to_vec().unwrap()
+            );
+            assert_eq!(event.amount_received, claim_info.amount);
+        }
+        DispenserEvent::ClaimV2(_) => panic!("expected a ClaimEvent"),
+    }
+    match events.next().unwrap().unwrap() {
+        DispenserEvent::ClaimV2(event) => assert_eq!(event, claim_event_v2),
+        DispenserEvent::Claim(_) => panic!("expected a ClaimEventV2"),
+    }
+    assert_eq!(
+        events.next().unwrap().err().unwrap(),
+        UndecodableEvent {
+            data: "bm90IGFuIGV2ZW50".to_string(),
+        }
+    );
+    assert!(events.next().is_none());
+}
@@ -0,0 +1,192 @@
+use {
+    super::events::{
+        decode_events,
+        DispenserEvent,
+        UndecodableEvent,
+    },
+    crate::{
+        get_identity_hash,
+        ClaimEvent,
+        ClaimInfo,
+        Identity,
+        DEFAULT_CAMPAIGN_ID,
+        HASH_SIZE,
+        IDENTITY_VARIANTS,
+    },
+    anchor_lang::prelude::Pubkey,
+    std::{
+        collections::{
+            BTreeMap,
+            HashMap,
+        },
+        io::{
+            self,
+            BufRead,
+        },
+    },
+};
+
+/**
+ * The cumulative amount claimed by an identity and the last claimant it authorized.
+ */
+#[derive(Clone)]
+pub struct IndexedClaim {
+    pub identity:       Identity,
+    pub claimant:       Pubkey,
+    pub amount_claimed: u64,
+}
+
+/**
+ * The claim state of a campaign rebuilt from the `ClaimEvent`s found in transaction logs.
+ * Events carry the cumulative amount of the identity, so ingesting the same transaction twice doesn't
+ * change the totals.
+ */
+#[derive(Default)]
+pub struct CampaignClaimIndex {
+    claims:                    HashMap<[u8; HASH_SIZE], IndexedClaim>,
+    pub claimed_per_ecosystem: [u64; IDENTITY_VARIANTS], // Indexed by `Identity::get_variant_index`
+    pub total_claimed:         u64,
+    pub claim_count:           u64,
+    /// Treasury balance after the last ingested claim
+    pub remaining_balance:     Option<u64>,
+}
+
+/**
+ * The claim state of every campaign found in transaction logs, see `CampaignClaimIndex`.
+ */
+#[derive(Default)]
+pub struct ClaimIndex {
+    campaigns:              BTreeMap<u64, CampaignClaimIndex>,
+    /// Data logged by the program that isn't a known event, the claims it may hold are missing from the totals
+    pub undecodable_events: Vec<UndecodableEvent>,
+}
+
+impl ClaimIndex {
+    /**
+     * `claim` emits a `ClaimEvent`, which has the leaf, then a `ClaimEventV2`, which has the campaign.
+     * A `ClaimEvent` that isn't followed by the `ClaimEventV2` of the same identity was emitted before
+     * campaigns existed, so it belongs to the default campaign.
+     */
+    pub fn ingest_logs<S: AsRef<str>>(&mut self, log_messages: &[S]) {
+        let mut pending_claim_event: Option<ClaimEvent> = None;
+        for event in decode_events(log_messages) {
+            match event {
+                Err(undecodable_event) => self.undecodable_events.push(undecodable_event),
+                Ok(DispenserEvent::Claim(claim_event)) => {
+                    if let Some(legacy_claim_event) = pending_claim_event.replace(claim_event) {
+                        self.ingest_claim_event(DEFAULT_CAMPAIGN_ID, &legacy_claim_event);
+                    }
+                }
+                Ok(DispenserEvent::ClaimV2(claim_event_v2)) => {
+                    if let Some(claim_event) = pending_claim_event.take() {
+                        let campaign_id = if get_identity_hash(&claim_event.claim_info.identity)
+                            == claim_event_v2.identity_hash
+                        {
+                            claim_event_v2.campaign_id
+                        } else {
+                            DEFAULT_CAMPAIGN_ID
+                        };
+                        self.ingest_claim_event(campaign_id, &claim_event);
+                    }
+                }
+            }
+        }
+        if let Some(legacy_claim_event) = pending_claim_event {
+            self.ingest_claim_event(DEFAULT_CAMPAIGN_ID, &legacy_claim_event);
+        }
+    }
+
+    /**
+     * Ingests a JSONL dump of transaction logs. Each line is either the JSON array of the log messages
+     * of a transaction or a transaction as returned by the `getTransaction` RPC method.
+     * Failed transactions still log the events emitted before the failure, so transactions whose
+     * `meta.err` isn't null are skipped.
+     */
+    pub fn ingest_jsonl<R: BufRead>(&mut self, reader: R) -> io::Result<()> {
+        for line in reader.lines() {
+            let line = line?;
+            if line.trim().is_empty() {
+                continue;
+            }
+            let value: serde_json::Value = serde_json::from_str(&line)?;
+            let meta = value.get("meta");
+            if meta
+                .and_then(|meta| meta.get("err"))
+                .map_or(false, |err| !err.is_null())
+            {
+                continue;
+            }
+            let log_messages = meta
+                .and_then(|meta| meta.get("logMessages"))
+                .unwrap_or(&value);
+            let log_messages: Vec<String> = serde_json::from_value(log_messages.clone())?;
+            self.ingest_logs(&log_messages);
+        }
+        Ok(())
+    }
+
+    pub fn ingest_claim_event(&mut self, campaign_id: u64, claim_event: &ClaimEvent) {
+        self.campaigns
+            .entry(campaign_id)
+            .or_default()
+            .ingest_claim_event(claim_event);
+    }
+
+    pub fn get_campaign(&self, campaign_id: u64) -> Option<&CampaignClaimIndex> {
+        self.campaigns.get(&campaign_id)
+    }
+
+    pub fn campaigns(&self) -> impl Iterator<Item = (&u64, &CampaignClaimIndex)> {
+        self.campaigns.iter()
+    }
+}
+
+impl CampaignClaimIndex {
+    pub fn ingest_claim_event(&mut self, claim_event: &ClaimEvent) {
+        let identity = &claim_event.claim_info.identity;
+        let indexed_claim = self
+            .claims
+            .entry(get_identity_hash(identity))
+            .or_insert_with(|| IndexedClaim {
+                identity:       identity.clone(),
+                claimant:       claim_event.claimant,
+                amount_claimed: 0,
+            });
+        if claim_event.claim_info.amount <= indexed_claim.amount_claimed {
+            return;
+        }
+
+        // Only the part of the cumulative amount that wasn't claimed yet was transferred
+        let amount_transferred = claim_event.claim_info.amount - indexed_claim.amount_claimed;
+        indexed_claim.amount_claimed = claim_event.claim_info.amount;
+        indexed_claim.claimant = claim_event.claimant;
+        self.claimed_per_ecosystem[identity.get_variant_index()] += amount_transferred;
+        self.total_claimed += amount_transferred;
+        self.claim_count += 1;
+        self.remaining_balance = Some(claim_event.remaining_balance);
+    }
+
+    pub fn get_claim(&self, identity: &Identity) -> Option<&IndexedClaim> {
+        self.claims.get(&get_identity_hash(identity))
+    }
+
+    pub fn get_amount_claimed(&self, identity: &Identity) -> u64 {
+        self.get_claim(identity)
+            .map_or(0, |indexed_claim| indexed_claim.amount_claimed)
+    }
+
+    pub fn claims(&self) -> impl Iterator<Item = &IndexedClaim> {
+        self.claims.values()
+    }
+
+    /**
+     * The leaves of an allocation whose amount hasn't been fully claimed, including the leaves of
+     * identities that only claimed a previous, smaller allocation.
+     */
+    pub fn get_unclaimed<'a>(&self, leaves: &'a [ClaimInfo]) -> Vec<&'a ClaimInfo> {
+        leaves
+            .iter()
+            .filter(|leaf| leaf.amount > self.get_amount_claimed(&leaf.identity))
+            .collect()
+    }
+}
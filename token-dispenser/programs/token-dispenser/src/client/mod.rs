@@ -0,0 +1,357 @@
+use {
+    crate::{
+        accounts,
+        ecosystems::{
+            ed25519::{
+                Ed25519InstructionData,
+                Ed25519InstructionHeader,
+                Ed25519Pubkey,
+                Ed25519Signature,
+            },
+            secp256k1::{
+                EvmPubkey,
+                Secp256k1InstructionData,
+                Secp256k1InstructionHeader,
+                Secp256k1Signature,
+            },
+            secp256r1::{
+                Secp256r1InstructionData,
+                Secp256r1InstructionHeader,
+                Secp256r1Pubkey,
+                Secp256r1Signature,
+                SECP256R1_ID,
+            },
+        },
+        get_claim_bumps,
+        get_claimant_total_pda,
+        get_denylist_pda,
+        get_identity_hash,
+        get_legacy_receipt_pda,
+        get_receipt_pda,
+        instruction,
+        ClaimCertificate,
+        ClaimInfo,
+        Config,
+        DenylistKey,
+        ErrorCode,
+        IdentityCertificate,
+        DEFAULT_CAMPAIGN_ID,
+    },
+    anchor_lang::{
+        prelude::*,
+        solana_program::{
+            ed25519_program::ID as ED25519_ID,
+            instruction::Instruction,
+            secp256k1_program::ID as SECP256K1_ID,
+        },
+        InstructionData,
+    },
+    spl_associated_token_account::get_associated_token_address_with_program_id,
+};
+
+pub mod events;
+pub mod indexer;
+pub mod simulate;
+
+/**
+ * The signature of the message that proves ownership of an identity, as returned by the wallet
+ * (or by the dispenser guard for Discord and Starknet).
+ * `message` is the exact byte string that was signed, it gets copied into the signature verification instruction.
+ */
+#[derive(Clone, Debug)]
+pub enum RawSignature {
+    Ed25519 {
+        signature: Ed25519Signature,
+        message:   Vec<u8>,
+    },
+    Secp256k1 {
+        signature:   Secp256k1Signature,
+        recovery_id: u8,
+        message:     Vec<u8>,
+    },
+    Secp256r1 {
+        signature: Secp256r1Signature,
+        message:   Vec<u8>,
+    },
+}
+
+/**
+ * Returns the instructions of a claim transaction in order :
+ * - The signature verification instruction, if the ecosystem needs one. It is placed first, so
+ *   `verification_instruction_index` of the certificate should be 0 unless the caller prepends other instructions.
+ * - The claim instruction, with the receipt PDA as its first remaining account, followed by the claimant
+ *   total PDA if the campaign has a `max_per_claimant`, by the denylist entries of the leaf and the claimant,
+ *   by the dispenser guard if the campaign requires its signature for the ecosystem of the identity, and by
+ *   the legacy receipt of the leaf for the default campaign.
+ * The caller is responsible for adding `funder` and `claimant`, and the dispenser guard if needed, as signers.
+ * `token_program` is the owner of the mint, either the token program or token-2022.
+ */
+pub fn get_claim_instructions(
+    config: &Config,
+    campaign_id: u64,
+    funder: &Pubkey,
+    claimant: &Pubkey,
+    token_program: &Pubkey,
+    claim_certificate: ClaimCertificate,
+    signature: Option<RawSignature>,
+) -> Result<Vec<Instruction>> {
+    let claim_info = get_claim_info(&claim_certificate, claimant)?;
+
+    let mut instructions = vec![];
+    if let Some(verification_instruction) =
+        get_verification_instruction(config, &claim_certificate.proof_of_identity, signature)?
+    {
+        instructions.push(verification_instruction);
+    }
+    instructions.push(get_claim_instruction(
+        config,
+        campaign_id,
+        funder,
+        claimant,
+        token_program,
+        claim_certificate,
+        &claim_info,
+    ));
+    Ok(instructions)
+}
+
+pub fn get_claim_instruction(
+    config: &Config,
+    campaign_id: u64,
+    funder: &Pubkey,
+    claimant: &Pubkey,
+    token_program: &Pubkey,
+    claim_certificate: ClaimCertificate,
+    claim_info: &ClaimInfo,
+) -> Instruction {
+    let mut accounts = accounts::Claim::populate(
+        campaign_id,
+        *funder,
+        *claimant,
+        config.mint,
+        get_associated_token_address_with_program_id(claimant, &config.mint, token_program),
+        config.treasury,
+        *token_program,
+    )
+    .to_account_metas(None);
+    accounts.push(AccountMeta::new(
+        get_receipt_pda(campaign_id, &claim_info.identity).0,
+        false,
+    ));
+    if config.max_per_claimant.is_some() {
+        accounts.push(AccountMeta::new(
+            get_claimant_total_pda(campaign_id, claimant).0,
+            false,
+        ));
+    }
+    let denylist_keys = [
+        DenylistKey::Identity {
+            identity_hash: get_identity_hash(&claim_info.identity),
+        },
+        DenylistKey::Claimant { pubkey: *claimant },
+    ];
+    for denylist_key in denylist_keys.iter() {
+        accounts.push(AccountMeta::new_readonly(
+            get_denylist_pda(campaign_id, denylist_key).0,
+            false,
+        ));
+    }
+    if config.requires_guard_signature(&claim_info.identity) {
+        accounts.push(AccountMeta::new_readonly(config.dispenser_guard, true));
+    }
+    if campaign_id == DEFAULT_CAMPAIGN_ID {
+        accounts.push(AccountMeta::new_readonly(
+            get_legacy_receipt_pda(&claim_info.try_to_vec().unwrap()).0,
+            false,
+        ));
+    }
+
+    Instruction::new_with_bytes(
+        crate::id(),
+        &instruction::Claim {
+            campaign_id,
+            claim_certificate,
+            bumps: Some(get_claim_bumps(campaign_id, claim_info, claimant)),
+        }
+        .data(),
+        accounts,
+    )
+}
+
+/**
+ * Builds the signature verification instruction matching the proof of identity.
+ * Errors if a signature is missing or if it is not of the type the program will check.
+ */
+pub fn get_verification_instruction(
+    config: &Config,
+    proof_of_identity: &IdentityCertificate,
+    signature: Option<RawSignature>,
+) -> Result<Option<Instruction>> {
+    match (proof_of_identity, signature) {
+        (
+            IdentityCertificate::Solana
+            | IdentityCertificate::Cosmwasm { .. }
+            | IdentityCertificate::CosmwasmSignDoc { .. },
+            None,
+        ) => Ok(None),
+        (
+            IdentityCertificate::Discord {
+                verification_instruction_index,
+                ..
+            }
+            | IdentityCertificate::Starknet {
+                verification_instruction_index,
+                ..
+            },
+            Some(RawSignature::Ed25519 { signature, message }),
+        ) => Ok(Some(get_ed25519_verification_instruction(
+            &Ed25519Pubkey::from(config.dispenser_guard),
+            &signature,
+            &message,
+            *verification_instruction_index,
+        )?)),
+        (
+            IdentityCertificate::Sui {
+                pubkey,
+                verification_instruction_index,
+            }
+            | IdentityCertificate::Aptos {
+                pubkey,
+                verification_instruction_index,
+            }
+            | IdentityCertificate::Cardano {
+                pubkey,
+                verification_instruction_index,
+            },
+            Some(RawSignature::Ed25519 { signature, message }),
+        ) => Ok(Some(get_ed25519_verification_instruction(
+            pubkey,
+            &signature,
+            &message,
+            *verification_instruction_index,
+        )?)),
+        (
+            IdentityCertificate::Evm {
+                pubkey,
+                verification_instruction_index,
+            }
+            | IdentityCertificate::Injective {
+                pubkey,
+                verification_instruction_index,
+            },
+            Some(RawSignature::Secp256k1 {
+                signature,
+                recovery_id,
+                message,
+            }),
+        ) => Ok(Some(get_secp256k1_verification_instruction(
+            pubkey,
+            &signature,
+            recovery_id,
+            &message,
+            *verification_instruction_index,
+        )?)),
+        (
+            IdentityCertificate::Passkey {
+                pubkey,
+                verification_instruction_index,
+                ..
+            },
+            Some(RawSignature::Secp256r1 { signature, message }),
+        ) => Ok(Some(get_secp256r1_verification_instruction(
+            pubkey,
+            &signature,
+            &message,
+            *verification_instruction_index,
+        )?)),
+        _ => err!(ErrorCode::SignatureVerificationWrongProgram),
+    }
+}
+
+/**
+ * Returns the leaf that the program will check against the merkle root, without verifying any signature.
+ */
+pub fn get_claim_info(
+    claim_certificate: &ClaimCertificate,
+    claimant: &Pubkey,
+) -> Result<ClaimInfo> {
+    Ok(ClaimInfo {
+        identity: claim_certificate.proof_of_identity.get_identity(claimant)?,
+        amount:   claim_certificate.amount,
+    })
+}
+
+pub fn get_ed25519_verification_instruction(
+    pubkey: &Ed25519Pubkey,
+    signature: &Ed25519Signature,
+    message: &[u8],
+    instruction_index: u8,
+) -> Result<Instruction> {
+    let instruction_data = Ed25519InstructionData {
+        header:    Ed25519InstructionHeader::expected_header(
+            get_message_length(message)?,
+            instruction_index,
+        ),
+        signature: signature.clone(),
+        pubkey:    pubkey.clone(),
+        message:   message.to_vec(),
+    };
+    Ok(Instruction {
+        program_id: ED25519_ID,
+        accounts:   vec![],
+        data:       instruction_data.try_to_vec()?,
+    })
+}
+
+pub fn get_secp256k1_verification_instruction(
+    eth_address: &EvmPubkey,
+    signature: &Secp256k1Signature,
+    recovery_id: u8,
+    message: &[u8],
+    instruction_index: u8,
+) -> Result<Instruction> {
+    let instruction_data = Secp256k1InstructionData {
+        header: Secp256k1InstructionHeader::expected_header(
+            get_message_length(message)?,
+            instruction_index,
+        ),
+        eth_address: *eth_address,
+        signature: signature.clone(),
+        recovery_id,
+        message: message.to_vec(),
+    };
+    Ok(Instruction {
+        program_id: SECP256K1_ID,
+        accounts:   vec![],
+        data:       instruction_data.try_to_vec()?,
+    })
+}
+
+pub fn get_secp256r1_verification_instruction(
+    pubkey: &Secp256r1Pubkey,
+    signature: &Secp256r1Signature,
+    message: &[u8],
+    instruction_index: u8,
+) -> Result<Instruction> {
+    let instruction_data = Secp256r1InstructionData {
+        header:    Secp256r1InstructionHeader::expected_header(
+            get_message_length(message)?,
+            instruction_index,
+        ),
+        signature: signature.clone(),
+        pubkey:    pubkey.clone(),
+        message:   message.to_vec(),
+    };
+    Ok(Instruction {
+        program_id: SECP256R1_ID,
+        accounts:   vec![],
+        data:       instruction_data.try_to_vec()?,
+    })
+}
+
+fn get_message_length(message: &[u8]) -> Result<u16> {
+    message
+        .len()
+        .try_into()
+        .map_err(|_| error!(ErrorCode::SignatureVerificationWrongPayload))
+}
@@ -0,0 +1,362 @@
+use {
+    super::get_claim_info,
+    crate::{
+        ecosystems::{
+            cosmos::ADMISSIBLE_CHAIN_IDS,
+            ed25519::{
+                Ed25519InstructionData,
+                Ed25519Pubkey,
+            },
+            get_expected_payload,
+            secp256k1::Secp256k1InstructionData,
+            secp256r1::Secp256r1InstructionData,
+        },
+        get_receipt_pda,
+        ClaimCertificate,
+        ClaimInfo,
+        Config,
+        ErrorCode,
+        IdentityCertificate,
+        Receipt,
+    },
+    anchor_lang::{
+        prelude::*,
+        solana_program::{
+            instruction::{
+                BorrowedAccountMeta,
+                BorrowedInstruction,
+                Instruction,
+            },
+            sysvar::{
+                self,
+                instructions::{
+                    construct_instructions_data,
+                    ID as SYSVAR_IX_ID,
+                },
+            },
+        },
+    },
+};
+
+/**
+ * The checks performed by the `claim` instruction, in the order they are performed.
+ */
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum ClaimStage {
+    PrecompileHeader,
+    Signer,
+    Payload,
+    Expiry,
+    ChainId,
+    Inclusion,
+    Receipt,
+    Nonce,
+    MaxTransfer,
+}
+
+#[derive(Debug)]
+pub struct ClaimFailure {
+    pub stage:    ClaimStage,
+    pub error:    Error,
+    pub expected: Option<String>,
+    pub actual:   Option<String>,
+}
+
+/**
+ * The result of running every check of `claim` off-chain.
+ * `decoded_message` is the message signed by the identity, lossily decoded as utf-8 since most wallets sign text.
+ */
+pub struct ClaimReport {
+    pub claim_info:      Option<ClaimInfo>,
+    pub receipt:         Option<Pubkey>,
+    pub decoded_message: Option<String>,
+    pub failure:         Option<ClaimFailure>,
+}
+
+impl ClaimReport {
+    pub fn is_success(&self) -> bool {
+        self.failure.is_none()
+    }
+}
+
+/**
+ * Runs the checks of the `claim` instruction against `config`, without sending a transaction.
+ * `instructions` are all the instructions of the claim transaction, so that `verification_instruction_index`
+ * can be resolved like on-chain. `receipt` is the receipt of the identity, `None` if the receipt PDA isn't owned by
+ * the program.
+ * `unix_timestamp` is the time the claim is simulated at, which `expires_at` is checked against.
+ */
+pub fn simulate_claim(
+    config: &Config,
+    campaign_id: u64,
+    claimant: &Pubkey,
+    claim_certificate: &ClaimCertificate,
+    instructions: &[Instruction],
+    receipt: Option<&Receipt>,
+    unix_timestamp: i64,
+) -> ClaimReport {
+    let amount_claimed = receipt.map(|receipt| receipt.amount_claimed);
+    let nonce = receipt.map_or(0, |receipt| receipt.nonce);
+    let decoded_message = get_signed_message(&claim_certificate.proof_of_identity, instructions)
+        .map(|message| String::from_utf8_lossy(&message).into_owned());
+    let mut report = ClaimReport {
+        claim_info: None,
+        receipt: None,
+        decoded_message,
+        failure: None,
+    };
+
+    let mut sysvar_data = construct_instructions_data(
+        &instructions
+            .iter()
+            .map(borrow_instruction)
+            .collect::<Vec<BorrowedInstruction>>(),
+    );
+    let mut lamports = 0;
+    let sysvar_instruction = AccountInfo::new(
+        &SYSVAR_IX_ID,
+        false,
+        false,
+        &mut lamports,
+        &mut sysvar_data,
+        &sysvar::ID,
+        false,
+        0,
+    );
+
+    let claim_info = match claim_certificate.checked_into_claim_info(
+        &sysvar_instruction,
+        campaign_id,
+        &config.merkle_root,
+        claimant,
+        &config.dispenser_guard,
+        unix_timestamp,
+    ) {
+        Ok(claim_info) => claim_info,
+        Err(error) => {
+            let stage = get_identity_stage(&error);
+            let (expected, actual) = match stage {
+                ClaimStage::Signer => (
+                    get_expected_signer(config, &claim_certificate.proof_of_identity),
+                    get_signer(&claim_certificate.proof_of_identity, instructions),
+                ),
+                ClaimStage::Payload => (
+                    Some(get_expected_payload(campaign_id, claimant)),
+                    report.decoded_message.clone(),
+                ),
+                ClaimStage::Expiry => (
+                    claim_certificate
+                        .expires_at
+                        .map(|expires_at| expires_at.to_string()),
+                    Some(unix_timestamp.to_string()),
+                ),
+                ClaimStage::ChainId => (
+                    Some(ADMISSIBLE_CHAIN_IDS.join(", ")),
+                    get_chain_id(&claim_certificate.proof_of_identity),
+                ),
+                _ => (None, None),
+            };
+            report.failure = Some(ClaimFailure {
+                stage,
+                error,
+                expected,
+                actual,
+            });
+            // The leaf can still be derived, which helps debugging the later stages
+            report.claim_info = get_claim_info(claim_certificate, claimant).ok();
+            return report;
+        }
+    };
+
+    let leaf_vector = claim_info.try_to_vec().unwrap();
+    report.receipt = Some(get_receipt_pda(campaign_id, &claim_info.identity).0);
+    report.claim_info = Some(claim_info.clone());
+
+    if !config
+        .merkle_root
+        .check(claim_certificate.proof_of_inclusion.clone(), &leaf_vector)
+    {
+        report.failure = Some(ClaimFailure {
+            stage:    ClaimStage::Inclusion,
+            error:    error!(ErrorCode::InvalidInclusionProof),
+            expected: Some(hex::encode(config.merkle_root.as_bytes())),
+            actual:   None,
+        });
+    } else if amount_claimed.map_or(false, |amount_claimed| claim_info.amount <= amount_claimed) {
+        report.failure = Some(ClaimFailure {
+            stage:    ClaimStage::Receipt,
+            error:    error!(ErrorCode::AlreadyClaimed),
+            expected: None,
+            actual:   report.receipt.map(|receipt| receipt.to_string()),
+        });
+    } else if claim_certificate
+        .nonce
+        .map_or(false, |signed_nonce| signed_nonce != nonce)
+    {
+        report.failure = Some(ClaimFailure {
+            stage:    ClaimStage::Nonce,
+            error:    error!(ErrorCode::NonceMismatch),
+            expected: Some(nonce.to_string()),
+            actual:   claim_certificate.nonce.map(|nonce| nonce.to_string()),
+        });
+    } else if claim_info.amount - amount_claimed.unwrap_or(0) > config.max_transfer {
+        report.failure = Some(ClaimFailure {
+            stage:    ClaimStage::MaxTransfer,
+            error:    error!(ErrorCode::TransferExceedsMax),
+            expected: Some(config.max_transfer.to_string()),
+            actual:   Some((claim_info.amount - amount_claimed.unwrap_or(0)).to_string()),
+        });
+    }
+    report
+}
+
+/// Errors that aren't signature verification errors come from loading or deserializing the precompile instruction
+fn get_identity_stage(error: &Error) -> ClaimStage {
+    if *error == error!(ErrorCode::SignatureVerificationWrongSigner) {
+        ClaimStage::Signer
+    } else if *error == error!(ErrorCode::SignatureVerificationWrongPayload)
+        || *error == error!(ErrorCode::SignatureVerificationWrongPayloadMetadata)
+    {
+        ClaimStage::Payload
+    } else if *error == error!(ErrorCode::AuthorizationExpired) {
+        ClaimStage::Expiry
+    } else if *error == error!(ErrorCode::UnauthorizedCosmosChainId) {
+        ClaimStage::ChainId
+    } else {
+        ClaimStage::PrecompileHeader
+    }
+}
+
+fn borrow_instruction(instruction: &Instruction) -> BorrowedInstruction {
+    BorrowedInstruction {
+        program_id: &instruction.program_id,
+        accounts:   instruction
+            .accounts
+            .iter()
+            .map(|meta| BorrowedAccountMeta {
+                pubkey:      &meta.pubkey,
+                is_signer:   meta.is_signer,
+                is_writable: meta.is_writable,
+            })
+            .collect(),
+        data:       &instruction.data,
+    }
+}
+
+fn find_verification_instruction<'a>(
+    proof_of_identity: &IdentityCertificate,
+    instructions: &'a [Instruction],
+) -> Option<&'a Instruction> {
+    match proof_of_identity {
+        IdentityCertificate::Discord {
+            verification_instruction_index,
+            ..
+        }
+        | IdentityCertificate::Evm {
+            verification_instruction_index,
+            ..
+        }
+        | IdentityCertificate::Sui {
+            verification_instruction_index,
+            ..
+        }
+        | IdentityCertificate::Aptos {
+            verification_instruction_index,
+            ..
+        }
+        | IdentityCertificate::Injective {
+            verification_instruction_index,
+            ..
+        }
+        | IdentityCertificate::Passkey {
+            verification_instruction_index,
+            ..
+        }
+        | IdentityCertificate::Starknet {
+            verification_instruction_index,
+            ..
+        }
+        | IdentityCertificate::Cardano {
+            verification_instruction_index,
+            ..
+        } => instructions.get(*verification_instruction_index as usize),
+        IdentityCertificate::Solana
+        | IdentityCertificate::Cosmwasm { .. }
+        | IdentityCertificate::CosmwasmSignDoc { .. } => None,
+    }
+}
+
+/// Returns the message and the signer of the precompile instruction, without checking them
+fn decode_verification_instruction(
+    proof_of_identity: &IdentityCertificate,
+    instructions: &[Instruction],
+) -> Option<(Vec<u8>, Vec<u8>)> {
+    let instruction = find_verification_instruction(proof_of_identity, instructions)?;
+    match proof_of_identity {
+        IdentityCertificate::Evm { .. } | IdentityCertificate::Injective { .. } => {
+            Secp256k1InstructionData::try_from_slice(&instruction.data)
+                .ok()
+                .map(|data| (data.message, data.eth_address.try_to_vec().unwrap()))
+        }
+        IdentityCertificate::Passkey { .. } => {
+            Secp256r1InstructionData::try_from_slice(&instruction.data)
+                .ok()
+                .map(|data| (data.message, data.pubkey.try_to_vec().unwrap()))
+        }
+        _ => Ed25519InstructionData::try_from_slice(&instruction.data)
+            .ok()
+            .map(|data| (data.message, data.pubkey.try_to_vec().unwrap())),
+    }
+}
+
+fn get_signed_message(
+    proof_of_identity: &IdentityCertificate,
+    instructions: &[Instruction],
+) -> Option<Vec<u8>> {
+    match proof_of_identity {
+        IdentityCertificate::Solana => None,
+        IdentityCertificate::Cosmwasm { message, .. } => Some(message.clone()),
+        IdentityCertificate::CosmwasmSignDoc { sign_doc, .. } => Some(sign_doc.clone()),
+        // The challenge of a passkey is inside the client data, the signed message is binary
+        IdentityCertificate::Passkey {
+            client_data_json, ..
+        } => Some(client_data_json.clone()),
+        _ => decode_verification_instruction(proof_of_identity, instructions)
+            .map(|(message, _)| message),
+    }
+}
+
+fn get_signer(
+    proof_of_identity: &IdentityCertificate,
+    instructions: &[Instruction],
+) -> Option<String> {
+    decode_verification_instruction(proof_of_identity, instructions)
+        .map(|(_, signer)| hex::encode(signer))
+}
+
+fn get_expected_signer(config: &Config, proof_of_identity: &IdentityCertificate) -> Option<String> {
+    let signer = match proof_of_identity {
+        IdentityCertificate::Discord { .. } | IdentityCertificate::Starknet { .. } => {
+            Ed25519Pubkey::from(config.dispenser_guard).try_to_vec()
+        }
+        IdentityCertificate::Evm { pubkey, .. } | IdentityCertificate::Injective { pubkey, .. } => {
+            pubkey.try_to_vec()
+        }
+        IdentityCertificate::Sui { pubkey, .. }
+        | IdentityCertificate::Aptos { pubkey, .. }
+        | IdentityCertificate::Cardano { pubkey, .. } => pubkey.try_to_vec(),
+        IdentityCertificate::Passkey { pubkey, .. } => pubkey.try_to_vec(),
+        IdentityCertificate::Cosmwasm { pubkey, .. }
+        | IdentityCertificate::CosmwasmSignDoc { pubkey, .. } => pubkey.try_to_vec(),
+        IdentityCertificate::Solana => return None,
+    };
+    signer.ok().map(hex::encode)
+}
+
+fn get_chain_id(proof_of_identity: &IdentityCertificate) -> Option<String> {
+    match proof_of_identity {
+        IdentityCertificate::Cosmwasm { chain_id, .. }
+        | IdentityCertificate::CosmwasmSignDoc { chain_id, .. } => Some(chain_id.clone()),
+        _ => None,
+    }
+}
@@ -1,8 +1,3 @@
-#[cfg(test)]
-use super::{
-    ed25519::Ed25519TestMessage,
-    get_expected_payload,
-};
 use {
     super::ed25519::Ed25519Pubkey,
     crate::ErrorCode,
@@ -13,6 +8,14 @@ use {
     },
     sha3::Digest,
 };
+#[cfg(test)]
+use {
+    super::{
+        ed25519::Ed25519TestMessage,
+        get_expected_payload,
+    },
+    crate::DEFAULT_CAMPAIGN_ID,
+};
 
 pub const APTOS_PREFIX: &[u8] = b"APTOS\nmessage: ";
 pub const APTOS_SUFFIX: &[u8] = b"\nnonce: nonce";
@@ -45,7 +48,7 @@ impl AptosMessage {
 #[cfg(test)]
 impl Ed25519TestMessage for AptosMessage {
     fn for_claimant(claimant: &Pubkey) -> Self {
-        Self(get_expected_payload(claimant).into_bytes())
+        Self(get_expected_payload(DEFAULT_CAMPAIGN_ID, claimant).into_bytes())
     }
 
     fn get_message_with_metadata(&self) -> Vec<u8> {
@@ -73,7 +76,6 @@ impl From<Ed25519Pubkey> for AptosAddress {
     }
 }
 
-#[cfg(test)]
 impl From<[u8; Self::LEN]> for AptosAddress {
     fn from(bytes: [u8; Self::LEN]) -> Self {
         AptosAddress(bytes)
@@ -0,0 +1,364 @@
+use {
+    super::ed25519::Ed25519Pubkey,
+    crate::ErrorCode,
+    anchor_lang::{
+        prelude::*,
+        AnchorDeserialize,
+        AnchorSerialize,
+    },
+};
+#[cfg(test)]
+use {
+    super::{
+        ed25519::Ed25519TestMessage,
+        get_expected_payload,
+    },
+    crate::DEFAULT_CAMPAIGN_ID,
+};
+
+pub const COSE_SIGN1_CONTEXT: &[u8] = b"Signature1";
+pub const COSE_ALGORITHM_LABEL: i64 = 1;
+pub const COSE_ALGORITHM_EDDSA: i64 = -8;
+pub const CIP8_ADDRESS_LABEL: &[u8] = b"address";
+/// Shelley address types whose first credential is a key hash: base (key, key), base (key, script),
+/// pointer, enterprise and reward addresses.
+pub const KEY_HASH_ADDRESS_TYPES: [u8; 5] = [0b0000, 0b0010, 0b0100, 0b0110, 0b1110];
+
+pub const CBOR_UNSIGNED_INTEGER: u8 = 0;
+pub const CBOR_NEGATIVE_INTEGER: u8 = 1;
+pub const CBOR_BYTE_STRING: u8 = 2;
+pub const CBOR_TEXT_STRING: u8 = 3;
+pub const CBOR_ARRAY: u8 = 4;
+pub const CBOR_MAP: u8 = 5;
+
+/**
+* A CIP-8 message signed by a Cardano wallet through CIP-30 `signData`.
+* Only the payload and the address from the protected header are stored in this struct.
+* The message signed is the CBOR-encoded COSE `Sig_structure`:
+* `["Signature1", protected header, external aad (empty), payload]`.
+*/
+#[derive(AnchorDeserialize, AnchorSerialize, Clone)]
+pub struct CardanoMessage {
+    address: Vec<u8>,
+    payload: Vec<u8>,
+}
+
+impl CardanoMessage {
+    pub fn parse(data: &[u8]) -> Result<Self> {
+        let mut reader = CborReader(data);
+        if reader.read_array_header()? != 4 {
+            return err!(ErrorCode::SignatureVerificationWrongPayloadMetadata);
+        }
+
+        if reader.read_text_string()? != COSE_SIGN1_CONTEXT {
+            return err!(ErrorCode::SignatureVerificationWrongPayloadMetadata);
+        }
+
+        let protected_header = reader.read_byte_string()?;
+
+        if !reader.read_byte_string()?.is_empty() {
+            return err!(ErrorCode::SignatureVerificationWrongPayloadMetadata);
+        }
+
+        let payload = reader.read_byte_string()?;
+
+        if !reader.is_empty() {
+            return err!(ErrorCode::SignatureVerificationWrongPayloadMetadata);
+        }
+
+        Ok(CardanoMessage {
+            address: CardanoMessage::parse_protected_header(protected_header)?,
+            payload: payload.to_vec(),
+        })
+    }
+
+    /**
+     * The protected header is a CBOR map that needs to contain the EdDSA algorithm and the address of the signer.
+     */
+    fn parse_protected_header(data: &[u8]) -> Result<Vec<u8>> {
+        let mut reader = CborReader(data);
+        let mut algorithm = None;
+        let mut address = None;
+
+        for _ in 0..reader.read_map_header()? {
+            match reader.peek_major_type()? {
+                CBOR_UNSIGNED_INTEGER | CBOR_NEGATIVE_INTEGER => {
+                    if reader.read_integer()? == COSE_ALGORITHM_LABEL {
+                        algorithm = Some(reader.read_integer()?);
+                    } else {
+                        reader.skip_item()?;
+                    }
+                }
+                CBOR_TEXT_STRING => {
+                    if reader.read_text_string()? == CIP8_ADDRESS_LABEL {
+                        address = Some(reader.read_byte_string()?.to_vec());
+                    } else {
+                        reader.skip_item()?;
+                    }
+                }
+                _ => return err!(ErrorCode::SignatureVerificationWrongPayloadMetadata),
+            }
+        }
+
+        if !reader.is_empty() || algorithm != Some(COSE_ALGORITHM_EDDSA) {
+            return err!(ErrorCode::SignatureVerificationWrongPayloadMetadata);
+        }
+
+        address.ok_or_else(|| error!(ErrorCode::SignatureVerificationWrongPayloadMetadata))
+    }
+
+    pub fn get_payload(&self) -> &[u8] {
+        self.payload.as_slice()
+    }
+
+    /**
+     * Checks that the address in the protected header belongs to `pubkey` and returns the key hash.
+     */
+    pub fn check_signer(&self, pubkey: &Ed25519Pubkey) -> Result<CardanoKeyHash> {
+        let key_hash = CardanoKeyHash::from(pubkey.clone());
+        let address_type = self
+            .address
+            .first()
+            .ok_or_else(|| error!(ErrorCode::SignatureVerificationWrongSigner))?
+            >> 4;
+
+        if !(KEY_HASH_ADDRESS_TYPES.contains(&address_type)
+            && self.address.len() > CardanoKeyHash::LEN
+            && self.address[1..CardanoKeyHash::LEN + 1] == key_hash.0)
+        {
+            return err!(ErrorCode::SignatureVerificationWrongSigner);
+        }
+        Ok(key_hash)
+    }
+}
+
+/**
+ * A minimal CBOR reader for the subset of CBOR used in the COSE `Sig_structure`.
+ * Indefinite lengths are not supported.
+ */
+struct CborReader<'a>(&'a [u8]);
+
+impl<'a> CborReader<'a> {
+    fn is_empty(&self) -> bool {
+        self.0.is_empty()
+    }
+
+    fn read_bytes(&mut self, len: u64) -> Result<&'a [u8]> {
+        if (self.0.len() as u64) < len {
+            return err!(ErrorCode::SignatureVerificationWrongPayloadMetadata);
+        }
+        let (bytes, rest) = self.0.split_at(len as usize);
+        self.0 = rest;
+        Ok(bytes)
+    }
+
+    fn peek_major_type(&self) -> Result<u8> {
+        self.0
+            .first()
+            .map(|initial_byte| initial_byte >> 5)
+            .ok_or_else(|| error!(ErrorCode::SignatureVerificationWrongPayloadMetadata))
+    }
+
+    fn read_header(&mut self) -> Result<(u8, u64)> {
+        let initial_byte = self.read_bytes(1)?[0];
+        let argument = match initial_byte & 0x1f {
+            additional_info @ 0..=23 => additional_info as u64,
+            24 => self.read_bytes(1)?[0] as u64,
+            25 => u16::from_be_bytes(self.read_bytes(2)?.try_into().unwrap()) as u64,
+            26 => u32::from_be_bytes(self.read_bytes(4)?.try_into().unwrap()) as u64,
+            27 => u64::from_be_bytes(self.read_bytes(8)?.try_into().unwrap()),
+            _ => return err!(ErrorCode::SignatureVerificationWrongPayloadMetadata),
+        };
+        Ok((initial_byte >> 5, argument))
+    }
+
+    fn read_argument(&mut self, expected_major_type: u8) -> Result<u64> {
+        let (major_type, argument) = self.read_header()?;
+        if major_type != expected_major_type {
+            return err!(ErrorCode::SignatureVerificationWrongPayloadMetadata);
+        }
+        Ok(argument)
+    }
+
+    fn read_byte_string(&mut self) -> Result<&'a [u8]> {
+        let len = self.read_argument(CBOR_BYTE_STRING)?;
+        self.read_bytes(len)
+    }
+
+    fn read_text_string(&mut self) -> Result<&'a [u8]> {
+        let len = self.read_argument(CBOR_TEXT_STRING)?;
+        self.read_bytes(len)
+    }
+
+    fn read_array_header(&mut self) -> Result<u64> {
+        self.read_argument(CBOR_ARRAY)
+    }
+
+    fn read_map_header(&mut self) -> Result<u64> {
+        self.read_argument(CBOR_MAP)
+    }
+
+    fn read_integer(&mut self) -> Result<i64> {
+        let (major_type, argument) = self.read_header()?;
+        let argument = i64::try_from(argument)
+            .map_err(|_| error!(ErrorCode::SignatureVerificationWrongPayloadMetadata))?;
+        match major_type {
+            CBOR_UNSIGNED_INTEGER => Ok(argument),
+            CBOR_NEGATIVE_INTEGER => Ok(-1 - argument),
+            _ => err!(ErrorCode::SignatureVerificationWrongPayloadMetadata),
+        }
+    }
+
+    /**
+     * Skips a header value we don't care about, only integers and strings are supported.
+     */
+    fn skip_item(&mut self) -> Result<()> {
+        match self.read_header()? {
+            (CBOR_UNSIGNED_INTEGER | CBOR_NEGATIVE_INTEGER, _) => Ok(()),
+            (CBOR_BYTE_STRING | CBOR_TEXT_STRING, len) => self.read_bytes(len).map(|_| ()),
+            _ => err!(ErrorCode::SignatureVerificationWrongPayloadMetadata),
+        }
+    }
+}
+
+/**
+ * The blake2b-224 hash of an ed25519 public key, this is the credential used in Cardano addresses.
+ */
+#[derive(AnchorDeserialize, AnchorSerialize, Clone, PartialEq, Debug)]
+pub struct CardanoKeyHash([u8; Self::LEN]);
+
+impl CardanoKeyHash {
+    pub const LEN: usize = 28;
+}
+
+impl From<Ed25519Pubkey> for CardanoKeyHash {
+    fn from(val: Ed25519Pubkey) -> Self {
+        let mut result = CardanoKeyHash([0u8; Self::LEN]);
+        result.0.copy_from_slice(
+            blake2_rfc::blake2b::blake2b(Self::LEN, &[], &val.to_bytes()).as_bytes(),
+        );
+        result
+    }
+}
+
+impl From<[u8; Self::LEN]> for CardanoKeyHash {
+    fn from(bytes: [u8; Self::LEN]) -> Self {
+        CardanoKeyHash(bytes)
+    }
+}
+
+#[cfg(test)]
+pub const ENTERPRISE_ADDRESS_HEADER: u8 = 0b0110_0001;
+
+#[cfg(test)]
+fn encode_cbor_header(major_type: u8, argument: usize) -> Vec<u8> {
+    match argument {
+        0..=23 => vec![major_type << 5 | argument as u8],
+        24..=0xff => vec![major_type << 5 | 24, argument as u8],
+        _ => {
+            let mut header = vec![major_type << 5 | 25];
+            header.extend_from_slice(&(argument as u16).to_be_bytes());
+            header
+        }
+    }
+}
+
+#[cfg(test)]
+impl CardanoMessage {
+    /// Builds the message signed by `pubkey` through its enterprise address
+    pub fn for_signer(claimant: &Pubkey, pubkey: &Ed25519Pubkey) -> Self {
+        let mut address = vec![ENTERPRISE_ADDRESS_HEADER];
+        address.extend_from_slice(&CardanoKeyHash::from(pubkey.clone()).0);
+        Self {
+            address,
+            payload: get_expected_payload(DEFAULT_CAMPAIGN_ID, claimant).into_bytes(),
+        }
+    }
+
+    pub fn get_protected_header(&self) -> Vec<u8> {
+        let mut protected_header = encode_cbor_header(CBOR_MAP, 2);
+        protected_header.extend(encode_cbor_header(CBOR_UNSIGNED_INTEGER, 1));
+        protected_header.extend(encode_cbor_header(CBOR_NEGATIVE_INTEGER, 7)); // -8
+        protected_header.extend(encode_cbor_header(
+            CBOR_TEXT_STRING,
+            CIP8_ADDRESS_LABEL.len(),
+        ));
+        protected_header.extend_from_slice(CIP8_ADDRESS_LABEL);
+        protected_header.extend(encode_cbor_header(CBOR_BYTE_STRING, self.address.len()));
+        protected_header.extend_from_slice(&self.address);
+        protected_header
+    }
+}
+
+#[cfg(test)]
+impl Ed25519TestMessage for CardanoMessage {
+    /// The address of this message doesn't belong to any known key, use `CardanoMessage::for_signer`
+    /// to get a message that can be verified.
+    fn for_claimant(claimant: &Pubkey) -> Self {
+        Self::for_signer(claimant, &Ed25519Pubkey::from(Pubkey::new_unique()))
+    }
+
+    fn get_message_with_metadata(&self) -> Vec<u8> {
+        let protected_header = self.get_protected_header();
+        let mut message = encode_cbor_header(CBOR_ARRAY, 4);
+        message.extend(encode_cbor_header(
+            CBOR_TEXT_STRING,
+            COSE_SIGN1_CONTEXT.len(),
+        ));
+        message.extend_from_slice(COSE_SIGN1_CONTEXT);
+        message.extend(encode_cbor_header(CBOR_BYTE_STRING, protected_header.len()));
+        message.extend(protected_header);
+        message.extend(encode_cbor_header(CBOR_BYTE_STRING, 0));
+        message.extend(encode_cbor_header(CBOR_BYTE_STRING, self.payload.len()));
+        message.extend_from_slice(&self.payload);
+        message
+    }
+}
+
+#[test]
+pub fn test_cardano_parse_and_check_signer() {
+    let claimant = Pubkey::new_unique();
+    let pubkey = Ed25519Pubkey::from(Pubkey::new_unique());
+    let message = CardanoMessage::for_signer(&claimant, &pubkey);
+    let parsed_message = CardanoMessage::parse(&message.get_message_with_metadata()).unwrap();
+
+    assert_eq!(
+        parsed_message.get_payload(),
+        get_expected_payload(DEFAULT_CAMPAIGN_ID, &claimant).as_bytes()
+    );
+    assert_eq!(
+        parsed_message.check_signer(&pubkey).unwrap(),
+        CardanoKeyHash::from(pubkey.clone())
+    );
+
+    // wrong signer
+    assert_eq!(
+        parsed_message
+            .check_signer(&Ed25519Pubkey::from(Pubkey::new_unique()))
+            .unwrap_err(),
+        error!(ErrorCode::SignatureVerificationWrongSigner)
+    );
+
+    // script address
+    let mut script_address_message = parsed_message.clone();
+    script_address_message.address[0] = 0b0111_0001;
+    assert_eq!(
+        script_address_message.check_signer(&pubkey).unwrap_err(),
+        error!(ErrorCode::SignatureVerificationWrongSigner)
+    );
+
+    // trailing bytes
+    let mut wrong_message = message.get_message_with_metadata();
+    wrong_message.push(0);
+    assert_eq!(
+        CardanoMessage::parse(&wrong_message).unwrap_err(),
+        error!(ErrorCode::SignatureVerificationWrongPayloadMetadata)
+    );
+
+    // truncated message
+    let wrong_message = message.get_message_with_metadata();
+    assert_eq!(
+        CardanoMessage::parse(&wrong_message[..wrong_message.len() - 1]).unwrap_err(),
+        error!(ErrorCode::SignatureVerificationWrongPayloadMetadata)
+    );
+}
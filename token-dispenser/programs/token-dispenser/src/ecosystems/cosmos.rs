@@ -2,13 +2,13 @@
 use super::secp256k1::Secp256k1TestMessage;
 use {
     super::{
-        get_expected_payload,
         secp256k1::{
             EvmPubkey,
             SECP256K1_COMPRESSED_PUBKEY_LENGTH,
             SECP256K1_EVEN_PREFIX,
             SECP256K1_ODD_PREFIX,
         },
+        Authorization,
     },
     crate::ErrorCode,
     anchor_lang::{
@@ -46,6 +46,12 @@ pub struct CosmosMessage {
 }
 
 impl CosmosMessage {
+    /**
+     * Parses a JSON sign doc. Since the document is deserialized, this accepts any valid JSON
+     * encoding of the ADR036 doc (e.g. the sorted, HTML-escaped amino JSON that Ledger signs) and
+     * not only the exact bytes produced by `build_message`. Fields that aren't part of the doc are
+     * rejected, so the signed bytes can't carry content that isn't checked here.
+     */
     pub fn parse(data: &[u8], signer: &CosmosBech32Address) -> Result<Self> {
         let sign_doc: CosmosStdSignDoc = serde_json::from_slice(data)
             .map_err(|_| error!(ErrorCode::SignatureVerificationWrongPayloadMetadata))?;
@@ -106,21 +112,25 @@ impl CosmosMessage {
             .to_vec();
     }
 
+    /**
+     * Cosmos wallets sign the Sha256 hash of the JSON sign doc.
+     */
+    pub fn hash_sign_doc(sign_doc: &[u8]) -> [u8; 32] {
+        hash::hashv(&[sign_doc]).to_bytes()
+    }
+
     pub fn get_expected_hash(payload: &[u8], signer: &CosmosBech32Address) -> [u8; 32] {
-        hash::hashv(&[&CosmosMessage::build_message(payload, signer)]).to_bytes()
+        CosmosMessage::hash_sign_doc(&CosmosMessage::build_message(payload, signer))
     }
 
     pub fn check_hashed_payload(
         hashed_message: &[u8],
         signer: &CosmosBech32Address,
-        claimant: &Pubkey,
+        authorization: &Authorization,
     ) -> Result<()> {
-        if hashed_message
-            != CosmosMessage::get_expected_hash(get_expected_payload(claimant).as_bytes(), signer)
-        {
-            return err!(ErrorCode::SignatureVerificationWrongPayload);
-        }
-        Ok(())
+        authorization.check_signed_payload(|payload| {
+            hashed_message == CosmosMessage::get_expected_hash(payload, signer)
+        })
     }
 }
 
@@ -130,6 +140,7 @@ impl CosmosMessage {
 * For ADR036 (arbitrary messages), a lot of fields are zeroed.
  */
 #[derive(Serialize, Deserialize, Debug)]
+#[serde(deny_unknown_fields)]
 pub struct CosmosStdSignDoc {
     account_number: String,
     chain_id:       String,
@@ -143,6 +154,7 @@ pub struct CosmosStdSignDoc {
 * A cosmos message, there can be more than one in a signed doc.
 */
 #[derive(Serialize, Deserialize, Debug)]
+#[serde(deny_unknown_fields)]
 struct CosmosStdMsg {
     r#type: String,
     value:  CosmosAdr036Value,
@@ -152,6 +164,7 @@ struct CosmosStdMsg {
 * The payload of a Cosmos ADR036 message.
 */
 #[derive(Serialize, Deserialize, Debug)]
+#[serde(deny_unknown_fields)]
 struct CosmosAdr036Value {
     data:   String,
     signer: String,
@@ -161,6 +174,7 @@ struct CosmosAdr036Value {
 * Fee information in a signed doc. for ADR036 this is zeroed.
 */
 #[derive(Serialize, Deserialize, Debug)]
+#[serde(deny_unknown_fields)]
 struct CosmosStdFee {
     amount: Vec<CosmosCoin>,
     gas:    String,
@@ -170,6 +184,7 @@ struct CosmosStdFee {
 * A Cosmos coin used in the fee information.
 */
 #[derive(Serialize, Deserialize, Debug)]
+#[serde(deny_unknown_fields)]
 struct CosmosCoin {
     amount: String,
     denom:  String,
@@ -221,7 +236,6 @@ impl UncompressedSecp256k1Pubkey {
 }
 
 
-#[cfg(test)]
 impl From<[u8; Self::LEN]> for UncompressedSecp256k1Pubkey {
     fn from(bytes: [u8; Self::LEN]) -> Self {
         UncompressedSecp256k1Pubkey(bytes)
@@ -246,7 +260,6 @@ impl From<EvmPubkey> for CosmosBech32Address {
 }
 
 
-#[cfg(test)]
 impl From<&str> for CosmosBech32Address {
     fn from(bytes: &str) -> Self {
         CosmosBech32Address(bytes.to_string())
@@ -289,3 +302,10 @@ impl CosmosMessage {
         self.signer.clone()
     }
 }
+
+#[cfg(test)]
+impl CosmosBech32Address {
+    pub fn as_str(&self) -> &str {
+        self.0.as_str()
+    }
+}
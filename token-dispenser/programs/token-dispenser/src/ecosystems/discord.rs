@@ -17,6 +17,13 @@ pub struct DiscordMessage {
 }
 
 impl DiscordMessage {
+    pub fn new(username: &str, claimant: &Pubkey) -> Self {
+        Self {
+            username: username.to_string(),
+            claimant: *claimant,
+        }
+    }
+
     pub fn parse_and_check_claimant_and_username(
         data: &[u8],
         username: &str,
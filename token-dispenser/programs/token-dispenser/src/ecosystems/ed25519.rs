@@ -18,7 +18,6 @@ impl Ed25519Signature {
     pub const LEN: usize = 64;
 }
 
-#[cfg(test)]
 impl From<[u8; Self::LEN]> for Ed25519Signature {
     fn from(bytes: [u8; Self::LEN]) -> Self {
         Ed25519Signature(bytes)
@@ -45,7 +44,6 @@ impl Ed25519Pubkey {
     }
 }
 
-#[cfg(test)]
 impl From<[u8; Self::LEN]> for Ed25519Pubkey {
     fn from(bytes: [u8; Self::LEN]) -> Self {
         Ed25519Pubkey(bytes)
@@ -1,24 +1,37 @@
 use {
-    crate::ErrorCode,
+    crate::{
+        DispenserMerkleRoot,
+        ErrorCode,
+        DEFAULT_CAMPAIGN_ID,
+        HASH_SIZE,
+    },
     anchor_lang::prelude::{
         Pubkey,
         *,
     },
+    std::fmt::Write,
 };
 
 pub mod aptos;
+pub mod cardano;
 pub mod cosmos;
 pub mod discord;
 pub mod ed25519;
 pub mod evm;
+pub mod passkey;
 pub mod secp256k1;
+pub mod secp256r1;
+pub mod starknet;
 pub mod sui;
 
+/**
+ * Version of the authorization payload definition, bumped whenever one of the constants below changes.
+ * The typescript sdk reads the constants from `authorization_payload.json`, which must be kept in sync with them.
+ */
+pub const AUTHORIZATION_PAYLOAD_VERSION: u8 = 4;
+
 /**
  * Ecosystem agnostic authorization payload that the identity on the leaf needs to sign.
- *
- * NOTE: Any changes to this must also be made to the corresponding
- * constant in the typescript sdk
  * */
 pub const AUTHORIZATION_PAYLOAD: [&str; 3] = [
     "Pyth Grant PID:\n",
@@ -27,24 +40,266 @@ pub const AUTHORIZATION_PAYLOAD: [&str; 3] = [
 ];
 
 /**
- * Check a payload matches the expected authorization payload.
+ * Inserted after the program id in the payload of campaigns other than the default one, followed by the campaign id.
+ */
+pub const CAMPAIGN_PAYLOAD: &str = "\nCampaign: ";
+
+/**
+ * Appended to the v1 payload with the amount and the hex encoded merkle root, so that wallets display
+ * exactly what is being claimed and the signature can't be used for another tree.
+ */
+pub const AUTHORIZATION_PAYLOAD_V2: [&str; 3] = ["Amount: ", "\nMerkle root: ", "\n"];
+
+/**
+ * Optionally appended to the v1 or v2 payload with a unix timestamp after which the signature can't be used anymore.
  */
-pub fn check_payload(payload: &[u8], claimant: &Pubkey) -> Result<()> {
-    if payload != get_expected_payload(claimant).as_bytes() {
-        return err!(ErrorCode::SignatureVerificationWrongPayload);
+pub const EXPIRY_PAYLOAD: [&str; 2] = ["Expires at: ", "\n"];
+
+/**
+ * Optionally appended last with the nonce of the receipt of the identity, so that the signature can only be used for
+ * one claim of the identity.
+ */
+pub const NONCE_PAYLOAD: [&str; 2] = ["Nonce: ", "\n"];
+
+/**
+ * The claim that the identity authorizes by signing the payload.
+ */
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Authorization<'a> {
+    pub campaign_id:    u64,
+    pub claimant:       Pubkey,
+    pub merkle_root:    &'a [u8],
+    pub amount:         u64,
+    pub expires_at:     Option<i64>,
+    /** Checked against the receipt of the identity once it is loaded, see `checked_update_claim_receipt` */
+    pub nonce:          Option<u64>,
+    /** The time at which the authorization is being used */
+    pub unix_timestamp: i64,
+}
+
+impl<'a> Authorization<'a> {
+    pub fn new(
+        campaign_id: u64,
+        claimant: &Pubkey,
+        merkle_root: &'a DispenserMerkleRoot,
+        amount: u64,
+        expires_at: Option<i64>,
+        nonce: Option<u64>,
+        unix_timestamp: i64,
+    ) -> Self {
+        Authorization {
+            campaign_id,
+            claimant: *claimant,
+            merkle_root: merkle_root.as_bytes(),
+            amount,
+            expires_at,
+            nonce,
+            unix_timestamp,
+        }
+    }
+
+    /**
+     * The payloads that authorize this claim, the identity can sign any of them.
+     * The v2 payload starts with the v1 payload, which is only built once since encoding the claimant is costly.
+     * They are written on the stack since this runs on every claim.
+     */
+    pub fn get_accepted_payloads(&self) -> [PayloadBuffer; 2] {
+        let mut payload = PayloadBuffer::default();
+        write_expected_payload(&mut payload, self.campaign_id, &self.claimant);
+        let mut payload_v2 = payload;
+        push_payload_v2(&mut payload_v2, self);
+        push_expiry_payload(&mut payload, self.expires_at);
+        push_expiry_payload(&mut payload_v2, self.expires_at);
+        push_nonce_payload(&mut payload, self.nonce);
+        push_nonce_payload(&mut payload_v2, self.nonce);
+        [payload, payload_v2]
+    }
+
+    /**
+     * Check that the identity signed one of the accepted payloads, `is_signed` tells whether a payload is the one
+     * that was signed. Then check that the deadline committed to by the payload, if any, hasn't passed.
+     */
+    pub fn check_signed_payload<F: Fn(&[u8]) -> bool>(&self, is_signed: F) -> Result<()> {
+        if !self
+            .get_accepted_payloads()
+            .iter()
+            .any(|payload| is_signed(payload.as_bytes()))
+        {
+            return err!(ErrorCode::SignatureVerificationWrongPayload);
+        }
+        if let Some(expires_at) = self.expires_at {
+            if self.unix_timestamp > expires_at {
+                return err!(ErrorCode::AuthorizationExpired);
+            }
+        }
+        Ok(())
     }
-    Ok(())
 }
 
 /**
- * Get the expected authorization payload given the claimant authorized to receive the claim.
+ * Check a payload matches one of the accepted authorization payloads and hasn't expired.
  */
-pub fn get_expected_payload(claimant: &Pubkey) -> String {
-    AUTHORIZATION_PAYLOAD[0].to_string()
-        + &crate::ID.to_string()
-        + AUTHORIZATION_PAYLOAD[1]
-        + claimant.to_string().as_str()
-        + AUTHORIZATION_PAYLOAD[2]
+pub fn check_payload(payload: &[u8], authorization: &Authorization) -> Result<()> {
+    authorization.check_signed_payload(|expected_payload| payload == expected_payload)
+}
+
+/**
+ * The base58 encoding of the program id, so that claims don't have to encode it. It must match `declare_id!`.
+ */
+pub const PROGRAM_ID_BASE58: &str = "Fg6PaFpoGXkYsidMpWTK6W2BeZ7FEfcYkg476zPFsLnS";
+
+/**
+ * Upper bounds of the length of the payloads including the expiry and the nonce, used to size them once. A base58
+ * pubkey has at most 44 characters, a u64 or an i64 at most 20.
+ */
+const PAYLOAD_CAPACITY: usize = 69 + 11 + 20 + 2 * MAX_BASE58_LEN + 33 + 28;
+const PAYLOAD_V2_CAPACITY: usize = 23 + 20 + 2 * HASH_SIZE;
+const MAX_PAYLOAD_LEN: usize = PAYLOAD_CAPACITY + PAYLOAD_V2_CAPACITY;
+const MAX_BASE58_LEN: usize = 44;
+const HEX_DIGITS: &[u8; 16] = b"0123456789abcdef";
+const BASE58_ALPHABET: &[u8; 58] = b"123456789ABCDEFGHJKLMNPQRSTUVWXYZabcdefghijkmnopqrstuvwxyz";
+
+/**
+ * An authorization payload written on the stack, large enough for the longest accepted payload.
+ */
+#[derive(Clone, Copy)]
+pub struct PayloadBuffer {
+    bytes: [u8; MAX_PAYLOAD_LEN],
+    len:   usize,
+}
+
+impl Default for PayloadBuffer {
+    fn default() -> Self {
+        PayloadBuffer {
+            bytes: [0u8; MAX_PAYLOAD_LEN],
+            len:   0,
+        }
+    }
+}
+
+impl PayloadBuffer {
+    pub fn as_bytes(&self) -> &[u8] {
+        &self.bytes[..self.len]
+    }
+}
+
+impl Write for PayloadBuffer {
+    fn write_str(&mut self, s: &str) -> std::fmt::Result {
+        let end = self.len + s.len();
+        self.bytes
+            .get_mut(self.len..end)
+            .ok_or(std::fmt::Error)?
+            .copy_from_slice(s.as_bytes());
+        self.len = end;
+        Ok(())
+    }
+}
+
+/**
+ * Get the expected authorization payload given the campaign and the claimant authorized to receive the claim.
+ */
+pub fn get_expected_payload(campaign_id: u64, claimant: &Pubkey) -> String {
+    let mut payload = String::with_capacity(PAYLOAD_CAPACITY);
+    write_expected_payload(&mut payload, campaign_id, claimant);
+    payload
+}
+
+/**
+ * Same as `get_expected_payload` followed by the amount and the merkle root of the claim.
+ */
+pub fn get_expected_payload_v2(authorization: &Authorization) -> String {
+    let mut payload = get_expected_payload(authorization.campaign_id, &authorization.claimant);
+    payload.reserve(PAYLOAD_V2_CAPACITY);
+    push_payload_v2(&mut payload, authorization);
+    payload
+}
+
+fn write_expected_payload<W: Write>(payload: &mut W, campaign_id: u64, claimant: &Pubkey) {
+    payload.write_str(AUTHORIZATION_PAYLOAD[0]).unwrap();
+    payload.write_str(PROGRAM_ID_BASE58).unwrap();
+    if campaign_id != DEFAULT_CAMPAIGN_ID {
+        payload.write_str(CAMPAIGN_PAYLOAD).unwrap();
+        write!(payload, "{}", campaign_id).unwrap();
+    }
+    payload.write_str(AUTHORIZATION_PAYLOAD[1]).unwrap();
+    push_base58(payload, claimant);
+    payload.write_str(AUTHORIZATION_PAYLOAD[2]).unwrap();
+}
+
+/**
+ * Same as the `Display` of `Pubkey`, which allocates the encoded string.
+ */
+fn push_base58<W: Write>(payload: &mut W, pubkey: &Pubkey) {
+    let bytes = pubkey.as_ref();
+    // Little endian base 58 digits
+    let mut digits = [0u8; MAX_BASE58_LEN];
+    let mut len = 0;
+    for &byte in bytes {
+        let mut carry = byte as u32;
+        for digit in digits[..len].iter_mut() {
+            carry += (*digit as u32) << 8;
+            *digit = (carry % 58) as u8;
+            carry /= 58;
+        }
+        while carry > 0 {
+            digits[len] = (carry % 58) as u8;
+            len += 1;
+            carry /= 58;
+        }
+    }
+    // Each leading zero byte is encoded as a leading '1'
+    len += bytes.iter().take_while(|&&byte| byte == 0).count();
+    for &digit in digits[..len].iter().rev() {
+        payload
+            .write_char(BASE58_ALPHABET[digit as usize] as char)
+            .unwrap();
+    }
+}
+
+fn push_payload_v2<W: Write>(payload: &mut W, authorization: &Authorization) {
+    payload.write_str(AUTHORIZATION_PAYLOAD_V2[0]).unwrap();
+    write!(payload, "{}", authorization.amount).unwrap();
+    payload.write_str(AUTHORIZATION_PAYLOAD_V2[1]).unwrap();
+    for &byte in authorization.merkle_root {
+        payload
+            .write_char(HEX_DIGITS[(byte >> 4) as usize] as char)
+            .unwrap();
+        payload
+            .write_char(HEX_DIGITS[(byte & 0x0f) as usize] as char)
+            .unwrap();
+    }
+    payload.write_str(AUTHORIZATION_PAYLOAD_V2[2]).unwrap();
+}
+
+#[cfg(test)]
+impl Authorization<'static> {
+    pub fn for_claimant(campaign_id: u64, claimant: &Pubkey) -> Self {
+        Authorization {
+            campaign_id,
+            claimant: *claimant,
+            merkle_root: &[1u8; HASH_SIZE],
+            amount: 1000,
+            expires_at: None,
+            nonce: None,
+            unix_timestamp: 0,
+        }
+    }
+}
+
+fn push_expiry_payload<W: Write>(payload: &mut W, expires_at: Option<i64>) {
+    if let Some(expires_at) = expires_at {
+        payload.write_str(EXPIRY_PAYLOAD[0]).unwrap();
+        write!(payload, "{}", expires_at).unwrap();
+        payload.write_str(EXPIRY_PAYLOAD[1]).unwrap();
+    }
+}
+
+fn push_nonce_payload<W: Write>(payload: &mut W, nonce: Option<u64>) {
+    if let Some(nonce) = nonce {
+        payload.write_str(NONCE_PAYLOAD[0]).unwrap();
+        write!(payload, "{}", nonce).unwrap();
+        payload.write_str(NONCE_PAYLOAD[1]).unwrap();
+    }
 }
 
 #[test]
@@ -56,7 +311,11 @@ pub fn test_check_payload() {
         + claimant.to_string().as_str()
         + AUTHORIZATION_PAYLOAD[2];
 
-    assert!(check_payload(payload.as_bytes(), &claimant).is_ok());
+    assert!(check_payload(
+        payload.as_bytes(),
+        &Authorization::for_claimant(DEFAULT_CAMPAIGN_ID, &claimant)
+    )
+    .is_ok());
 
     // incorrect claimant
     let wrong_payload = AUTHORIZATION_PAYLOAD[0].to_string()
@@ -65,7 +324,10 @@ pub fn test_check_payload() {
         + &(Pubkey::new_unique()).to_string()
         + AUTHORIZATION_PAYLOAD[2];
 
-    let res = check_payload(wrong_payload.as_bytes(), &claimant);
+    let res = check_payload(
+        wrong_payload.as_bytes(),
+        &Authorization::for_claimant(DEFAULT_CAMPAIGN_ID, &claimant),
+    );
     assert!(res.is_err());
     assert_eq!(
         res.unwrap_err(),
@@ -79,7 +341,10 @@ pub fn test_check_payload() {
         + &claimant.to_string()
         + AUTHORIZATION_PAYLOAD[2];
 
-    let res = check_payload(wrong_payload.as_bytes(), &claimant);
+    let res = check_payload(
+        wrong_payload.as_bytes(),
+        &Authorization::for_claimant(DEFAULT_CAMPAIGN_ID, &claimant),
+    );
     assert!(res.is_err());
     assert_eq!(
         res.unwrap_err(),
@@ -93,10 +358,246 @@ pub fn test_check_payload() {
         + &claimant.to_string()
         + AUTHORIZATION_PAYLOAD[2];
 
-    let res = check_payload(wrong_payload.as_bytes(), &claimant);
+    let res = check_payload(
+        wrong_payload.as_bytes(),
+        &Authorization::for_claimant(DEFAULT_CAMPAIGN_ID, &claimant),
+    );
     assert!(res.is_err());
     assert_eq!(
         res.unwrap_err(),
         Error::from(ErrorCode::SignatureVerificationWrongPayload)
     );
 }
+
+#[test]
+pub fn test_check_campaign_payload() {
+    let claimant = Pubkey::new_unique();
+    let payload = AUTHORIZATION_PAYLOAD[0].to_string()
+        + &crate::ID.to_string()
+        + "\nCampaign: 2"
+        + AUTHORIZATION_PAYLOAD[1]
+        + claimant.to_string().as_str()
+        + AUTHORIZATION_PAYLOAD[2];
+
+    assert!(check_payload(
+        payload.as_bytes(),
+        &Authorization::for_claimant(2, &claimant)
+    )
+    .is_ok());
+
+    // incorrect campaign
+    for campaign_id in [DEFAULT_CAMPAIGN_ID, 1, 20] {
+        assert_eq!(
+            check_payload(
+                payload.as_bytes(),
+                &Authorization::for_claimant(campaign_id, &claimant)
+            )
+            .unwrap_err(),
+            Error::from(ErrorCode::SignatureVerificationWrongPayload)
+        );
+    }
+    assert_ne!(
+        get_expected_payload(DEFAULT_CAMPAIGN_ID, &claimant),
+        get_expected_payload(1, &claimant)
+    );
+}
+
+#[test]
+pub fn test_check_payload_v2() {
+    let claimant = Pubkey::new_unique();
+    let authorization = Authorization::for_claimant(DEFAULT_CAMPAIGN_ID, &claimant);
+    let payload = get_expected_payload(DEFAULT_CAMPAIGN_ID, &claimant)
+        + "Amount: 1000\nMerkle root: "
+        + &"01".repeat(HASH_SIZE)
+        + "\n";
+
+    assert_eq!(get_expected_payload_v2(&authorization), payload);
+    assert!(check_payload(payload.as_bytes(), &authorization).is_ok());
+    // v1 is still accepted
+    assert!(check_payload(
+        get_expected_payload(DEFAULT_CAMPAIGN_ID, &claimant).as_bytes(),
+        &authorization
+    )
+    .is_ok());
+
+    // incorrect amount
+    let wrong_authorization = Authorization {
+        amount: 1001,
+        ..authorization
+    };
+    assert_eq!(
+        check_payload(payload.as_bytes(), &wrong_authorization).unwrap_err(),
+        Error::from(ErrorCode::SignatureVerificationWrongPayload)
+    );
+
+    // incorrect merkle root
+    let wrong_authorization = Authorization {
+        merkle_root: &[2u8; HASH_SIZE],
+        ..authorization
+    };
+    assert_eq!(
+        check_payload(payload.as_bytes(), &wrong_authorization).unwrap_err(),
+        Error::from(ErrorCode::SignatureVerificationWrongPayload)
+    );
+
+    // incorrect campaign
+    let wrong_authorization = Authorization {
+        campaign_id: 1,
+        ..authorization
+    };
+    assert_eq!(
+        check_payload(payload.as_bytes(), &wrong_authorization).unwrap_err(),
+        Error::from(ErrorCode::SignatureVerificationWrongPayload)
+    );
+}
+
+#[test]
+pub fn test_check_payload_expiry() {
+    let claimant = Pubkey::new_unique();
+    let authorization = Authorization {
+        expires_at: Some(1_700_000_000),
+        unix_timestamp: 1_600_000_000,
+        ..Authorization::for_claimant(DEFAULT_CAMPAIGN_ID, &claimant)
+    };
+    let payload = get_expected_payload(DEFAULT_CAMPAIGN_ID, &claimant) + "Expires at: 1700000000\n";
+    let payload_v2 = get_expected_payload_v2(&authorization) + "Expires at: 1700000000\n";
+
+    assert!(check_payload(payload.as_bytes(), &authorization).is_ok());
+    assert!(check_payload(payload_v2.as_bytes(), &authorization).is_ok());
+    // the deadline is inclusive
+    assert!(check_payload(
+        payload.as_bytes(),
+        &Authorization {
+            unix_timestamp: 1_700_000_000,
+            ..authorization
+        }
+    )
+    .is_ok());
+
+    // expired
+    assert_eq!(
+        check_payload(
+            payload.as_bytes(),
+            &Authorization {
+                unix_timestamp: 1_700_000_001,
+                ..authorization
+            }
+        )
+        .unwrap_err(),
+        Error::from(ErrorCode::AuthorizationExpired)
+    );
+
+    // the signed deadline must match the one of the claim certificate
+    for expires_at in [None, Some(1_700_000_001)] {
+        assert_eq!(
+            check_payload(
+                payload.as_bytes(),
+                &Authorization {
+                    expires_at,
+                    ..authorization
+                }
+            )
+            .unwrap_err(),
+            Error::from(ErrorCode::SignatureVerificationWrongPayload)
+        );
+    }
+    assert_eq!(
+        check_payload(
+            get_expected_payload(DEFAULT_CAMPAIGN_ID, &claimant).as_bytes(),
+            &authorization
+        )
+        .unwrap_err(),
+        Error::from(ErrorCode::SignatureVerificationWrongPayload)
+    );
+}
+
+#[test]
+pub fn test_check_payload_nonce() {
+    let claimant = Pubkey::new_unique();
+    let authorization = Authorization {
+        expires_at: Some(1_700_000_000),
+        nonce: Some(2),
+        ..Authorization::for_claimant(DEFAULT_CAMPAIGN_ID, &claimant)
+    };
+    let payload =
+        get_expected_payload(DEFAULT_CAMPAIGN_ID, &claimant) + "Expires at: 1700000000\nNonce: 2\n";
+    let payload_v2 = get_expected_payload_v2(&authorization) + "Expires at: 1700000000\nNonce: 2\n";
+
+    assert!(check_payload(payload.as_bytes(), &authorization).is_ok());
+    assert!(check_payload(payload_v2.as_bytes(), &authorization).is_ok());
+
+    // the signed nonce must match the one of the claim certificate
+    for nonce in [None, Some(3)] {
+        assert_eq!(
+            check_payload(
+                payload.as_bytes(),
+                &Authorization {
+                    nonce,
+                    ..authorization
+                }
+            )
+            .unwrap_err(),
+            Error::from(ErrorCode::SignatureVerificationWrongPayload)
+        );
+    }
+}
+
+#[test]
+pub fn test_authorization_payload_definition() {
+    let definition: serde_json::Value =
+        serde_json::from_str(include_str!("../../authorization_payload.json")).unwrap();
+
+    assert_eq!(definition["version"], AUTHORIZATION_PAYLOAD_VERSION);
+    assert_eq!(
+        definition["payload"],
+        serde_json::json!(AUTHORIZATION_PAYLOAD)
+    );
+    assert_eq!(definition["campaign"], CAMPAIGN_PAYLOAD);
+    assert_eq!(
+        definition["claim"],
+        serde_json::json!(AUTHORIZATION_PAYLOAD_V2)
+    );
+    assert_eq!(definition["expiry"], serde_json::json!(EXPIRY_PAYLOAD));
+    assert_eq!(definition["nonce"], serde_json::json!(NONCE_PAYLOAD));
+}
+
+#[test]
+pub fn test_program_id_base58() {
+    assert_eq!(PROGRAM_ID_BASE58, crate::ID.to_string());
+}
+
+#[test]
+pub fn test_push_base58() {
+    let mut leading_zeros = [0u8; 32];
+    leading_zeros[31] = 1;
+    for pubkey in [
+        Pubkey::new_unique(),
+        Pubkey::new_from_array([0u8; 32]),
+        Pubkey::new_from_array(leading_zeros),
+        Pubkey::new_from_array([u8::MAX; 32]),
+    ] {
+        let mut encoded = String::new();
+        push_base58(&mut encoded, &pubkey);
+        assert_eq!(encoded, pubkey.to_string());
+    }
+}
+
+#[test]
+pub fn test_longest_payload_fits_buffer() {
+    let claimant = Pubkey::new_from_array([u8::MAX; 32]);
+    let authorization = Authorization {
+        campaign_id: u64::MAX,
+        claimant,
+        merkle_root: &[u8::MAX; HASH_SIZE],
+        amount: u64::MAX,
+        expires_at: Some(i64::MIN),
+        nonce: Some(u64::MAX),
+        unix_timestamp: 0,
+    };
+    let mut payload_v2 = get_expected_payload_v2(&authorization);
+    push_expiry_payload(&mut payload_v2, authorization.expires_at);
+    push_nonce_payload(&mut payload_v2, authorization.nonce);
+
+    let [_, accepted_payload_v2] = authorization.get_accepted_payloads();
+    assert_eq!(accepted_payload_v2.as_bytes(), payload_v2.as_bytes());
+}
@@ -0,0 +1,183 @@
+#[cfg(test)]
+use {
+    super::get_expected_payload,
+    crate::DEFAULT_CAMPAIGN_ID,
+};
+use {
+    super::Authorization,
+    crate::ErrorCode,
+    anchor_lang::{
+        prelude::*,
+        solana_program::hash,
+        AnchorDeserialize,
+        AnchorSerialize,
+    },
+    base64::{
+        engine::general_purpose::URL_SAFE_NO_PAD as base64_url_engine,
+        Engine as _,
+    },
+    serde::Deserialize,
+};
+
+pub const WEBAUTHN_GET_TYPE: &str = "webauthn.get";
+/// rpIdHash (32 bytes) + flags (1 byte) + signCount (4 bytes)
+pub const AUTHENTICATOR_DATA_MIN_LEN: usize = 37;
+pub const AUTHENTICATOR_DATA_FLAGS_INDEX: usize = 32;
+pub const USER_PRESENT_FLAG: u8 = 0x01;
+
+/**
+* A WebAuthn assertion produced by a passkey.
+* The authenticator signs `authenticatorData || sha256(clientDataJSON)` and the payload is
+* committed to through the `challenge` field of `clientDataJSON`. Since the challenge has to be
+* short, it is the sha256 hash of the authorization payload rather than the payload itself.
+*/
+#[derive(AnchorDeserialize, AnchorSerialize, Clone)]
+pub struct WebAuthnMessage {
+    authenticator_data: Vec<u8>,
+    client_data_json:   Vec<u8>,
+}
+
+/**
+* The fields of the WebAuthn `CollectedClientData` that we check.
+*/
+#[derive(Deserialize, Debug)]
+struct CollectedClientData {
+    r#type:    String,
+    challenge: String,
+}
+
+impl WebAuthnMessage {
+    pub fn new(authenticator_data: &[u8], client_data_json: &[u8]) -> Self {
+        WebAuthnMessage {
+            authenticator_data: authenticator_data.to_vec(),
+            client_data_json:   client_data_json.to_vec(),
+        }
+    }
+
+    pub fn get_authenticator_data(&self) -> &[u8] {
+        self.authenticator_data.as_slice()
+    }
+
+    pub fn get_client_data_json(&self) -> &[u8] {
+        self.client_data_json.as_slice()
+    }
+
+    pub fn get_signed_message(&self) -> Vec<u8> {
+        let mut message = self.authenticator_data.clone();
+        message.extend_from_slice(&hash::hashv(&[&self.client_data_json]).to_bytes());
+        message
+    }
+
+    pub fn get_challenge(payload: &[u8]) -> String {
+        base64_url_engine.encode(hash::hashv(&[payload]))
+    }
+
+    /**
+     * Checks that `signed_message` (the message verified by the secp256r1 program) is made of this
+     * assertion and that the assertion's challenge commits to one of the accepted payloads.
+     */
+    pub fn check_signed_message_and_challenge(
+        &self,
+        signed_message: &[u8],
+        authorization: &Authorization,
+    ) -> Result<()> {
+        if signed_message != self.get_signed_message() {
+            return err!(ErrorCode::SignatureVerificationWrongPayloadMetadata);
+        }
+
+        if !(self.authenticator_data.len() >= AUTHENTICATOR_DATA_MIN_LEN
+            && self.authenticator_data[AUTHENTICATOR_DATA_FLAGS_INDEX] & USER_PRESENT_FLAG != 0)
+        {
+            return err!(ErrorCode::SignatureVerificationWrongPayloadMetadata);
+        }
+
+        let client_data: CollectedClientData = serde_json::from_slice(&self.client_data_json)
+            .map_err(|_| error!(ErrorCode::SignatureVerificationWrongPayloadMetadata))?;
+
+        if client_data.r#type != WEBAUTHN_GET_TYPE {
+            return err!(ErrorCode::SignatureVerificationWrongPayloadMetadata);
+        }
+
+        authorization.check_signed_payload(|payload| {
+            client_data.challenge == WebAuthnMessage::get_challenge(payload)
+        })
+    }
+}
+
+#[cfg(test)]
+impl WebAuthnMessage {
+    pub fn for_claimant(claimant: &Pubkey) -> Self {
+        let mut authenticator_data = vec![0u8; AUTHENTICATOR_DATA_MIN_LEN];
+        authenticator_data[AUTHENTICATOR_DATA_FLAGS_INDEX] = USER_PRESENT_FLAG;
+        let client_data_json = format!(
+            r#"{{"type":"{}","challenge":"{}","origin":"https://example.com","crossOrigin":false}}"#,
+            WEBAUTHN_GET_TYPE,
+            WebAuthnMessage::get_challenge(
+                get_expected_payload(DEFAULT_CAMPAIGN_ID, claimant).as_bytes()
+            )
+        );
+        WebAuthnMessage::new(&authenticator_data, client_data_json.as_bytes())
+    }
+}
+
+#[test]
+pub fn test_check_signed_message_and_challenge() {
+    let claimant = Pubkey::new_unique();
+    let message = WebAuthnMessage::for_claimant(&claimant);
+    let authorization = Authorization::for_claimant(DEFAULT_CAMPAIGN_ID, &claimant);
+
+    assert!(message
+        .check_signed_message_and_challenge(&message.get_signed_message(), &authorization)
+        .is_ok());
+
+    // wrong claimant
+    assert_eq!(
+        message.check_signed_message_and_challenge(
+            &message.get_signed_message(),
+            &Authorization::for_claimant(DEFAULT_CAMPAIGN_ID, &Pubkey::new_unique())
+        ),
+        err!(ErrorCode::SignatureVerificationWrongPayload)
+    );
+
+    // wrong campaign
+    assert_eq!(
+        message.check_signed_message_and_challenge(
+            &message.get_signed_message(),
+            &Authorization::for_claimant(1, &claimant)
+        ),
+        err!(ErrorCode::SignatureVerificationWrongPayload)
+    );
+
+    // the signed message doesn't match the assertion
+    let mut wrong_signed_message = message.get_signed_message();
+    wrong_signed_message[0] ^= 0xff;
+    assert_eq!(
+        message.check_signed_message_and_challenge(&wrong_signed_message, &authorization),
+        err!(ErrorCode::SignatureVerificationWrongPayloadMetadata)
+    );
+
+    // user not present
+    let mut wrong_message = message.clone();
+    wrong_message.authenticator_data[AUTHENTICATOR_DATA_FLAGS_INDEX] = 0;
+    assert_eq!(
+        wrong_message.check_signed_message_and_challenge(
+            &wrong_message.get_signed_message(),
+            &authorization
+        ),
+        err!(ErrorCode::SignatureVerificationWrongPayloadMetadata)
+    );
+
+    // registration instead of assertion
+    let mut wrong_message = message.clone();
+    wrong_message.client_data_json = String::from_utf8(message.client_data_json)
+        .unwrap()
+        .replace(WEBAUTHN_GET_TYPE, "webauthn.create")
+        .into_bytes();
+    assert_eq!(
+        wrong_message.check_signed_message_and_challenge(
+            &wrong_message.get_signed_message(),
+            &authorization
+        ),
+        err!(ErrorCode::SignatureVerificationWrongPayloadMetadata)
+    );
+}
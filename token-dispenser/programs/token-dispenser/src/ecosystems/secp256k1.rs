@@ -29,7 +29,6 @@ impl EvmPubkey {
     }
 }
 
-#[cfg(test)]
 impl From<[u8; Self::LEN]> for EvmPubkey {
     fn from(bytes: [u8; Self::LEN]) -> Self {
         EvmPubkey(bytes)
@@ -42,7 +41,6 @@ impl Secp256k1Signature {
     pub const LEN: usize = 64;
 }
 
-#[cfg(test)]
 impl From<[u8; Self::LEN]> for Secp256k1Signature {
     fn from(bytes: [u8; Self::LEN]) -> Self {
         Secp256k1Signature(bytes)
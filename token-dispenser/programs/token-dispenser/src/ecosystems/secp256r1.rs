@@ -0,0 +1,244 @@
+use {
+    crate::ErrorCode,
+    anchor_lang::{
+        prelude::*,
+        solana_program::{
+            instruction::Instruction,
+            pubkey,
+        },
+        AnchorDeserialize,
+        AnchorSerialize,
+    },
+};
+
+/// Address of the secp256r1 signature verification precompile
+pub const SECP256R1_ID: Pubkey = pubkey!("Secp256r1SigVerify1111111111111111111111111");
+
+/// SEC1 tags of a compressed point, depending on the parity of its y coordinate
+pub const SECP256R1_ODD_PREFIX: u8 = 0x03;
+pub const SECP256R1_EVEN_PREFIX: u8 = 0x02;
+
+#[derive(AnchorDeserialize, AnchorSerialize, Clone, PartialEq, Debug)]
+pub struct Secp256r1Signature([u8; Secp256r1Signature::LEN]);
+impl Secp256r1Signature {
+    pub const LEN: usize = 64;
+}
+
+impl From<[u8; Self::LEN]> for Secp256r1Signature {
+    fn from(bytes: [u8; Self::LEN]) -> Self {
+        Secp256r1Signature(bytes)
+    }
+}
+
+/**
+ * A compressed secp256r1 (P-256) public key, this is the format expected by the precompile.
+ */
+#[derive(AnchorDeserialize, AnchorSerialize, Clone, PartialEq, Debug)]
+pub struct Secp256r1Pubkey([u8; Secp256r1Pubkey::LEN]);
+impl Secp256r1Pubkey {
+    pub const LEN: usize = 33;
+}
+
+impl From<[u8; Self::LEN]> for Secp256r1Pubkey {
+    fn from(bytes: [u8; Self::LEN]) -> Self {
+        Secp256r1Pubkey(bytes)
+    }
+}
+
+#[derive(PartialEq, Debug)]
+/** The layout of a Secp256r1 signature verification instruction on Solana */
+pub struct Secp256r1InstructionData {
+    pub header:    Secp256r1InstructionHeader,
+    pub signature: Secp256r1Signature,
+    pub pubkey:    Secp256r1Pubkey,
+    pub message:   Vec<u8>,
+}
+
+/// The secp256r1 precompile uses the same offsets layout as the ed25519 precompile
+#[derive(AnchorDeserialize, AnchorSerialize, Clone, PartialEq, Eq, Debug)]
+pub struct Secp256r1InstructionHeader {
+    num_signatures:               u8,
+    padding:                      u8,
+    signature_offset:             u16,
+    signature_instruction_index:  u16,
+    public_key_offset:            u16,
+    public_key_instruction_index: u16,
+    message_data_offset:          u16,
+    message_data_size:            u16,
+    message_instruction_index:    u16,
+}
+
+impl Secp256r1InstructionHeader {
+    pub const LEN: u16 = 2 + 2 + 2 + 2 + 2 + 2 + 2 + 2;
+}
+
+impl Secp256r1InstructionHeader {
+    pub fn expected_header(message_length: u16, instruction_index: u8) -> Self {
+        Secp256r1InstructionHeader {
+            num_signatures:               1,
+            padding:                      0,
+            signature_offset:             Secp256r1InstructionHeader::LEN
+                + Secp256r1Pubkey::LEN as u16,
+            signature_instruction_index:  instruction_index as u16,
+            public_key_offset:            Secp256r1InstructionHeader::LEN,
+            public_key_instruction_index: instruction_index as u16,
+            message_data_offset:          Secp256r1InstructionHeader::LEN
+                + Secp256r1Signature::LEN as u16
+                + Secp256r1Pubkey::LEN as u16,
+            message_data_size:            message_length,
+            message_instruction_index:    instruction_index as u16,
+        }
+    }
+}
+
+impl Secp256r1InstructionData {
+    pub fn extract_message_and_check_signature(
+        instruction: &Instruction,
+        pubkey: &Secp256r1Pubkey,
+        verification_instruction_index: &u8,
+    ) -> Result<Vec<u8>> {
+        if instruction.program_id != SECP256R1_ID {
+            return err!(ErrorCode::SignatureVerificationWrongProgram);
+        }
+
+        if !instruction.accounts.is_empty() {
+            return err!(ErrorCode::SignatureVerificationWrongAccounts);
+        }
+
+        let result = Self::try_from_slice(&instruction.data)?;
+        if result.header
+            != Secp256r1InstructionHeader::expected_header(
+                result.header.message_data_size,
+                *verification_instruction_index,
+            )
+        {
+            return err!(ErrorCode::SignatureVerificationWrongHeader);
+        }
+
+        if result.pubkey != *pubkey {
+            return err!(ErrorCode::SignatureVerificationWrongSigner);
+        }
+
+        Ok(result.message)
+    }
+}
+
+impl AnchorDeserialize for Secp256r1InstructionData {
+    fn deserialize(
+        buf: &mut &[u8],
+    ) -> std::result::Result<Secp256r1InstructionData, std::io::Error> {
+        let header = Secp256r1InstructionHeader::deserialize(buf)?;
+        let pubkey = Secp256r1Pubkey::deserialize(buf)?;
+        let signature = Secp256r1Signature::deserialize(buf)?;
+
+        let mut message: Vec<u8> = vec![];
+        if buf.len() < header.message_data_size as usize {
+            return Err(std::io::Error::from(std::io::ErrorKind::UnexpectedEof));
+        }
+
+        message.extend_from_slice(&buf[..header.message_data_size as usize]);
+        *buf = &buf[header.message_data_size as usize..];
+        Ok(Secp256r1InstructionData {
+            header,
+            pubkey,
+            signature,
+            message,
+        })
+    }
+}
+
+impl AnchorSerialize for Secp256r1InstructionData {
+    fn serialize<W: std::io::Write>(
+        &self,
+        writer: &mut W,
+    ) -> std::result::Result<(), std::io::Error> {
+        self.header.serialize(writer)?;
+        self.pubkey.serialize(writer)?;
+        self.signature.serialize(writer)?;
+
+        writer.write_all(&self.message)?;
+        Ok(())
+    }
+}
+
+#[test]
+pub fn test_signature_verification() {
+    let secp256r1_ix = Secp256r1InstructionData {
+        header:    Secp256r1InstructionHeader::expected_header(5, 0),
+        signature: Secp256r1Signature([0; Secp256r1Signature::LEN]),
+        pubkey:    Secp256r1Pubkey([0; Secp256r1Pubkey::LEN]),
+        message:   b"hello".to_vec(),
+    };
+
+    assert_eq!(
+        Secp256r1InstructionData::extract_message_and_check_signature(
+            &Instruction {
+                program_id: SECP256R1_ID,
+                accounts:   vec![],
+                data:       secp256r1_ix.try_to_vec().unwrap(),
+            },
+            &Secp256r1Pubkey([0; Secp256r1Pubkey::LEN]),
+            &0,
+        )
+        .unwrap(),
+        b"hello".to_vec()
+    );
+
+    assert_eq!(
+        Secp256r1InstructionData::extract_message_and_check_signature(
+            &Instruction {
+                program_id: Pubkey::new_unique(),
+                accounts:   vec![],
+                data:       secp256r1_ix.try_to_vec().unwrap(),
+            },
+            &Secp256r1Pubkey([0; Secp256r1Pubkey::LEN]),
+            &0,
+        )
+        .unwrap_err(),
+        ErrorCode::SignatureVerificationWrongProgram.into()
+    );
+
+    assert_eq!(
+        Secp256r1InstructionData::extract_message_and_check_signature(
+            &Instruction {
+                program_id: SECP256R1_ID,
+                accounts:   vec![],
+                data:       secp256r1_ix.try_to_vec().unwrap(),
+            },
+            &Secp256r1Pubkey([0; Secp256r1Pubkey::LEN]),
+            &1, // wrong instruction index
+        )
+        .unwrap_err(),
+        ErrorCode::SignatureVerificationWrongHeader.into()
+    );
+
+    assert_eq!(
+        Secp256r1InstructionData::extract_message_and_check_signature(
+            &Instruction {
+                program_id: SECP256R1_ID,
+                accounts:   vec![],
+                data:       secp256r1_ix.try_to_vec().unwrap(),
+            },
+            &Secp256r1Pubkey([1; Secp256r1Pubkey::LEN]),
+            &0,
+        )
+        .unwrap_err(),
+        ErrorCode::SignatureVerificationWrongSigner.into()
+    );
+}
+
+#[test]
+pub fn test_serde() {
+    let expected_secp256r1_ix = Secp256r1InstructionData {
+        header:    Secp256r1InstructionHeader::expected_header(5, 0),
+        signature: Secp256r1Signature([1; Secp256r1Signature::LEN]),
+        pubkey:    Secp256r1Pubkey([2; Secp256r1Pubkey::LEN]),
+        message:   b"hello".to_vec(),
+    };
+
+    let secp256r1_ix =
+        Secp256r1InstructionData::try_from_slice(&expected_secp256r1_ix.try_to_vec().unwrap())
+            .unwrap();
+
+    assert_eq!(secp256r1_ix, expected_secp256r1_ix);
+}
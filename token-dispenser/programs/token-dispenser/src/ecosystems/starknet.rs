@@ -0,0 +1,185 @@
+#[cfg(test)]
+use super::ed25519::Ed25519TestMessage;
+use {
+    crate::ErrorCode,
+    anchor_lang::prelude::*,
+};
+
+/**
+ * Prepended to the serialized `StarknetMessage` so that an attestation for one ecosystem can't
+ * be replayed as an attestation for another one, since they are all signed by the dispenser guard.
+ */
+pub const STARKNET_MESSAGE_PREFIX: &[u8] = b"Pyth Grant Starknet\n";
+
+/**
+ * The prime of the STARK field, 2^251 + 17 * 2^192 + 1, as 32 big endian bytes.
+ */
+pub const STARK_PRIME: [u8; StarknetAddress::LEN] = [
+    0x08, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x11, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00,
+    0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x01,
+];
+
+/**
+ * Starknet accounts are contracts and their signatures (on the STARK curve) can't be checked on Solana.
+ * Instead, the dispenser guard verifies off-chain the SNIP-12 typed data signature of the
+ * authorization payload by the Starknet account and then signs this message (prefixed with
+ * `STARKNET_MESSAGE_PREFIX` and borsh-serialized).
+ * These attestations are issued by the `starknet_signed_message` endpoint of `token-dispenser-guard`.
+ * The dispenser guard key should not be used for anything else.
+ */
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Debug)]
+pub struct StarknetMessage {
+    address:  StarknetAddress,
+    claimant: Pubkey,
+}
+
+impl StarknetMessage {
+    pub fn new(address: &StarknetAddress, claimant: &Pubkey) -> Self {
+        Self {
+            address:  address.clone(),
+            claimant: *claimant,
+        }
+    }
+
+    /**
+     * The message signed by the dispenser guard.
+     */
+    pub fn get_signed_message(&self) -> Vec<u8> {
+        let mut message = STARKNET_MESSAGE_PREFIX.to_vec();
+        message.extend_from_slice(&self.try_to_vec().unwrap());
+        message
+    }
+
+    pub fn parse_and_check_claimant_and_address(
+        data: &[u8],
+        address: &StarknetAddress,
+        claimant: &Pubkey,
+    ) -> Result<Self> {
+        let result = if let Some(message) = data.strip_prefix(STARKNET_MESSAGE_PREFIX) {
+            StarknetMessage::try_from_slice(message)?
+        } else {
+            return err!(ErrorCode::SignatureVerificationWrongPayloadMetadata);
+        };
+
+        if result.address != *address || !result.address.is_field_element() {
+            return err!(ErrorCode::SignatureVerificationWrongPayload);
+        }
+
+        if result.claimant != *claimant {
+            return err!(ErrorCode::SignatureVerificationWrongPayload);
+        }
+
+        Ok(result)
+    }
+
+    pub fn get_address(&self) -> StarknetAddress {
+        self.address.clone()
+    }
+}
+
+/**
+ * A Starknet account address. This is a field element, serialized as 32 big endian bytes.
+ */
+#[derive(AnchorDeserialize, AnchorSerialize, Clone, PartialEq, Debug)]
+pub struct StarknetAddress([u8; Self::LEN]);
+
+impl StarknetAddress {
+    pub const LEN: usize = 32;
+
+    /**
+     * Whether the address is a canonical field element, i.e. smaller than `STARK_PRIME`.
+     * Otherwise two different addresses could designate the same account.
+     */
+    pub fn is_field_element(&self) -> bool {
+        self.0 < STARK_PRIME
+    }
+
+    pub fn as_bytes(&self) -> [u8; Self::LEN] {
+        self.0
+    }
+}
+
+impl From<[u8; Self::LEN]> for StarknetAddress {
+    fn from(bytes: [u8; Self::LEN]) -> Self {
+        StarknetAddress(bytes)
+    }
+}
+
+#[cfg(test)]
+impl Ed25519TestMessage for StarknetMessage {
+    fn for_claimant(claimant: &Pubkey) -> Self {
+        let mut address = Pubkey::new_unique().to_bytes();
+        // Field elements are smaller than 2^252
+        address[0] = 0;
+        Self {
+            address:  StarknetAddress(address),
+            claimant: *claimant,
+        }
+    }
+
+    fn get_message_with_metadata(&self) -> Vec<u8> {
+        self.get_signed_message()
+    }
+}
+
+#[test]
+pub fn test_starknet_parse_and_check_claimant_and_address() {
+    let claimant = Pubkey::new_unique();
+    let message = StarknetMessage::for_claimant(&claimant);
+    assert_eq!(
+        StarknetMessage::parse_and_check_claimant_and_address(
+            &message.get_message_with_metadata(),
+            &message.get_address(),
+            &claimant,
+        )
+        .unwrap()
+        .get_address(),
+        message.get_address()
+    );
+
+    let res = StarknetMessage::parse_and_check_claimant_and_address(
+        &message.get_message_with_metadata(),
+        &message.get_address(),
+        &Pubkey::new_unique(),
+    );
+    assert_eq!(
+        res.unwrap_err(),
+        Error::from(ErrorCode::SignatureVerificationWrongPayload)
+    );
+
+    let res = StarknetMessage::parse_and_check_claimant_and_address(
+        &message.get_message_with_metadata(),
+        &StarknetAddress([1; StarknetAddress::LEN]),
+        &claimant,
+    );
+    assert_eq!(
+        res.unwrap_err(),
+        Error::from(ErrorCode::SignatureVerificationWrongPayload)
+    );
+
+    // not a field element
+    let mut address = STARK_PRIME;
+    let wrong_message = StarknetMessage::new(&StarknetAddress(address), &claimant);
+    let res = StarknetMessage::parse_and_check_claimant_and_address(
+        &wrong_message.get_message_with_metadata(),
+        &wrong_message.get_address(),
+        &claimant,
+    );
+    assert_eq!(
+        res.unwrap_err(),
+        Error::from(ErrorCode::SignatureVerificationWrongPayload)
+    );
+    address[StarknetAddress::LEN - 1] = 0;
+    assert!(StarknetAddress(address).is_field_element());
+
+    // missing prefix
+    let res = StarknetMessage::parse_and_check_claimant_and_address(
+        &message.try_to_vec().unwrap(),
+        &message.get_address(),
+        &claimant,
+    );
+    assert_eq!(
+        res.unwrap_err(),
+        Error::from(ErrorCode::SignatureVerificationWrongPayloadMetadata)
+    );
+}
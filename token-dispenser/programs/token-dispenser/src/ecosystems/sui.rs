@@ -1,11 +1,8 @@
-#[cfg(test)]
-use super::ed25519::Ed25519TestMessage;
 use {
     super::{
         ed25519::Ed25519Pubkey,
-        get_expected_payload,
+        Authorization,
     },
-    crate::ErrorCode,
     anchor_lang::{
         prelude::*,
         AnchorDeserialize,
@@ -14,6 +11,18 @@ use {
     blake2_rfc::blake2b::Blake2b,
     uleb128::WriteULeb128Ext,
 };
+#[cfg(test)]
+use {
+    super::{
+        ed25519::Ed25519TestMessage,
+        get_expected_payload,
+        get_expected_payload_v2,
+    },
+    crate::{
+        ErrorCode,
+        DEFAULT_CAMPAIGN_ID,
+    },
+};
 
 
 pub const SUI_SIGNATURE_SCHEME_ID: u8 = 0;
@@ -30,11 +39,11 @@ pub struct SuiMessage(Vec<u8>);
 
 
 impl SuiMessage {
-    pub fn get_expected_hash(payload: &str) -> Vec<u8> {
+    pub fn get_expected_hash(payload: &[u8]) -> Vec<u8> {
         let mut result: Vec<u8> = Vec::<u8>::new();
         result.extend(SUI_PREFIX);
         result.write_uleb128_u64(payload.len() as u64).unwrap();
-        result.extend_from_slice(payload.as_bytes());
+        result.extend_from_slice(payload);
         blake2_rfc::blake2b::blake2b(32, &[], &result)
             .as_bytes()
             .to_vec()
@@ -46,11 +55,10 @@ impl SuiMessage {
      * of parsing the message as in other ecosystems. Instead we just check that the hash of the prefixed payload
      * matches the hash of the expected payload
      */
-    pub fn check_hashed_payload(payload: &[u8], claimant: &Pubkey) -> Result<()> {
-        if payload != SuiMessage::get_expected_hash(&get_expected_payload(claimant)) {
-            return err!(ErrorCode::SignatureVerificationWrongPayload);
-        }
-        Ok(())
+    pub fn check_hashed_payload(payload: &[u8], authorization: &Authorization) -> Result<()> {
+        authorization.check_signed_payload(|expected_payload| {
+            payload == SuiMessage::get_expected_hash(expected_payload)
+        })
     }
 }
 
@@ -58,7 +66,7 @@ impl SuiMessage {
 #[cfg(test)]
 impl Ed25519TestMessage for SuiMessage {
     fn for_claimant(claimant: &Pubkey) -> Self {
-        Self(get_expected_payload(claimant).into_bytes())
+        Self(get_expected_payload(DEFAULT_CAMPAIGN_ID, claimant).into_bytes())
     }
 
     fn get_message_with_metadata(&self) -> Vec<u8> {
@@ -92,7 +100,6 @@ impl From<Ed25519Pubkey> for SuiAddress {
     }
 }
 
-#[cfg(test)]
 impl From<[u8; Self::LEN]> for SuiAddress {
     fn from(bytes: [u8; Self::LEN]) -> Self {
         SuiAddress(bytes)
@@ -103,18 +110,36 @@ impl From<[u8; Self::LEN]> for SuiAddress {
 #[test]
 pub fn test_check_hashed_payload() {
     let claimant = Pubkey::new_unique();
-    let expected_hash = SuiMessage::get_expected_hash(&get_expected_payload(&claimant));
+    let authorization = Authorization::for_claimant(DEFAULT_CAMPAIGN_ID, &claimant);
+    let expected_hash = SuiMessage::get_expected_hash(
+        get_expected_payload(DEFAULT_CAMPAIGN_ID, &claimant).as_bytes(),
+    );
 
-    assert!(SuiMessage::check_hashed_payload(&expected_hash, &claimant).is_ok());
+    assert!(SuiMessage::check_hashed_payload(&expected_hash, &authorization).is_ok());
+    assert!(SuiMessage::check_hashed_payload(
+        &SuiMessage::get_expected_hash(get_expected_payload_v2(&authorization).as_bytes()),
+        &authorization
+    )
+    .is_ok());
 
     assert_eq!(
-        SuiMessage::check_hashed_payload(&expected_hash, &Pubkey::new_unique()),
+        SuiMessage::check_hashed_payload(
+            &expected_hash,
+            &Authorization::for_claimant(DEFAULT_CAMPAIGN_ID, &Pubkey::new_unique())
+        ),
+        err!(ErrorCode::SignatureVerificationWrongPayload)
+    );
+    assert_eq!(
+        SuiMessage::check_hashed_payload(
+            &expected_hash,
+            &Authorization::for_claimant(1, &claimant)
+        ),
         err!(ErrorCode::SignatureVerificationWrongPayload)
     );
     assert_eq!(
         SuiMessage::check_hashed_payload(
-            &SuiMessage::get_expected_hash("this_is_the_wrong_payload"),
-            &claimant
+            &SuiMessage::get_expected_hash(b"this_is_the_wrong_payload"),
+            &authorization
         ),
         err!(ErrorCode::SignatureVerificationWrongPayload)
     );
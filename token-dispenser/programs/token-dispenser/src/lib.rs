@@ -4,6 +4,7 @@ use {
     anchor_lang::{
         prelude::*,
         solana_program::{
+            bpf_loader_upgradeable,
             keccak::hashv,
             program::{
                 invoke,
@@ -16,24 +17,21 @@ use {
             },
         },
         system_program,
+        Discriminator,
     },
-    anchor_spl::{
-        associated_token::{
-            self,
-            AssociatedToken,
-        },
-        token::{
-            spl_token,
-            Mint,
-            Token,
-            TokenAccount,
-        },
+    anchor_spl::associated_token::{
+        self,
+        AssociatedToken,
     },
     ecosystems::{
         aptos::{
             AptosAddress,
             AptosMessage,
         },
+        cardano::{
+            CardanoKeyHash,
+            CardanoMessage,
+        },
         check_payload,
         cosmos::{
             CosmosBech32Address,
@@ -45,17 +43,30 @@ use {
             Ed25519InstructionData,
             Ed25519Pubkey,
         },
-        evm::EvmPrefixedMessage,
+        evm::{
+            EvmPrefixedMessage,
+            EVM_MESSAGE_PREFIX,
+        },
+        passkey::WebAuthnMessage,
         secp256k1::{
             secp256k1_verify_signer,
             EvmPubkey,
             Secp256k1InstructionData,
             Secp256k1Signature,
         },
+        secp256r1::{
+            Secp256r1InstructionData,
+            Secp256r1Pubkey,
+        },
+        starknet::{
+            StarknetAddress,
+            StarknetMessage,
+        },
         sui::{
             SuiAddress,
             SuiMessage,
         },
+        Authorization,
     },
     pythnet_sdk::{
         accumulators::merkle::{
@@ -65,33 +76,96 @@ use {
         },
         hashers::Hasher,
     },
+    token_interface::{
+        checked_create_associated_token_account,
+        is_token_program,
+        transfer_checked,
+        unpack_mint,
+        unpack_token_account,
+    },
 };
 
 #[cfg(test)]
 mod tests;
 
-mod ecosystems;
+#[cfg(any(test, feature = "client"))]
+pub mod client;
+pub mod ecosystems;
+pub mod token_interface;
 
 declare_id!("Fg6PaFpoGXkYsidMpWTK6W2BeZ7FEfcYkg476zPFsLnS");
 
 const CONFIG_SEED: &[u8] = b"config";
 const RECEIPT_SEED: &[u8] = b"receipt";
+const CLAIMANT_TOTAL_SEED: &[u8] = b"claimant_total";
+const DENYLIST_SEED: &[u8] = b"denylist";
+const DENYLIST_IDENTITY_SEED: &[u8] = b"identity";
+const DENYLIST_CLAIMANT_SEED: &[u8] = b"claimant";
+/// The layout of `Config`, configs of the previous layouts are upgraded with `migrate_config`.
+pub const CONFIG_VERSION: u8 = 1;
+/// The campaign of the initial distribution. Its accounts and authorization payload don't mention the campaign
+/// id, so they are the same as before campaigns were introduced.
+pub const DEFAULT_CAMPAIGN_ID: u64 = 0;
 #[program]
 pub mod token_dispenser {
-    use {
-        super::*,
-        anchor_spl::token,
-    };
+    use super::*;
 
-    /// This can only be called once and should be called right after the program is deployed.
+    /// This can only be called once per campaign. Each campaign has its own tree, mint, treasury and
+    /// guard and claims from different campaigns are independent.
+    /// The variant of `merkle_root` is the hasher of the tree of the campaign, see `DispenserMerkleRoot`.
+    /// If `escrow` is set, the treasury is the associated token account of the config, created here and
+    /// funded with `deposit`. Otherwise it is an external token account that delegates to the config.
+    /// If `global_cap` is set, claims fail once the total claimed would exceed it.
+    /// If `max_per_claimant` is set, claims fail once the total claimed by a claimant across all their
+    /// identities would exceed it.
+    /// The payer becomes the admin of the campaign, who can adjust its rate limit and guard signature policy
+    /// and manage its denylist. Campaigns other than the default one can only be initialized by the upgrade
+    /// authority of the program, so that their ids can't be squatted.
+    #[allow(clippy::too_many_arguments)]
     pub fn initialize(
         ctx: Context<Initialize>,
-        merkle_root: MerkleRoot<SolanaHasher>,
+        campaign_id: u64,
+        merkle_root: DispenserMerkleRoot,
         dispenser_guard: Pubkey,
         funder: Pubkey,
         max_transfer: u64,
+        escrow: bool,
+        global_cap: Option<u64>,
+        max_per_claimant: Option<u64>,
     ) -> Result<()> {
         require_keys_neq!(dispenser_guard, Pubkey::default());
+        if campaign_id != DEFAULT_CAMPAIGN_ID {
+            let program_data = Account::<ProgramData>::try_from(&ctx.accounts.program_data)?;
+            require!(
+                program_data.upgrade_authority_address == Some(ctx.accounts.payer.key()),
+                ErrorCode::UpgradeAuthorityRequired
+            );
+        }
+        // The mint and the treasury can belong to the token program or to token-2022
+        unpack_mint(&ctx.accounts.mint)?;
+        if ctx.accounts.mint.owner != ctx.accounts.token_program.key {
+            return Err(anchor_lang::error::ErrorCode::ConstraintOwner.into());
+        }
+        if escrow {
+            checked_create_associated_token_account(
+                &ctx.accounts.treasury,
+                &ctx.accounts.payer,
+                &ctx.accounts.config.to_account_info(),
+                &ctx.accounts.mint,
+                &ctx.accounts.system_program,
+                &ctx.accounts.token_program,
+                &ctx.accounts.associated_token_program,
+            )?;
+        } else {
+            let treasury = unpack_token_account(&ctx.accounts.treasury)?;
+            if treasury.mint != ctx.accounts.mint.key() {
+                return Err(anchor_lang::error::ErrorCode::ConstraintTokenMint.into());
+            }
+            if ctx.accounts.treasury.owner != ctx.accounts.mint.owner {
+                return Err(anchor_lang::error::ErrorCode::ConstraintOwner.into());
+            }
+        }
+
         let config: &mut Account<'_, Config> = &mut ctx.accounts.config;
         config.bump = *ctx.bumps.get("config").unwrap();
         config.merkle_root = merkle_root;
@@ -101,32 +175,290 @@ pub mod token_dispenser {
         config.address_lookup_table = ctx.accounts.address_lookup_table.key();
         config.funder = funder;
         config.max_transfer = max_transfer;
+        config.escrow = escrow;
+        config.global_cap = global_cap;
+        config.max_per_claimant = max_per_claimant;
+        config.admin = ctx.accounts.payer.key();
+        config.version = CONFIG_VERSION;
+        Ok(())
+    }
+
+    /**
+     * Upgrades the config of a campaign created before configs were versioned, whose layout is `LegacyConfig`.
+     * The account is reallocated to `Config::LEN` and the new fields get the values of a config initialized without
+     * escrow, claimant cap, rate limit, denylist or guard signature policy. The upgrade authority of the program signs
+     * the migration, pays the rent of the new space and becomes the admin of the campaign.
+     *
+     * The legacy program kept no statistics, so the claims made before the migration are seeded from
+     * `claimed_per_identity` and `claim_count`, e.g. the totals of the `CampaignClaimIndex` of the campaign.
+     * `global_cap` then counts these claims too. Each of these claims created a legacy receipt, so `claim_count` is
+     * also the number of legacy receipts that must be migrated before the tree can be amended.
+     */
+    pub fn migrate_config(
+        ctx: Context<MigrateConfig>,
+        _campaign_id: u64,
+        claimed_per_identity: [u64; IDENTITY_VARIANTS],
+        claim_count: u64,
+        global_cap: Option<u64>,
+    ) -> Result<()> {
+        let config_account = ctx.accounts.config.to_account_info();
+        if config_account.data_len() != LegacyConfig::LEN {
+            return err!(ErrorCode::ConfigAlreadyMigrated);
+        }
+        let legacy_config = {
+            let data = config_account.try_borrow_data()?;
+            if data[..8] != Config::DISCRIMINATOR {
+                return Err(anchor_lang::error::ErrorCode::AccountDiscriminatorMismatch.into());
+            }
+            LegacyConfig::try_from_slice(&data[8..])?
+        };
+        let total_claimed = claimed_per_identity
+            .iter()
+            .try_fold(0u64, |total, claimed| total.checked_add(*claimed))
+            .ok_or(ErrorCode::ArithmeticOverflow)?;
+
+        let rent_due = Rent::get()?
+            .minimum_balance(Config::LEN)
+            .saturating_sub(config_account.lamports());
+        invoke(
+            &system_instruction::transfer(
+                &ctx.accounts.authority.key(),
+                &config_account.key(),
+                rent_due,
+            ),
+            &[
+                ctx.accounts.authority.to_account_info(),
+                config_account.clone(),
+                ctx.accounts.system_program.to_account_info(),
+            ],
+        )?;
+        config_account.realloc(Config::LEN, true)?;
+
+        let config = Config {
+            bump: legacy_config.bump,
+            merkle_root: DispenserMerkleRoot::Keccak160(legacy_config.merkle_root),
+            dispenser_guard: legacy_config.dispenser_guard,
+            mint: legacy_config.mint,
+            treasury: legacy_config.treasury,
+            address_lookup_table: legacy_config.address_lookup_table,
+            funder: legacy_config.funder,
+            max_transfer: legacy_config.max_transfer,
+            escrow: false,
+            total_deposited: 0,
+            total_claimed,
+            claim_count,
+            claimed_per_identity,
+            global_cap,
+            max_per_claimant: None,
+            admin: ctx.accounts.authority.key(),
+            rate_limit: None,
+            denylist_len: 0,
+            guard_policy: 0,
+            legacy_receipts: claim_count,
+            version: CONFIG_VERSION,
+        };
+        config.try_serialize(&mut &mut config_account.try_borrow_mut_data()?[..])?;
+        Ok(())
+    }
+
+    /**
+     * Sets the maximum amount that claims can transfer out of the treasury per window of `window_seconds`,
+     * or removes the limit if `max_amount` is None. The amounts claimed in the current and previous windows are kept
+     * as long as `window_seconds` doesn't change.
+     */
+    pub fn set_rate_limit(
+        ctx: Context<UpdateConfig>,
+        _campaign_id: u64,
+        max_amount: Option<u64>,
+        window_seconds: i64,
+    ) -> Result<()> {
+        let config = &mut ctx.accounts.config;
+        config.rate_limit = match max_amount {
+            Some(max_amount) => {
+                require_gt!(window_seconds, 0, ErrorCode::InvalidRateLimit);
+                Some(RateLimit {
+                    max_amount,
+                    window_seconds,
+                    ..config.rate_limit.unwrap_or_default()
+                })
+            }
+            None => None,
+        };
+        Ok(())
+    }
+
+    /**
+     * Sets the ecosystems whose claims must be signed by the dispenser guard, as a bitmask indexed by
+     * `Identity::get_variant_index`. This lets the guard gate claims with off-chain checks for any ecosystem.
+     */
+    pub fn set_guard_signature_policy(
+        ctx: Context<UpdateConfig>,
+        _campaign_id: u64,
+        guard_policy: u16,
+    ) -> Result<()> {
+        ctx.accounts.config.guard_policy = guard_policy;
+        Ok(())
+    }
+
+    /**
+     * Replaces the tree of the campaign, e.g. to top up allocations. Leaves hold cumulative amounts and receipts are
+     * keyed by identity, so identities that already claimed are only paid the difference.
+     * Legacy receipts of the default campaign, see `migrate_receipt`, only match the leaves they were created for,
+     * so the tree can't be replaced until they have all been migrated.
+     */
+    pub fn update_merkle_root(
+        ctx: Context<UpdateConfig>,
+        _campaign_id: u64,
+        merkle_root: DispenserMerkleRoot,
+    ) -> Result<()> {
+        require_eq!(
+            ctx.accounts.config.legacy_receipts,
+            0,
+            ErrorCode::LegacyReceiptsNotMigrated
+        );
+        ctx.accounts.config.merkle_root = merkle_root;
+        Ok(())
+    }
+
+    /**
+     * Copies a legacy receipt of the default campaign into the receipt of its identity. Before receipts were keyed by
+     * identity, a claim created an empty receipt keyed by the hash of the leaf, see `get_legacy_receipt_pda`.
+     * `claim_info` is the leaf of the legacy receipt. Anyone can migrate a receipt, since it only records a claim that
+     * happened. Until the tree is amended, the receipt of an identity with a legacy receipt can only be created here,
+     * since its claims fail with `AlreadyClaimed`, so creating it counts the legacy receipt as migrated.
+     */
+    pub fn migrate_receipt(ctx: Context<MigrateReceipt>, claim_info: ClaimInfo) -> Result<()> {
+        let legacy_receipt = &ctx.accounts.legacy_receipt;
+        require_keys_eq!(
+            legacy_receipt.key(),
+            get_legacy_receipt_pda(&claim_info.try_to_vec()?).0,
+            ErrorCode::WrongPda
+        );
+        if !legacy_receipt.owner.eq(&crate::id()) {
+            return err!(ErrorCode::LegacyReceiptNotFound);
+        }
+
+        let identity_hash = get_identity_hash(&claim_info.identity);
+        let (receipt_pubkey, bump) =
+            get_receipt_pda_from_identity_hash(DEFAULT_CAMPAIGN_ID, &identity_hash);
+        let receipt_account = ctx.accounts.receipt.to_account_info();
+        require_keys_eq!(receipt_account.key(), receipt_pubkey, ErrorCode::WrongPda);
+        let mut receipt = if receipt_account.owner.eq(&crate::id()) {
+            Receipt::try_deserialize(&mut &receipt_account.try_borrow_data()?[..])?
+        } else {
+            create_claim_receipt(
+                DEFAULT_CAMPAIGN_ID,
+                &identity_hash,
+                bump,
+                &receipt_account,
+                &ctx.accounts.payer,
+                &ctx.accounts.system_program,
+            )?;
+            let config = &mut ctx.accounts.config;
+            config.legacy_receipts = config.legacy_receipts.saturating_sub(1);
+            Receipt {
+                amount_claimed: 0,
+                bump,
+                nonce: 0,
+            }
+        };
+        receipt.amount_claimed = receipt.amount_claimed.max(claim_info.amount);
+        receipt.try_serialize(&mut &mut receipt_account.try_borrow_mut_data()?[..])?;
+        Ok(())
+    }
+
+    /**
+     * Adds an identity or a claimant to the denylist of the campaign, their claims fail with `IdentityBlocked`
+     * until they are removed with `unblock`. Identities are keyed like their receipts, so they stay blocked
+     * when the tree is amended.
+     */
+    pub fn block(ctx: Context<Block>, _campaign_id: u64, _denylist_key: DenylistKey) -> Result<()> {
+        let config = &mut ctx.accounts.config;
+        config.denylist_len += 1;
+        Ok(())
+    }
+
+    /**
+     * Removes an identity or a claimant from the denylist of the campaign, the rent of the entry goes back to the admin.
+     */
+    pub fn unblock(
+        ctx: Context<Unblock>,
+        _campaign_id: u64,
+        _denylist_key: DenylistKey,
+    ) -> Result<()> {
+        let config = &mut ctx.accounts.config;
+        config.denylist_len = config.denylist_len.saturating_sub(1);
+        Ok(())
+    }
+
+    /**
+     * Deposit tokens into the escrow treasury of a campaign. Anyone can deposit, the amount received by
+     * the treasury is added to `total_deposited`.
+     */
+    pub fn deposit(ctx: Context<Deposit>, _campaign_id: u64, amount: u64) -> Result<()> {
+        // Mints with the transfer fee extension withhold part of the deposit in the treasury
+        let treasury_balance = unpack_token_account(&ctx.accounts.treasury)?.amount;
+        transfer_checked(
+            &ctx.accounts.token_program,
+            &ctx.accounts.depositor_fund,
+            &ctx.accounts.mint,
+            &ctx.accounts.treasury,
+            &ctx.accounts.depositor,
+            &[],
+            amount,
+            unpack_mint(&ctx.accounts.mint)?.decimals,
+        )?;
+        let deposited = unpack_token_account(&ctx.accounts.treasury)?
+            .amount
+            .saturating_sub(treasury_balance);
+
+        let config = &mut ctx.accounts.config;
+        config.total_deposited = config.total_deposited.saturating_add(deposited);
         Ok(())
     }
 
     /**
      * Claim a claimant's tokens. This instructions needs to enforce :
-     * - The dispenser guard has signed the transaction - DONE
+     * - The dispenser guard has signed the transaction, if the guard signature policy of the campaign
+     *   requires it for the ecosystem of the identity - DONE
      * - The claimant is claiming no more than once per ecosystem - DONE
      * - The claimant has provided a valid proof of identity (is the owner of the wallet
      *   entitled to the tokens)
      * - The claimant has provided a valid proof of inclusion (this confirm that the claimant --
      *   DONE
      * - The claimant has not already claimed tokens -- DONE
+     * `bumps` are the canonical bumps of the PDAs in `remaining_accounts`, so that the program doesn't search for them.
+     * Without them the program searches for the bumps, which costs more compute units.
      */
     pub fn claim<'info>(
         ctx: Context<'_, '_, '_, 'info, Claim<'info>>,
+        campaign_id: u64,
         claim_certificate: ClaimCertificate,
+        bumps: Option<ClaimBumps>,
     ) -> Result<()> {
         let config = &ctx.accounts.config;
-        let treasury = &mut ctx.accounts.treasury;
+        let treasury = &ctx.accounts.treasury;
         let claimant_fund = &ctx.accounts.claimant_fund;
 
+        checked_create_associated_token_account(
+            claimant_fund,
+            &ctx.accounts.funder,
+            &ctx.accounts.claimant,
+            &ctx.accounts.mint,
+            &ctx.accounts.system_program,
+            &ctx.accounts.token_program,
+            &ctx.accounts.associated_token_program,
+        )?;
+
+        let clock = Clock::get()?;
         // Check that the identity corresponding to the leaf has authorized the claimant
         let claim_info = claim_certificate.checked_into_claim_info(
             &ctx.accounts.sysvar_instruction,
+            campaign_id,
+            &config.merkle_root,
             ctx.accounts.claimant.key,
             &ctx.accounts.config.dispenser_guard,
+            clock.unix_timestamp,
         )?;
         // Each leaf of the tree is a hash of the serialized claim info
         let leaf_vector = claim_info.try_to_vec()?;
@@ -137,11 +469,46 @@ pub mod token_dispenser {
         {
             return err!(ErrorCode::InvalidInclusionProof);
         };
+        let leaf_hash = config.merkle_root.hash_leaf(&leaf_vector);
+        // The leaf is the serialized identity followed by the amount, so the identity doesn't need to be serialized again
+        let identity_hash =
+            SolanaHasher32::hashv(
+                &[&leaf_vector[..leaf_vector.len() - std::mem::size_of::<u64>()]],
+            );
 
+        // The guard signs as an extra remaining account
+        if config.requires_guard_signature(&claim_info.identity)
+            && !ctx
+                .remaining_accounts
+                .iter()
+                .any(|account| account.is_signer && account.key == &config.dispenser_guard)
+        {
+            return err!(ErrorCode::GuardSignatureRequired);
+        }
+
+        let bumps = bumps.unwrap_or_else(|| {
+            get_claim_bumps(campaign_id, &claim_info, ctx.accounts.claimant.key)
+        });
+        check_not_denylisted(
+            campaign_id,
+            &identity_hash,
+            ctx.accounts.claimant.key,
+            &bumps,
+            ctx.remaining_accounts,
+        )?;
 
-        checked_create_claim_receipt(
+        // A leaf of the default campaign may have been claimed before receipts were keyed by identity
+        let legacy_leaf_hash = (campaign_id == DEFAULT_CAMPAIGN_ID)
+            .then(|| get_legacy_leaf_hash(&config.merkle_root, &leaf_vector, &leaf_hash));
+        // The leaf holds the cumulative amount of the identity, only the part that hasn't been claimed yet is paid
+        let amount_due = checked_update_claim_receipt(
             0,
-            &leaf_vector,
+            campaign_id,
+            &claim_info,
+            &identity_hash,
+            legacy_leaf_hash.as_ref(),
+            &bumps,
+            claim_certificate.nonce,
             &ctx.accounts.funder,
             &ctx.accounts.system_program,
             ctx.remaining_accounts,
@@ -149,31 +516,71 @@ pub mod token_dispenser {
 
         require_gte!(
             config.max_transfer,
-            claim_info.amount,
+            amount_due,
             ErrorCode::TransferExceedsMax
         );
+        ctx.accounts
+            .config
+            .checked_record_claim(&claim_info.identity, amount_due)?;
+        if let Some(rate_limit) = ctx.accounts.config.rate_limit.as_mut() {
+            rate_limit.checked_record_outflow(amount_due, clock.unix_timestamp)?;
+        }
+        let config = &ctx.accounts.config;
+        if let Some(max_per_claimant) = config.max_per_claimant {
+            checked_update_claimant_total(
+                1,
+                campaign_id,
+                ctx.accounts.claimant.key,
+                amount_due,
+                max_per_claimant,
+                bumps.claimant_total,
+                &ctx.accounts.funder,
+                &ctx.accounts.system_program,
+                ctx.remaining_accounts,
+            )?;
+        }
 
-        token::transfer(
-            CpiContext::new_with_signer(
-                ctx.accounts.token_program.to_account_info(),
-                token::Transfer {
-                    from:      treasury.to_account_info(),
-                    to:        claimant_fund.to_account_info(),
-                    authority: config.to_account_info(),
-                },
-                &[&[CONFIG_SEED, &[config.bump]]],
-            ),
-            claim_info.amount,
+        // Mints with the transfer fee extension withhold part of the transfer in the claimant fund
+        let claimant_fund_balance = unpack_token_account(claimant_fund)?.amount;
+        transfer_checked(
+            &ctx.accounts.token_program,
+            treasury,
+            &ctx.accounts.mint,
+            claimant_fund,
+            &config.to_account_info(),
+            &[&[CONFIG_SEED, &get_campaign_seed(campaign_id), &[config.bump]]],
+            amount_due,
+            unpack_mint(&ctx.accounts.mint)?.decimals,
         )?;
 
-        // reload treasury account from storage to get the updated balance
-        treasury.reload()?;
+        let remaining_balance = unpack_token_account(treasury)?.amount;
+        let amount_received = unpack_token_account(claimant_fund)?
+            .amount
+            .saturating_sub(claimant_fund_balance);
+        let claim_event_v2 = ClaimEventV2 {
+            campaign_id,
+            claimant: *ctx.accounts.claimant.key,
+            claimant_fund: claimant_fund.key(),
+            ecosystem: claim_info.identity.get_variant_index() as u8,
+            identity_hash,
+            leaf_hash,
+            receipt: ctx.remaining_accounts[0].key(),
+            merkle_root: config.merkle_root.to_padded_bytes(),
+            amount: claim_info.amount,
+            amount_transferred: amount_due,
+            amount_received,
+            remaining_balance,
+            slot: clock.slot,
+            unix_timestamp: clock.unix_timestamp,
+        };
 
         emit!(ClaimEvent {
-            remaining_balance: treasury.amount,
+            remaining_balance,
             claimant: *ctx.accounts.claimant.key,
             claim_info,
+            amount_received,
         });
+        emit!(claim_event_v2);
 
 
         Ok(())
@@ -185,45 +592,133 @@ pub mod token_dispenser {
 ////////////////////////////////////////////////////////////////////////////////
 
 #[derive(Accounts)]
+#[instruction(campaign_id : u64)]
 pub struct Initialize<'info> {
     #[account(mut)]
-    pub payer:                Signer<'info>,
-    #[account(init, payer = payer, space = Config::LEN, seeds = [CONFIG_SEED], bump)]
-    pub config:               Account<'info, Config>,
-    /// Mint of the treasury
-    pub mint:                 Account<'info, Mint>,
-    /// Treasury token account. This is an externally owned token account and
+    pub payer:                    Signer<'info>,
+    #[account(init, payer = payer, space = Config::LEN, seeds = [CONFIG_SEED, get_campaign_seed(campaign_id).as_slice()], bump)]
+    pub config:                   Account<'info, Config>,
+    /// CHECK: Mint of the treasury, owned by the token program or by token-2022. It is unpacked in the instruction.
+    pub mint:                     UncheckedAccount<'info>,
+    /// CHECK: Treasury token account. This is an externally owned token account and
     /// the owner of this account will approve the config as a delegate using the
     /// solana CLI command `spl-token approve <treasury_account_address> <approve_amount> <config_address>`
-    #[account( token::mint = mint )]
-    pub treasury:             Account<'info, TokenAccount>,
-    pub system_program:       Program<'info, System>,
+    /// In escrow mode, it is instead the associated token account of the config and gets created here.
+    /// It is unpacked and checked against the mint in the instruction.
+    #[account(mut)]
+    pub treasury:                 UncheckedAccount<'info>,
+    pub system_program:           Program<'info, System>,
     /// CHECK: Anchor doesn't have built-in support for address lookup table so adding this check to make sure at least the PDA owner is correct
     #[account(owner = solana_address_lookup_table_program::id())]
-    pub address_lookup_table: UncheckedAccount<'info>,
+    pub address_lookup_table:     UncheckedAccount<'info>,
+    /// CHECK: Either the token program or token-2022
+    #[account(constraint = is_token_program(token_program.key) @ anchor_lang::error::ErrorCode::InvalidProgramId)]
+    pub token_program:            UncheckedAccount<'info>,
+    pub associated_token_program: Program<'info, AssociatedToken>,
+    /// CHECK: `ProgramData` of the program, only read when initializing a campaign other than the default one
+    #[account(seeds = [crate::id().as_ref()], bump, seeds::program = bpf_loader_upgradeable::id())]
+    pub program_data:             UncheckedAccount<'info>,
+}
+
+#[derive(Accounts)]
+#[instruction(campaign_id : u64)]
+pub struct Deposit<'info> {
+    pub depositor:      Signer<'info>,
+    /// CHECK: Token account debited by the deposit, the token program checks that the depositor owns it
+    #[account(mut, owner = token_program.key())]
+    pub depositor_fund: UncheckedAccount<'info>,
+    #[account(mut, seeds = [CONFIG_SEED, get_campaign_seed(campaign_id).as_slice()], bump = config.bump, has_one = treasury, has_one = mint, constraint = config.escrow @ ErrorCode::TreasuryNotEscrowed)]
+    pub config:         Account<'info, Config>,
+    /// CHECK: The mint is the one of the config and belongs to `token_program`
+    #[account(owner = token_program.key())]
+    pub mint:           UncheckedAccount<'info>,
+    /// CHECK: The treasury is the one of the config and belongs to `token_program`
+    #[account(mut, owner = token_program.key())]
+    pub treasury:       UncheckedAccount<'info>,
+    /// CHECK: Either the token program or token-2022
+    #[account(constraint = is_token_program(token_program.key) @ anchor_lang::error::ErrorCode::InvalidProgramId)]
+    pub token_program:  UncheckedAccount<'info>,
+}
+
+#[derive(Accounts)]
+#[instruction(campaign_id : u64)]
+pub struct UpdateConfig<'info> {
+    pub admin:  Signer<'info>,
+    #[account(mut, seeds = [CONFIG_SEED, get_campaign_seed(campaign_id).as_slice()], bump = config.bump, has_one = admin)]
+    pub config: Account<'info, Config>,
+}
+
+#[derive(Accounts)]
+#[instruction(campaign_id : u64)]
+pub struct MigrateConfig<'info> {
+    #[account(mut)]
+    pub authority:      Signer<'info>,
+    /// CHECK: Config in the `LegacyConfig` layout, it is deserialized and reallocated in the instruction
+    #[account(mut, owner = crate::id(), seeds = [CONFIG_SEED, get_campaign_seed(campaign_id).as_slice()], bump)]
+    pub config:         UncheckedAccount<'info>,
+    #[account(seeds = [crate::id().as_ref()], bump, seeds::program = bpf_loader_upgradeable::id(), constraint = program_data.upgrade_authority_address == Some(authority.key()))]
+    pub program_data:   Account<'info, ProgramData>,
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct MigrateReceipt<'info> {
+    #[account(mut)]
+    pub payer:          Signer<'info>,
+    /// CHECK: Legacy receipt of the leaf, its address and its owner are checked in the instruction
+    pub legacy_receipt: UncheckedAccount<'info>,
+    /// CHECK: Receipt of the identity of the leaf, created if needed. Its address is checked in the instruction
+    #[account(mut)]
+    pub receipt:        UncheckedAccount<'info>,
+    #[account(mut, seeds = [CONFIG_SEED, get_campaign_seed(DEFAULT_CAMPAIGN_ID).as_slice()], bump = config.bump)]
+    pub config:         Account<'info, Config>,
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+#[instruction(campaign_id : u64, denylist_key : DenylistKey)]
+pub struct Block<'info> {
+    #[account(mut)]
+    pub admin:          Signer<'info>,
+    #[account(mut, seeds = [CONFIG_SEED, get_campaign_seed(campaign_id).as_slice()], bump = config.bump, has_one = admin)]
+    pub config:         Account<'info, Config>,
+    #[account(init, payer = admin, space = DenylistEntry::LEN, seeds = [DENYLIST_SEED, get_campaign_seed(campaign_id).as_slice(), denylist_key.get_kind_seed(), denylist_key.get_key_seed()], bump)]
+    pub denylist_entry: Account<'info, DenylistEntry>,
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+#[instruction(campaign_id : u64, denylist_key : DenylistKey)]
+pub struct Unblock<'info> {
+    #[account(mut)]
+    pub admin:          Signer<'info>,
+    #[account(mut, seeds = [CONFIG_SEED, get_campaign_seed(campaign_id).as_slice()], bump = config.bump, has_one = admin)]
+    pub config:         Account<'info, Config>,
+    #[account(mut, close = admin, seeds = [DENYLIST_SEED, get_campaign_seed(campaign_id).as_slice(), denylist_key.get_kind_seed(), denylist_key.get_key_seed()], bump)]
+    pub denylist_entry: Account<'info, DenylistEntry>,
 }
 
 #[derive(Accounts)]
-#[instruction(claim_certificate : ClaimCertificate)]
+#[instruction(campaign_id : u64)]
 pub struct Claim<'info> {
     #[account(mut)]
     pub funder:                   Signer<'info>, // Funds the claimant_fund and the claim receipt account
     pub claimant:                 Signer<'info>,
-    /// Claimant's associated token account to receive the tokens
-    /// Should be initialized outside of this program.
-    #[account(
-        init_if_needed,
-        payer = funder,
-        associated_token::authority = claimant,
-        associated_token::mint = mint,
-    )]
-    pub claimant_fund:            Account<'info, TokenAccount>,
-    #[account(seeds = [CONFIG_SEED], bump = config.bump, has_one = treasury, has_one = mint)]
-    pub config:                   Account<'info, Config>,
-    pub mint:                     Account<'info, Mint>,
+    /// CHECK: Claimant's associated token account to receive the tokens. It is created under `token_program`
+    /// if needed and checked in `checked_create_associated_token_account`.
     #[account(mut)]
-    pub treasury:                 Account<'info, TokenAccount>,
-    pub token_program:            Program<'info, Token>,
+    pub claimant_fund:            UncheckedAccount<'info>,
+    #[account(mut, seeds = [CONFIG_SEED, get_campaign_seed(campaign_id).as_slice()], bump = config.bump, has_one = treasury, has_one = mint)]
+    pub config:                   Account<'info, Config>,
+    /// CHECK: The mint is the one of the config and belongs to `token_program`
+    #[account(owner = token_program.key())]
+    pub mint:                     UncheckedAccount<'info>,
+    /// CHECK: The treasury is the one of the config and belongs to `token_program`
+    #[account(mut, owner = token_program.key())]
+    pub treasury:                 UncheckedAccount<'info>,
+    /// CHECK: Either the token program or token-2022
+    #[account(constraint = is_token_program(token_program.key) @ anchor_lang::error::ErrorCode::InvalidProgramId)]
+    pub token_program:            UncheckedAccount<'info>,
     pub system_program:           Program<'info, System>,
     /// CHECK : Anchor wants me to write this comment because I'm using AccountInfo which doesn't check for ownership and doesn't deserialize the account automatically. But it's fine because I check the address and I load it using load_instruction_at_checked.
     #[account(address = SYSVAR_IX_ID)]
@@ -236,6 +731,32 @@ pub struct Claim<'info> {
 // Instruction calldata.
 ////////////////////////////////////////////////////////////////////////////////
 
+/**
+ * An entry of the denylist of a campaign, either an identity, by the hash that seeds its receipt, see
+ * `get_identity_hash`, or a claimant.
+ */
+#[derive(AnchorDeserialize, AnchorSerialize, Clone, PartialEq, Debug)]
+pub enum DenylistKey {
+    Identity { identity_hash: [u8; HASH_SIZE] },
+    Claimant { pubkey: Pubkey },
+}
+
+impl DenylistKey {
+    pub fn get_kind_seed(&self) -> &'static [u8] {
+        match self {
+            DenylistKey::Identity { .. } => DENYLIST_IDENTITY_SEED,
+            DenylistKey::Claimant { .. } => DENYLIST_CLAIMANT_SEED,
+        }
+    }
+
+    pub fn get_key_seed(&self) -> &[u8] {
+        match self {
+            DenylistKey::Identity { identity_hash } => identity_hash,
+            DenylistKey::Claimant { pubkey } => pubkey.as_ref(),
+        }
+    }
+}
+
 #[derive(AnchorDeserialize, AnchorSerialize, Clone)]
 pub struct ClaimInfo {
     pub identity: Identity,
@@ -256,6 +777,31 @@ pub enum Identity {
     Aptos { address: AptosAddress },
     Cosmwasm { address: CosmosBech32Address },
     Injective { address: CosmosBech32Address },
+    Passkey { pubkey: Secp256r1Pubkey },
+    Starknet { address: StarknetAddress },
+    Cardano { key_hash: CardanoKeyHash },
+}
+
+pub const IDENTITY_VARIANTS: usize = 10;
+
+impl Identity {
+    /**
+     * The position of the variant in `Identity`, which is also its borsh discriminator.
+     */
+    pub fn get_variant_index(&self) -> usize {
+        match self {
+            Identity::Discord { .. } => 0,
+            Identity::Solana { .. } => 1,
+            Identity::Evm { .. } => 2,
+            Identity::Sui { .. } => 3,
+            Identity::Aptos { .. } => 4,
+            Identity::Cosmwasm { .. } => 5,
+            Identity::Injective { .. } => 6,
+            Identity::Passkey { .. } => 7,
+            Identity::Starknet { .. } => 8,
+            Identity::Cardano { .. } => 9,
+        }
+    }
 }
 
 #[derive(AnchorDeserialize, AnchorSerialize, Clone)]
@@ -288,13 +834,51 @@ pub enum IdentityCertificate {
         pubkey:                         EvmPubkey,
         verification_instruction_index: u8,
     },
+    /// Same as `Cosmwasm` but the claimant submits the signed sign doc instead of its hash.
+    /// This is needed for Ledger wallets, which sign an amino JSON serialization that can differ from ours.
+    CosmwasmSignDoc {
+        chain_id:    String,
+        signature:   Secp256k1Signature,
+        recovery_id: u8,
+        pubkey:      UncompressedSecp256k1Pubkey,
+        sign_doc:    Vec<u8>,
+    },
+    Passkey {
+        pubkey:                         Secp256r1Pubkey,
+        authenticator_data:             Vec<u8>,
+        client_data_json:               Vec<u8>,
+        verification_instruction_index: u8,
+    },
+    Starknet {
+        address:                        StarknetAddress,
+        verification_instruction_index: u8,
+    },
+    Cardano {
+        pubkey:                         Ed25519Pubkey,
+        verification_instruction_index: u8,
+    },
 }
 
 #[derive(AnchorDeserialize, AnchorSerialize, Clone)]
 pub struct ClaimCertificate {
     pub amount:             u64,
     pub proof_of_identity:  IdentityCertificate,
-    pub proof_of_inclusion: MerklePath<SolanaHasher>, // Proof that the leaf is in the tree
+    pub proof_of_inclusion: DispenserMerklePath, // Proof that the leaf is in the tree
+    pub expires_at:         Option<i64>,         // Deadline included in the signed payload, if any
+    pub nonce:              Option<u64>, // Nonce of the receipt included in the signed payload, if any
+}
+
+/**
+ * The bumps of the PDAs that a claim passes in `remaining_accounts`, the bumps of the accounts that the claim doesn't
+ * use are ignored. They are checked with `create_program_address` only, see `check_pda`.
+ */
+#[derive(AnchorDeserialize, AnchorSerialize, Clone, Copy, Debug, PartialEq)]
+pub struct ClaimBumps {
+    pub receipt:           u8,
+    pub claimant_total:    u8,
+    pub denylist_identity: u8,
+    pub denylist_claimant: u8,
+    pub legacy_receipt:    u8,
 }
 
 ////////////////////////////////////////////////////////////////////////////////
@@ -317,92 +901,426 @@ impl Hasher for SolanaHasher {
     }
 }
 
+/**
+ * Same as `SolanaHasher` but keeps the full 32 bytes of keccak256, for distributions where 80 bits of
+ * collision resistance aren't enough.
+ */
+#[derive(Default, Debug, Clone, PartialEq)]
+pub struct SolanaHasher32 {}
+impl Hasher for SolanaHasher32 {
+    type Hash = [u8; 32];
+
+    fn hashv(data: &[impl AsRef<[u8]>]) -> Self::Hash {
+        hashv(&data.iter().map(|x| x.as_ref()).collect::<Vec<&[u8]>>()).to_bytes()
+    }
+}
+
+/**
+ * Size of the hashes stored by the program. The hashes of `SolanaHasher` are zero-padded.
+ */
+pub const HASH_SIZE: usize = 32;
+
+/**
+ * Size of the hashes of `SolanaHasher`, which key the legacy receipts.
+ */
+pub const LEGACY_HASH_SIZE: usize = 20;
+
+fn pad_hash(hash: &[u8]) -> [u8; HASH_SIZE] {
+    let mut padded = [0u8; HASH_SIZE];
+    padded[..hash.len()].copy_from_slice(hash);
+    padded
+}
+
+/**
+ * The root of the distribution tree of a campaign. The variant is the hasher of the tree, so that a single
+ * deployment can verify campaigns of both hash sizes.
+ */
+#[derive(AnchorDeserialize, AnchorSerialize, Clone, PartialEq, Debug)]
+pub enum DispenserMerkleRoot {
+    Keccak160(MerkleRoot<SolanaHasher>),
+    Keccak256(MerkleRoot<SolanaHasher32>),
+}
+
+/**
+ * A proof of inclusion in a `DispenserMerkleRoot`, it only verifies against a root of the same hasher.
+ */
+#[derive(AnchorDeserialize, AnchorSerialize, Clone)]
+pub enum DispenserMerklePath {
+    Keccak160(MerklePath<SolanaHasher>),
+    Keccak256(MerklePath<SolanaHasher32>),
+}
+
+impl DispenserMerkleRoot {
+    pub const LEN: usize = 1 + HASH_SIZE;
+
+    pub fn check(&self, proof: DispenserMerklePath, leaf: &[u8]) -> bool {
+        match (self, proof) {
+            (DispenserMerkleRoot::Keccak160(root), DispenserMerklePath::Keccak160(proof)) => {
+                root.check(proof, leaf)
+            }
+            (DispenserMerkleRoot::Keccak256(root), DispenserMerklePath::Keccak256(proof)) => {
+                root.check(proof, leaf)
+            }
+            _ => false,
+        }
+    }
+
+    pub fn hash_leaf(&self, leaf: &[u8]) -> [u8; HASH_SIZE] {
+        match self {
+            DispenserMerkleRoot::Keccak160(_) => {
+                pad_hash(&MerkleTree::<SolanaHasher>::hash_leaf(leaf))
+            }
+            DispenserMerkleRoot::Keccak256(_) => MerkleTree::<SolanaHasher32>::hash_leaf(leaf),
+        }
+    }
+
+    pub fn as_bytes(&self) -> &[u8] {
+        match self {
+            DispenserMerkleRoot::Keccak160(root) => root.as_bytes(),
+            DispenserMerkleRoot::Keccak256(root) => root.as_bytes(),
+        }
+    }
+
+    pub fn to_padded_bytes(&self) -> [u8; HASH_SIZE] {
+        pad_hash(self.as_bytes())
+    }
+}
+
+impl From<MerkleRoot<SolanaHasher>> for DispenserMerkleRoot {
+    fn from(root: MerkleRoot<SolanaHasher>) -> Self {
+        DispenserMerkleRoot::Keccak160(root)
+    }
+}
+
+impl From<MerkleRoot<SolanaHasher32>> for DispenserMerkleRoot {
+    fn from(root: MerkleRoot<SolanaHasher32>) -> Self {
+        DispenserMerkleRoot::Keccak256(root)
+    }
+}
+
+impl From<MerklePath<SolanaHasher>> for DispenserMerklePath {
+    fn from(path: MerklePath<SolanaHasher>) -> Self {
+        DispenserMerklePath::Keccak160(path)
+    }
+}
+
+impl From<MerklePath<SolanaHasher32>> for DispenserMerklePath {
+    fn from(path: MerklePath<SolanaHasher32>) -> Self {
+        DispenserMerklePath::Keccak256(path)
+    }
+}
+
 #[account]
 #[derive(PartialEq, Debug)]
 pub struct Config {
     pub bump:                 u8,
-    pub merkle_root:          MerkleRoot<SolanaHasher>,
+    pub merkle_root:          DispenserMerkleRoot,
     pub dispenser_guard:      Pubkey,
     pub mint:                 Pubkey,
     pub treasury:             Pubkey,
     pub address_lookup_table: Pubkey,
     pub funder:               Pubkey,
     pub max_transfer:         u64, // This is an extra safeguard to prevent the dispenser from being drained
+    pub escrow:               bool, // Whether the treasury is owned by the config rather than delegated to it
+    pub total_deposited:      u64,  // Amount received by the treasury through `deposit`
+    pub total_claimed:        u64,  // Amount transferred out of the treasury by `claim`
+    pub claim_count:          u64,
+    pub claimed_per_identity: [u64; IDENTITY_VARIANTS], // Indexed by `Identity::get_variant_index`
+    pub global_cap:           Option<u64>, // Claims stop once `total_claimed` would exceed it
+    pub max_per_claimant:     Option<u64>, // Maximum total claimed by a claimant, across all their identities
+    pub admin:                Pubkey,
+    pub rate_limit:           Option<RateLimit>,
+    pub denylist_len:         u64, // Informational count of the denylist entries, claims look the entries up by address
+    pub guard_policy:         u16, // Bitmask of the ecosystems whose claims need the signature of the guard
+    pub legacy_receipts:      u64, // Legacy receipts of the default campaign that haven't been migrated yet
+    pub version:              u8,  // `CONFIG_VERSION` of the layout
 }
 
 impl Config {
-    pub const LEN: usize = 8 + 1 + 20 + 32 + 32 + 32 + 32 + 32 + 8;
-}
+    pub const LEN: usize = 8
+        + 1
+        + DispenserMerkleRoot::LEN
+        + 32
+        + 32
+        + 32
+        + 32
+        + 32
+        + 8
+        + 1
+        + 8
+        + 8
+        + 8
+        + 8 * IDENTITY_VARIANTS
+        + 1
+        + 8
+        + 1
+        + 8
+        + 32
+        + 1
+        + RateLimit::LEN
+        + 8
+        + 2
+        + 8
+        + 1;
 
-#[account]
-pub struct Receipt {}
-
-////////////////////////////////////////////////////////////////////////////////
-// Error.
-////////////////////////////////////////////////////////////////////////////////
+    /**
+     * Adds a claim of `amount` by `identity` to the statistics of the campaign.
+     */
+    pub fn checked_record_claim(&mut self, identity: &Identity, amount: u64) -> Result<()> {
+        self.total_claimed = self.total_claimed.saturating_add(amount);
+        if let Some(global_cap) = self.global_cap {
+            require_gte!(global_cap, self.total_claimed, ErrorCode::GlobalCapExceeded);
+        }
+        self.claim_count += 1;
+        let claimed = &mut self.claimed_per_identity[identity.get_variant_index()];
+        *claimed = claimed.saturating_add(amount);
+        Ok(())
+    }
 
-#[error_code]
-pub enum ErrorCode {
-    AlreadyClaimed,
-    InvalidInclusionProof,
-    WrongPda,
-    // Signature verification errors
-    SignatureVerificationWrongProgram,
-    SignatureVerificationWrongAccounts,
-    SignatureVerificationWrongHeader,
-    SignatureVerificationWrongPayload,
-    SignatureVerificationWrongPayloadMetadata,
-    SignatureVerificationWrongSigner,
-    UnauthorizedCosmosChainId,
-    TransferExceedsMax,
-}
+    pub fn requires_guard_signature(&self, identity: &Identity) -> bool {
+        self.guard_policy & (1 << identity.get_variant_index()) != 0
+    }
 
-pub fn check_claim_receipt_is_uninitialized(claim_receipt_account: &AccountInfo) -> Result<()> {
-    if claim_receipt_account.owner.eq(&crate::id()) {
-        return Err(ErrorCode::AlreadyClaimed.into());
+    /**
+     * The deposited tokens that haven't been claimed yet. Only meaningful for escrow treasuries, tokens
+     * transferred to the treasury without `deposit` aren't accounted for.
+     */
+    pub fn get_remaining(&self) -> u64 {
+        self.total_deposited.saturating_sub(self.total_claimed)
     }
-    Ok(())
 }
 
 /**
- * Checks that a proof of identity is valid and returns the underlying identity.
- * For some ecosystems like EVM we use a signature verification program,
- * for others like cosmos the signature is included in the ClaimCertificate.
+ * The layout of the config before it was versioned, when a deployment had a single campaign. `migrate_config`
+ * converts it to a `Config`.
+ */
+#[derive(AnchorDeserialize, AnchorSerialize, Clone, PartialEq, Debug)]
+pub struct LegacyConfig {
+    pub bump:                 u8,
+    pub merkle_root:          MerkleRoot<SolanaHasher>,
+    pub dispenser_guard:      Pubkey,
+    pub mint:                 Pubkey,
+    pub treasury:             Pubkey,
+    pub address_lookup_table: Pubkey,
+    pub funder:               Pubkey,
+    pub max_transfer:         u64,
+}
+
+impl LegacyConfig {
+    pub const LEN: usize = 8 + 1 + 20 + 32 + 32 + 32 + 32 + 32 + 8;
+}
+
+/**
+ * A circuit breaker on the outflows of the treasury, claims fail once the amount claimed over the last
+ * `window_seconds` would exceed `max_amount`. That amount is approximated with two windows aligned on multiples
+ * of `window_seconds`: the current one counts in full and the previous one in proportion to its overlap with the
+ * sliding window, so a burst right before a boundary still counts right after it.
+ */
+#[derive(AnchorDeserialize, AnchorSerialize, Clone, Copy, Default, PartialEq, Debug)]
+pub struct RateLimit {
+    pub max_amount:              u64,
+    pub window_seconds:          i64,
+    pub window_start:            i64,
+    pub window_claimed:          u64, // Amount claimed since `window_start`
+    pub previous_window_claimed: u64, // Amount claimed in the window before `window_start`
+}
+
+impl RateLimit {
+    pub const LEN: usize = 8 + 8 + 8 + 8 + 8;
+
+    pub fn checked_record_outflow(&mut self, amount: u64, unix_timestamp: i64) -> Result<()> {
+        let window_start = unix_timestamp - unix_timestamp.rem_euclid(self.window_seconds);
+        if window_start != self.window_start {
+            self.previous_window_claimed =
+                if window_start == self.window_start.saturating_add(self.window_seconds) {
+                    self.window_claimed
+                } else {
+                    0
+                };
+            self.window_start = window_start;
+            self.window_claimed = 0;
+        }
+        self.window_claimed = self.window_claimed.saturating_add(amount);
+
+        // The part of the previous window that still overlaps the sliding window, rounded up
+        let overlap = (self.window_seconds - (unix_timestamp - window_start)) as u128;
+        let window_seconds = self.window_seconds as u128;
+        let previous_window_weighted = ((self.previous_window_claimed as u128 * overlap
+            + window_seconds
+            - 1)
+            / window_seconds) as u64;
+        require_gte!(
+            self.max_amount,
+            self.window_claimed.saturating_add(previous_window_weighted),
+            ErrorCode::RateLimitExceeded
+        );
+        Ok(())
+    }
+}
+
+/**
+ * The claim receipt of an identity, it records the cumulative amount paid to the identity so that a
+ * leaf of an amended tree only pays the difference. It also records its canonical bump, which claims
+ * provide to derive its address without a bump search.
+ * `nonce` is the number of claims made by the identity. A signed payload that includes a nonce is only
+ * accepted while it is equal to the nonce of the receipt, so it can't be replayed for a later claim.
+ */
+#[account]
+#[derive(Default, PartialEq, Debug)]
+pub struct Receipt {
+    pub amount_claimed: u64,
+    pub bump:           u8,
+    pub nonce:          u64,
+}
+
+impl Receipt {
+    pub const LEN: usize = 8 + 8 + 1 + 8;
+}
+
+/**
+ * The total claimed by a claimant across all the identities that authorized it. It only exists for campaigns
+ * with a `max_per_claimant`, since a single claimant can aggregate many identities.
+ */
+#[account]
+#[derive(Default, PartialEq, Debug)]
+pub struct ClaimantTotal {
+    pub amount_claimed: u64,
+}
+
+impl ClaimantTotal {
+    pub const LEN: usize = 8 + 8;
+}
+
+/**
+ * A blocked identity or claimant, see `DenylistKey`. The entry only holds the discriminator, it is blocking as
+ * long as it exists.
+ */
+#[account]
+#[derive(Default, PartialEq, Debug)]
+pub struct DenylistEntry {}
+
+impl DenylistEntry {
+    pub const LEN: usize = 8;
+}
+
+////////////////////////////////////////////////////////////////////////////////
+// Error.
+////////////////////////////////////////////////////////////////////////////////
+
+#[error_code]
+pub enum ErrorCode {
+    AlreadyClaimed,
+    InvalidInclusionProof,
+    WrongPda,
+    // Signature verification errors
+    SignatureVerificationWrongProgram,
+    SignatureVerificationWrongAccounts,
+    SignatureVerificationWrongHeader,
+    SignatureVerificationWrongPayload,
+    SignatureVerificationWrongPayloadMetadata,
+    SignatureVerificationWrongSigner,
+    UnauthorizedCosmosChainId,
+    TransferExceedsMax,
+    AuthorizationExpired,
+    TreasuryNotEscrowed,
+    GlobalCapExceeded,
+    ClaimantCapExceeded,
+    RateLimitExceeded,
+    InvalidRateLimit,
+    IdentityBlocked,
+    GuardSignatureRequired,
+    LegacyReceiptNotFound,
+    ConfigAlreadyMigrated,
+    ArithmeticOverflow,
+    UpgradeAuthorityRequired,
+    NonceMismatch,
+    LegacyReceiptsNotMigrated,
+}
+
+/**
+ * Checks that a proof of identity is valid and returns the underlying identity.
+ * For some ecosystems like EVM we use a signature verification program,
+ * for others like cosmos the signature is included in the ClaimCertificate.
  */
 impl IdentityCertificate {
+    /**
+     * Derives the identity that the proof of identity claims, without verifying anything.
+     * This is shared by `checked_into_identity` and by the off-chain client, so that they both build the same leaf.
+     */
+    pub fn get_identity(&self, claimant: &Pubkey) -> Result<Identity> {
+        Ok(match self {
+            IdentityCertificate::Discord { username, .. } => Identity::Discord {
+                username: username.clone(),
+            },
+            IdentityCertificate::Evm { pubkey, .. } => Identity::Evm { pubkey: *pubkey },
+            IdentityCertificate::Cosmwasm {
+                pubkey, chain_id, ..
+            }
+            | IdentityCertificate::CosmwasmSignDoc {
+                pubkey, chain_id, ..
+            } => Identity::Cosmwasm {
+                address: pubkey.into_bech32(chain_id)?,
+            },
+            IdentityCertificate::Aptos { pubkey, .. } => Identity::Aptos {
+                address: Into::<AptosAddress>::into(pubkey.clone()),
+            },
+            IdentityCertificate::Sui { pubkey, .. } => Identity::Sui {
+                address: Into::<SuiAddress>::into(pubkey.clone()),
+            },
+            IdentityCertificate::Solana => Identity::Solana {
+                pubkey: Ed25519Pubkey::from(*claimant), // Solana verification relies on claimant signing the Solana transaction
+            },
+            IdentityCertificate::Injective { pubkey, .. } => Identity::Injective {
+                address: CosmosBech32Address::from(*pubkey),
+            },
+            IdentityCertificate::Passkey { pubkey, .. } => Identity::Passkey {
+                pubkey: pubkey.clone(),
+            },
+            IdentityCertificate::Starknet { address, .. } => Identity::Starknet {
+                address: address.clone(),
+            },
+            IdentityCertificate::Cardano { pubkey, .. } => Identity::Cardano {
+                key_hash: CardanoKeyHash::from(pubkey.clone()),
+            },
+        })
+    }
+
     pub fn checked_into_identity(
         &self,
         sysvar_instruction: &AccountInfo,
-        claimant: &Pubkey,
+        authorization: &Authorization,
         dispenser_guard: &Pubkey,
     ) -> Result<Identity> {
-        match self {
-            IdentityCertificate::Discord {
-                username,
-                verification_instruction_index,
-            } => {
+        let identity = self.get_identity(&authorization.claimant)?;
+        match (self, &identity) {
+            (
+                IdentityCertificate::Discord {
+                    username,
+                    verification_instruction_index,
+                },
+                _,
+            ) => {
                 let signature_verification_instruction = load_instruction_at_checked(
                     *verification_instruction_index as usize,
                     sysvar_instruction,
                 )?;
-                let discord_message = DiscordMessage::parse_and_check_claimant_and_username(
+                DiscordMessage::parse_and_check_claimant_and_username(
                     &Ed25519InstructionData::extract_message_and_check_signature(
                         &signature_verification_instruction,
                         &Ed25519Pubkey::from(*dispenser_guard),
                         verification_instruction_index,
                     )?,
                     username,
-                    claimant,
+                    &authorization.claimant,
                 )?;
-
-                Ok(Identity::Discord {
-                    username: discord_message.get_username(),
-                })
             }
-            IdentityCertificate::Evm {
-                pubkey,
-                verification_instruction_index,
-            } => {
+            (
+                IdentityCertificate::Evm {
+                    pubkey,
+                    verification_instruction_index,
+                },
+                _,
+            ) => {
                 let signature_verification_instruction = load_instruction_at_checked(
                     *verification_instruction_index as usize,
                     sysvar_instruction,
@@ -416,28 +1334,29 @@ impl IdentityCertificate {
                         )?,
                     )?
                     .get_payload(),
-                    claimant,
+                    authorization,
                 )?;
-                Ok(Identity::Evm { pubkey: *pubkey })
             }
-            IdentityCertificate::Cosmwasm {
-                pubkey,
-                chain_id,
-                signature,
-                recovery_id,
-                message,
-            } => {
+            (
+                IdentityCertificate::Cosmwasm {
+                    pubkey,
+                    signature,
+                    recovery_id,
+                    message,
+                    ..
+                },
+                Identity::Cosmwasm { address },
+            ) => {
                 secp256k1_verify_signer(signature, recovery_id, pubkey, message)?;
-                let cosmos_bech32 = pubkey.into_bech32(chain_id)?;
-                CosmosMessage::check_hashed_payload(message, &cosmos_bech32, claimant)?;
-                Ok(Identity::Cosmwasm {
-                    address: cosmos_bech32,
-                })
+                CosmosMessage::check_hashed_payload(message, address, authorization)?;
             }
-            IdentityCertificate::Aptos {
-                pubkey,
-                verification_instruction_index,
-            } => {
+            (
+                IdentityCertificate::Aptos {
+                    pubkey,
+                    verification_instruction_index,
+                },
+                _,
+            ) => {
                 let signature_verification_instruction = load_instruction_at_checked(
                     *verification_instruction_index as usize,
                     sysvar_instruction,
@@ -451,16 +1370,16 @@ impl IdentityCertificate {
                         )?,
                     )?
                     .get_payload(),
-                    claimant,
+                    authorization,
                 )?;
-                Ok(Identity::Aptos {
-                    address: Into::<AptosAddress>::into(pubkey.clone()),
-                })
             }
-            IdentityCertificate::Sui {
-                pubkey,
-                verification_instruction_index,
-            } => {
+            (
+                IdentityCertificate::Sui {
+                    pubkey,
+                    verification_instruction_index,
+                },
+                _,
+            ) => {
                 let signature_verification_instruction = load_instruction_at_checked(
                     *verification_instruction_index as usize,
                     sysvar_instruction,
@@ -471,40 +1390,132 @@ impl IdentityCertificate {
                         pubkey,
                         verification_instruction_index,
                     )?,
-                    claimant,
+                    authorization,
                 )?;
-                Ok(Identity::Sui {
-                    address: Into::<SuiAddress>::into(pubkey.clone()),
-                })
             }
-            IdentityCertificate::Solana => Ok(Identity::Solana {
-                pubkey: Ed25519Pubkey::from(*claimant), // Solana verification relies on claimant signing the Solana transaction
-            }),
-            IdentityCertificate::Injective {
-                pubkey,
-                verification_instruction_index,
-            } => {
+            (IdentityCertificate::Solana, _) => {}
+            (
+                IdentityCertificate::Injective {
+                    pubkey,
+                    verification_instruction_index,
+                },
+                Identity::Injective { address },
+            ) => {
                 let signature_verification_instruction = load_instruction_at_checked(
                     *verification_instruction_index as usize,
                     sysvar_instruction,
                 )?;
-                let cosmos_bech32 = CosmosBech32Address::from(*pubkey);
+                let message = Secp256k1InstructionData::extract_message_and_check_signature(
+                    &signature_verification_instruction,
+                    pubkey,
+                    verification_instruction_index,
+                )?;
+                // Injective accounts can sign with an EVM wallet (EIP-191) or with Keplr (ADR-036),
+                // both are keccak256 hashed so they are verified by the secp256k1 program
+                if message.starts_with(EVM_MESSAGE_PREFIX.as_bytes()) {
+                    check_payload(
+                        EvmPrefixedMessage::parse(&message)?.get_payload(),
+                        authorization,
+                    )?;
+                } else {
+                    check_payload(
+                        CosmosMessage::parse(&message, address)?.get_payload(),
+                        authorization,
+                    )?;
+                }
+            }
+            (
+                IdentityCertificate::CosmwasmSignDoc {
+                    pubkey,
+                    signature,
+                    recovery_id,
+                    sign_doc,
+                    ..
+                },
+                Identity::Cosmwasm { address },
+            ) => {
+                secp256k1_verify_signer(
+                    signature,
+                    recovery_id,
+                    pubkey,
+                    &CosmosMessage::hash_sign_doc(sign_doc),
+                )?;
                 check_payload(
-                    EvmPrefixedMessage::parse(
-                        &Secp256k1InstructionData::extract_message_and_check_signature(
+                    CosmosMessage::parse(sign_doc, address)?.get_payload(),
+                    authorization,
+                )?;
+            }
+            (
+                IdentityCertificate::Passkey {
+                    pubkey,
+                    authenticator_data,
+                    client_data_json,
+                    verification_instruction_index,
+                },
+                _,
+            ) => {
+                let signature_verification_instruction = load_instruction_at_checked(
+                    *verification_instruction_index as usize,
+                    sysvar_instruction,
+                )?;
+                WebAuthnMessage::new(authenticator_data, client_data_json)
+                    .check_signed_message_and_challenge(
+                        &Secp256r1InstructionData::extract_message_and_check_signature(
                             &signature_verification_instruction,
                             pubkey,
                             verification_instruction_index,
                         )?,
-                    )?
-                    .get_payload(),
-                    claimant,
+                        authorization,
+                    )?;
+            }
+            (
+                IdentityCertificate::Starknet {
+                    address,
+                    verification_instruction_index,
+                },
+                _,
+            ) => {
+                let signature_verification_instruction = load_instruction_at_checked(
+                    *verification_instruction_index as usize,
+                    sysvar_instruction,
+                )?;
+                StarknetMessage::parse_and_check_claimant_and_address(
+                    &Ed25519InstructionData::extract_message_and_check_signature(
+                        &signature_verification_instruction,
+                        &Ed25519Pubkey::from(*dispenser_guard),
+                        verification_instruction_index,
+                    )?,
+                    address,
+                    &authorization.claimant,
                 )?;
-                Ok(Identity::Injective {
-                    address: cosmos_bech32,
-                })
             }
+            (
+                IdentityCertificate::Cardano {
+                    pubkey,
+                    verification_instruction_index,
+                },
+                _,
+            ) => {
+                let signature_verification_instruction = load_instruction_at_checked(
+                    *verification_instruction_index as usize,
+                    sysvar_instruction,
+                )?;
+                let cardano_message = CardanoMessage::parse(
+                    &Ed25519InstructionData::extract_message_and_check_signature(
+                        &signature_verification_instruction,
+                        pubkey,
+                        verification_instruction_index,
+                    )?,
+                )?;
+                check_payload(cardano_message.get_payload(), authorization)?;
+                cardano_message.check_signer(pubkey)?;
+            }
+            // `get_identity` returns an identity of the ecosystem of the certificate
+            (IdentityCertificate::Cosmwasm { .. }, _)
+            | (IdentityCertificate::CosmwasmSignDoc { .. }, _)
+            | (IdentityCertificate::Injective { .. }, _) => unreachable!(),
         }
+        Ok(identity)
     }
 }
 
@@ -515,13 +1526,24 @@ impl ClaimCertificate {
     pub fn checked_into_claim_info(
         &self,
         sysvar_instruction: &AccountInfo,
+        campaign_id: u64,
+        merkle_root: &DispenserMerkleRoot,
         claimant: &Pubkey,
         dispenser_guard: &Pubkey,
+        unix_timestamp: i64,
     ) -> Result<ClaimInfo> {
         Ok(ClaimInfo {
             identity: self.proof_of_identity.checked_into_identity(
                 sysvar_instruction,
-                claimant,
+                &Authorization::new(
+                    campaign_id,
+                    claimant,
+                    merkle_root,
+                    self.amount,
+                    self.expires_at,
+                    self.nonce,
+                    unix_timestamp,
+                ),
                 dispenser_guard,
             )?,
             amount:   self.amount,
@@ -531,60 +1553,311 @@ impl ClaimCertificate {
 
 
 /**
- * Creates a claim receipt for the claimant. This is an account that contains no data. Each leaf
- * is associated with a unique claim receipt account. Since the number of claim receipt accounts
- * to be passed to the program is dynamic and equal to the size of `claim_certificates`, it is
- * awkward to declare them in the anchor context. Instead, we pass them inside
- * remaining_accounts. If the account is initialized, the assign instruction will fail.
+ * Records in the claim receipt of the identity of `claim_info` that the cumulative amount of the leaf has been
+ * claimed, and returns the amount that is still due, i.e. the difference with the amount claimed so far.
+ * Each identity is associated with a unique claim receipt account, which is created the first time it claims.
+ * Since the number of claim receipt accounts to be passed to the program is dynamic and equal to the size of
+ * `claim_certificates`, it is awkward to declare them in the anchor context. Instead, we pass them inside
+ * remaining_accounts.
+ * `legacy_leaf_hash` is the hash of the leaf that keys its legacy receipt, for claims of the default campaign.
+ * If the signed payload includes a `nonce`, it must be the nonce of the receipt, which every claim increments.
  */
-pub fn checked_create_claim_receipt<'info>(
+#[allow(clippy::too_many_arguments)]
+pub fn checked_update_claim_receipt<'info>(
     index: usize,
+    campaign_id: u64,
+    claim_info: &ClaimInfo,
+    identity_hash: &[u8; HASH_SIZE],
+    legacy_leaf_hash: Option<&[u8; LEGACY_HASH_SIZE]>,
+    bumps: &ClaimBumps,
+    nonce: Option<u64>,
+    funder: &AccountInfo<'info>,
+    system_program: &AccountInfo<'info>,
+    remaining_accounts: &[AccountInfo<'info>],
+) -> Result<u64> {
+    // The claim receipt accounts should appear in remaining accounts in the same order as the claim certificates
+    let claim_receipt_account = &remaining_accounts[index];
+    check_pda(
+        claim_receipt_account,
+        &[RECEIPT_SEED, &get_campaign_seed(campaign_id), identity_hash],
+        bumps.receipt,
+    )?;
+
+    let mut receipt = if claim_receipt_account.owner.eq(&crate::id()) {
+        let receipt = Receipt::try_deserialize(&mut &claim_receipt_account.try_borrow_data()?[..])?;
+        if claim_info.amount <= receipt.amount_claimed {
+            return err!(ErrorCode::AlreadyClaimed);
+        }
+        receipt
+    } else {
+        if let Some(legacy_leaf_hash) = legacy_leaf_hash {
+            if is_legacy_receipt_initialized(
+                legacy_leaf_hash,
+                bumps.legacy_receipt,
+                remaining_accounts,
+            )? {
+                return err!(ErrorCode::AlreadyClaimed);
+            }
+        }
+        create_claim_receipt(
+            campaign_id,
+            identity_hash,
+            bumps.receipt,
+            claim_receipt_account,
+            funder,
+            system_program,
+        )?;
+        Receipt {
+            amount_claimed: 0,
+            bump:           bumps.receipt,
+            nonce:          0,
+        }
+    };
+    if let Some(nonce) = nonce {
+        require_eq!(receipt.nonce, nonce, ErrorCode::NonceMismatch);
+    }
+
+    let amount_due = claim_info.amount - receipt.amount_claimed;
+    receipt.amount_claimed = claim_info.amount;
+    receipt.nonce = receipt
+        .nonce
+        .checked_add(1)
+        .ok_or(ErrorCode::ArithmeticOverflow)?;
+    receipt.try_serialize(&mut &mut claim_receipt_account.try_borrow_mut_data()?[..])?;
+    Ok(amount_due)
+}
+
+/**
+ * Whether the legacy receipt keyed by `legacy_leaf_hash` exists. Claims of the default campaign pass it in
+ * `remaining_accounts`, after the other accounts, since it is only read when the receipt of the identity is created.
+ */
+fn is_legacy_receipt_initialized(
+    legacy_leaf_hash: &[u8; LEGACY_HASH_SIZE],
+    bump: u8,
+    remaining_accounts: &[AccountInfo],
+) -> Result<bool> {
+    let legacy_receipt = find_pda(remaining_accounts, &[RECEIPT_SEED, legacy_leaf_hash], bump)?;
+    Ok(legacy_receipt.owner.eq(&crate::id()))
+}
+
+/**
+ * The hash of `leaf` in the 20-byte tree of the time of legacy receipts. It is the start of `leaf_hash` when the tree
+ * is still a 20-byte tree, so that the leaf isn't hashed twice.
+ */
+fn get_legacy_leaf_hash(
+    merkle_root: &DispenserMerkleRoot,
     leaf: &[u8],
+    leaf_hash: &[u8; HASH_SIZE],
+) -> [u8; LEGACY_HASH_SIZE] {
+    match merkle_root {
+        DispenserMerkleRoot::Keccak160(_) => {
+            let mut legacy_leaf_hash = [0u8; LEGACY_HASH_SIZE];
+            legacy_leaf_hash.copy_from_slice(&leaf_hash[..LEGACY_HASH_SIZE]);
+            legacy_leaf_hash
+        }
+        DispenserMerkleRoot::Keccak256(_) => MerkleTree::<SolanaHasher>::hash_leaf(leaf),
+    }
+}
+
+fn create_claim_receipt<'info>(
+    campaign_id: u64,
+    identity_hash: &[u8; HASH_SIZE],
+    bump: u8,
+    claim_receipt_account: &AccountInfo<'info>,
+    funder: &AccountInfo<'info>,
+    system_program: &AccountInfo<'info>,
+) -> Result<()> {
+    create_program_account(
+        claim_receipt_account,
+        Receipt::LEN,
+        &[
+            RECEIPT_SEED,
+            &get_campaign_seed(campaign_id),
+            identity_hash,
+            &[bump],
+        ],
+        funder,
+        system_program,
+    )
+}
+
+/**
+ * Adds `amount` to the total claimed by `claimant` and checks it against `max_per_claimant`.
+ * Like the claim receipt, the claimant total account is passed in `remaining_accounts`, right after the receipt,
+ * and it is created the first time the claimant claims.
+ */
+#[allow(clippy::too_many_arguments)]
+pub fn checked_update_claimant_total<'info>(
+    index: usize,
+    campaign_id: u64,
+    claimant: &Pubkey,
+    amount: u64,
+    max_per_claimant: u64,
+    bump: u8,
     funder: &AccountInfo<'info>,
     system_program: &AccountInfo<'info>,
     remaining_accounts: &[AccountInfo<'info>],
 ) -> Result<()> {
-    let (receipt_pubkey, bump) = get_receipt_pda(leaf);
+    let claimant_total_account = remaining_accounts
+        .get(index)
+        .ok_or(anchor_lang::error::ErrorCode::AccountNotEnoughKeys)?;
+    check_pda(
+        claimant_total_account,
+        &[
+            CLAIMANT_TOTAL_SEED,
+            &get_campaign_seed(campaign_id),
+            claimant.as_ref(),
+        ],
+        bump,
+    )?;
 
+    let mut claimant_total = if claimant_total_account.owner.eq(&crate::id()) {
+        ClaimantTotal::try_deserialize(&mut &claimant_total_account.try_borrow_data()?[..])?
+    } else {
+        create_program_account(
+            claimant_total_account,
+            ClaimantTotal::LEN,
+            &[
+                CLAIMANT_TOTAL_SEED,
+                &get_campaign_seed(campaign_id),
+                claimant.as_ref(),
+                &[bump],
+            ],
+            funder,
+            system_program,
+        )?;
+        ClaimantTotal::default()
+    };
 
-    // The claim receipt accounts should appear in remaining accounts in the same order as the claim certificates
-    let claim_receipt_account = &remaining_accounts[index];
-    require_keys_eq!(
-        claim_receipt_account.key(),
-        receipt_pubkey,
-        ErrorCode::WrongPda
+    claimant_total.amount_claimed = claimant_total.amount_claimed.saturating_add(amount);
+    require_gte!(
+        max_per_claimant,
+        claimant_total.amount_claimed,
+        ErrorCode::ClaimantCapExceeded
     );
+    claimant_total.try_serialize(&mut &mut claimant_total_account.try_borrow_mut_data()?[..])?;
+    Ok(())
+}
+
+/**
+ * Checks that neither the identity nor the claimant are in the denylist. Their denylist entries are found by address
+ * in `remaining_accounts`, so every claim passes them, and an entry blocks the claim if it exists.
+ */
+pub fn check_not_denylisted(
+    campaign_id: u64,
+    identity_hash: &[u8; HASH_SIZE],
+    claimant: &Pubkey,
+    bumps: &ClaimBumps,
+    remaining_accounts: &[AccountInfo],
+) -> Result<()> {
+    let denylist_keys = [
+        (
+            DenylistKey::Identity {
+                identity_hash: *identity_hash,
+            },
+            bumps.denylist_identity,
+        ),
+        (
+            DenylistKey::Claimant { pubkey: *claimant },
+            bumps.denylist_claimant,
+        ),
+    ];
+    for (denylist_key, bump) in denylist_keys.iter() {
+        let denylist_entry = find_pda(
+            remaining_accounts,
+            &[
+                DENYLIST_SEED,
+                &get_campaign_seed(campaign_id),
+                denylist_key.get_kind_seed(),
+                denylist_key.get_key_seed(),
+            ],
+            *bump,
+        )?;
+        if denylist_entry.owner.eq(&crate::id()) {
+            return err!(ErrorCode::IdentityBlocked);
+        }
+    }
+    Ok(())
+}
+
+/**
+ * Checks that `account` is the PDA of `seeds` with the `bump` provided by the client, using `create_program_address`
+ * only. It accepts any bump that yields an off-curve address, so if the account doesn't exist every higher bump must
+ * fail, otherwise a client could point a claim to an empty account to create a second receipt or to skip a denylist
+ * entry. An account owned by the program is at the canonical address, since the program only creates accounts there,
+ * so deriving its address once is enough.
+ */
+fn check_pda(account: &AccountInfo, seeds: &[&[u8]], bump: u8) -> Result<()> {
+    require_keys_eq!(account.key(), create_pda(seeds, bump)?, ErrorCode::WrongPda);
+    if !account.owner.eq(&crate::id()) {
+        for higher_bump in (bump..u8::MAX).map(|bump| bump + 1) {
+            require!(create_pda(seeds, higher_bump).is_err(), ErrorCode::WrongPda);
+        }
+    }
+    Ok(())
+}
 
-    check_claim_receipt_is_uninitialized(claim_receipt_account)?;
+/**
+ * Same as `check_pda` for an account found by address in `remaining_accounts`.
+ */
+fn find_pda<'a, 'info>(
+    remaining_accounts: &'a [AccountInfo<'info>],
+    seeds: &[&[u8]],
+    bump: u8,
+) -> Result<&'a AccountInfo<'info>> {
+    let pubkey = create_pda(seeds, bump)?;
+    let account = remaining_accounts
+        .iter()
+        .find(|account| account.key == &pubkey)
+        .ok_or(anchor_lang::error::ErrorCode::AccountNotEnoughKeys)?;
+    check_pda(account, seeds, bump)?;
+    Ok(account)
+}
+
+fn create_pda(seeds: &[&[u8]], bump: u8) -> Result<Pubkey> {
+    let bump_seed = [bump];
+    let mut seeds_with_bump = seeds.to_vec();
+    seeds_with_bump.push(&bump_seed);
+    Pubkey::create_program_address(&seeds_with_bump, &crate::id())
+        .map_err(|_| error!(ErrorCode::WrongPda))
+}
 
-    let account_infos = vec![
-        claim_receipt_account.clone(),
+/**
+ * Creates an account of `space` bytes owned by this program at the PDA of `signer_seeds`, the funder pays the rent.
+ * The account may already hold lamports, so it can't be created with `system_instruction::create_account`.
+ */
+fn create_program_account<'info>(
+    account: &AccountInfo<'info>,
+    space: usize,
+    signer_seeds: &[&[u8]],
+    funder: &AccountInfo<'info>,
+    system_program: &AccountInfo<'info>,
+) -> Result<()> {
+    let account_infos = [
+        account.clone(),
         funder.to_account_info(),
         system_program.to_account_info(),
     ];
-    // Pay rent for the receipt account
+    // Pay rent for the account
     let transfer_instruction = system_instruction::transfer(
         &funder.key(),
-        &claim_receipt_account.key(),
+        &account.key(),
         Rent::get()?
-            .minimum_balance(0)
-            .saturating_sub(claim_receipt_account.lamports()),
+            .minimum_balance(space)
+            .saturating_sub(account.lamports()),
     );
     invoke(&transfer_instruction, &account_infos)?;
 
-    // Assign it to the program, this instruction will fail if the account already belongs to the
-    // program
-    let assign_instruction = system_instruction::assign(&claim_receipt_account.key(), &crate::id());
     invoke_signed(
-        &assign_instruction,
+        &system_instruction::allocate(&account.key(), space as u64),
         &account_infos,
-        &[&[
-            RECEIPT_SEED,
-            &MerkleTree::<SolanaHasher>::hash_leaf(leaf),
-            &[bump],
-        ]],
-    )
-    .map_err(|_| ErrorCode::AlreadyClaimed)?;
+        &[signer_seeds],
+    )?;
+    invoke_signed(
+        &system_instruction::assign(&account.key(), &crate::id()),
+        &account_infos,
+        &[signer_seeds],
+    )?;
 
     Ok(())
 }
@@ -594,51 +1867,234 @@ pub fn checked_create_claim_receipt<'info>(
 // Sdk.
 ////////////////////////////////////////////////////////////////////////////////
 
-pub fn get_config_pda() -> (Pubkey, u8) {
-    Pubkey::find_program_address(&[CONFIG_SEED], &crate::id())
+/**
+ * The seed that scopes the config and the claim receipts to a campaign. It is empty for the default campaign,
+ * which keeps the addresses of the accounts created before campaigns were introduced.
+ */
+pub fn get_campaign_seed(campaign_id: u64) -> Vec<u8> {
+    if campaign_id == DEFAULT_CAMPAIGN_ID {
+        vec![]
+    } else {
+        campaign_id.to_le_bytes().to_vec()
+    }
+}
+
+pub fn get_config_pda(campaign_id: u64) -> (Pubkey, u8) {
+    Pubkey::find_program_address(
+        &[CONFIG_SEED, &get_campaign_seed(campaign_id)],
+        &crate::id(),
+    )
+}
+
+/**
+ * The treasury of a campaign in escrow mode, the associated token account of its config.
+ */
+pub fn get_escrow_treasury(campaign_id: u64, mint: &Pubkey, token_program: &Pubkey) -> Pubkey {
+    spl_associated_token_account::get_associated_token_address_with_program_id(
+        &get_config_pda(campaign_id).0,
+        mint,
+        token_program,
+    )
+}
+
+/**
+ * Receipts are keyed by the identity rather than by the leaf, so that the leaves of an identity in successive
+ * trees share the receipt. The hash is the full keccak256 whatever the hasher of the tree.
+ */
+pub fn get_identity_hash(identity: &Identity) -> [u8; HASH_SIZE] {
+    SolanaHasher32::hashv(&[identity.try_to_vec().unwrap()])
+}
+
+pub fn get_claimant_total_pda(campaign_id: u64, claimant: &Pubkey) -> (Pubkey, u8) {
+    Pubkey::find_program_address(
+        &[
+            CLAIMANT_TOTAL_SEED,
+            &get_campaign_seed(campaign_id),
+            claimant.as_ref(),
+        ],
+        &crate::id(),
+    )
 }
 
-pub fn get_receipt_pda(leaf: &[u8]) -> (Pubkey, u8) {
+pub fn get_denylist_pda(campaign_id: u64, denylist_key: &DenylistKey) -> (Pubkey, u8) {
+    Pubkey::find_program_address(
+        &[
+            DENYLIST_SEED,
+            &get_campaign_seed(campaign_id),
+            denylist_key.get_kind_seed(),
+            denylist_key.get_key_seed(),
+        ],
+        &crate::id(),
+    )
+}
+
+pub fn get_receipt_pda(campaign_id: u64, identity: &Identity) -> (Pubkey, u8) {
+    get_receipt_pda_from_identity_hash(campaign_id, &get_identity_hash(identity))
+}
+
+/**
+ * The receipt of a leaf of the default campaign claimed before receipts were keyed by identity. It is an empty
+ * account keyed by the hash of the leaf in the 20-byte tree of the time.
+ */
+pub fn get_legacy_receipt_pda(leaf: &[u8]) -> (Pubkey, u8) {
     Pubkey::find_program_address(
         &[RECEIPT_SEED, &MerkleTree::<SolanaHasher>::hash_leaf(leaf)],
         &crate::id(),
     )
 }
 
+/**
+ * The bumps of the accounts of a claim of `claim_info` by `claimant`, `claim` searches for them if the client doesn't
+ * provide them.
+ */
+pub fn get_claim_bumps(campaign_id: u64, claim_info: &ClaimInfo, claimant: &Pubkey) -> ClaimBumps {
+    let identity_hash = get_identity_hash(&claim_info.identity);
+    ClaimBumps {
+        receipt:           get_receipt_pda_from_identity_hash(campaign_id, &identity_hash).1,
+        claimant_total:    get_claimant_total_pda(campaign_id, claimant).1,
+        denylist_identity: get_denylist_pda(campaign_id, &DenylistKey::Identity { identity_hash })
+            .1,
+        denylist_claimant: get_denylist_pda(
+            campaign_id,
+            &DenylistKey::Claimant { pubkey: *claimant },
+        )
+        .1,
+        legacy_receipt:    get_legacy_receipt_pda(&claim_info.try_to_vec().unwrap()).1,
+    }
+}
+
+pub fn get_receipt_pda_from_identity_hash(
+    campaign_id: u64,
+    identity_hash: &[u8; HASH_SIZE],
+) -> (Pubkey, u8) {
+    Pubkey::find_program_address(
+        &[RECEIPT_SEED, &get_campaign_seed(campaign_id), identity_hash],
+        &crate::id(),
+    )
+}
+
 impl crate::accounts::Initialize {
     pub fn populate(
+        campaign_id: u64,
         payer: Pubkey,
         mint: Pubkey,
         treasury: Pubkey,
         address_lookup_table: Pubkey,
+        token_program: Pubkey,
     ) -> Self {
         crate::accounts::Initialize {
             payer,
-            config: get_config_pda().0,
+            config: get_config_pda(campaign_id).0,
             mint,
             treasury,
             system_program: system_program::System::id(),
             address_lookup_table,
+            token_program,
+            associated_token_program: associated_token::ID,
+            program_data: Pubkey::find_program_address(
+                &[crate::id().as_ref()],
+                &bpf_loader_upgradeable::id(),
+            )
+            .0,
+        }
+    }
+}
+
+impl crate::accounts::Deposit {
+    pub fn populate(
+        campaign_id: u64,
+        depositor: Pubkey,
+        depositor_fund: Pubkey,
+        mint: Pubkey,
+        treasury: Pubkey,
+        token_program: Pubkey,
+    ) -> Self {
+        crate::accounts::Deposit {
+            depositor,
+            depositor_fund,
+            config: get_config_pda(campaign_id).0,
+            mint,
+            treasury,
+            token_program,
+        }
+    }
+}
+
+impl crate::accounts::UpdateConfig {
+    pub fn populate(campaign_id: u64, admin: Pubkey) -> Self {
+        crate::accounts::UpdateConfig {
+            admin,
+            config: get_config_pda(campaign_id).0,
+        }
+    }
+}
+
+impl crate::accounts::MigrateConfig {
+    pub fn populate(campaign_id: u64, authority: Pubkey) -> Self {
+        crate::accounts::MigrateConfig {
+            authority,
+            config: get_config_pda(campaign_id).0,
+            program_data: Pubkey::find_program_address(
+                &[crate::id().as_ref()],
+                &bpf_loader_upgradeable::id(),
+            )
+            .0,
+            system_program: system_program::System::id(),
+        }
+    }
+}
+
+impl crate::accounts::MigrateReceipt {
+    pub fn populate(payer: Pubkey, claim_info: &ClaimInfo) -> Self {
+        crate::accounts::MigrateReceipt {
+            payer,
+            legacy_receipt: get_legacy_receipt_pda(&claim_info.try_to_vec().unwrap()).0,
+            receipt: get_receipt_pda(DEFAULT_CAMPAIGN_ID, &claim_info.identity).0,
+            config: get_config_pda(DEFAULT_CAMPAIGN_ID).0,
+            system_program: system_program::System::id(),
+        }
+    }
+}
+
+impl crate::accounts::Block {
+    pub fn populate(campaign_id: u64, admin: Pubkey, denylist_key: &DenylistKey) -> Self {
+        crate::accounts::Block {
+            admin,
+            config: get_config_pda(campaign_id).0,
+            denylist_entry: get_denylist_pda(campaign_id, denylist_key).0,
+            system_program: system_program::System::id(),
+        }
+    }
+}
+
+impl crate::accounts::Unblock {
+    pub fn populate(campaign_id: u64, admin: Pubkey, denylist_key: &DenylistKey) -> Self {
+        crate::accounts::Unblock {
+            admin,
+            config: get_config_pda(campaign_id).0,
+            denylist_entry: get_denylist_pda(campaign_id, denylist_key).0,
         }
     }
 }
 
 impl crate::accounts::Claim {
     pub fn populate(
+        campaign_id: u64,
         funder: Pubkey,
         claimant: Pubkey,
         mint: Pubkey,
         claimant_fund: Pubkey,
         treasury: Pubkey,
+        token_program: Pubkey,
     ) -> Self {
         crate::accounts::Claim {
             funder,
             claimant,
             claimant_fund,
-            config: get_config_pda().0,
+            config: get_config_pda(campaign_id).0,
             mint,
             treasury,
-            token_program: spl_token::id(),
+            token_program,
             system_program: system_program::System::id(),
             sysvar_instruction: SYSVAR_IX_ID,
             associated_token_program: associated_token::ID,
@@ -656,4 +2112,29 @@ pub struct ClaimEvent {
     pub remaining_balance: u64,
     pub claimant:          Pubkey,
     pub claim_info:        ClaimInfo,
+    pub amount_received:   u64, // Amount received by the claimant fund, net of the transfer fee of the mint if any
+}
+
+/**
+ * Emitted by `claim` after `ClaimEvent`. All the fields have a fixed size and the hashes are the ones
+ * of the tree and of the receipt seed, so that indexers can join claims with receipts and trees without
+ * deserializing the identity. `campaign_id` lets indexers split the logs of the program by campaign.
+ */
+#[event]
+#[derive(Clone, Debug, PartialEq)]
+pub struct ClaimEventV2 {
+    pub campaign_id:        u64,
+    pub claimant:           Pubkey,
+    pub claimant_fund:      Pubkey,
+    pub ecosystem:          u8, // `Identity::get_variant_index` of the identity
+    pub identity_hash:      [u8; HASH_SIZE], // Seed of the receipt, see `get_identity_hash`
+    pub leaf_hash:          [u8; HASH_SIZE], // Hash of the leaf in the tree of `merkle_root`
+    pub receipt:            Pubkey,
+    pub merkle_root:        [u8; HASH_SIZE], // Zero-padded like `leaf_hash`
+    pub amount:             u64,             // Cumulative amount of the leaf
+    pub amount_transferred: u64,             // Amount that left the treasury
+    pub amount_received:    u64, // Amount received by the claimant fund, net of the transfer fee
+    pub remaining_balance:  u64,
+    pub slot:               u64,
+    pub unix_timestamp:     i64,
 }
@@ -1,23 +1,40 @@
 use {
-    super::test_happy_path::TestClaimCertificate,
+    super::{
+        test_happy_path::TestClaimCertificate,
+        test_passkey::process_secp256r1_instruction,
+    },
     crate::{
         accounts,
+        ecosystems::secp256r1::SECP256R1_ID,
+        get_claim_bumps,
+        get_claimant_total_pda,
         get_config_pda,
+        get_denylist_pda,
+        get_escrow_treasury,
+        get_identity_hash,
+        get_legacy_receipt_pda,
         get_receipt_pda,
         instruction,
         tests::merkleize,
         ClaimInfo,
+        DenylistKey,
+        DispenserMerklePath,
+        DispenserMerkleRoot,
         ErrorCode,
         SolanaHasher,
+        DEFAULT_CAMPAIGN_ID,
+        IDENTITY_VARIANTS,
     },
     anchor_lang::{
         prelude::{
             AccountMeta,
+            Clock,
             ProgramError,
             Pubkey,
             Rent,
         },
         solana_program::{
+            bpf_loader_upgradeable,
             hash,
             instruction::Instruction,
             program_option::COption,
@@ -34,29 +51,13 @@ use {
         InstructionData,
         ToAccountMetas,
     },
-    anchor_spl::{
-        associated_token::get_associated_token_address,
-        token::{
-            spl_token,
-            spl_token::{
-                error::TokenError,
-                instruction::{
-                    initialize_account3,
-                    initialize_mint2,
-                    mint_to,
-                },
-            },
-            Mint,
-            Token,
-            TokenAccount,
-        },
-    },
-    pythnet_sdk::accumulators::merkle::{
-        MerklePath,
-        MerkleRoot,
-        MerkleTree,
+    anchor_spl::token::{
+        spl_token,
+        spl_token::error::TokenError,
     },
+    pythnet_sdk::accumulators::merkle::MerkleTree,
     solana_program_test::{
+        processor,
         BanksClient,
         BanksClientError,
         ProgramTest,
@@ -76,6 +77,23 @@ use {
             TransactionError,
         },
     },
+    spl_associated_token_account::get_associated_token_address_with_program_id,
+    spl_token_2022::{
+        extension::{
+            transfer_fee::instruction::initialize_transfer_fee_config,
+            ExtensionType,
+            StateWithExtensions,
+        },
+        instruction::{
+            initialize_account3,
+            initialize_mint2,
+            mint_to,
+        },
+        state::{
+            Account as TokenAccount,
+            Mint,
+        },
+    },
 };
 
 pub struct DispenserSimulator {
@@ -86,11 +104,79 @@ pub struct DispenserSimulator {
     /// also the owner/authority of `pyth_treasury`
     pub pyth_mint_authority: Keypair,
     pub pyth_treasury:       Pubkey,
+    /// Upgrade authority of the program in its `ProgramData`, funded so that it can initialize campaigns
+    pub upgrade_authority:   Keypair,
+    /// The campaign that `initialize` and `claim` target
+    pub campaign_id:         u64,
+    /// The owner of the mint and of the token accounts, either the token program or token-2022
+    pub token_program:       Pubkey,
+    /// Transfer fee of the mint in basis points, only for token-2022 mints
+    pub transfer_fee_bps:    Option<u16>,
+    /// Whether `initialize` creates an escrow treasury that `setup_treasury` funds with `deposit`
+    pub escrow:              bool,
+    /// The total amount that claims of the campaign can't exceed, set by `initialize`
+    pub global_cap:          Option<u64>,
+    /// The total amount a claimant can receive across all their identities, set by `initialize`
+    pub max_per_claimant:    Option<u64>,
+    /// Co-signs the claims of the ecosystems whose guard signature is required by the config
+    pub guard_cosigner:      Option<Keypair>,
+    /// Log messages of the successful transactions, in order
+    pub logs:                Vec<String>,
+    /// Compute units consumed by the successful transactions, in order
+    pub compute_units:       Vec<u64>,
 }
 
 impl DispenserSimulator {
     pub async fn new() -> Self {
-        let program_test = ProgramTest::new("token_dispenser", crate::id(), None);
+        DispenserSimulator::new_with_token_program(spl_token::id(), None, vec![]).await
+    }
+
+    /**
+     * Same as `new` but the ledger starts with `accounts`, e.g. accounts created by a previous version of the program.
+     */
+    pub async fn new_with_accounts(accounts: Vec<(Pubkey, Account)>) -> Self {
+        DispenserSimulator::new_with_token_program(spl_token::id(), None, accounts).await
+    }
+
+    /**
+     * Same as `new` but the mint and the treasury belong to token-2022. If `transfer_fee_bps` is provided,
+     * the mint has the transfer fee extension and withholds this fee (uncapped) on every transfer.
+     */
+    pub async fn new_token_2022(transfer_fee_bps: Option<u16>) -> Self {
+        DispenserSimulator::new_with_token_program(spl_token_2022::id(), transfer_fee_bps, vec![])
+            .await
+    }
+
+    async fn new_with_token_program(
+        token_program: Pubkey,
+        transfer_fee_bps: Option<u16>,
+        accounts: Vec<(Pubkey, Account)>,
+    ) -> Self {
+        let mut program_test = ProgramTest::new("token_dispenser", crate::id(), None);
+        program_test.add_program(
+            "secp256r1",
+            SECP256R1_ID,
+            processor!(process_secp256r1_instruction),
+        );
+        let upgrade_authority = Keypair::new();
+        let (program_data, program_data_account) =
+            get_program_data_account(&upgrade_authority.pubkey());
+        if !accounts.iter().any(|(pubkey, _)| *pubkey == program_data) {
+            program_test.add_account(program_data, program_data_account);
+        }
+        program_test.add_account(
+            upgrade_authority.pubkey(),
+            Account {
+                lamports:   1_000_000_000,
+                data:       vec![],
+                owner:      system_program::ID,
+                executable: false,
+                rent_epoch: 0,
+            },
+        );
+        for (pubkey, account) in accounts {
+            program_test.add_account(pubkey, account);
+        }
         let (banks_client, genesis_keypair, recent_blockhash) = program_test.start().await;
         let mint_keypair = Keypair::new();
         let pyth_mint_authority = Keypair::new();
@@ -102,6 +188,16 @@ impl DispenserSimulator {
             mint_keypair,
             pyth_mint_authority,
             pyth_treasury: pyth_treasury.pubkey(),
+            upgrade_authority,
+            campaign_id: DEFAULT_CAMPAIGN_ID,
+            token_program,
+            transfer_fee_bps,
+            escrow: false,
+            global_cap: None,
+            max_per_claimant: None,
+            guard_cosigner: None,
+            logs: vec![],
+            compute_units: vec![],
         };
 
         simulator
@@ -133,11 +229,16 @@ impl DispenserSimulator {
         vec![
             TestClaimCertificate::random_evm(claimant),
             TestClaimCertificate::random_cosmos(claimant),
+            TestClaimCertificate::random_cosmos_sign_doc(claimant),
             TestClaimCertificate::random_discord(claimant, &keypair),
             TestClaimCertificate::random_aptos(claimant),
             TestClaimCertificate::random_sui(claimant),
             TestClaimCertificate::random_solana(claimant),
             TestClaimCertificate::random_injective(claimant),
+            TestClaimCertificate::random_injective_adr036(claimant),
+            TestClaimCertificate::random_starknet(claimant, &keypair),
+            TestClaimCertificate::random_cardano(claimant),
+            TestClaimCertificate::random_passkey(claimant),
         ]
     }
 
@@ -146,6 +247,10 @@ impl DispenserSimulator {
         self.banks_client.get_rent().await.unwrap()
     }
 
+    pub async fn get_clock(&mut self) -> Clock {
+        self.banks_client.get_sysvar::<Clock>().await.unwrap()
+    }
+
     pub async fn airdrop(&mut self, target: Pubkey, amount: u64) -> Result<(), BanksClientError> {
         let airdrop_ix =
             system_instruction::transfer(&self.genesis_keypair.pubkey(), &target, amount);
@@ -158,26 +263,59 @@ impl DispenserSimulator {
         mint_authority: &Pubkey,
         decimals: u8,
     ) -> Result<(), BanksClientError> {
-        let space = Mint::LEN;
+        let space = ExtensionType::get_account_len::<Mint>(&self.get_mint_extensions());
         let rent = &self.get_rent().await;
-        let init_mint_ixs = &[
-            create_account(
-                &self.genesis_keypair.pubkey(),
-                &mint_keypair.pubkey(),
-                rent.minimum_balance(space),
-                space as u64,
-                &Token::id(),
-            ),
+        let mut init_mint_ixs = vec![create_account(
+            &self.genesis_keypair.pubkey(),
+            &mint_keypair.pubkey(),
+            rent.minimum_balance(space),
+            space as u64,
+            &self.token_program,
+        )];
+        if let Some(transfer_fee_bps) = self.transfer_fee_bps {
+            init_mint_ixs.push(
+                initialize_transfer_fee_config(
+                    &self.token_program,
+                    &mint_keypair.pubkey(),
+                    Some(mint_authority),
+                    Some(mint_authority),
+                    transfer_fee_bps,
+                    u64::MAX,
+                )
+                .unwrap(),
+            );
+        }
+        init_mint_ixs.push(
             initialize_mint2(
-                &Token::id(),
+                &self.token_program,
                 &mint_keypair.pubkey(),
                 mint_authority,
                 None,
                 decimals,
             )
             .unwrap(),
-        ];
-        self.process_ix(init_mint_ixs, &vec![mint_keypair]).await
+        );
+        self.process_ix(&init_mint_ixs, &vec![mint_keypair]).await
+    }
+
+    fn get_mint_extensions(&self) -> Vec<ExtensionType> {
+        match self.transfer_fee_bps {
+            Some(_) => vec![ExtensionType::TransferFeeConfig],
+            None => vec![],
+        }
+    }
+
+    /// The treasury that `initialize` uses by default
+    pub fn get_treasury(&self) -> Pubkey {
+        if self.escrow {
+            get_escrow_treasury(
+                self.campaign_id,
+                &self.mint_keypair.pubkey(),
+                &self.token_program,
+            )
+        } else {
+            self.pyth_treasury
+        }
     }
 
     pub async fn setup_treasury(&mut self, mint_amount: u64) -> Result<(), BanksClientError> {
@@ -186,14 +324,20 @@ impl DispenserSimulator {
             .await
             .unwrap();
 
-        self.approve_treasury_delegate(get_config_pda().0, mint_amount)
+        if self.escrow {
+            // `pyth_treasury` is only the source of the deposit
+            self.deposit(mint_amount).await.unwrap();
+            return Ok(());
+        }
+
+        self.approve_treasury_delegate(get_config_pda(self.campaign_id).0, mint_amount)
             .await
             .unwrap();
 
         self.verify_token_account_data(
             self.pyth_treasury,
             mint_amount,
-            COption::Some(get_config_pda().0),
+            COption::Some(get_config_pda(self.campaign_id).0),
             mint_amount,
         )
         .await
@@ -201,9 +345,145 @@ impl DispenserSimulator {
         Ok(())
     }
 
+    /// Deposits `amount` from `pyth_treasury` into the escrow treasury
+    pub async fn deposit(&mut self, amount: u64) -> Result<(), BanksClientError> {
+        let accounts = accounts::Deposit::populate(
+            self.campaign_id,
+            self.pyth_mint_authority.pubkey(),
+            self.pyth_treasury,
+            self.mint_keypair.pubkey(),
+            self.get_treasury(),
+            self.token_program,
+        )
+        .to_account_metas(None);
+        let instruction_data = instruction::Deposit {
+            _campaign_id: self.campaign_id,
+            amount,
+        };
+        let instruction =
+            Instruction::new_with_bytes(crate::id(), &instruction_data.data(), accounts);
+        self.process_ix(
+            &[instruction],
+            &vec![&copy_keypair(&self.pyth_mint_authority)],
+        )
+        .await
+    }
+
+    pub async fn set_rate_limit(
+        &mut self,
+        admin: &Keypair,
+        max_amount: Option<u64>,
+        window_seconds: i64,
+    ) -> Result<(), BanksClientError> {
+        let accounts = accounts::UpdateConfig::populate(self.campaign_id, admin.pubkey())
+            .to_account_metas(None);
+        let instruction_data = instruction::SetRateLimit {
+            _campaign_id: self.campaign_id,
+            max_amount,
+            window_seconds,
+        };
+        let instruction =
+            Instruction::new_with_bytes(crate::id(), &instruction_data.data(), accounts);
+        self.process_ix(&[instruction], &vec![admin]).await
+    }
+
+    pub async fn set_guard_signature_policy(
+        &mut self,
+        admin: &Keypair,
+        guard_policy: u16,
+    ) -> Result<(), BanksClientError> {
+        let accounts = accounts::UpdateConfig::populate(self.campaign_id, admin.pubkey())
+            .to_account_metas(None);
+        let instruction_data = instruction::SetGuardSignaturePolicy {
+            _campaign_id: self.campaign_id,
+            guard_policy,
+        };
+        let instruction =
+            Instruction::new_with_bytes(crate::id(), &instruction_data.data(), accounts);
+        self.process_ix(&[instruction], &vec![admin]).await
+    }
+
+    pub async fn update_merkle_root(
+        &mut self,
+        admin: &Keypair,
+        merkle_root: impl Into<DispenserMerkleRoot>,
+    ) -> Result<(), BanksClientError> {
+        let accounts = accounts::UpdateConfig::populate(self.campaign_id, admin.pubkey())
+            .to_account_metas(None);
+        let instruction_data = instruction::UpdateMerkleRoot {
+            _campaign_id: self.campaign_id,
+            merkle_root:  merkle_root.into(),
+        };
+        let instruction =
+            Instruction::new_with_bytes(crate::id(), &instruction_data.data(), accounts);
+        self.process_ix(&[instruction], &vec![admin]).await
+    }
+
+    pub async fn migrate_receipt(&mut self, claim_info: ClaimInfo) -> Result<(), BanksClientError> {
+        let accounts =
+            accounts::MigrateReceipt::populate(self.genesis_keypair.pubkey(), &claim_info)
+                .to_account_metas(None);
+        let instruction_data = instruction::MigrateReceipt { claim_info };
+        let instruction =
+            Instruction::new_with_bytes(crate::id(), &instruction_data.data(), accounts);
+        self.process_ix(&[instruction], &vec![]).await
+    }
+
+    pub async fn migrate_config(
+        &mut self,
+        authority: &Keypair,
+        claimed_per_identity: [u64; IDENTITY_VARIANTS],
+        claim_count: u64,
+        global_cap: Option<u64>,
+    ) -> Result<(), BanksClientError> {
+        let accounts = accounts::MigrateConfig::populate(self.campaign_id, authority.pubkey())
+            .to_account_metas(None);
+        let instruction_data = instruction::MigrateConfig {
+            _campaign_id: self.campaign_id,
+            claimed_per_identity,
+            claim_count,
+            global_cap,
+        };
+        let instruction =
+            Instruction::new_with_bytes(crate::id(), &instruction_data.data(), accounts);
+        self.process_ix(&[instruction], &vec![authority]).await
+    }
+
+    pub async fn block(
+        &mut self,
+        admin: &Keypair,
+        denylist_key: DenylistKey,
+    ) -> Result<(), BanksClientError> {
+        let accounts = accounts::Block::populate(self.campaign_id, admin.pubkey(), &denylist_key)
+            .to_account_metas(None);
+        let instruction_data = instruction::Block {
+            _campaign_id:  self.campaign_id,
+            _denylist_key: denylist_key,
+        };
+        let instruction =
+            Instruction::new_with_bytes(crate::id(), &instruction_data.data(), accounts);
+        self.process_ix(&[instruction], &vec![admin]).await
+    }
+
+    pub async fn unblock(
+        &mut self,
+        admin: &Keypair,
+        denylist_key: DenylistKey,
+    ) -> Result<(), BanksClientError> {
+        let accounts = accounts::Unblock::populate(self.campaign_id, admin.pubkey(), &denylist_key)
+            .to_account_metas(None);
+        let instruction_data = instruction::Unblock {
+            _campaign_id:  self.campaign_id,
+            _denylist_key: denylist_key,
+        };
+        let instruction =
+            Instruction::new_with_bytes(crate::id(), &instruction_data.data(), accounts);
+        self.process_ix(&[instruction], &vec![admin]).await
+    }
+
     pub async fn mint_to_treasury(&mut self, mint_amount: u64) -> Result<(), BanksClientError> {
         let mint_to_ix = &[mint_to(
-            &Token::id(),
+            &self.token_program,
             &self.mint_keypair.pubkey(),
             &self.pyth_treasury,
             &self.pyth_mint_authority.pubkey(),
@@ -220,6 +500,38 @@ impl DispenserSimulator {
         instructions: &[Instruction],
         signers: &Vec<&Keypair>,
     ) -> Result<(), BanksClientError> {
+        let transaction = self.sign_transaction(instructions, signers).await;
+        let result = self
+            .banks_client
+            .process_transaction_with_metadata(transaction)
+            .await?;
+        result.result?;
+        if let Some(metadata) = result.metadata {
+            self.logs.extend(metadata.log_messages);
+            self.compute_units.push(metadata.compute_units_consumed);
+        }
+        Ok(())
+    }
+
+    /**
+     * Same as `process_ix` but the transaction isn't committed, returns the compute units it consumed.
+     */
+    pub async fn simulate_ix(
+        &mut self,
+        instructions: &[Instruction],
+        signers: &Vec<&Keypair>,
+    ) -> Result<u64, BanksClientError> {
+        let transaction = self.sign_transaction(instructions, signers).await;
+        let simulation = self.banks_client.simulate_transaction(transaction).await?;
+        simulation.result.unwrap()?;
+        Ok(simulation.simulation_details.unwrap().units_consumed)
+    }
+
+    async fn sign_transaction(
+        &mut self,
+        instructions: &[Instruction],
+        signers: &Vec<&Keypair>,
+    ) -> Transaction {
         let mut transaction =
             Transaction::new_with_payer(instructions, Some(&self.genesis_keypair.pubkey()));
 
@@ -232,7 +544,7 @@ impl DispenserSimulator {
 
         transaction.partial_sign(&[&self.genesis_keypair], self.recent_blockhash);
         transaction.partial_sign(signers, self.recent_blockhash);
-        self.banks_client.process_transaction(transaction).await
+        transaction
     }
 
 
@@ -258,10 +570,10 @@ impl DispenserSimulator {
             self.genesis_keypair.pubkey(),
             Some(self.genesis_keypair.pubkey()),
             vec![
-                get_config_pda().0,
-                self.pyth_treasury,
+                get_config_pda(self.campaign_id).0,
+                self.get_treasury(),
                 self.mint_keypair.pubkey(),
-                spl_token::id(),
+                self.token_program,
                 system_program::System::id(),
                 SYSVAR_IX_ID,
                 spl_associated_token_account::id(),
@@ -274,9 +586,44 @@ impl DispenserSimulator {
         Ok(address_lookup_table)
     }
 
+    /**
+     * Initializes the campaign of the simulator. The genesis keypair pays for the default campaign and the upgrade
+     * authority of the program for the other campaigns, see `initialize_with_payer`.
+     */
     pub async fn initialize(
         &mut self,
-        merkle_root: MerkleRoot<SolanaHasher>,
+        merkle_root: impl Into<DispenserMerkleRoot>,
+        dispenser_guard: Pubkey,
+        address_lookup_table: Pubkey,
+        mint_pubkey_override: Option<Pubkey>,
+        treasury_pubkey_override: Option<Pubkey>,
+        max_transfer_override: Option<u64>,
+    ) -> Result<(), BanksClientError> {
+        let payer = if self.campaign_id == DEFAULT_CAMPAIGN_ID {
+            copy_keypair(&self.genesis_keypair)
+        } else {
+            copy_keypair(&self.upgrade_authority)
+        };
+        self.initialize_with_payer(
+            &payer,
+            merkle_root,
+            dispenser_guard,
+            address_lookup_table,
+            mint_pubkey_override,
+            treasury_pubkey_override,
+            max_transfer_override,
+        )
+        .await
+    }
+
+    /**
+     * Same as `initialize` but `payer` pays for the config and becomes the admin of the campaign.
+     */
+    #[allow(clippy::too_many_arguments)]
+    pub async fn initialize_with_payer(
+        &mut self,
+        payer: &Keypair,
+        merkle_root: impl Into<DispenserMerkleRoot>,
         dispenser_guard: Pubkey,
         address_lookup_table: Pubkey,
         mint_pubkey_override: Option<Pubkey>,
@@ -284,21 +631,27 @@ impl DispenserSimulator {
         max_transfer_override: Option<u64>,
     ) -> Result<(), BanksClientError> {
         let accounts = accounts::Initialize::populate(
-            self.genesis_keypair.pubkey(),
+            self.campaign_id,
+            payer.pubkey(),
             mint_pubkey_override.unwrap_or(self.mint_keypair.pubkey()),
-            treasury_pubkey_override.unwrap_or(self.pyth_treasury),
+            treasury_pubkey_override.unwrap_or(self.get_treasury()),
             address_lookup_table,
+            self.token_program,
         )
         .to_account_metas(None);
         let instruction_data = instruction::Initialize {
-            merkle_root,
+            campaign_id: self.campaign_id,
+            merkle_root: merkle_root.into(),
             dispenser_guard,
             funder: self.genesis_keypair.pubkey(),
             max_transfer: max_transfer_override.unwrap_or(u64::MAX),
+            escrow: self.escrow,
+            global_cap: self.global_cap,
+            max_per_claimant: self.max_per_claimant,
         };
         let instruction =
             Instruction::new_with_bytes(crate::id(), &instruction_data.data(), accounts);
-        self.process_ix(&[instruction], &vec![]).await
+        self.process_ix(&[instruction], &vec![payer]).await
     }
 
 
@@ -369,8 +722,8 @@ impl DispenserSimulator {
         delegate: Pubkey,
         amount: u64,
     ) -> Result<(), BanksClientError> {
-        let approve_ix = spl_token::instruction::approve(
-            &Token::id(),
+        let approve_ix = spl_token_2022::instruction::approve(
+            &self.token_program,
             &self.pyth_treasury,
             &delegate,
             &self.pyth_mint_authority.pubkey(),
@@ -387,8 +740,8 @@ impl DispenserSimulator {
     }
 
     pub async fn revoke_treasury_delegate(&mut self) -> Result<(), BanksClientError> {
-        let revoke_ix = spl_token::instruction::revoke(
-            &Token::id(),
+        let revoke_ix = spl_token_2022::instruction::revoke(
+            &self.token_program,
             &self.pyth_treasury,
             &self.pyth_mint_authority.pubkey(),
             &[],
@@ -412,40 +765,135 @@ impl DispenserSimulator {
         off_chain_claim_certificate: &TestClaimCertificate,
         merkle_tree: &MerkleTree<SolanaHasher>,
         claimant_fund: Option<Pubkey>,
-        merkle_proof_override: Option<MerklePath<SolanaHasher>>,
+        merkle_proof_override: Option<DispenserMerklePath>,
         claim_receipt_override: Option<Pubkey>,
     ) -> Result<(), BanksClientError> {
+        let (instructions, guard_cosigner) = self
+            .get_claim_instructions(
+                claimant,
+                off_chain_claim_certificate,
+                merkle_tree,
+                claimant_fund,
+                merkle_proof_override,
+                claim_receipt_override,
+                true,
+            )
+            .await;
+        let mut signers = vec![claimant];
+        signers.extend(guard_cosigner.as_ref());
+        self.process_ix(&instructions, &signers).await
+    }
+
+    /**
+     * Simulates the claim without the bumps of its accounts, so that the program searches for them, and returns the
+     * compute units it consumed.
+     */
+    pub async fn simulate_claim_without_bumps(
+        &mut self,
+        claimant: &Keypair,
+        off_chain_claim_certificate: &TestClaimCertificate,
+        merkle_tree: &MerkleTree<SolanaHasher>,
+    ) -> Result<u64, BanksClientError> {
+        let (instructions, guard_cosigner) = self
+            .get_claim_instructions(
+                claimant,
+                off_chain_claim_certificate,
+                merkle_tree,
+                None,
+                None,
+                None,
+                false,
+            )
+            .await;
+        let mut signers = vec![claimant];
+        signers.extend(guard_cosigner.as_ref());
+        self.simulate_ix(&instructions, &signers).await
+    }
+
+    /**
+     * The instructions of the claim and the guard co-signer that must sign them, if any.
+     */
+    #[allow(clippy::too_many_arguments)]
+    async fn get_claim_instructions(
+        &mut self,
+        claimant: &Keypair,
+        off_chain_claim_certificate: &TestClaimCertificate,
+        merkle_tree: &MerkleTree<SolanaHasher>,
+        claimant_fund: Option<Pubkey>,
+        merkle_proof_override: Option<DispenserMerklePath>,
+        claim_receipt_override: Option<Pubkey>,
+        with_bumps: bool,
+    ) -> (Vec<Instruction>, Option<Keypair>) {
         let (claim_certificate, option_instruction) =
             off_chain_claim_certificate.as_claim_certificate(merkle_tree, 0, merkle_proof_override);
         let config = self
-            .get_account_data::<crate::Config>(get_config_pda().0)
+            .get_account_data::<crate::Config>(get_config_pda(self.campaign_id).0)
             .await
             .unwrap();
         let mut accounts = accounts::Claim::populate(
+            self.campaign_id,
             self.genesis_keypair.pubkey(),
             claimant.pubkey(),
             config.mint,
-            claimant_fund
-                .unwrap_or_else(|| get_associated_token_address(&claimant.pubkey(), &config.mint)),
+            claimant_fund.unwrap_or_else(|| {
+                get_associated_token_address_with_program_id(
+                    &claimant.pubkey(),
+                    &config.mint,
+                    &self.token_program,
+                )
+            }),
             config.treasury,
+            self.token_program,
         )
         .to_account_metas(None);
 
+        let claim_info = ClaimInfo::from(off_chain_claim_certificate.clone());
         accounts.push(AccountMeta::new(
-            claim_receipt_override.unwrap_or(
-                get_receipt_pda(
-                    &<TestClaimCertificate as Into<ClaimInfo>>::into(
-                        off_chain_claim_certificate.clone(),
-                    )
-                    .try_to_vec()?,
-                )
-                .0,
-            ),
+            claim_receipt_override
+                .unwrap_or_else(|| get_receipt_pda(self.campaign_id, &claim_info.identity).0),
             false,
         ));
+        if config.max_per_claimant.is_some() {
+            accounts.push(AccountMeta::new(
+                get_claimant_total_pda(self.campaign_id, &claimant.pubkey()).0,
+                false,
+            ));
+        }
+        let denylist_keys = [
+            DenylistKey::Identity {
+                identity_hash: get_identity_hash(&claim_info.identity),
+            },
+            DenylistKey::Claimant {
+                pubkey: claimant.pubkey(),
+            },
+        ];
+        for denylist_key in denylist_keys.iter() {
+            accounts.push(AccountMeta::new_readonly(
+                get_denylist_pda(self.campaign_id, denylist_key).0,
+                false,
+            ));
+        }
+        let guard_cosigner = self
+            .guard_cosigner
+            .as_ref()
+            .filter(|_| config.requires_guard_signature(&claim_info.identity))
+            .map(copy_keypair);
+        if let Some(guard_cosigner) = guard_cosigner.as_ref() {
+            accounts.push(AccountMeta::new_readonly(guard_cosigner.pubkey(), true));
+        }
+        if self.campaign_id == DEFAULT_CAMPAIGN_ID {
+            accounts.push(AccountMeta::new_readonly(
+                get_legacy_receipt_pda(&claim_info.try_to_vec().unwrap()).0,
+                false,
+            ));
+        }
 
-
-        let instruction_data: instruction::Claim = instruction::Claim { claim_certificate };
+        let instruction_data: instruction::Claim = instruction::Claim {
+            campaign_id: self.campaign_id,
+            claim_certificate,
+            bumps: with_bumps
+                .then(|| get_claim_bumps(self.campaign_id, &claim_info, &claimant.pubkey())),
+        };
 
         let mut instructions = vec![];
 
@@ -459,8 +907,7 @@ impl DispenserSimulator {
             accounts,
         ));
 
-
-        self.process_ix(&instructions, &vec![claimant]).await
+        (instructions, guard_cosigner)
     }
 
     pub async fn get_account(&mut self, key: Pubkey) -> Option<Account> {
@@ -473,22 +920,34 @@ impl DispenserSimulator {
             .and_then(|a| <T>::try_deserialize(&mut a.data()).ok())
     }
 
+    /// Token accounts of token-2022 can have extensions, so they are unpacked with `StateWithExtensions`
+    pub async fn get_token_account(&mut self, key: Pubkey) -> Option<TokenAccount> {
+        self.get_account(key).await.and_then(|a| {
+            StateWithExtensions::<TokenAccount>::unpack(a.data())
+                .ok()
+                .map(|state| state.base)
+        })
+    }
+
     pub async fn create_token_account(
         &mut self,
         mint: Pubkey,
         owner: &Keypair,
         token_account: &Keypair,
     ) -> Result<(), BanksClientError> {
+        let space = ExtensionType::get_account_len::<TokenAccount>(
+            &ExtensionType::get_required_init_account_extensions(&self.get_mint_extensions()),
+        );
         let init_token_account_ixs = &[
             create_account(
                 &self.genesis_keypair.pubkey(),
                 &token_account.pubkey(),
-                self.get_rent().await.minimum_balance(TokenAccount::LEN),
-                TokenAccount::LEN as u64,
-                &Token::id(),
+                self.get_rent().await.minimum_balance(space),
+                space as u64,
+                &self.token_program,
             ),
             initialize_account3(
-                &Token::id(),
+                &self.token_program,
                 &token_account.pubkey(),
                 &mint,
                 &owner.pubkey(),
@@ -509,7 +968,7 @@ impl DispenserSimulator {
                 &self.genesis_keypair.pubkey(),
                 owner,
                 mint,
-                &spl_token::id(),
+                &self.token_program,
             );
         self.process_ix(&[create_associated_token_account_ix], &vec![])
             .await
@@ -522,10 +981,7 @@ impl DispenserSimulator {
         expected_delegate: COption<Pubkey>,
         expected_delegated_amount: u64,
     ) -> Result<(), BanksClientError> {
-        let token_account_data = self
-            .get_account_data::<TokenAccount>(token_account)
-            .await
-            .unwrap();
+        let token_account_data = self.get_token_account(token_account).await.unwrap();
         assert_eq!(token_account_data.amount, expected_amount);
         assert_eq!(token_account_data.delegate, expected_delegate);
         assert_eq!(
@@ -536,6 +992,28 @@ impl DispenserSimulator {
     }
 }
 
+/**
+ * The `ProgramData` account of the program, as created by the upgradeable loader when the program is deployed.
+ */
+pub fn get_program_data_account(upgrade_authority: &Pubkey) -> (Pubkey, Account) {
+    // Bincode serialization of `UpgradeableLoaderState::ProgramData`, without the program bytes
+    let mut data = 3u32.to_le_bytes().to_vec();
+    data.extend_from_slice(&0u64.to_le_bytes());
+    data.push(1);
+    data.extend_from_slice(upgrade_authority.as_ref());
+
+    (
+        Pubkey::find_program_address(&[crate::id().as_ref()], &bpf_loader_upgradeable::id()).0,
+        Account {
+            lamports: Rent::default().minimum_balance(data.len()),
+            data,
+            owner: bpf_loader_upgradeable::id(),
+            executable: false,
+            rent_epoch: 0,
+        },
+    )
+}
+
 pub fn copy_keypair(keypair: &Keypair) -> Keypair {
     Keypair::from_bytes(&keypair.to_bytes()).unwrap()
 }
@@ -9,15 +9,34 @@ use {
 
 mod dispenser_simulator;
 mod test_aptos;
+mod test_campaign;
+mod test_cardano;
 mod test_claim;
+mod test_claimant_cap;
+mod test_client;
+mod test_compute_budget;
 mod test_cosmos;
+mod test_denylist;
 mod test_discord;
 mod test_ed25519;
+mod test_escrow;
+mod test_events;
+mod test_expiry;
+mod test_guard_signature;
 mod test_happy_path;
+mod test_indexer;
 mod test_initialize;
+mod test_injective;
 mod test_merkle_tree;
+mod test_migrate_config;
+mod test_passkey;
+mod test_rate_limit;
 mod test_secp256k1;
+mod test_simulate;
 mod test_solana;
+mod test_stats;
+mod test_token_2022;
+mod test_top_up;
 
 /// Merkleizes a vector of `ClaimInfo`s and returns the `MerkleTree` and the serialized `ClaimInfo`s.
 pub fn merkleize(merkle_items: Vec<ClaimInfo>) -> (MerkleTree<SolanaHasher>, Vec<Vec<u8>>) {
@@ -1,8 +1,18 @@
 use {
-    crate::ecosystems::{
-        aptos::AptosMessage,
-        ed25519::Ed25519TestMessage,
-        get_expected_payload,
+    crate::{
+        ecosystems::{
+            aptos::{
+                AptosMessage,
+                APTOS_PREFIX,
+                APTOS_SUFFIX,
+            },
+            check_payload,
+            ed25519::Ed25519TestMessage,
+            get_expected_payload,
+            get_expected_payload_v2,
+            Authorization,
+        },
+        DEFAULT_CAMPAIGN_ID,
     },
     anchor_lang::prelude::Pubkey,
     solana_program_test::tokio,
@@ -15,6 +25,17 @@ pub async fn test_aptos_message() {
         AptosMessage::parse(&AptosMessage::for_claimant(&claimant).get_message_with_metadata())
             .unwrap()
             .get_payload(),
-        get_expected_payload(&claimant).as_bytes()
+        get_expected_payload(DEFAULT_CAMPAIGN_ID, &claimant).as_bytes()
     );
 }
+
+#[tokio::test]
+pub async fn test_aptos_message_v2() {
+    let authorization = Authorization::for_claimant(DEFAULT_CAMPAIGN_ID, &Pubkey::new_unique());
+    let payload = get_expected_payload_v2(&authorization);
+    let message =
+        AptosMessage::parse(&[APTOS_PREFIX, payload.as_bytes(), APTOS_SUFFIX].concat()).unwrap();
+
+    assert_eq!(message.get_payload(), payload.as_bytes());
+    assert!(check_payload(message.get_payload(), &authorization).is_ok());
+}
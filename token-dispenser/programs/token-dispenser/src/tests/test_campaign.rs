@@ -0,0 +1,264 @@
+use {
+    super::{
+        dispenser_simulator::{
+            copy_keypair,
+            DispenserSimulator,
+            IntoTransactionError,
+        },
+        merkleize,
+        test_happy_path::{
+            assert_claim_receipts_exist,
+            TestClaimCertificate,
+        },
+    },
+    crate::{
+        get_config_pda,
+        get_receipt_pda,
+        ClaimInfo,
+        Config,
+        DispenserMerkleRoot,
+        ErrorCode,
+        SolanaHasher32,
+        DEFAULT_CAMPAIGN_ID,
+    },
+    anchor_lang::AnchorDeserialize,
+    pythnet_sdk::accumulators::merkle::MerkleTree,
+    solana_program_test::tokio,
+    solana_sdk::signer::{
+        keypair::Keypair,
+        Signer,
+    },
+};
+
+#[tokio::test]
+pub async fn test_claim_from_campaign() {
+    let dispenser_guard = Keypair::new();
+    let mut simulator = DispenserSimulator::new().await;
+    simulator.campaign_id = 2;
+
+    let claimant = Keypair::new();
+    let signer = ed25519_dalek::Keypair::from_bytes(&dispenser_guard.to_bytes()).unwrap();
+    let solana = TestClaimCertificate::random_solana(&claimant.pubkey());
+    let discord = TestClaimCertificate::random_discord(&claimant.pubkey(), &signer);
+    let evm = TestClaimCertificate::random_evm(&claimant.pubkey());
+    let (merkle_tree, merkle_items_serialized) = merkleize(
+        [&solana, &discord, &evm]
+            .iter()
+            .map(|item| (*item).clone().into())
+            .collect::<Vec<ClaimInfo>>(),
+    );
+
+    let address_lookup_table = simulator.init_lookup_table().await.unwrap();
+    simulator
+        .initialize(
+            merkle_tree.root.clone(),
+            dispenser_guard.pubkey(),
+            address_lookup_table,
+            None,
+            None,
+            None,
+        )
+        .await
+        .unwrap();
+    simulator
+        .setup_treasury(solana.amount + discord.amount + evm.amount)
+        .await
+        .unwrap();
+
+    assert!(simulator
+        .get_account_data::<Config>(get_config_pda(2).0)
+        .await
+        .is_some());
+    assert!(simulator
+        .get_account(get_config_pda(DEFAULT_CAMPAIGN_ID).0)
+        .await
+        .is_none());
+
+    for offchain_claim_certificate in [&solana, &discord] {
+        simulator
+            .claim(
+                &claimant,
+                offchain_claim_certificate,
+                &merkle_tree,
+                None,
+                None,
+                None,
+            )
+            .await
+            .unwrap();
+    }
+    assert_claim_receipts_exist(&merkle_items_serialized[..2].to_vec(), &mut simulator).await;
+    for serialized_item in &merkle_items_serialized[..2] {
+        assert!(simulator
+            .get_account(
+                get_receipt_pda(
+                    DEFAULT_CAMPAIGN_ID,
+                    &ClaimInfo::try_from_slice(serialized_item).unwrap().identity
+                )
+                .0
+            )
+            .await
+            .is_none());
+    }
+
+    // The EVM message authorizes a claim from the default campaign
+    assert_eq!(
+        simulator
+            .claim(&claimant, &evm, &merkle_tree, None, None, None)
+            .await
+            .unwrap_err()
+            .unwrap(),
+        ErrorCode::SignatureVerificationWrongPayload
+            .into_transaction_error(evm.as_instruction_error_index(&merkle_tree))
+    );
+}
+
+#[tokio::test]
+pub async fn test_claim_from_campaign_with_32_byte_hashes() {
+    let dispenser_guard = Keypair::new();
+    let mut simulator = DispenserSimulator::new().await;
+    simulator.campaign_id = 3;
+
+    let claimant = Keypair::new();
+    let solana = TestClaimCertificate::random_solana(&claimant.pubkey());
+    let evm = TestClaimCertificate::random_evm(&claimant.pubkey());
+    let (merkle_tree, merkle_items_serialized) = merkleize(
+        [&solana, &evm]
+            .iter()
+            .map(|item| (*item).clone().into())
+            .collect::<Vec<ClaimInfo>>(),
+    );
+    let merkle_tree_32: MerkleTree<SolanaHasher32> = MerkleTree::new(
+        &merkle_items_serialized
+            .iter()
+            .map(|item| item.as_slice())
+            .collect::<Vec<&[u8]>>(),
+    )
+    .unwrap();
+
+    let address_lookup_table = simulator.init_lookup_table().await.unwrap();
+    simulator
+        .initialize(
+            merkle_tree_32.root.clone(),
+            dispenser_guard.pubkey(),
+            address_lookup_table,
+            None,
+            None,
+            None,
+        )
+        .await
+        .unwrap();
+    simulator
+        .setup_treasury(solana.amount + evm.amount)
+        .await
+        .unwrap();
+    assert_eq!(
+        simulator
+            .get_account_data::<Config>(get_config_pda(3).0)
+            .await
+            .unwrap()
+            .merkle_root,
+        DispenserMerkleRoot::Keccak256(merkle_tree_32.root.clone())
+    );
+
+    // A proof of the 20-byte tree of the same leaves doesn't verify against the 32-byte root
+    assert_eq!(
+        simulator
+            .claim(&claimant, &solana, &merkle_tree, None, None, None)
+            .await
+            .unwrap_err()
+            .unwrap(),
+        ErrorCode::InvalidInclusionProof
+            .into_transaction_error(solana.as_instruction_error_index(&merkle_tree))
+    );
+
+    for (offchain_claim_certificate, serialized_item) in
+        [&solana, &evm].iter().zip(merkle_items_serialized.iter())
+    {
+        simulator
+            .claim(
+                &claimant,
+                offchain_claim_certificate,
+                &merkle_tree,
+                None,
+                Some(merkle_tree_32.prove(serialized_item).unwrap().into()),
+                None,
+            )
+            .await
+            .unwrap();
+    }
+    assert_claim_receipts_exist(&merkle_items_serialized, &mut simulator).await;
+}
+
+#[tokio::test]
+pub async fn test_initialize_campaign_requires_upgrade_authority() {
+    let dispenser_guard = Keypair::new();
+    let mut simulator = DispenserSimulator::new().await;
+    simulator.campaign_id = 2;
+    let merkle_tree = merkleize(vec![ClaimInfo::from(TestClaimCertificate::random_evm(
+        &Keypair::new().pubkey(),
+    ))])
+    .0;
+    let address_lookup_table = simulator.init_lookup_table().await.unwrap();
+
+    // Anyone else would become the admin of the campaign and squat its id
+    let impostor = Keypair::new();
+    simulator
+        .airdrop(impostor.pubkey(), 1_000_000_000)
+        .await
+        .unwrap();
+    assert_eq!(
+        simulator
+            .initialize_with_payer(
+                &impostor,
+                merkle_tree.root.clone(),
+                dispenser_guard.pubkey(),
+                address_lookup_table,
+                None,
+                None,
+                None,
+            )
+            .await
+            .unwrap_err()
+            .unwrap(),
+        ErrorCode::UpgradeAuthorityRequired.into_transaction_error(0)
+    );
+    assert!(simulator.get_account(get_config_pda(2).0).await.is_none());
+
+    let upgrade_authority = copy_keypair(&simulator.upgrade_authority);
+    simulator
+        .initialize_with_payer(
+            &upgrade_authority,
+            merkle_tree.root.clone(),
+            dispenser_guard.pubkey(),
+            address_lookup_table,
+            None,
+            None,
+            None,
+        )
+        .await
+        .unwrap();
+    assert_eq!(
+        simulator
+            .get_account_data::<Config>(get_config_pda(2).0)
+            .await
+            .unwrap()
+            .admin,
+        upgrade_authority.pubkey()
+    );
+
+    // The default campaign doesn't need the upgrade authority
+    simulator.campaign_id = DEFAULT_CAMPAIGN_ID;
+    simulator
+        .initialize_with_payer(
+            &impostor,
+            merkle_tree.root.clone(),
+            dispenser_guard.pubkey(),
+            address_lookup_table,
+            None,
+            None,
+            None,
+        )
+        .await
+        .unwrap();
+}
@@ -0,0 +1,51 @@
+use {
+    super::test_ed25519::Ed25519TestIdentityCertificate,
+    crate::{
+        ecosystems::{
+            cardano::CardanoMessage,
+            ed25519::{
+                Ed25519Pubkey,
+                Ed25519TestMessage,
+            },
+        },
+        Identity,
+        IdentityCertificate,
+    },
+    anchor_lang::prelude::Pubkey,
+    ed25519_dalek::{
+        Keypair,
+        Signer,
+    },
+    rand_compatible::rngs::OsRng,
+};
+
+impl Ed25519TestIdentityCertificate<CardanoMessage> {
+    /// The signed message contains the address of the signer so it can't be built before the keypair
+    pub fn random_with_signer_address(claimant: &Pubkey) -> Self {
+        let mut csprng = OsRng {};
+        let keypair: Keypair = Keypair::generate(&mut csprng);
+        let message =
+            CardanoMessage::for_signer(claimant, &Ed25519Pubkey::from(keypair.public.to_bytes()));
+        let signature = keypair.sign(&message.get_message_with_metadata());
+        Self {
+            message,
+            signature,
+            public_key: keypair.public,
+        }
+    }
+
+    pub fn as_proof_of_identity(&self, verification_instruction_index: u8) -> IdentityCertificate {
+        IdentityCertificate::Cardano {
+            pubkey: Ed25519Pubkey::from(self.public_key.to_bytes()),
+            verification_instruction_index,
+        }
+    }
+}
+
+impl From<Ed25519TestIdentityCertificate<CardanoMessage>> for Identity {
+    fn from(val: Ed25519TestIdentityCertificate<CardanoMessage>) -> Self {
+        Identity::Cardano {
+            key_hash: Ed25519Pubkey::from(val.public_key.to_bytes()).into(),
+        }
+    }
+}
@@ -1,24 +1,35 @@
 use {
     super::dispenser_simulator::DispenserSimulator,
     crate::{
+        client::get_claim_instruction,
+        get_campaign_seed,
+        get_claim_bumps,
         get_config_pda,
+        get_identity_hash,
         get_receipt_pda,
+        instruction,
         tests::{
             dispenser_simulator::{
                 copy_keypair,
                 IntoTransactionError,
             },
+            merkleize,
             test_happy_path::TestClaimCertificate,
         },
+        ClaimBumps,
         ClaimInfo,
+        Config,
         ErrorCode,
+        Receipt,
         SolanaHasher,
+        DEFAULT_CAMPAIGN_ID,
+        RECEIPT_SEED,
     },
     anchor_lang::{
         prelude::Pubkey,
         solana_program::program_option::COption,
         system_program,
-        AnchorSerialize,
+        InstructionData,
     },
     anchor_spl::{
         associated_token::get_associated_token_address,
@@ -201,7 +212,7 @@ pub async fn test_claim_fails_with_insufficient_funds() {
     // approve enough for first checkout
     let delegated_amount = mock_offchain_certificates_and_claimants[0].2 + 1;
     simulator
-        .approve_treasury_delegate(get_config_pda().0, delegated_amount)
+        .approve_treasury_delegate(get_config_pda(DEFAULT_CAMPAIGN_ID).0, delegated_amount)
         .await
         .unwrap();
 
@@ -209,7 +220,7 @@ pub async fn test_claim_fails_with_insufficient_funds() {
         .verify_token_account_data(
             simulator.pyth_treasury,
             total_claim_sum,
-            COption::Some(get_config_pda().0),
+            COption::Some(get_config_pda(DEFAULT_CAMPAIGN_ID).0),
             delegated_amount,
         )
         .await
@@ -235,7 +246,7 @@ pub async fn test_claim_fails_with_insufficient_funds() {
         .verify_token_account_data(
             simulator.pyth_treasury,
             total_claim_sum - mock_offchain_certificates_and_claimants[0].2,
-            COption::Some(get_config_pda().0),
+            COption::Some(get_config_pda(DEFAULT_CAMPAIGN_ID).0),
             1,
         )
         .await
@@ -264,7 +275,7 @@ pub async fn test_claim_fails_with_insufficient_funds() {
 
     let delegated_amount = mock_offchain_certificates_and_claimants[1].2;
     simulator
-        .approve_treasury_delegate(get_config_pda().0, delegated_amount)
+        .approve_treasury_delegate(get_config_pda(DEFAULT_CAMPAIGN_ID).0, delegated_amount)
         .await
         .unwrap();
 
@@ -272,7 +283,7 @@ pub async fn test_claim_fails_with_insufficient_funds() {
         .verify_token_account_data(
             simulator.pyth_treasury,
             mock_offchain_certificates_and_claimants[1].2,
-            COption::Some(get_config_pda().0),
+            COption::Some(get_config_pda(DEFAULT_CAMPAIGN_ID).0),
             delegated_amount,
         )
         .await
@@ -364,7 +375,7 @@ pub async fn test_claim_fails_if_delegate_revoked() {
 
     simulator
         .approve_treasury_delegate(
-            get_config_pda().0,
+            get_config_pda(DEFAULT_CAMPAIGN_ID).0,
             mock_offchain_certificates_and_claimants[0].2,
         )
         .await
@@ -374,7 +385,7 @@ pub async fn test_claim_fails_if_delegate_revoked() {
         .verify_token_account_data(
             simulator.pyth_treasury,
             total_claim_sum,
-            COption::Some(get_config_pda().0),
+            COption::Some(get_config_pda(DEFAULT_CAMPAIGN_ID).0),
             mock_offchain_certificates_and_claimants[0].2,
         )
         .await
@@ -453,7 +464,7 @@ pub async fn test_claim_fails_with_wrong_merkle_proof() {
                         offchain_claim_certificate,
                         &merkle_tree,
                         None,
-                        Some(fake_merkle_tree.prove(fake_tree_leaf).unwrap()),
+                        Some(fake_merkle_tree.prove(fake_tree_leaf).unwrap().into()),
                         None
                     )
                     .await
@@ -507,6 +518,104 @@ pub async fn test_claim_fails_with_wrong_receipt_pubkey() {
     }
 }
 
+#[tokio::test]
+pub async fn test_claim_fails_with_non_canonical_receipt_bump() {
+    let dispenser_guard = Keypair::new();
+    let mut simulator = DispenserSimulator::new().await;
+
+    let claimant = Keypair::new();
+    let solana_certificate = TestClaimCertificate::random_solana(&claimant.pubkey());
+    let claim_info = ClaimInfo::from(solana_certificate.clone());
+    let (merkle_tree, _) = merkleize(vec![claim_info.clone()]);
+
+    let address_lookup_table = simulator.init_lookup_table().await.unwrap();
+    simulator
+        .initialize(
+            merkle_tree.root.clone(),
+            dispenser_guard.pubkey(),
+            address_lookup_table,
+            None,
+            None,
+            None,
+        )
+        .await
+        .unwrap();
+    simulator
+        .setup_treasury(solana_certificate.amount)
+        .await
+        .unwrap();
+
+    let config = simulator
+        .get_account_data::<Config>(get_config_pda(simulator.campaign_id).0)
+        .await
+        .unwrap();
+    let (claim_certificate, _) = solana_certificate.as_claim_certificate(&merkle_tree, 0, None);
+    let claim_instruction = get_claim_instruction(
+        &config,
+        simulator.campaign_id,
+        &simulator.genesis_keypair.pubkey(),
+        &claimant.pubkey(),
+        &simulator.token_program,
+        claim_certificate.clone(),
+        &claim_info,
+    );
+
+    // Another bump of the seeds of the receipt yields another valid address, that a claim can't use
+    let (receipt, receipt_bump) = get_receipt_pda(simulator.campaign_id, &claim_info.identity);
+    let (non_canonical_receipt, non_canonical_bump) = (0..receipt_bump)
+        .rev()
+        .find_map(|bump| {
+            Pubkey::create_program_address(
+                &[
+                    RECEIPT_SEED,
+                    &get_campaign_seed(simulator.campaign_id),
+                    &get_identity_hash(&claim_info.identity),
+                    &[bump],
+                ],
+                &crate::id(),
+            )
+            .ok()
+            .map(|pubkey| (pubkey, bump))
+        })
+        .unwrap();
+    let mut non_canonical_instruction = claim_instruction.clone();
+    non_canonical_instruction
+        .accounts
+        .iter_mut()
+        .filter(|account| account.pubkey == receipt)
+        .for_each(|account| account.pubkey = non_canonical_receipt);
+    non_canonical_instruction.data = instruction::Claim {
+        campaign_id: simulator.campaign_id,
+        claim_certificate,
+        bumps: Some(ClaimBumps {
+            receipt: non_canonical_bump,
+            ..get_claim_bumps(simulator.campaign_id, &claim_info, &claimant.pubkey())
+        }),
+    }
+    .data();
+    assert_eq!(
+        simulator
+            .process_ix(&[non_canonical_instruction], &vec![&claimant])
+            .await
+            .unwrap_err()
+            .unwrap(),
+        ErrorCode::WrongPda.into_transaction_error(0)
+    );
+
+    simulator
+        .process_ix(&[claim_instruction], &vec![&claimant])
+        .await
+        .unwrap();
+    assert_eq!(
+        simulator
+            .get_account_data::<Receipt>(receipt)
+            .await
+            .unwrap()
+            .bump,
+        receipt_bump
+    );
+}
+
 #[tokio::test]
 pub async fn test_claim_works_if_receipt_has_balance() {
     let dispenser_guard: Keypair = Keypair::new();
@@ -522,9 +631,9 @@ pub async fn test_claim_works_if_receipt_has_balance() {
     let (claimant, offchain_claim_certificates, _) = &mock_offchain_certificates_and_claimants[0];
     for offchain_claim_certificate in offchain_claim_certificates {
         let receipt_pda = get_receipt_pda(
+            DEFAULT_CAMPAIGN_ID,
             &<TestClaimCertificate as Into<ClaimInfo>>::into(offchain_claim_certificate.clone())
-                .try_to_vec()
-                .unwrap(),
+                .identity,
         )
         .0;
 
@@ -593,9 +702,9 @@ pub async fn test_claim_fails_if_exceeds_max_transfer() {
     let (_, offchain_claim_certificates, _) = &mock_offchain_certificates_and_claimants[0];
     for offchain_claim_certificate in offchain_claim_certificates {
         let receipt_pda = get_receipt_pda(
+            DEFAULT_CAMPAIGN_ID,
             &<TestClaimCertificate as Into<ClaimInfo>>::into(offchain_claim_certificate.clone())
-                .try_to_vec()
-                .unwrap(),
+                .identity,
         )
         .0;
         assert!(simulator.get_account(receipt_pda).await.is_none());
@@ -0,0 +1,163 @@
+use {
+    super::{
+        dispenser_simulator::{
+            DispenserSimulator,
+            IntoTransactionError,
+        },
+        merkleize,
+        test_happy_path::TestClaimCertificate,
+    },
+    crate::{
+        get_claimant_total_pda,
+        ClaimInfo,
+        ClaimantTotal,
+        ErrorCode,
+    },
+    solana_program_test::tokio,
+    solana_sdk::signer::{
+        keypair::Keypair,
+        Signer,
+    },
+};
+
+#[tokio::test]
+pub async fn test_claim_fails_over_claimant_cap() {
+    let dispenser_guard = Keypair::new();
+    let mut simulator = DispenserSimulator::new().await;
+    simulator.max_per_claimant = Some(150);
+
+    let claimant = Keypair::new();
+    let other_claimant = Keypair::new();
+    let solana_certificate = TestClaimCertificate {
+        amount: 100,
+        ..TestClaimCertificate::random_solana(&claimant.pubkey())
+    };
+    let evm_certificate = TestClaimCertificate {
+        amount: 100,
+        ..TestClaimCertificate::random_evm(&claimant.pubkey())
+    };
+    let other_evm_certificate = TestClaimCertificate {
+        amount: 100,
+        ..TestClaimCertificate::random_evm(&other_claimant.pubkey())
+    };
+    let (merkle_tree, _) = merkleize(vec![
+        ClaimInfo::from(solana_certificate.clone()),
+        ClaimInfo::from(evm_certificate.clone()),
+        ClaimInfo::from(other_evm_certificate.clone()),
+    ]);
+
+    let address_lookup_table = simulator.init_lookup_table().await.unwrap();
+    simulator
+        .initialize(
+            merkle_tree.root.clone(),
+            dispenser_guard.pubkey(),
+            address_lookup_table,
+            None,
+            None,
+            None,
+        )
+        .await
+        .unwrap();
+    simulator.setup_treasury(300).await.unwrap();
+
+    simulator
+        .claim(
+            &claimant,
+            &solana_certificate,
+            &merkle_tree,
+            None,
+            None,
+            None,
+        )
+        .await
+        .unwrap();
+    assert_eq!(
+        simulator
+            .get_account_data::<ClaimantTotal>(
+                get_claimant_total_pda(simulator.campaign_id, &claimant.pubkey()).0
+            )
+            .await
+            .unwrap(),
+        ClaimantTotal {
+            amount_claimed: 100,
+        }
+    );
+
+    // The second identity of the claimant would bring its total over the cap
+    assert_eq!(
+        simulator
+            .claim(&claimant, &evm_certificate, &merkle_tree, None, None, None)
+            .await
+            .unwrap_err()
+            .unwrap(),
+        ErrorCode::ClaimantCapExceeded
+            .into_transaction_error(evm_certificate.as_instruction_error_index(&merkle_tree))
+    );
+
+    // Other claimants have their own total
+    simulator
+        .claim(
+            &other_claimant,
+            &other_evm_certificate,
+            &merkle_tree,
+            None,
+            None,
+            None,
+        )
+        .await
+        .unwrap();
+    assert_eq!(
+        simulator
+            .get_account_data::<ClaimantTotal>(
+                get_claimant_total_pda(simulator.campaign_id, &other_claimant.pubkey()).0
+            )
+            .await
+            .unwrap(),
+        ClaimantTotal {
+            amount_claimed: 100,
+        }
+    );
+}
+
+#[tokio::test]
+pub async fn test_claimant_total_not_created_without_cap() {
+    let dispenser_guard = Keypair::new();
+    let mut simulator = DispenserSimulator::new().await;
+
+    let claimant = Keypair::new();
+    let solana_certificate = TestClaimCertificate::random_solana(&claimant.pubkey());
+    let (merkle_tree, _) = merkleize(vec![ClaimInfo::from(solana_certificate.clone())]);
+
+    let address_lookup_table = simulator.init_lookup_table().await.unwrap();
+    simulator
+        .initialize(
+            merkle_tree.root.clone(),
+            dispenser_guard.pubkey(),
+            address_lookup_table,
+            None,
+            None,
+            None,
+        )
+        .await
+        .unwrap();
+    simulator
+        .setup_treasury(solana_certificate.amount)
+        .await
+        .unwrap();
+
+    simulator
+        .claim(
+            &claimant,
+            &solana_certificate,
+            &merkle_tree,
+            None,
+            None,
+            None,
+        )
+        .await
+        .unwrap();
+    assert!(simulator
+        .get_account(get_claimant_total_pda(simulator.campaign_id, &claimant.pubkey()).0)
+        .await
+        .is_none());
+}
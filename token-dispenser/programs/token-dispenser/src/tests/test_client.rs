@@ -0,0 +1,235 @@
+use {
+    super::{
+        dispenser_simulator::DispenserSimulator,
+        test_happy_path::{
+            assert_claim_receipts_exist,
+            TestClaimCertificate,
+            TestIdentityCertificate,
+        },
+    },
+    crate::{
+        client::{
+            get_claim_info,
+            get_claim_instructions,
+            get_verification_instruction,
+            RawSignature,
+        },
+        ecosystems::{
+            ed25519::Ed25519TestMessage,
+            secp256k1::Secp256k1TestMessage,
+        },
+        get_config_pda,
+        tests::{
+            dispenser_simulator::copy_keypair,
+            merkleize,
+        },
+        ClaimInfo,
+        Config,
+        ErrorCode,
+        CONFIG_VERSION,
+        DEFAULT_CAMPAIGN_ID,
+        IDENTITY_VARIANTS,
+    },
+    anchor_lang::{
+        error,
+        AnchorSerialize,
+    },
+    solana_program_test::tokio,
+    solana_sdk::{
+        signature::Keypair,
+        signer::Signer,
+    },
+};
+
+impl TestIdentityCertificate {
+    pub fn as_raw_signature(&self) -> Option<RawSignature> {
+        match self {
+            Self::Evm(evm) => Some(RawSignature::Secp256k1 {
+                signature:   evm.signature.serialize().into(),
+                recovery_id: evm.recovery_id.serialize(),
+                message:     evm.message.get_message_with_metadata(),
+            }),
+            Self::Injective(injective) => Some(RawSignature::Secp256k1 {
+                signature:   injective.signature.serialize().into(),
+                recovery_id: injective.recovery_id.serialize(),
+                message:     injective.message.get_message_with_metadata(),
+            }),
+            Self::InjectiveAdr036(injective) => Some(RawSignature::Secp256k1 {
+                signature:   injective.signature.serialize().into(),
+                recovery_id: injective.recovery_id.serialize(),
+                message:     injective.message.get_message_with_metadata(),
+            }),
+            Self::Discord(discord) => Some(RawSignature::Ed25519 {
+                signature: discord.signature.to_bytes().into(),
+                message:   discord.message.get_message_with_metadata(),
+            }),
+            Self::Aptos(aptos) => Some(RawSignature::Ed25519 {
+                signature: aptos.signature.to_bytes().into(),
+                message:   aptos.message.get_message_with_metadata(),
+            }),
+            Self::Sui(sui) => Some(RawSignature::Ed25519 {
+                signature: sui.signature.to_bytes().into(),
+                message:   sui.message.get_message_with_metadata(),
+            }),
+            Self::Starknet(starknet) => Some(RawSignature::Ed25519 {
+                signature: starknet.signature.to_bytes().into(),
+                message:   starknet.message.get_message_with_metadata(),
+            }),
+            Self::Cardano(cardano) => Some(RawSignature::Ed25519 {
+                signature: cardano.signature.to_bytes().into(),
+                message:   cardano.message.get_message_with_metadata(),
+            }),
+            Self::Passkey(passkey) => Some(RawSignature::Secp256r1 {
+                signature: passkey.get_signature(),
+                message:   passkey.message.get_signed_message(),
+            }),
+            Self::Cosmos(_) | Self::CosmosSignDoc(_) | Self::Solana(_) => None,
+        }
+    }
+}
+
+#[tokio::test]
+pub async fn test_claim_with_client_instructions() {
+    let dispenser_guard: Keypair = Keypair::new();
+
+    let mut simulator = DispenserSimulator::new().await;
+    let claimant = copy_keypair(&simulator.genesis_keypair);
+
+    let mock_offchain_certificates =
+        DispenserSimulator::generate_test_claim_certs(&claimant.pubkey(), &dispenser_guard);
+    let merkle_items: Vec<ClaimInfo> = mock_offchain_certificates
+        .iter()
+        .map(|item: &TestClaimCertificate| item.clone().into())
+        .collect();
+    let (merkle_tree, merkle_items_serialized) = merkleize(merkle_items);
+
+    let address_lookup_table = simulator.init_lookup_table().await.unwrap();
+    simulator
+        .initialize(
+            merkle_tree.root.clone(),
+            dispenser_guard.pubkey(),
+            address_lookup_table,
+            None,
+            None,
+            None,
+        )
+        .await
+        .unwrap();
+    simulator
+        .setup_treasury(
+            mock_offchain_certificates
+                .iter()
+                .map(|item| item.amount)
+                .sum::<u64>(),
+        )
+        .await
+        .unwrap();
+
+    let config = simulator
+        .get_account_data::<Config>(get_config_pda(DEFAULT_CAMPAIGN_ID).0)
+        .await
+        .unwrap();
+
+    for offchain_claim_certificate in &mock_offchain_certificates {
+        let (claim_certificate, option_instruction) =
+            offchain_claim_certificate.as_claim_certificate(&merkle_tree, 0, None);
+        let raw_signature = offchain_claim_certificate
+            .off_chain_proof_of_identity
+            .as_raw_signature();
+
+        assert_eq!(
+            get_claim_info(&claim_certificate, &claimant.pubkey())
+                .unwrap()
+                .try_to_vec()
+                .unwrap(),
+            Into::<ClaimInfo>::into(offchain_claim_certificate.clone())
+                .try_to_vec()
+                .unwrap()
+        );
+        assert_eq!(
+            get_verification_instruction(
+                &config,
+                &claim_certificate.proof_of_identity,
+                raw_signature.clone()
+            )
+            .unwrap(),
+            option_instruction
+        );
+
+        let instructions = get_claim_instructions(
+            &config,
+            DEFAULT_CAMPAIGN_ID,
+            &claimant.pubkey(),
+            &claimant.pubkey(),
+            &simulator.token_program,
+            claim_certificate,
+            raw_signature,
+        )
+        .unwrap();
+        simulator
+            .process_ix(&instructions, &vec![&claimant])
+            .await
+            .unwrap();
+    }
+
+    assert_claim_receipts_exist(&merkle_items_serialized, &mut simulator).await;
+}
+
+#[test]
+pub fn test_verification_instruction_requires_matching_signature() {
+    let claimant = Keypair::new();
+    let evm_claim_certificate = TestClaimCertificate::random_evm(&claimant.pubkey());
+    let config = Config {
+        bump:                 0,
+        merkle_root:          merkleize(vec![evm_claim_certificate.clone().into()])
+            .0
+            .root
+            .into(),
+        dispenser_guard:      Keypair::new().pubkey(),
+        mint:                 Keypair::new().pubkey(),
+        treasury:             Keypair::new().pubkey(),
+        address_lookup_table: Keypair::new().pubkey(),
+        funder:               Keypair::new().pubkey(),
+        max_transfer:         u64::MAX,
+        escrow:               false,
+        total_deposited:      0,
+        total_claimed:        0,
+        claim_count:          0,
+        claimed_per_identity: [0; IDENTITY_VARIANTS],
+        global_cap:           None,
+        max_per_claimant:     None,
+        admin:                Keypair::new().pubkey(),
+        rate_limit:           None,
+        denylist_len:         0,
+        guard_policy:         0,
+        legacy_receipts:      0,
+        version:              CONFIG_VERSION,
+    };
+    let evm = evm_claim_certificate.off_chain_proof_of_identity;
+    let discord = TestClaimCertificate::random_discord(
+        &claimant.pubkey(),
+        &ed25519_dalek::Keypair::generate(&mut rand_compatible::rngs::OsRng {}),
+    )
+    .off_chain_proof_of_identity;
+
+    assert_eq!(
+        get_verification_instruction(&config, &evm.as_claim_certificate(0), None).unwrap_err(),
+        error!(ErrorCode::SignatureVerificationWrongProgram)
+    );
+    assert_eq!(
+        get_verification_instruction(
+            &config,
+            &evm.as_claim_certificate(0),
+            discord.as_raw_signature()
+        )
+        .unwrap_err(),
+        error!(ErrorCode::SignatureVerificationWrongProgram)
+    );
+    assert!(get_verification_instruction(
+        &config,
+        &discord.as_claim_certificate(0),
+        discord.as_raw_signature()
+    )
+    .unwrap()
+    .is_some());
+}
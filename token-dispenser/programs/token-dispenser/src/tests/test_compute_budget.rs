@@ -0,0 +1,81 @@
+use {
+    super::dispenser_simulator::{
+        copy_keypair,
+        DispenserSimulator,
+    },
+    crate::{
+        ClaimInfo,
+        Identity,
+    },
+    solana_program_test::tokio,
+    solana_sdk::signer::keypair::Keypair,
+};
+
+/**
+ * Compute units a claim of the ecosystem is allowed to consume, including the creation of the receipt and of the
+ * claimant fund. The budgets leave headroom under the default limit of 200,000 compute units per instruction, so
+ * that claims don't need a compute budget instruction.
+ */
+fn get_compute_budget(identity: &Identity) -> u64 {
+    match identity {
+        // Bech32 addresses are derived on-chain from the public key
+        Identity::Cosmwasm { .. } | Identity::Injective { .. } => 170_000,
+        Identity::Passkey { .. } => 170_000,
+        _ => 130_000,
+    }
+}
+
+#[tokio::test]
+pub async fn test_claim_compute_budget() {
+    let dispenser_guard = Keypair::new();
+    let mut simulator = DispenserSimulator::new().await;
+
+    let (merkle_tree, mock_offchain_certificates_and_claimants) = simulator
+        .initialize_with_claimants(
+            vec![copy_keypair(&simulator.genesis_keypair)],
+            &dispenser_guard,
+            None,
+        )
+        .await
+        .unwrap();
+
+    let (claimant, offchain_claim_certificates, _) = &mock_offchain_certificates_and_claimants[0];
+    for offchain_claim_certificate in offchain_claim_certificates {
+        // The baseline is the same claim without the bumps of its accounts, for which the program searches
+        let baseline_compute_units = simulator
+            .simulate_claim_without_bumps(
+                &copy_keypair(claimant),
+                offchain_claim_certificate,
+                &merkle_tree,
+            )
+            .await
+            .unwrap();
+        simulator
+            .claim(
+                &copy_keypair(claimant),
+                offchain_claim_certificate,
+                &merkle_tree,
+                None,
+                None,
+                None,
+            )
+            .await
+            .unwrap();
+
+        let identity = ClaimInfo::from(offchain_claim_certificate.clone()).identity;
+        let compute_units = *simulator.compute_units.last().unwrap();
+        assert!(
+            compute_units <= get_compute_budget(&identity),
+            "claim of ecosystem {} consumed {} compute units",
+            identity.get_variant_index(),
+            compute_units
+        );
+        assert!(
+            compute_units < baseline_compute_units,
+            "claim of ecosystem {} consumed {} compute units, {} without the bumps",
+            identity.get_variant_index(),
+            compute_units,
+            baseline_compute_units
+        );
+    }
+}
@@ -3,22 +3,33 @@ use {
     crate::{
         ecosystems::{
             cosmos::{
+                CosmosBech32Address,
                 CosmosMessage,
                 UncompressedSecp256k1Pubkey,
                 ADMISSIBLE_CHAIN_IDS,
             },
             get_expected_payload,
+            secp256k1::Secp256k1TestMessage,
         },
         ErrorCode,
         Identity,
         IdentityCertificate,
+        DEFAULT_CAMPAIGN_ID,
     },
     anchor_lang::{
         error,
         prelude::Pubkey,
     },
+    base64::{
+        engine::general_purpose::STANDARD as base64_standard_engine,
+        Engine as _,
+    },
     pythnet_sdk::hashers::Hasher,
     rand::seq::SliceRandom,
+    serde_json::{
+        json,
+        Value,
+    },
     solana_sdk::hash::hashv,
     std::marker::PhantomData,
 };
@@ -61,6 +72,114 @@ impl From<Secp256k1TestIdentityCertificate<CosmosMessage, Sha256>> for IdentityC
     }
 }
 
+/**
+ * A Cosmos message signed the way Keplr signs it with a Ledger device: the amino JSON sign doc is
+ * built independently of `CosmosMessage::build_message`, serialized with sorted keys and with HTML
+ * characters escaped.
+ */
+#[derive(Clone)]
+pub struct CosmosLedgerMessage(pub CosmosMessage);
+
+impl CosmosLedgerMessage {
+    /**
+     * Port of cosmjs `serializeSignDoc(makeADR36AminoSignDoc(signer, data))`.
+     */
+    pub fn serialize_sign_doc(payload: &[u8], signer: &CosmosBech32Address) -> Vec<u8> {
+        let sign_doc = json!({
+            "chain_id": "",
+            "account_number": "0",
+            "sequence": "0",
+            "fee": {
+                "gas": "0",
+                "amount": [],
+            },
+            "msgs": [{
+                "type": "sign/MsgSignData",
+                "value": {
+                    "signer": signer.as_str(),
+                    "data": base64_standard_engine.encode(payload),
+                },
+            }],
+            "memo": "",
+        });
+        serde_json::to_string(&sort_json_keys(sign_doc))
+            .unwrap()
+            .replace('&', "\\u0026")
+            .replace('<', "\\u003c")
+            .replace('>', "\\u003e")
+            .into_bytes()
+    }
+}
+
+/**
+ * Equivalent of cosmjs `sortedJsonStringify`, independent of whether `serde_json` preserves insertion order.
+ */
+fn sort_json_keys(value: Value) -> Value {
+    match value {
+        Value::Object(map) => {
+            let mut entries: Vec<(String, Value)> = map.into_iter().collect();
+            entries.sort_by(|a, b| a.0.cmp(&b.0));
+            Value::Object(
+                entries
+                    .into_iter()
+                    .map(|(key, value)| (key, sort_json_keys(value)))
+                    .collect(),
+            )
+        }
+        Value::Array(values) => Value::Array(values.into_iter().map(sort_json_keys).collect()),
+        value => value,
+    }
+}
+
+impl Secp256k1TestMessage for CosmosLedgerMessage {
+    fn get_message_with_metadata(&self) -> Vec<u8> {
+        CosmosLedgerMessage::serialize_sign_doc(self.0.get_payload(), &self.0.get_signer())
+    }
+}
+
+impl From<Secp256k1TestIdentityCertificate<CosmosLedgerMessage, Sha256>> for Identity {
+    fn from(val: Secp256k1TestIdentityCertificate<CosmosLedgerMessage, Sha256>) -> Self {
+        Identity::Cosmwasm {
+            address: val.message.0.get_signer(),
+        }
+    }
+}
+
+impl Secp256k1TestIdentityCertificate<CosmosLedgerMessage, Sha256> {
+    pub fn as_sign_doc_proof_of_identity(&self) -> IdentityCertificate {
+        IdentityCertificate::CosmwasmSignDoc {
+            chain_id:    self.message.0.extract_chain_id(),
+            signature:   self.signature.serialize().into(),
+            recovery_id: self.recovery_id.into(),
+            pubkey:      self.recover().serialize().into(),
+            sign_doc:    self.message.get_message_with_metadata(),
+        }
+    }
+
+    pub fn random(claimant: &Pubkey) -> Self {
+        let secret = libsecp256k1::SecretKey::random(&mut rand::thread_rng());
+        let public_key = libsecp256k1::PublicKey::from_secret_key(&secret);
+        let chain_id = ADMISSIBLE_CHAIN_IDS
+            .choose(&mut rand::thread_rng())
+            .unwrap()
+            .to_string();
+
+        let message = CosmosLedgerMessage(CosmosMessage::from((
+            get_expected_payload(DEFAULT_CAMPAIGN_ID, claimant).as_bytes(),
+            &UncompressedSecp256k1Pubkey::from(public_key.serialize())
+                .into_bech32(chain_id.as_str())
+                .unwrap(),
+        )));
+        let (signature, recovery_id) = libsecp256k1::sign(&Self::hash_message(&message), &secret);
+        Self {
+            message,
+            signature,
+            recovery_id,
+            _hasher: PhantomData,
+        }
+    }
+}
+
 impl Secp256k1TestIdentityCertificate<CosmosMessage, Sha256> {
     pub fn random(claimant: &Pubkey) -> Self {
         let secret = libsecp256k1::SecretKey::random(&mut rand::thread_rng());
@@ -71,7 +190,7 @@ impl Secp256k1TestIdentityCertificate<CosmosMessage, Sha256> {
             .to_string();
 
         let message = CosmosMessage::from((
-            get_expected_payload(claimant).as_bytes(),
+            get_expected_payload(DEFAULT_CAMPAIGN_ID, claimant).as_bytes(),
             &UncompressedSecp256k1Pubkey::from(public_key.serialize())
                 .into_bech32(chain_id.as_str())
                 .unwrap(),
@@ -101,3 +220,69 @@ pub fn test_authorized_cosmos_chain_ids() {
         error!(ErrorCode::UnauthorizedCosmosChainId)
     );
 }
+
+#[test]
+pub fn test_parse_amino_json_sign_doc() {
+    let claimant = Pubkey::new_unique();
+    let signer = "osmo1lv3rrn5trdea7vs43z5m4y34d5r3zxp484wcpu";
+    let data = base64_standard_engine.encode(get_expected_payload(DEFAULT_CAMPAIGN_ID, &claimant));
+
+    // Same doc as `CosmosMessage::build_message` but with a different key order and escaped characters
+    let sign_doc = format!(
+        r#"{{"sequence":"0","msgs":[{{"value":{{"signer":"{}","data":"{}"}},"type":"sign\u002fMsgSignData"}}],"memo":"","fee":{{"gas":"0","amount":[]}},"chain_id":"","account_number":"0"}}"#,
+        signer, data
+    );
+    assert_ne!(
+        sign_doc.as_bytes(),
+        CosmosMessage::build_message(
+            get_expected_payload(DEFAULT_CAMPAIGN_ID, &claimant).as_bytes(),
+            &CosmosBech32Address::from(signer)
+        )
+    );
+    assert_eq!(
+        CosmosMessage::parse(sign_doc.as_bytes(), &CosmosBech32Address::from(signer))
+            .unwrap()
+            .get_payload(),
+        get_expected_payload(DEFAULT_CAMPAIGN_ID, &claimant).as_bytes()
+    );
+
+    let wrong_sign_doc = sign_doc.replace(r#""sequence":"0""#, r#""sequence":"1""#);
+    assert_eq!(
+        CosmosMessage::parse(
+            wrong_sign_doc.as_bytes(),
+            &CosmosBech32Address::from(signer)
+        )
+        .unwrap_err(),
+        error!(ErrorCode::SignatureVerificationWrongPayloadMetadata)
+    );
+}
+
+#[test]
+pub fn test_parse_sign_doc_with_unknown_fields() {
+    let claimant = Pubkey::new_unique();
+    let signer = CosmosBech32Address::from("osmo1lv3rrn5trdea7vs43z5m4y34d5r3zxp484wcpu");
+    let sign_doc = String::from_utf8(CosmosMessage::build_message(
+        get_expected_payload(DEFAULT_CAMPAIGN_ID, &claimant).as_bytes(),
+        &signer,
+    ))
+    .unwrap();
+    assert!(CosmosMessage::parse(sign_doc.as_bytes(), &signer).is_ok());
+
+    // An extra field in the doc, in the message, in its value and in the fee
+    for (field, with_extra_field) in [
+        (r#""account_number":"#, r#""extra":"0","account_number":"#),
+        (r#""type":"#, r#""extra":"0","type":"#),
+        (r#""data":"#, r#""extra":"0","data":"#),
+        (r#""gas":"#, r#""extra":"0","gas":"#),
+    ] {
+        assert!(sign_doc.contains(field));
+        assert_eq!(
+            CosmosMessage::parse(
+                sign_doc.replacen(field, with_extra_field, 1).as_bytes(),
+                &signer
+            )
+            .unwrap_err(),
+            error!(ErrorCode::SignatureVerificationWrongPayloadMetadata)
+        );
+    }
+}
@@ -0,0 +1,397 @@
+use {
+    super::{
+        dispenser_simulator::{
+            copy_keypair,
+            DispenserSimulator,
+            IntoTransactionError,
+        },
+        merkleize,
+        test_happy_path::TestClaimCertificate,
+    },
+    crate::{
+        client::get_claim_instruction,
+        get_campaign_seed,
+        get_claim_bumps,
+        get_config_pda,
+        get_denylist_pda,
+        instruction,
+        ClaimBumps,
+        ClaimInfo,
+        Config,
+        DenylistKey,
+        ErrorCode,
+        DENYLIST_SEED,
+    },
+    anchor_lang::{
+        prelude::Pubkey,
+        InstructionData,
+    },
+    solana_program_test::tokio,
+    solana_sdk::signer::{
+        keypair::Keypair,
+        Signer,
+    },
+};
+
+#[tokio::test]
+pub async fn test_claim_fails_if_denylisted() {
+    let dispenser_guard = Keypair::new();
+    let mut simulator = DispenserSimulator::new().await;
+    let admin = copy_keypair(&simulator.genesis_keypair);
+
+    let claimant = Keypair::new();
+    let other_claimant = Keypair::new();
+    let solana_certificate = TestClaimCertificate::random_solana(&claimant.pubkey());
+    let evm_certificate = TestClaimCertificate::random_evm(&claimant.pubkey());
+    let other_evm_certificate = TestClaimCertificate::random_evm(&other_claimant.pubkey());
+    let (merkle_tree, _) = merkleize(vec![
+        ClaimInfo::from(solana_certificate.clone()),
+        ClaimInfo::from(evm_certificate.clone()),
+        ClaimInfo::from(other_evm_certificate.clone()),
+    ]);
+
+    let address_lookup_table = simulator.init_lookup_table().await.unwrap();
+    simulator
+        .initialize(
+            merkle_tree.root.clone(),
+            dispenser_guard.pubkey(),
+            address_lookup_table,
+            None,
+            None,
+            None,
+        )
+        .await
+        .unwrap();
+    simulator
+        .setup_treasury(
+            solana_certificate.amount + evm_certificate.amount + other_evm_certificate.amount,
+        )
+        .await
+        .unwrap();
+
+    let blocked_identity = DenylistKey::Identity {
+        identity_hash: solana_certificate.get_identity_hash(),
+    };
+    let blocked_claimant = DenylistKey::Claimant {
+        pubkey: other_claimant.pubkey(),
+    };
+
+    // Only the admin can manage the denylist
+    assert_eq!(
+        simulator
+            .block(&Keypair::new(), blocked_identity.clone())
+            .await
+            .unwrap_err()
+            .unwrap(),
+        anchor_lang::error::ErrorCode::ConstraintHasOne.into_transaction_error(0)
+    );
+    simulator
+        .block(&admin, blocked_identity.clone())
+        .await
+        .unwrap();
+    simulator
+        .block(&admin, blocked_claimant.clone())
+        .await
+        .unwrap();
+    assert_eq!(
+        simulator
+            .get_account_data::<Config>(get_config_pda(simulator.campaign_id).0)
+            .await
+            .unwrap()
+            .denylist_len,
+        2
+    );
+
+    assert_eq!(
+        simulator
+            .claim(
+                &claimant,
+                &solana_certificate,
+                &merkle_tree,
+                None,
+                None,
+                None,
+            )
+            .await
+            .unwrap_err()
+            .unwrap(),
+        ErrorCode::IdentityBlocked
+            .into_transaction_error(solana_certificate.as_instruction_error_index(&merkle_tree))
+    );
+    assert_eq!(
+        simulator
+            .claim(
+                &other_claimant,
+                &other_evm_certificate,
+                &merkle_tree,
+                None,
+                None,
+                None,
+            )
+            .await
+            .unwrap_err()
+            .unwrap(),
+        ErrorCode::IdentityBlocked
+            .into_transaction_error(other_evm_certificate.as_instruction_error_index(&merkle_tree))
+    );
+    // Other leaves of the claimant aren't blocked
+    simulator
+        .claim(&claimant, &evm_certificate, &merkle_tree, None, None, None)
+        .await
+        .unwrap();
+
+    simulator
+        .unblock(&admin, blocked_identity.clone())
+        .await
+        .unwrap();
+    assert!(simulator
+        .get_account(get_denylist_pda(simulator.campaign_id, &blocked_identity).0)
+        .await
+        .is_none());
+    simulator
+        .claim(
+            &claimant,
+            &solana_certificate,
+            &merkle_tree,
+            None,
+            None,
+            None,
+        )
+        .await
+        .unwrap();
+
+    simulator
+        .unblock(&admin, blocked_claimant.clone())
+        .await
+        .unwrap();
+    assert_eq!(
+        simulator
+            .get_account_data::<Config>(get_config_pda(simulator.campaign_id).0)
+            .await
+            .unwrap()
+            .denylist_len,
+        0
+    );
+    simulator
+        .claim(
+            &other_claimant,
+            &other_evm_certificate,
+            &merkle_tree,
+            None,
+            None,
+            None,
+        )
+        .await
+        .unwrap();
+}
+
+#[tokio::test]
+pub async fn test_denylist_entries_are_found_by_address() {
+    let dispenser_guard = Keypair::new();
+    let mut simulator = DispenserSimulator::new().await;
+    let admin = copy_keypair(&simulator.genesis_keypair);
+
+    let claimant = Keypair::new();
+    let solana_certificate = TestClaimCertificate::random_solana(&claimant.pubkey());
+    let (merkle_tree, _) = merkleize(vec![ClaimInfo::from(solana_certificate.clone())]);
+
+    let address_lookup_table = simulator.init_lookup_table().await.unwrap();
+    simulator
+        .initialize(
+            merkle_tree.root.clone(),
+            dispenser_guard.pubkey(),
+            address_lookup_table,
+            None,
+            None,
+            None,
+        )
+        .await
+        .unwrap();
+    simulator
+        .setup_treasury(solana_certificate.amount)
+        .await
+        .unwrap();
+    let blocked_claimant = DenylistKey::Claimant {
+        pubkey: claimant.pubkey(),
+    };
+    simulator
+        .block(&admin, blocked_claimant.clone())
+        .await
+        .unwrap();
+
+    let config = simulator
+        .get_account_data::<Config>(get_config_pda(simulator.campaign_id).0)
+        .await
+        .unwrap();
+    let (claim_certificate, _) = solana_certificate.as_claim_certificate(&merkle_tree, 0, None);
+    let claim_instruction = get_claim_instruction(
+        &config,
+        simulator.campaign_id,
+        &simulator.genesis_keypair.pubkey(),
+        &claimant.pubkey(),
+        &simulator.token_program,
+        claim_certificate.clone(),
+        &ClaimInfo::from(solana_certificate.clone()),
+    );
+    let denylist_entries = [
+        get_denylist_pda(
+            simulator.campaign_id,
+            &DenylistKey::Identity {
+                identity_hash: solana_certificate.get_identity_hash(),
+            },
+        )
+        .0,
+        get_denylist_pda(simulator.campaign_id, &blocked_claimant).0,
+    ];
+
+    // The entries block the claim wherever they are in the remaining accounts
+    let mut reordered_instruction = claim_instruction.clone();
+    reordered_instruction
+        .accounts
+        .retain(|account| !denylist_entries.contains(&account.pubkey));
+    let mut denylist_accounts = claim_instruction
+        .accounts
+        .iter()
+        .filter(|account| denylist_entries.contains(&account.pubkey))
+        .cloned()
+        .collect::<Vec<_>>();
+    denylist_accounts.reverse();
+    reordered_instruction.accounts.extend(denylist_accounts);
+    assert_eq!(
+        simulator
+            .process_ix(&[reordered_instruction], &vec![&claimant])
+            .await
+            .unwrap_err()
+            .unwrap(),
+        ErrorCode::IdentityBlocked.into_transaction_error(0)
+    );
+
+    // A claim can't skip the check by omitting the entries, even though the denylist only has one
+    let mut unchecked_instruction = claim_instruction.clone();
+    unchecked_instruction
+        .accounts
+        .retain(|account| !denylist_entries.contains(&account.pubkey));
+    assert_eq!(
+        simulator
+            .process_ix(&[unchecked_instruction], &vec![&claimant])
+            .await
+            .unwrap_err()
+            .unwrap(),
+        anchor_lang::error::ErrorCode::AccountNotEnoughKeys.into_transaction_error(0)
+    );
+
+    // Nor by passing the address of another bump of the seeds of an entry, where no entry can be created
+    let (blocked_claimant_entry, blocked_claimant_bump) =
+        get_denylist_pda(simulator.campaign_id, &blocked_claimant);
+    let (non_canonical_entry, non_canonical_bump) = (0..blocked_claimant_bump)
+        .rev()
+        .find_map(|bump| {
+            Pubkey::create_program_address(
+                &[
+                    DENYLIST_SEED,
+                    &get_campaign_seed(simulator.campaign_id),
+                    blocked_claimant.get_kind_seed(),
+                    blocked_claimant.get_key_seed(),
+                    &[bump],
+                ],
+                &crate::id(),
+            )
+            .ok()
+            .map(|pubkey| (pubkey, bump))
+        })
+        .unwrap();
+    let mut non_canonical_instruction = claim_instruction.clone();
+    non_canonical_instruction
+        .accounts
+        .iter_mut()
+        .filter(|account| account.pubkey == blocked_claimant_entry)
+        .for_each(|account| account.pubkey = non_canonical_entry);
+    non_canonical_instruction.data = instruction::Claim {
+        campaign_id: simulator.campaign_id,
+        claim_certificate,
+        bumps: Some(ClaimBumps {
+            denylist_claimant: non_canonical_bump,
+            ..get_claim_bumps(
+                simulator.campaign_id,
+                &ClaimInfo::from(solana_certificate.clone()),
+                &claimant.pubkey(),
+            )
+        }),
+    }
+    .data();
+    assert_eq!(
+        simulator
+            .process_ix(&[non_canonical_instruction], &vec![&claimant])
+            .await
+            .unwrap_err()
+            .unwrap(),
+        ErrorCode::WrongPda.into_transaction_error(0)
+    );
+
+    simulator.unblock(&admin, blocked_claimant).await.unwrap();
+    simulator
+        .process_ix(&[claim_instruction], &vec![&claimant])
+        .await
+        .unwrap();
+}
+
+#[tokio::test]
+pub async fn test_blocked_identity_stays_blocked_after_amending_the_tree() {
+    let dispenser_guard = Keypair::new();
+    let mut simulator = DispenserSimulator::new().await;
+    let admin = copy_keypair(&simulator.genesis_keypair);
+
+    let claimant = Keypair::new();
+    let mut solana_certificate = TestClaimCertificate::random_solana(&claimant.pubkey());
+    solana_certificate.amount = 100;
+    let (merkle_tree, _) = merkleize(vec![ClaimInfo::from(solana_certificate.clone())]);
+
+    let address_lookup_table = simulator.init_lookup_table().await.unwrap();
+    simulator
+        .initialize(
+            merkle_tree.root.clone(),
+            dispenser_guard.pubkey(),
+            address_lookup_table,
+            None,
+            None,
+            None,
+        )
+        .await
+        .unwrap();
+    simulator.setup_treasury(200).await.unwrap();
+    simulator
+        .block(
+            &admin,
+            DenylistKey::Identity {
+                identity_hash: solana_certificate.get_identity_hash(),
+            },
+        )
+        .await
+        .unwrap();
+
+    // The amended leaf has a new hash but the same identity
+    let mut amended_certificate = solana_certificate.clone();
+    amended_certificate.amount = 200;
+    let (amended_merkle_tree, _) = merkleize(vec![ClaimInfo::from(amended_certificate.clone())]);
+    simulator
+        .update_merkle_root(&admin, amended_merkle_tree.root.clone())
+        .await
+        .unwrap();
+    assert_eq!(
+        simulator
+            .claim(
+                &claimant,
+                &amended_certificate,
+                &amended_merkle_tree,
+                None,
+                None,
+                None,
+            )
+            .await
+            .unwrap_err()
+            .unwrap(),
+        ErrorCode::IdentityBlocked.into_transaction_error(
+            amended_certificate.as_instruction_error_index(&amended_merkle_tree)
+        )
+    );
+}
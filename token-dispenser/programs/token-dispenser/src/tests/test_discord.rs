@@ -12,6 +12,7 @@ use {
         },
         ClaimInfo,
         ErrorCode,
+        DEFAULT_CAMPAIGN_ID,
     },
     solana_program_test::tokio,
     solana_sdk::signer::{
@@ -68,7 +69,7 @@ pub async fn test_discord() {
 
     simulator.mint_to_treasury(total_claim_sum).await.unwrap();
     simulator
-        .approve_treasury_delegate(get_config_pda().0, total_claim_sum)
+        .approve_treasury_delegate(get_config_pda(DEFAULT_CAMPAIGN_ID).0, total_claim_sum)
         .await
         .unwrap();
 
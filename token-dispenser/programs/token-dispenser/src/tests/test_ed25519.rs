@@ -9,6 +9,7 @@ use {
                 Ed25519Pubkey,
                 Ed25519TestMessage,
             },
+            starknet::StarknetMessage,
             sui::SuiMessage,
         },
         tests::dispenser_simulator::DispenserSimulator,
@@ -142,6 +143,24 @@ impl Ed25519TestIdentityCertificate<DiscordMessage> {
     }
 }
 
+impl From<Ed25519TestIdentityCertificate<StarknetMessage>> for Identity {
+    fn from(val: Ed25519TestIdentityCertificate<StarknetMessage>) -> Self {
+        Identity::Starknet {
+            address: val.message.get_address(),
+        }
+    }
+}
+
+
+impl Ed25519TestIdentityCertificate<StarknetMessage> {
+    pub fn as_proof_of_identity(&self, verification_instruction_index: u8) -> IdentityCertificate {
+        IdentityCertificate::Starknet {
+            address: self.message.get_address(),
+            verification_instruction_index,
+        }
+    }
+}
+
 #[tokio::test]
 pub async fn test_verify_signed_message_onchain() {
     let signed_message =
@@ -0,0 +1,214 @@
+use {
+    super::{
+        dispenser_simulator::{
+            copy_keypair,
+            DispenserSimulator,
+            IntoTransactionError,
+        },
+        merkleize,
+        test_happy_path::TestClaimCertificate,
+    },
+    crate::{
+        get_config_pda,
+        ClaimInfo,
+        Config,
+        ErrorCode,
+    },
+    anchor_lang::solana_program::program_option::COption,
+    anchor_spl::token::spl_token::error::TokenError::InsufficientFunds,
+    solana_program_test::tokio,
+    solana_sdk::signer::{
+        keypair::Keypair,
+        Signer,
+    },
+};
+
+#[tokio::test]
+pub async fn test_claim_escrow() {
+    let dispenser_guard = Keypair::new();
+    let mut simulator = DispenserSimulator::new().await;
+    simulator.escrow = true;
+    let claimant_1 = Keypair::new();
+
+    let (merkle_tree, mock_offchain_certificates_and_claimants) = simulator
+        .initialize_with_claimants(
+            vec![
+                copy_keypair(&simulator.genesis_keypair),
+                copy_keypair(&claimant_1),
+            ],
+            &dispenser_guard,
+            None,
+        )
+        .await
+        .unwrap();
+    let total_claim_sum = mock_offchain_certificates_and_claimants
+        .iter()
+        .map(|(_, _, amount)| amount)
+        .sum::<u64>();
+
+    // The treasury is owned by the config and holds the deposit, there is no delegation
+    let treasury = simulator.get_treasury();
+    let treasury_account = simulator.get_token_account(treasury).await.unwrap();
+    assert_eq!(
+        treasury_account.owner,
+        get_config_pda(simulator.campaign_id).0
+    );
+    simulator
+        .verify_token_account_data(treasury, total_claim_sum, COption::None, 0)
+        .await
+        .unwrap();
+    simulator
+        .verify_token_account_data(simulator.pyth_treasury, 0, COption::None, 0)
+        .await
+        .unwrap();
+
+    for (claimant, offchain_claim_certificates, _) in &mock_offchain_certificates_and_claimants {
+        for offchain_claim_certificate in offchain_claim_certificates {
+            simulator
+                .claim(
+                    &copy_keypair(claimant),
+                    offchain_claim_certificate,
+                    &merkle_tree,
+                    None,
+                    None,
+                    None,
+                )
+                .await
+                .unwrap();
+        }
+    }
+
+    simulator
+        .verify_token_account_data(treasury, 0, COption::None, 0)
+        .await
+        .unwrap();
+    let config = simulator
+        .get_account_data::<Config>(get_config_pda(simulator.campaign_id).0)
+        .await
+        .unwrap();
+    assert!(config.escrow);
+    assert_eq!(config.treasury, treasury);
+    assert_eq!(config.total_deposited, total_claim_sum);
+    assert_eq!(config.total_claimed, total_claim_sum);
+    assert_eq!(config.get_remaining(), 0);
+}
+
+#[tokio::test]
+pub async fn test_escrow_claims_are_limited_by_deposits() {
+    let dispenser_guard = Keypair::new();
+    let mut simulator = DispenserSimulator::new().await;
+    simulator.escrow = true;
+
+    let claimant = Keypair::new();
+    let offchain_claim_certificate = TestClaimCertificate {
+        amount: 100,
+        ..TestClaimCertificate::random_solana(&claimant.pubkey())
+    };
+    let (merkle_tree, _) = merkleize(vec![ClaimInfo::from(offchain_claim_certificate.clone())]);
+
+    let address_lookup_table = simulator.init_lookup_table().await.unwrap();
+    simulator
+        .initialize(
+            merkle_tree.root.clone(),
+            dispenser_guard.pubkey(),
+            address_lookup_table,
+            None,
+            None,
+            None,
+        )
+        .await
+        .unwrap();
+    simulator.setup_treasury(50).await.unwrap();
+
+    assert_eq!(
+        simulator
+            .claim(
+                &claimant,
+                &offchain_claim_certificate,
+                &merkle_tree,
+                None,
+                None,
+                None
+            )
+            .await
+            .unwrap_err()
+            .unwrap(),
+        InsufficientFunds.into_transaction_error(
+            offchain_claim_certificate.as_instruction_error_index(&merkle_tree)
+        )
+    );
+
+    simulator.setup_treasury(50).await.unwrap();
+    let config = simulator
+        .get_account_data::<Config>(get_config_pda(simulator.campaign_id).0)
+        .await
+        .unwrap();
+    assert_eq!(config.total_deposited, 100);
+    assert_eq!(config.get_remaining(), 100);
+
+    simulator
+        .claim(
+            &claimant,
+            &offchain_claim_certificate,
+            &merkle_tree,
+            None,
+            None,
+            None,
+        )
+        .await
+        .unwrap();
+    let config = simulator
+        .get_account_data::<Config>(get_config_pda(simulator.campaign_id).0)
+        .await
+        .unwrap();
+    assert_eq!(config.total_claimed, 100);
+    assert_eq!(config.get_remaining(), 0);
+}
+
+#[tokio::test]
+pub async fn test_escrow_fails_with_wrong_treasury() {
+    let dispenser_guard = Keypair::new();
+    let mut simulator = DispenserSimulator::new().await;
+    let (merkle_tree, _) = merkleize(vec![ClaimInfo::from(TestClaimCertificate::random_solana(
+        &Keypair::new().pubkey(),
+    ))]);
+    let address_lookup_table = simulator.init_lookup_table().await.unwrap();
+
+    // In escrow mode the treasury has to be owned by the config
+    simulator.escrow = true;
+    let pyth_treasury = simulator.pyth_treasury;
+    assert_eq!(
+        simulator
+            .initialize(
+                merkle_tree.root.clone(),
+                dispenser_guard.pubkey(),
+                address_lookup_table,
+                None,
+                Some(pyth_treasury),
+                None
+            )
+            .await
+            .unwrap_err()
+            .unwrap(),
+        anchor_lang::error::ErrorCode::ConstraintTokenOwner.into_transaction_error(0)
+    );
+
+    // Deposits are only possible into an escrow treasury
+    simulator.escrow = false;
+    simulator
+        .initialize(
+            merkle_tree.root.clone(),
+            dispenser_guard.pubkey(),
+            address_lookup_table,
+            None,
+            None,
+            None,
+        )
+        .await
+        .unwrap();
+    simulator.mint_to_treasury(100).await.unwrap();
+    assert_eq!(
+        simulator.deposit(100).await.unwrap_err().unwrap(),
+        ErrorCode::TreasuryNotEscrowed.into_transaction_error(0)
+    );
+}
@@ -0,0 +1,126 @@
+use {
+    super::dispenser_simulator::{
+        copy_keypair,
+        DispenserSimulator,
+    },
+    crate::{
+        client::events::{
+            decode_events,
+            DispenserEvent,
+        },
+        get_identity_hash,
+        get_receipt_pda,
+        ClaimInfo,
+        DispenserMerkleRoot,
+    },
+    anchor_lang::AnchorSerialize,
+    anchor_spl::associated_token::get_associated_token_address,
+    solana_program_test::tokio,
+    solana_sdk::signer::{
+        keypair::Keypair,
+        Signer,
+    },
+};
+
+#[tokio::test]
+pub async fn test_claim_events() {
+    let dispenser_guard = Keypair::new();
+    let mut simulator = DispenserSimulator::new().await;
+    let claimant_1 = Keypair::new();
+
+    let (merkle_tree, mock_offchain_certificates_and_claimants) = simulator
+        .initialize_with_claimants(
+            vec![
+                copy_keypair(&simulator.genesis_keypair),
+                copy_keypair(&claimant_1),
+            ],
+            &dispenser_guard,
+            None,
+        )
+        .await
+        .unwrap();
+    let merkle_root = DispenserMerkleRoot::from(merkle_tree.root.clone());
+    let mut remaining_balance = mock_offchain_certificates_and_claimants
+        .iter()
+        .map(|(_, _, amount)| amount)
+        .sum::<u64>();
+
+    let mut expected_events = vec![];
+    for (claimant, offchain_claim_certificates, _) in &mock_offchain_certificates_and_claimants {
+        for offchain_claim_certificate in offchain_claim_certificates {
+            simulator
+                .claim(
+                    &copy_keypair(claimant),
+                    offchain_claim_certificate,
+                    &merkle_tree,
+                    None,
+                    None,
+                    None,
+                )
+                .await
+                .unwrap();
+            remaining_balance -= offchain_claim_certificate.amount;
+            expected_events.push((
+                claimant.pubkey(),
+                ClaimInfo::from(offchain_claim_certificate.clone()),
+                remaining_balance,
+            ));
+        }
+    }
+    let clock = simulator.get_clock().await;
+
+    let events: Vec<DispenserEvent> = decode_events(&simulator.logs)
+        .into_iter()
+        .map(|event| event.unwrap())
+        .collect();
+    // Each claim emits `ClaimEvent` then `ClaimEventV2`
+    assert_eq!(events.len(), 2 * expected_events.len());
+    for (event_pair, (claimant, claim_info, remaining_balance)) in
+        events.chunks(2).zip(expected_events.iter())
+    {
+        match &event_pair[0] {
+            DispenserEvent::Claim(event) => {
+                assert_eq!(event.claimant, *claimant);
+                assert_eq!(
+                    event.claim_info.try_to_vec().unwrap(),
+                    claim_info.try_to_vec().unwrap()
+                );
+                assert_eq!(event.remaining_balance, *remaining_balance);
+                assert_eq!(event.amount_received, claim_info.amount);
+            }
+            DispenserEvent::ClaimV2(_) => panic!("expected a ClaimEvent"),
+        }
+
+        match &event_pair[1] {
+            DispenserEvent::ClaimV2(event) => {
+                assert_eq!(event.campaign_id, simulator.campaign_id);
+                assert_eq!(event.claimant, *claimant);
+                assert_eq!(
+                    event.claimant_fund,
+                    get_associated_token_address(claimant, &simulator.mint_keypair.pubkey())
+                );
+                assert_eq!(
+                    event.ecosystem as usize,
+                    claim_info.identity.get_variant_index()
+                );
+                assert_eq!(event.identity_hash, get_identity_hash(&claim_info.identity));
+                assert_eq!(
+                    event.leaf_hash,
+                    merkle_root.hash_leaf(&claim_info.try_to_vec().unwrap())
+                );
+                assert_eq!(
+                    event.receipt,
+                    get_receipt_pda(simulator.campaign_id, &claim_info.identity).0
+                );
+                assert_eq!(event.merkle_root, merkle_root.to_padded_bytes());
+                assert_eq!(event.amount, claim_info.amount);
+                assert_eq!(event.amount_transferred, claim_info.amount);
+                assert_eq!(event.amount_received, claim_info.amount);
+                assert_eq!(event.remaining_balance, *remaining_balance);
+                assert!(event.slot <= clock.slot);
+                assert!(event.unix_timestamp <= clock.unix_timestamp);
+            }
+            DispenserEvent::Claim(_) => panic!("expected a ClaimEventV2"),
+        }
+    }
+}
@@ -0,0 +1,211 @@
+use {
+    super::{
+        dispenser_simulator::{
+            copy_keypair,
+            DispenserSimulator,
+            IntoTransactionError,
+        },
+        merkleize,
+        test_happy_path::{
+            TestClaimCertificate,
+            TestIdentityCertificate,
+        },
+        test_secp256k1::Secp256k1TestIdentityCertificate,
+    },
+    crate::{
+        ecosystems::{
+            evm::EvmPrefixedMessage,
+            get_expected_payload,
+        },
+        get_receipt_pda,
+        ClaimInfo,
+        ErrorCode,
+        Receipt,
+        DEFAULT_CAMPAIGN_ID,
+    },
+    anchor_lang::prelude::Pubkey,
+    pythnet_sdk::hashers::keccak256::Keccak256,
+    solana_program_test::tokio,
+    solana_sdk::signer::{
+        keypair::Keypair,
+        Signer,
+    },
+};
+
+/// An EVM claim certificate whose signed payload expires at `signed_expires_at`,
+/// submitted with `expires_at` in the claim certificate.
+fn evm_with_expiry(
+    claimant: &Pubkey,
+    signed_expires_at: i64,
+    expires_at: Option<i64>,
+) -> TestClaimCertificate {
+    let payload = get_expected_payload(DEFAULT_CAMPAIGN_ID, claimant)
+        + &format!("Expires at: {}\n", signed_expires_at);
+    TestClaimCertificate {
+        amount: TestClaimCertificate::random_amount(),
+        off_chain_proof_of_identity: TestIdentityCertificate::Evm(
+            Secp256k1TestIdentityCertificate::<EvmPrefixedMessage, Keccak256>::random_with_payload(
+                &payload,
+            ),
+        ),
+        expires_at,
+        nonce: None,
+    }
+}
+
+#[tokio::test]
+pub async fn test_claim_with_expiring_authorization() {
+    let dispenser_guard = Keypair::new();
+    let mut simulator = DispenserSimulator::new().await;
+    let now = simulator.get_clock().await.unix_timestamp;
+
+    let claimant = Keypair::new();
+    let valid = evm_with_expiry(&claimant.pubkey(), now + 3600, Some(now + 3600));
+    let expired = evm_with_expiry(&claimant.pubkey(), now - 1, Some(now - 1));
+    let missing_expiry = evm_with_expiry(&claimant.pubkey(), now + 3600, None);
+    let (merkle_tree, _) = merkleize(
+        [&valid, &expired, &missing_expiry]
+            .iter()
+            .map(|item| (*item).clone().into())
+            .collect::<Vec<ClaimInfo>>(),
+    );
+
+    let address_lookup_table = simulator.init_lookup_table().await.unwrap();
+    simulator
+        .initialize(
+            merkle_tree.root.clone(),
+            dispenser_guard.pubkey(),
+            address_lookup_table,
+            None,
+            None,
+            None,
+        )
+        .await
+        .unwrap();
+    simulator
+        .setup_treasury(valid.amount + expired.amount + missing_expiry.amount)
+        .await
+        .unwrap();
+
+    assert_eq!(
+        simulator
+            .claim(&claimant, &expired, &merkle_tree, None, None, None)
+            .await
+            .unwrap_err()
+            .unwrap(),
+        ErrorCode::AuthorizationExpired
+            .into_transaction_error(expired.as_instruction_error_index(&merkle_tree))
+    );
+
+    // The signed deadline must be provided to rebuild the payload
+    assert_eq!(
+        simulator
+            .claim(&claimant, &missing_expiry, &merkle_tree, None, None, None)
+            .await
+            .unwrap_err()
+            .unwrap(),
+        ErrorCode::SignatureVerificationWrongPayload
+            .into_transaction_error(missing_expiry.as_instruction_error_index(&merkle_tree))
+    );
+
+    simulator
+        .claim(&claimant, &valid, &merkle_tree, None, None, None)
+        .await
+        .unwrap();
+}
+
+/// An EVM claim certificate of the identity of `secret` whose signed payload includes `nonce`.
+fn evm_with_nonce(
+    claimant: &Pubkey,
+    secret: &libsecp256k1::SecretKey,
+    amount: u64,
+    nonce: u64,
+) -> TestClaimCertificate {
+    let payload =
+        get_expected_payload(DEFAULT_CAMPAIGN_ID, claimant) + &format!("Nonce: {}\n", nonce);
+    TestClaimCertificate {
+        amount,
+        off_chain_proof_of_identity: TestIdentityCertificate::Evm(
+            Secp256k1TestIdentityCertificate::<EvmPrefixedMessage, Keccak256>::new_with_payload(
+                secret, &payload,
+            ),
+        ),
+        expires_at: None,
+        nonce: Some(nonce),
+    }
+}
+
+#[tokio::test]
+pub async fn test_claim_with_nonce() {
+    let dispenser_guard = Keypair::new();
+    let mut simulator = DispenserSimulator::new().await;
+
+    let claimant = Keypair::new();
+    let secret = libsecp256k1::SecretKey::random(&mut rand::thread_rng());
+    // The allocation of the identity is amended from 100 to 150 after its first claim
+    let initial = evm_with_nonce(&claimant.pubkey(), &secret, 100, 0);
+    let amended = evm_with_nonce(&claimant.pubkey(), &secret, 150, 1);
+    let replayed = TestClaimCertificate {
+        amount: 150,
+        ..initial.clone()
+    };
+    let (merkle_tree, _) = merkleize(vec![initial.clone().into()]);
+    let (amended_merkle_tree, _) = merkleize(vec![amended.clone().into()]);
+
+    let address_lookup_table = simulator.init_lookup_table().await.unwrap();
+    simulator
+        .initialize(
+            merkle_tree.root.clone(),
+            dispenser_guard.pubkey(),
+            address_lookup_table,
+            None,
+            None,
+            None,
+        )
+        .await
+        .unwrap();
+    simulator.setup_treasury(1000).await.unwrap();
+
+    simulator
+        .claim(&claimant, &initial, &merkle_tree, None, None, None)
+        .await
+        .unwrap();
+    let admin = copy_keypair(&simulator.genesis_keypair);
+    simulator
+        .update_merkle_root(&admin, amended_merkle_tree.root.clone())
+        .await
+        .unwrap();
+
+    // The signature of the first claim can't be replayed for the top-up
+    assert_eq!(
+        simulator
+            .claim(&claimant, &replayed, &amended_merkle_tree, None, None, None)
+            .await
+            .unwrap_err()
+            .unwrap(),
+        ErrorCode::NonceMismatch
+            .into_transaction_error(replayed.as_instruction_error_index(&amended_merkle_tree))
+    );
+
+    simulator
+        .claim(&claimant, &amended, &amended_merkle_tree, None, None, None)
+        .await
+        .unwrap();
+    assert_eq!(
+        simulator
+            .get_account_data::<Receipt>(
+                get_receipt_pda(DEFAULT_CAMPAIGN_ID, &ClaimInfo::from(amended).identity).0
+            )
+            .await
+            .unwrap(),
+        Receipt {
+            amount_claimed: 150,
+            bump:           get_receipt_pda(
+                DEFAULT_CAMPAIGN_ID,
+                &ClaimInfo::from(initial).identity
+            )
+            .1,
+            nonce:          2,
+        }
+    );
+}
@@ -0,0 +1,120 @@
+use {
+    super::{
+        dispenser_simulator::{
+            copy_keypair,
+            DispenserSimulator,
+            IntoTransactionError,
+        },
+        merkleize,
+        test_happy_path::TestClaimCertificate,
+    },
+    crate::{
+        get_config_pda,
+        ClaimInfo,
+        Config,
+        ErrorCode,
+        Identity,
+    },
+    solana_program_test::tokio,
+    solana_sdk::signer::{
+        keypair::Keypair,
+        Signer,
+    },
+};
+
+#[tokio::test]
+pub async fn test_claim_requires_guard_signature() {
+    let dispenser_guard = Keypair::new();
+    let mut simulator = DispenserSimulator::new().await;
+    let admin = copy_keypair(&simulator.genesis_keypair);
+
+    let claimant = Keypair::new();
+    let solana_certificate = TestClaimCertificate::random_solana(&claimant.pubkey());
+    let evm_certificate = TestClaimCertificate::random_evm(&claimant.pubkey());
+    let evm_identity = ClaimInfo::from(evm_certificate.clone()).identity;
+    let (merkle_tree, _) = merkleize(vec![
+        ClaimInfo::from(solana_certificate.clone()),
+        ClaimInfo::from(evm_certificate.clone()),
+    ]);
+
+    let address_lookup_table = simulator.init_lookup_table().await.unwrap();
+    simulator
+        .initialize(
+            merkle_tree.root.clone(),
+            dispenser_guard.pubkey(),
+            address_lookup_table,
+            None,
+            None,
+            None,
+        )
+        .await
+        .unwrap();
+    simulator
+        .setup_treasury(solana_certificate.amount + evm_certificate.amount)
+        .await
+        .unwrap();
+
+    // Only the admin can set the policy
+    let evm_only = 1 << evm_identity.get_variant_index();
+    assert_eq!(
+        simulator
+            .set_guard_signature_policy(&Keypair::new(), evm_only)
+            .await
+            .unwrap_err()
+            .unwrap(),
+        anchor_lang::error::ErrorCode::ConstraintHasOne.into_transaction_error(0)
+    );
+    simulator
+        .set_guard_signature_policy(&admin, evm_only)
+        .await
+        .unwrap();
+    let config = simulator
+        .get_account_data::<Config>(get_config_pda(simulator.campaign_id).0)
+        .await
+        .unwrap();
+    assert!(config.requires_guard_signature(&evm_identity));
+    assert!(!config.requires_guard_signature(&Identity::Solana {
+        pubkey: claimant.pubkey().into(),
+    }));
+
+    // Ecosystems outside of the policy don't need the guard
+    simulator
+        .claim(
+            &claimant,
+            &solana_certificate,
+            &merkle_tree,
+            None,
+            None,
+            None,
+        )
+        .await
+        .unwrap();
+
+    assert_eq!(
+        simulator
+            .claim(&claimant, &evm_certificate, &merkle_tree, None, None, None)
+            .await
+            .unwrap_err()
+            .unwrap(),
+        ErrorCode::GuardSignatureRequired
+            .into_transaction_error(evm_certificate.as_instruction_error_index(&merkle_tree))
+    );
+
+    // Another signer doesn't count as the guard
+    simulator.guard_cosigner = Some(Keypair::new());
+    assert_eq!(
+        simulator
+            .claim(&claimant, &evm_certificate, &merkle_tree, None, None, None)
+            .await
+            .unwrap_err()
+            .unwrap(),
+        ErrorCode::GuardSignatureRequired
+            .into_transaction_error(evm_certificate.as_instruction_error_index(&merkle_tree))
+    );
+
+    simulator.guard_cosigner = Some(copy_keypair(&dispenser_guard));
+    simulator
+        .claim(&claimant, &evm_certificate, &merkle_tree, None, None, None)
+        .await
+        .unwrap();
+}
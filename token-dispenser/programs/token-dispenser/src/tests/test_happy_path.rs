@@ -1,20 +1,27 @@
 use {
     super::{
         dispenser_simulator::DispenserSimulator,
-        test_cosmos::Sha256,
+        test_cosmos::{
+            CosmosLedgerMessage,
+            Sha256,
+        },
         test_ed25519::Ed25519TestIdentityCertificate,
+        test_passkey::PasskeyTestIdentityCertificate,
         test_secp256k1::Secp256k1TestIdentityCertificate,
         test_solana::SolanaTestIdentityCertificate,
     },
     crate::{
         ecosystems::{
             aptos::AptosMessage,
+            cardano::CardanoMessage,
             cosmos::CosmosMessage,
             discord::DiscordMessage,
             evm::EvmPrefixedMessage,
+            starknet::StarknetMessage,
             sui::SuiMessage,
         },
         get_config_pda,
+        get_identity_hash,
         get_receipt_pda,
         tests::{
             dispenser_simulator::{
@@ -26,24 +33,28 @@ use {
         ClaimCertificate,
         ClaimInfo,
         Config,
+        DispenserMerklePath,
         ErrorCode,
         Identity,
         IdentityCertificate,
+        Receipt,
         SolanaHasher,
+        CONFIG_VERSION,
+        DEFAULT_CAMPAIGN_ID,
+        HASH_SIZE,
+        IDENTITY_VARIANTS,
     },
     anchor_lang::{
         prelude::Pubkey,
         solana_program::program_option::COption,
+        AccountDeserialize,
         AnchorDeserialize,
         AnchorSerialize,
     },
     anchor_spl::associated_token::get_associated_token_address,
     pythnet_sdk::{
         accumulators::{
-            merkle::{
-                MerklePath,
-                MerkleTree,
-            },
+            merkle::MerkleTree,
             Accumulator,
         },
         hashers::keccak256::Keccak256,
@@ -75,6 +86,8 @@ use {
 pub struct TestClaimCertificate {
     pub amount:                      u64,
     pub off_chain_proof_of_identity: TestIdentityCertificate,
+    pub expires_at:                  Option<i64>,
+    pub nonce:                       Option<u64>,
 }
 
 pub const MAX_AMOUNT: u64 = 1000;
@@ -86,6 +99,8 @@ impl TestClaimCertificate {
     pub fn random_evm(claimant: &Pubkey) -> Self {
         Self {
             amount:                      Self::random_amount(),
+            expires_at:                  None,
+            nonce:                       None,
             off_chain_proof_of_identity: TestIdentityCertificate::Evm(
                 Secp256k1TestIdentityCertificate::<EvmPrefixedMessage, Keccak256>::random(claimant),
             ),
@@ -95,24 +110,76 @@ impl TestClaimCertificate {
     pub fn random_cosmos(claimant: &Pubkey) -> Self {
         Self {
             amount:                      Self::random_amount(),
+            expires_at:                  None,
+            nonce:                       None,
             off_chain_proof_of_identity: TestIdentityCertificate::Cosmos(
                 Secp256k1TestIdentityCertificate::<CosmosMessage, Sha256>::random(claimant),
             ),
         }
     }
 
+    pub fn random_cosmos_sign_doc(claimant: &Pubkey) -> Self {
+        Self {
+            amount:                      Self::random_amount(),
+            expires_at:                  None,
+            nonce:                       None,
+            off_chain_proof_of_identity: TestIdentityCertificate::CosmosSignDoc(
+                Secp256k1TestIdentityCertificate::<CosmosLedgerMessage, Sha256>::random(claimant),
+            ),
+        }
+    }
+
     pub fn random_discord(claimant: &Pubkey, signer: &ed25519_dalek::Keypair) -> Self {
         Self {
             amount:                      Self::random_amount(),
+            expires_at:                  None,
+            nonce:                       None,
             off_chain_proof_of_identity: TestIdentityCertificate::Discord(
                 Ed25519TestIdentityCertificate::<DiscordMessage>::new(claimant, signer),
             ),
         }
     }
 
+    pub fn random_starknet(claimant: &Pubkey, signer: &ed25519_dalek::Keypair) -> Self {
+        Self {
+            amount:                      Self::random_amount(),
+            expires_at:                  None,
+            nonce:                       None,
+            off_chain_proof_of_identity: TestIdentityCertificate::Starknet(
+                Ed25519TestIdentityCertificate::<StarknetMessage>::new(claimant, signer),
+            ),
+        }
+    }
+
+    pub fn random_cardano(claimant: &Pubkey) -> Self {
+        Self {
+            amount:                      Self::random_amount(),
+            expires_at:                  None,
+            nonce:                       None,
+            off_chain_proof_of_identity: TestIdentityCertificate::Cardano(
+                Ed25519TestIdentityCertificate::<CardanoMessage>::random_with_signer_address(
+                    claimant,
+                ),
+            ),
+        }
+    }
+
+    pub fn random_passkey(claimant: &Pubkey) -> Self {
+        Self {
+            amount:                      Self::random_amount(),
+            expires_at:                  None,
+            nonce:                       None,
+            off_chain_proof_of_identity: TestIdentityCertificate::Passkey(
+                PasskeyTestIdentityCertificate::random(claimant),
+            ),
+        }
+    }
+
     pub fn random_aptos(claimant: &Pubkey) -> Self {
         Self {
             amount:                      Self::random_amount(),
+            expires_at:                  None,
+            nonce:                       None,
             off_chain_proof_of_identity: TestIdentityCertificate::Aptos(
                 Ed25519TestIdentityCertificate::<AptosMessage>::random(claimant),
             ),
@@ -122,6 +189,8 @@ impl TestClaimCertificate {
     pub fn random_sui(claimant: &Pubkey) -> Self {
         Self {
             amount:                      Self::random_amount(),
+            expires_at:                  None,
+            nonce:                       None,
             off_chain_proof_of_identity: TestIdentityCertificate::Sui(
                 Ed25519TestIdentityCertificate::<SuiMessage>::random(claimant),
             ),
@@ -131,6 +200,8 @@ impl TestClaimCertificate {
     pub fn random_solana(claimant: &Pubkey) -> Self {
         Self {
             amount:                      Self::random_amount(),
+            expires_at:                  None,
+            nonce:                       None,
             off_chain_proof_of_identity: TestIdentityCertificate::Solana(
                 SolanaTestIdentityCertificate::new(claimant),
             ),
@@ -140,11 +211,24 @@ impl TestClaimCertificate {
     pub fn random_injective(claimant: &Pubkey) -> Self {
         Self {
             amount:                      Self::random_amount(),
+            expires_at:                  None,
+            nonce:                       None,
             off_chain_proof_of_identity: TestIdentityCertificate::Injective(
                 Secp256k1TestIdentityCertificate::<EvmPrefixedMessage, Keccak256>::random(claimant),
             ),
         }
     }
+
+    pub fn random_injective_adr036(claimant: &Pubkey) -> Self {
+        Self {
+            amount:                      Self::random_amount(),
+            expires_at:                  None,
+            nonce:                       None,
+            off_chain_proof_of_identity: TestIdentityCertificate::InjectiveAdr036(
+                Secp256k1TestIdentityCertificate::<CosmosMessage, Keccak256>::random(claimant),
+            ),
+        }
+    }
 }
 
 impl From<TestClaimCertificate> for ClaimInfo {
@@ -161,18 +245,27 @@ impl TestClaimCertificate {
         &self,
         merkle_tree: &MerkleTree<SolanaHasher>,
         index: u8,
-        proof_of_inclusion_override: Option<MerklePath<SolanaHasher>>,
+        proof_of_inclusion_override: Option<DispenserMerklePath>,
     ) -> (ClaimCertificate, Option<Instruction>) {
         let option_instruction = match &self.off_chain_proof_of_identity {
             TestIdentityCertificate::Evm(evm) => Some(evm.as_instruction(index, true)),
             TestIdentityCertificate::Discord(discord) => Some(discord.as_instruction(index, true)),
             TestIdentityCertificate::Cosmos(_) => None,
+            TestIdentityCertificate::CosmosSignDoc(_) => None,
             TestIdentityCertificate::Aptos(aptos) => Some(aptos.as_instruction(index, true)),
             TestIdentityCertificate::Sui(sui) => Some(sui.as_instruction(index, true)),
             TestIdentityCertificate::Solana(_) => None,
             TestIdentityCertificate::Injective(injective) => {
                 Some(injective.as_instruction(index, true))
             }
+            TestIdentityCertificate::InjectiveAdr036(injective) => {
+                Some(injective.as_instruction(index, true))
+            }
+            TestIdentityCertificate::Starknet(starknet) => {
+                Some(starknet.as_instruction(index, true))
+            }
+            TestIdentityCertificate::Cardano(cardano) => Some(cardano.as_instruction(index, true)),
+            TestIdentityCertificate::Passkey(passkey) => Some(passkey.as_instruction(index, true)),
         };
         (
             ClaimCertificate {
@@ -181,8 +274,11 @@ impl TestClaimCertificate {
                 proof_of_inclusion: proof_of_inclusion_override.unwrap_or(
                     merkle_tree
                         .prove(&Into::<ClaimInfo>::into(self.clone()).try_to_vec().unwrap())
-                        .unwrap(),
+                        .unwrap()
+                        .into(),
                 ),
+                expires_at:         self.expires_at,
+                nonce:              self.nonce,
             },
             option_instruction,
         )
@@ -194,11 +290,16 @@ impl From<TestIdentityCertificate> for Identity {
         match val {
             TestIdentityCertificate::Evm(evm) => evm.into(),
             TestIdentityCertificate::Cosmos(cosmos) => cosmos.into(),
+            TestIdentityCertificate::CosmosSignDoc(cosmos) => cosmos.into(),
             TestIdentityCertificate::Discord(discord) => discord.into(),
             TestIdentityCertificate::Aptos(aptos) => aptos.into(),
             TestIdentityCertificate::Sui(sui) => sui.into(),
             TestIdentityCertificate::Solana(solana) => solana.into(),
             TestIdentityCertificate::Injective(injective) => injective.into(),
+            TestIdentityCertificate::InjectiveAdr036(injective) => injective.into(),
+            TestIdentityCertificate::Starknet(starknet) => starknet.into(),
+            TestIdentityCertificate::Cardano(cardano) => cardano.into(),
+            TestIdentityCertificate::Passkey(passkey) => passkey.into(),
         }
     }
 }
@@ -208,6 +309,7 @@ impl TestIdentityCertificate {
         match self {
             Self::Evm(evm) => evm.as_proof_of_identity(verification_instruction_index),
             Self::Cosmos(cosmos) => cosmos.clone().into(),
+            Self::CosmosSignDoc(cosmos) => cosmos.as_sign_doc_proof_of_identity(),
             Self::Discord(discord) => discord.as_proof_of_identity(verification_instruction_index),
             Self::Aptos(aptos) => aptos.as_proof_of_identity(verification_instruction_index),
             Self::Sui(sui) => sui.as_proof_of_identity(verification_instruction_index),
@@ -215,6 +317,14 @@ impl TestIdentityCertificate {
             Self::Injective(injective) => {
                 injective.as_proof_of_identity(verification_instruction_index)
             }
+            Self::InjectiveAdr036(injective) => {
+                injective.as_proof_of_identity(verification_instruction_index)
+            }
+            Self::Starknet(starknet) => {
+                starknet.as_proof_of_identity(verification_instruction_index)
+            }
+            Self::Cardano(cardano) => cardano.as_proof_of_identity(verification_instruction_index),
+            Self::Passkey(passkey) => passkey.as_proof_of_identity(verification_instruction_index),
         }
     }
 }
@@ -226,6 +336,10 @@ impl TestClaimCertificate {
             None => 0,
         }
     }
+
+    pub fn get_identity_hash(&self) -> [u8; HASH_SIZE] {
+        get_identity_hash(&ClaimInfo::from(self.clone()).identity)
+    }
 }
 
 #[derive(Clone)]
@@ -233,10 +347,15 @@ pub enum TestIdentityCertificate {
     Evm(Secp256k1TestIdentityCertificate<EvmPrefixedMessage, Keccak256>),
     Discord(Ed25519TestIdentityCertificate<DiscordMessage>),
     Cosmos(Secp256k1TestIdentityCertificate<CosmosMessage, Sha256>),
+    CosmosSignDoc(Secp256k1TestIdentityCertificate<CosmosLedgerMessage, Sha256>),
     Aptos(Ed25519TestIdentityCertificate<AptosMessage>),
     Sui(Ed25519TestIdentityCertificate<SuiMessage>),
     Solana(SolanaTestIdentityCertificate),
     Injective(Secp256k1TestIdentityCertificate<EvmPrefixedMessage, Keccak256>),
+    InjectiveAdr036(Secp256k1TestIdentityCertificate<CosmosMessage, Keccak256>),
+    Starknet(Ed25519TestIdentityCertificate<StarknetMessage>),
+    Cardano(Ed25519TestIdentityCertificate<CardanoMessage>),
+    Passkey(PasskeyTestIdentityCertificate),
 }
 
 #[tokio::test]
@@ -257,7 +376,7 @@ pub async fn test_happy_path() {
 
     let (merkle_tree, merkle_items_serialized) = merkleize(merkle_items);
 
-    let (config_pubkey, config_bump) = get_config_pda();
+    let (config_pubkey, config_bump) = get_config_pda(DEFAULT_CAMPAIGN_ID);
     let treasury = simulator.pyth_treasury;
 
     simulator
@@ -284,18 +403,31 @@ pub async fn test_happy_path() {
 
     let expected_target_config = Config {
         bump: config_bump,
-        merkle_root: merkle_tree.root.clone(),
+        merkle_root: merkle_tree.root.clone().into(),
         dispenser_guard: dispenser_guard.pubkey(),
         mint: simulator.mint_keypair.pubkey(),
         treasury,
         address_lookup_table,
         funder: simulator.genesis_keypair.pubkey(),
         max_transfer: u64::MAX,
+        escrow: false,
+        total_deposited: 0,
+        total_claimed: 0,
+        claim_count: 0,
+        claimed_per_identity: [0; IDENTITY_VARIANTS],
+        global_cap: None,
+        max_per_claimant: None,
+        admin: simulator.genesis_keypair.pubkey(),
+        rate_limit: None,
+        denylist_len: 0,
+        guard_policy: 0,
+        legacy_receipts: 0,
+        version: CONFIG_VERSION,
     };
 
 
     let config_account: Account = simulator.get_account(config_pubkey).await.unwrap();
-    let config_data: Config = Config::try_from_slice(&config_account.data[8..]).unwrap();
+    let config_data = Config::try_deserialize(&mut config_account.data.as_slice()).unwrap();
     assert_eq!(expected_target_config, config_data);
     let claim_sum = mock_offchain_certificates
         .iter()
@@ -310,7 +442,13 @@ pub async fn test_happy_path() {
     // verify receipt pdas don't exist
     for serialized_item in &merkle_items_serialized {
         assert!(simulator
-            .get_account(get_receipt_pda(serialized_item).0)
+            .get_account(
+                get_receipt_pda(
+                    simulator.campaign_id,
+                    &ClaimInfo::try_from_slice(serialized_item).unwrap().identity
+                )
+                .0
+            )
             .await
             .is_none());
     }
@@ -324,7 +462,7 @@ pub async fn test_happy_path() {
 
     // approve total claim sum amount
     simulator
-        .approve_treasury_delegate(get_config_pda().0, claim_sum)
+        .approve_treasury_delegate(get_config_pda(DEFAULT_CAMPAIGN_ID).0, claim_sum)
         .await
         .unwrap();
 
@@ -437,11 +575,18 @@ pub async fn assert_claim_receipts_exist(
     simulator: &mut DispenserSimulator,
 ) {
     for serialized_item in claimed_items_serialized {
+        let claim_info = ClaimInfo::try_from_slice(serialized_item).unwrap();
         let receipt_account: Account = simulator
-            .get_account(get_receipt_pda(serialized_item).0)
+            .get_account(get_receipt_pda(simulator.campaign_id, &claim_info.identity).0)
             .await
             .unwrap();
 
         assert_eq!(receipt_account.owner, crate::id());
+        assert_eq!(
+            Receipt::try_deserialize(&mut receipt_account.data.as_slice())
+                .unwrap()
+                .amount_claimed,
+            claim_info.amount
+        );
     }
 }
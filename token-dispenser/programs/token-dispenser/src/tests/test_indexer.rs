@@ -0,0 +1,231 @@
+use {
+    super::{
+        dispenser_simulator::{
+            copy_keypair,
+            DispenserSimulator,
+        },
+        merkleize,
+        test_happy_path::TestClaimCertificate,
+    },
+    crate::{
+        client::{
+            events::{
+                decode_event,
+                DispenserEvent,
+            },
+            indexer::ClaimIndex,
+        },
+        ClaimInfo,
+        DEFAULT_CAMPAIGN_ID,
+        IDENTITY_VARIANTS,
+    },
+    anchor_lang::AnchorSerialize,
+    solana_program_test::tokio,
+    solana_sdk::signer::{
+        keypair::Keypair,
+        Signer,
+    },
+};
+
+#[tokio::test]
+pub async fn test_index_claim_events() {
+    let dispenser_guard = Keypair::new();
+    let mut simulator = DispenserSimulator::new().await;
+    let claimant_1 = Keypair::new();
+
+    let (merkle_tree, mock_offchain_certificates_and_claimants) = simulator
+        .initialize_with_claimants(
+            vec![
+                copy_keypair(&simulator.genesis_keypair),
+                copy_keypair(&claimant_1),
+            ],
+            &dispenser_guard,
+            None,
+        )
+        .await
+        .unwrap();
+
+    // Only the first claimant claims
+    let (claimant, offchain_claim_certificates, claimed_amount) =
+        &mock_offchain_certificates_and_claimants[0];
+    let mut expected_claimed_per_ecosystem = [0u64; IDENTITY_VARIANTS];
+    for offchain_claim_certificate in offchain_claim_certificates {
+        simulator
+            .claim(
+                &copy_keypair(claimant),
+                offchain_claim_certificate,
+                &merkle_tree,
+                None,
+                None,
+                None,
+            )
+            .await
+            .unwrap();
+        expected_claimed_per_ecosystem[ClaimInfo::from(offchain_claim_certificate.clone())
+            .identity
+            .get_variant_index()] += offchain_claim_certificate.amount;
+    }
+
+    let mut index = ClaimIndex::default();
+    index.ingest_logs(&simulator.logs);
+    let claim_index = index.get_campaign(simulator.campaign_id).unwrap();
+
+    assert_eq!(
+        claim_index.claimed_per_ecosystem,
+        expected_claimed_per_ecosystem
+    );
+    assert_eq!(claim_index.total_claimed, *claimed_amount);
+    assert_eq!(
+        claim_index.claim_count,
+        offchain_claim_certificates.len() as u64
+    );
+    let treasury = simulator
+        .get_token_account(simulator.get_treasury())
+        .await
+        .unwrap();
+    assert_eq!(claim_index.remaining_balance, Some(treasury.amount));
+    for offchain_claim_certificate in offchain_claim_certificates {
+        let claim_info = ClaimInfo::from(offchain_claim_certificate.clone());
+        let indexed_claim = claim_index.get_claim(&claim_info.identity).unwrap();
+        assert_eq!(indexed_claim.claimant, claimant.pubkey());
+        assert_eq!(indexed_claim.amount_claimed, claim_info.amount);
+    }
+
+    // The leaves of the second claimant are the only unclaimed ones
+    let leaves: Vec<ClaimInfo> = mock_offchain_certificates_and_claimants
+        .iter()
+        .flat_map(|(_, offchain_claim_certificates, _)| offchain_claim_certificates)
+        .map(|offchain_claim_certificate| ClaimInfo::from(offchain_claim_certificate.clone()))
+        .collect();
+    let expected_unclaimed: Vec<Vec<u8>> = mock_offchain_certificates_and_claimants[1]
+        .1
+        .iter()
+        .map(|offchain_claim_certificate| {
+            ClaimInfo::from(offchain_claim_certificate.clone())
+                .try_to_vec()
+                .unwrap()
+        })
+        .collect();
+    let unclaimed: Vec<Vec<u8>> = claim_index
+        .get_unclaimed(&leaves)
+        .iter()
+        .map(|leaf| leaf.try_to_vec().unwrap())
+        .collect();
+    assert_eq!(unclaimed, expected_unclaimed);
+
+    // Ingesting the same transactions again from a JSONL dump doesn't change the totals
+    let logs_json = serde_json::to_string(&simulator.logs).unwrap();
+    let jsonl = format!(
+        "{}\n\n{{\"slot\":1,\"meta\":{{\"logMessages\":{}}}}}\n",
+        logs_json, logs_json
+    );
+    let mut jsonl_index = ClaimIndex::default();
+    jsonl_index.ingest_jsonl(jsonl.as_bytes()).unwrap();
+    let jsonl_claim_index = jsonl_index.get_campaign(simulator.campaign_id).unwrap();
+    assert_eq!(
+        jsonl_claim_index.claimed_per_ecosystem,
+        expected_claimed_per_ecosystem
+    );
+    assert_eq!(jsonl_claim_index.total_claimed, *claimed_amount);
+    assert_eq!(jsonl_claim_index.claim_count, claim_index.claim_count);
+    assert_eq!(
+        jsonl_claim_index.remaining_balance,
+        claim_index.remaining_balance
+    );
+
+    assert!(ClaimIndex::default()
+        .ingest_jsonl("not json\n".as_bytes())
+        .is_err());
+}
+
+#[tokio::test]
+pub async fn test_index_claim_events_by_campaign() {
+    let dispenser_guard = Keypair::new();
+    let mut simulator = DispenserSimulator::new().await;
+    let claimant = Keypair::new();
+    let address_lookup_table = simulator.init_lookup_table().await.unwrap();
+
+    // The same identity claims from the default campaign and from campaign 2
+    let mut claimed_per_campaign = vec![];
+    for (campaign_id, amount) in [(DEFAULT_CAMPAIGN_ID, 100), (2, 250)] {
+        simulator.campaign_id = campaign_id;
+        let mut solana = TestClaimCertificate::random_solana(&claimant.pubkey());
+        solana.amount = amount;
+        let (merkle_tree, _) = merkleize(vec![solana.clone().into()]);
+        simulator
+            .initialize(
+                merkle_tree.root.clone(),
+                dispenser_guard.pubkey(),
+                address_lookup_table,
+                None,
+                None,
+                None,
+            )
+            .await
+            .unwrap();
+        simulator.setup_treasury(solana.amount).await.unwrap();
+        let claim_logs_start = simulator.logs.len();
+        simulator
+            .claim(&claimant, &solana, &merkle_tree, None, None, None)
+            .await
+            .unwrap();
+        claimed_per_campaign.push((
+            campaign_id,
+            ClaimInfo::from(solana),
+            simulator.logs[claim_logs_start..].to_vec(),
+        ));
+    }
+
+    let mut claim_index = ClaimIndex::default();
+    claim_index.ingest_logs(&simulator.logs);
+    assert_eq!(claim_index.campaigns().count(), 2);
+    assert!(claim_index.undecodable_events.is_empty());
+    for (campaign_id, claim_info, _) in &claimed_per_campaign {
+        let campaign_claim_index = claim_index.get_campaign(*campaign_id).unwrap();
+        assert_eq!(campaign_claim_index.total_claimed, claim_info.amount);
+        assert_eq!(campaign_claim_index.claim_count, 1);
+        assert_eq!(
+            campaign_claim_index.get_amount_claimed(&claim_info.identity),
+            claim_info.amount
+        );
+    }
+
+    // Logs from before `ClaimEventV2` existed only have `ClaimEvent`s, which belong to the default campaign.
+    // Both claims are then the ones of a single identity whose allocation was topped up from 100 to 250
+    let legacy_logs: Vec<&String> = simulator
+        .logs
+        .iter()
+        .filter(|log_message| {
+            !matches!(
+                log_message.strip_prefix("Program data: ").map(decode_event),
+                Some(Ok(DispenserEvent::ClaimV2(_)))
+            )
+        })
+        .collect();
+    let mut legacy_claim_index = ClaimIndex::default();
+    legacy_claim_index.ingest_logs(&legacy_logs);
+    assert_eq!(legacy_claim_index.campaigns().count(), 1);
+    let default_claim_index = legacy_claim_index
+        .get_campaign(DEFAULT_CAMPAIGN_ID)
+        .unwrap();
+    assert_eq!(default_claim_index.claim_count, 2);
+    assert_eq!(default_claim_index.total_claimed, 250);
+
+    // The claim from campaign 2 is in a failed transaction of the JSONL dump, e.g. a later instruction failed
+    let jsonl = format!(
+        "{{\"meta\":{{\"err\":null,\"logMessages\":{}}}}}\n{{\"meta\":{{\"err\":{{\"InstructionError\":[2,{{\"Custom\":0}}]}},\"logMessages\":{}}}}}\n",
+        serde_json::to_string(&claimed_per_campaign[0].2).unwrap(),
+        serde_json::to_string(&claimed_per_campaign[1].2).unwrap(),
+    );
+    let mut jsonl_claim_index = ClaimIndex::default();
+    jsonl_claim_index.ingest_jsonl(jsonl.as_bytes()).unwrap();
+    assert_eq!(jsonl_claim_index.campaigns().count(), 1);
+    assert_eq!(
+        jsonl_claim_index
+            .get_campaign(DEFAULT_CAMPAIGN_ID)
+            .unwrap()
+            .total_claimed,
+        100
+    );
+    assert!(jsonl_claim_index.get_campaign(2).is_none());
+}
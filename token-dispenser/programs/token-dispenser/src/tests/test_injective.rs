@@ -0,0 +1,113 @@
+use {
+    super::{
+        dispenser_simulator::DispenserSimulator,
+        test_secp256k1::{
+            construct_evm_pubkey,
+            Secp256k1TestIdentityCertificate,
+        },
+    },
+    crate::{
+        ecosystems::{
+            cosmos::{
+                CosmosBech32Address,
+                CosmosMessage,
+            },
+            get_expected_payload,
+            secp256k1::{
+                EvmPubkey,
+                Secp256k1TestMessage,
+            },
+        },
+        ErrorCode,
+        Identity,
+        IdentityCertificate,
+        DEFAULT_CAMPAIGN_ID,
+    },
+    anchor_lang::{
+        error,
+        prelude::Pubkey,
+    },
+    pythnet_sdk::hashers::keccak256::Keccak256,
+    solana_program_test::tokio,
+    std::marker::PhantomData,
+};
+
+/**
+ * Keplr signs Injective ADR036 messages with eth_secp256k1, i.e. the JSON sign doc is hashed with Keccak256
+ * instead of Sha256. This means the signature can be checked by the secp256k1 program just like EVM signatures.
+ */
+impl From<Secp256k1TestIdentityCertificate<CosmosMessage, Keccak256>> for Identity {
+    fn from(val: Secp256k1TestIdentityCertificate<CosmosMessage, Keccak256>) -> Self {
+        Identity::Injective {
+            address: CosmosBech32Address::from(val.recover_as_evm_address()),
+        }
+    }
+}
+
+impl Secp256k1TestIdentityCertificate<CosmosMessage, Keccak256> {
+    pub fn as_proof_of_identity(&self, verification_instruction_index: u8) -> IdentityCertificate {
+        IdentityCertificate::Injective {
+            pubkey: self.recover_as_evm_address(),
+            verification_instruction_index,
+        }
+    }
+
+    pub fn random(claimant: &Pubkey) -> Self {
+        let secret = libsecp256k1::SecretKey::random(&mut rand::thread_rng());
+        let public_key = libsecp256k1::PublicKey::from_secret_key(&secret);
+        let message = CosmosMessage::from((
+            get_expected_payload(DEFAULT_CAMPAIGN_ID, claimant).as_bytes(),
+            &CosmosBech32Address::from(construct_evm_pubkey(&public_key)),
+        ));
+        let (signature, recovery_id) = libsecp256k1::sign(&Self::hash_message(&message), &secret);
+        Self {
+            message,
+            signature,
+            recovery_id,
+            _hasher: PhantomData,
+        }
+    }
+}
+
+#[test]
+pub fn test_injective_adr036_message() {
+    let claimant = Pubkey::new_unique();
+    let signed_message =
+        Secp256k1TestIdentityCertificate::<CosmosMessage, Keccak256>::random(&claimant);
+    let signer = CosmosBech32Address::from(signed_message.recover_as_evm_address());
+
+    assert_eq!(
+        CosmosMessage::parse(&signed_message.message.get_message_with_metadata(), &signer)
+            .unwrap()
+            .get_payload(),
+        get_expected_payload(DEFAULT_CAMPAIGN_ID, &claimant).as_bytes()
+    );
+
+    // the signer in the sign doc needs to be the inj address of the signing key
+    assert_eq!(
+        CosmosMessage::parse(
+            &signed_message.message.get_message_with_metadata(),
+            &CosmosBech32Address::from(EvmPubkey::from([0u8; EvmPubkey::LEN])),
+        )
+        .unwrap_err(),
+        error!(ErrorCode::SignatureVerificationWrongPayloadMetadata)
+    );
+}
+
+#[tokio::test]
+pub async fn test_verify_signed_adr036_message_onchain() {
+    let signed_message =
+        Secp256k1TestIdentityCertificate::<CosmosMessage, Keccak256>::random(&Pubkey::new_unique());
+
+    let mut simulator = DispenserSimulator::new().await;
+
+    assert!(simulator
+        .process_ix(&[signed_message.as_instruction(0, true)], &vec![])
+        .await
+        .is_ok());
+
+    assert!(simulator
+        .process_ix(&[signed_message.as_instruction(0, false)], &vec![])
+        .await
+        .is_err());
+}
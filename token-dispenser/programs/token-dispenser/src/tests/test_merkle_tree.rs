@@ -1,13 +1,22 @@
 use {
     crate::{
         ClaimInfo,
+        DispenserMerkleRoot,
         Identity,
         SolanaHasher,
+        SolanaHasher32,
+        HASH_SIZE,
     },
-    anchor_lang::AnchorSerialize,
-    pythnet_sdk::accumulators::{
-        merkle::MerkleTree,
-        Accumulator,
+    anchor_lang::{
+        solana_program::keccak,
+        AnchorSerialize,
+    },
+    pythnet_sdk::{
+        accumulators::{
+            merkle::MerkleTree,
+            Accumulator,
+        },
+        hashers::Hasher,
     },
     solana_sdk::pubkey,
 };
@@ -101,3 +110,88 @@ fn test_merkle_tree() {
         );
     }
 }
+
+#[test]
+fn test_merkle_tree_32() {
+    let merkle_items_serialized = (0..5)
+        .map(|amount| {
+            ClaimInfo {
+                amount,
+                identity: Identity::Discord {
+                    username: "pepito".to_string(),
+                },
+            }
+            .try_to_vec()
+            .unwrap()
+        })
+        .collect::<Vec<Vec<u8>>>();
+    let leaves = merkle_items_serialized
+        .iter()
+        .map(|item| item.as_slice())
+        .collect::<Vec<&[u8]>>();
+
+    let merkle_tree: MerkleTree<SolanaHasher32> = MerkleTree::new(&leaves).unwrap();
+    let truncated_merkle_tree: MerkleTree<SolanaHasher> = MerkleTree::new(&leaves).unwrap();
+    assert_eq!(merkle_tree.root.as_bytes().len(), 32);
+    assert_ne!(
+        merkle_tree.root.as_bytes()[..20],
+        truncated_merkle_tree.root.as_bytes()[..]
+    );
+
+    for leaf in &leaves {
+        assert!(merkle_tree
+            .root
+            .check(merkle_tree.prove(leaf).unwrap(), leaf));
+        // Leaf hashes are the same as in 20-byte trees, without the truncation
+        let leaf_hash = MerkleTree::<SolanaHasher32>::hash_leaf(leaf);
+        assert_eq!(leaf_hash, keccak::hashv(&[&[0], leaf]).to_bytes());
+        assert_eq!(
+            leaf_hash[..20],
+            MerkleTree::<SolanaHasher>::hash_leaf(leaf)[..]
+        );
+    }
+
+    assert!(!merkle_tree
+        .root
+        .check(merkle_tree.prove(leaves[0]).unwrap(), leaves[1]));
+    assert_eq!(
+        SolanaHasher32::hashv(&[b"a"]),
+        keccak::hash(b"a").to_bytes()
+    );
+}
+
+#[test]
+fn test_dispenser_merkle_root() {
+    let leaves: [&[u8]; 3] = [b"a", b"b", b"c"];
+    let merkle_tree: MerkleTree<SolanaHasher> = MerkleTree::new(&leaves).unwrap();
+    let merkle_tree_32: MerkleTree<SolanaHasher32> = MerkleTree::new(&leaves).unwrap();
+    let merkle_root = DispenserMerkleRoot::from(merkle_tree.root.clone());
+    let merkle_root_32 = DispenserMerkleRoot::from(merkle_tree_32.root.clone());
+
+    for leaf in leaves {
+        assert!(merkle_root.check(merkle_tree.prove(leaf).unwrap().into(), leaf));
+        assert!(merkle_root_32.check(merkle_tree_32.prove(leaf).unwrap().into(), leaf));
+        // A proof only verifies against a root of the same hasher
+        assert!(!merkle_root.check(merkle_tree_32.prove(leaf).unwrap().into(), leaf));
+        assert!(!merkle_root_32.check(merkle_tree.prove(leaf).unwrap().into(), leaf));
+
+        let mut padded_leaf_hash = [0u8; HASH_SIZE];
+        padded_leaf_hash[..20].copy_from_slice(&MerkleTree::<SolanaHasher>::hash_leaf(leaf));
+        assert_eq!(merkle_root.hash_leaf(leaf), padded_leaf_hash);
+        assert_eq!(
+            merkle_root_32.hash_leaf(leaf),
+            MerkleTree::<SolanaHasher32>::hash_leaf(leaf)
+        );
+    }
+
+    assert_eq!(merkle_root.as_bytes(), merkle_tree.root.as_bytes());
+    assert_eq!(
+        merkle_root.to_padded_bytes()[..20],
+        merkle_tree.root.as_bytes()[..]
+    );
+    assert_eq!(merkle_root.to_padded_bytes()[20..], [0u8; 12]);
+    assert_eq!(
+        merkle_root_32.to_padded_bytes()[..],
+        merkle_tree_32.root.as_bytes()[..]
+    );
+}
@@ -0,0 +1,273 @@
+use {
+    super::dispenser_simulator::{
+        get_program_data_account,
+        DispenserSimulator,
+        IntoTransactionError,
+    },
+    crate::{
+        get_config_pda,
+        get_legacy_receipt_pda,
+        ClaimInfo,
+        Config,
+        DispenserMerkleRoot,
+        ErrorCode,
+        Identity,
+        LegacyConfig,
+        SolanaHasher,
+        CONFIG_VERSION,
+        DEFAULT_CAMPAIGN_ID,
+        IDENTITY_VARIANTS,
+    },
+    anchor_lang::{
+        prelude::{
+            Pubkey,
+            Rent,
+        },
+        AccountDeserialize,
+        AnchorSerialize,
+        Discriminator,
+    },
+    pythnet_sdk::accumulators::merkle::MerkleTree,
+    solana_program_test::tokio,
+    solana_sdk::{
+        account::Account,
+        signer::{
+            keypair::Keypair,
+            Signer,
+        },
+    },
+};
+
+/**
+ * The config of the default campaign as stored by the previous version of the program, without the fields added since.
+ */
+fn get_legacy_config_account(legacy_config: &LegacyConfig) -> (Pubkey, Account) {
+    let mut data = Config::DISCRIMINATOR.to_vec();
+    data.extend(legacy_config.try_to_vec().unwrap());
+    assert_eq!(data.len(), LegacyConfig::LEN);
+
+    (
+        get_config_pda(DEFAULT_CAMPAIGN_ID).0,
+        Account {
+            lamports: Rent::default().minimum_balance(LegacyConfig::LEN),
+            data,
+            owner: crate::id(),
+            executable: false,
+            rent_epoch: 0,
+        },
+    )
+}
+
+#[tokio::test]
+pub async fn test_migrate_config() {
+    let upgrade_authority = Keypair::new();
+    let legacy_config = LegacyConfig {
+        bump:                 get_config_pda(DEFAULT_CAMPAIGN_ID).1,
+        merkle_root:          MerkleTree::<SolanaHasher>::new(&[b"leaf".as_slice()])
+            .unwrap()
+            .root,
+        dispenser_guard:      Pubkey::new_unique(),
+        mint:                 Pubkey::new_unique(),
+        treasury:             Pubkey::new_unique(),
+        address_lookup_table: Pubkey::new_unique(),
+        funder:               Pubkey::new_unique(),
+        max_transfer:         1000,
+    };
+
+    let mut simulator = DispenserSimulator::new_with_accounts(vec![
+        get_legacy_config_account(&legacy_config),
+        get_program_data_account(&upgrade_authority.pubkey()),
+    ])
+    .await;
+    simulator
+        .airdrop(upgrade_authority.pubkey(), 1_000_000_000)
+        .await
+        .unwrap();
+
+    // Totals of the claims made with the legacy program, e.g. from an index of its logs
+    let identity = Identity::Discord {
+        username: "username".to_string(),
+    };
+    let mut claimed_per_identity = [0; IDENTITY_VARIANTS];
+    claimed_per_identity[identity.get_variant_index()] = 300;
+    claimed_per_identity[IDENTITY_VARIANTS - 1] = 500;
+
+    // Only the upgrade authority of the program can migrate
+    let impostor = Keypair::new();
+    simulator
+        .airdrop(impostor.pubkey(), 1_000_000_000)
+        .await
+        .unwrap();
+    assert_eq!(
+        simulator
+            .migrate_config(&impostor, claimed_per_identity, 3, Some(1000))
+            .await
+            .unwrap_err()
+            .unwrap(),
+        anchor_lang::error::ErrorCode::ConstraintRaw.into_transaction_error(0)
+    );
+
+    simulator
+        .migrate_config(&upgrade_authority, claimed_per_identity, 3, Some(1000))
+        .await
+        .unwrap();
+
+    let config_account = simulator
+        .get_account(get_config_pda(DEFAULT_CAMPAIGN_ID).0)
+        .await
+        .unwrap();
+    assert_eq!(config_account.data.len(), Config::LEN);
+    assert!(config_account.lamports >= Rent::default().minimum_balance(Config::LEN));
+    let mut config = Config::try_deserialize(&mut config_account.data.as_slice()).unwrap();
+    assert_eq!(
+        config,
+        Config {
+            bump: legacy_config.bump,
+            merkle_root: DispenserMerkleRoot::Keccak160(legacy_config.merkle_root),
+            dispenser_guard: legacy_config.dispenser_guard,
+            mint: legacy_config.mint,
+            treasury: legacy_config.treasury,
+            address_lookup_table: legacy_config.address_lookup_table,
+            funder: legacy_config.funder,
+            max_transfer: legacy_config.max_transfer,
+            escrow: false,
+            total_deposited: 0,
+            total_claimed: 800,
+            claim_count: 3,
+            claimed_per_identity,
+            global_cap: Some(1000),
+            max_per_claimant: None,
+            admin: upgrade_authority.pubkey(),
+            rate_limit: None,
+            denylist_len: 0,
+            guard_policy: 0,
+            legacy_receipts: 3,
+            version: CONFIG_VERSION,
+        }
+    );
+
+    // The global cap counts the claims made before the migration
+    assert!(config.clone().checked_record_claim(&identity, 201).is_err());
+    config.checked_record_claim(&identity, 200).unwrap();
+
+    // A config can only be migrated once
+    assert_eq!(
+        simulator
+            .migrate_config(&upgrade_authority, claimed_per_identity, 3, Some(1000))
+            .await
+            .unwrap_err()
+            .unwrap(),
+        ErrorCode::ConfigAlreadyMigrated.into_transaction_error(0)
+    );
+
+    // The upgrade authority administers the migrated campaign
+    simulator
+        .set_rate_limit(&upgrade_authority, Some(100), 3600)
+        .await
+        .unwrap();
+    assert!(simulator
+        .get_account_data::<Config>(get_config_pda(DEFAULT_CAMPAIGN_ID).0)
+        .await
+        .unwrap()
+        .rate_limit
+        .is_some());
+}
+
+#[tokio::test]
+pub async fn test_update_merkle_root_after_migrating_legacy_receipts() {
+    let upgrade_authority = Keypair::new();
+    let claim_infos = ["alice", "bob"].map(|username| ClaimInfo {
+        identity: Identity::Discord {
+            username: username.to_string(),
+        },
+        amount:   100,
+    });
+    let leaves = claim_infos
+        .iter()
+        .map(|claim_info| claim_info.try_to_vec().unwrap())
+        .collect::<Vec<Vec<u8>>>();
+    let legacy_config = LegacyConfig {
+        bump:                 get_config_pda(DEFAULT_CAMPAIGN_ID).1,
+        merkle_root:          MerkleTree::<SolanaHasher>::new(
+            &leaves
+                .iter()
+                .map(|leaf| leaf.as_slice())
+                .collect::<Vec<&[u8]>>(),
+        )
+        .unwrap()
+        .root,
+        dispenser_guard:      Pubkey::new_unique(),
+        mint:                 Pubkey::new_unique(),
+        treasury:             Pubkey::new_unique(),
+        address_lookup_table: Pubkey::new_unique(),
+        funder:               Pubkey::new_unique(),
+        max_transfer:         1000,
+    };
+
+    // Both leaves were claimed with the legacy program, which created an empty receipt keyed by the leaf
+    let mut accounts = vec![
+        get_legacy_config_account(&legacy_config),
+        get_program_data_account(&upgrade_authority.pubkey()),
+    ];
+    for leaf in &leaves {
+        accounts.push((
+            get_legacy_receipt_pda(leaf).0,
+            Account {
+                lamports:   Rent::default().minimum_balance(0),
+                data:       vec![],
+                owner:      crate::id(),
+                executable: false,
+                rent_epoch: 0,
+            },
+        ));
+    }
+    let mut simulator = DispenserSimulator::new_with_accounts(accounts).await;
+    simulator
+        .airdrop(upgrade_authority.pubkey(), 1_000_000_000)
+        .await
+        .unwrap();
+    let mut claimed_per_identity = [0; IDENTITY_VARIANTS];
+    claimed_per_identity[claim_infos[0].identity.get_variant_index()] = 200;
+    simulator
+        .migrate_config(&upgrade_authority, claimed_per_identity, 2, None)
+        .await
+        .unwrap();
+
+    // The amended leaves wouldn't match the legacy receipts, so the tree can't be replaced yet
+    let amended_merkle_tree = MerkleTree::<SolanaHasher>::new(&[ClaimInfo {
+        amount: 150,
+        ..claim_infos[0].clone()
+    }
+    .try_to_vec()
+    .unwrap()
+    .as_slice()])
+    .unwrap();
+    for claim_info in claim_infos.iter() {
+        assert_eq!(
+            simulator
+                .update_merkle_root(&upgrade_authority, amended_merkle_tree.root.clone())
+                .await
+                .unwrap_err()
+                .unwrap(),
+            ErrorCode::LegacyReceiptsNotMigrated.into_transaction_error(0)
+        );
+
+        // Migrating a receipt twice only counts once
+        for _ in 0..2 {
+            simulator.migrate_receipt(claim_info.clone()).await.unwrap();
+        }
+    }
+    assert_eq!(
+        simulator
+            .get_account_data::<Config>(get_config_pda(DEFAULT_CAMPAIGN_ID).0)
+            .await
+            .unwrap()
+            .legacy_receipts,
+        0
+    );
+
+    simulator
+        .update_merkle_root(&upgrade_authority, amended_merkle_tree.root.clone())
+        .await
+        .unwrap();
+}
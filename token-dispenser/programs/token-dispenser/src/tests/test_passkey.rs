@@ -0,0 +1,181 @@
+use {
+    super::dispenser_simulator::DispenserSimulator,
+    crate::{
+        ecosystems::{
+            passkey::WebAuthnMessage,
+            secp256r1::{
+                Secp256r1InstructionData,
+                Secp256r1InstructionHeader,
+                Secp256r1Pubkey,
+                Secp256r1Signature,
+                SECP256R1_ID,
+            },
+        },
+        Identity,
+        IdentityCertificate,
+    },
+    anchor_lang::{
+        prelude::{
+            AccountInfo,
+            ProgramError,
+            Pubkey,
+        },
+        solana_program::entrypoint::ProgramResult,
+        AnchorSerialize,
+    },
+    p256::ecdsa::{
+        signature::{
+            Signer,
+            Verifier,
+        },
+        Signature,
+        SigningKey,
+        VerifyingKey,
+    },
+    solana_program_test::tokio,
+    solana_sdk::instruction::Instruction,
+};
+
+#[derive(Clone)]
+pub struct PasskeyTestIdentityCertificate {
+    pub message:       WebAuthnMessage,
+    pub signature:     Signature,
+    pub verifying_key: VerifyingKey,
+}
+
+impl PasskeyTestIdentityCertificate {
+    pub fn random(claimant: &Pubkey) -> Self {
+        let signing_key = SigningKey::random(&mut rand::thread_rng());
+        let message = WebAuthnMessage::for_claimant(claimant);
+        let signature: Signature = signing_key.sign(&message.get_signed_message());
+        Self {
+            message,
+            // The precompile only accepts low-S signatures
+            signature: signature.normalize_s().unwrap_or(signature),
+            verifying_key: *signing_key.verifying_key(),
+        }
+    }
+
+    pub fn get_pubkey(&self) -> Secp256r1Pubkey {
+        Secp256r1Pubkey::from(
+            <[u8; Secp256r1Pubkey::LEN]>::try_from(
+                self.verifying_key.to_encoded_point(true).as_bytes(),
+            )
+            .unwrap(),
+        )
+    }
+
+    pub fn get_signature(&self) -> Secp256r1Signature {
+        Secp256r1Signature::from(
+            <[u8; Secp256r1Signature::LEN]>::try_from(self.signature.to_bytes().as_slice())
+                .unwrap(),
+        )
+    }
+
+    pub fn as_instruction(&self, instruction_index: u8, valid_signature: bool) -> Instruction {
+        let message = self.message.get_signed_message();
+        let header = Secp256r1InstructionHeader::expected_header(
+            message.len().try_into().unwrap(),
+            instruction_index,
+        );
+
+        let mut signature_bytes: [u8; Secp256r1Signature::LEN] =
+            self.signature.to_bytes().as_slice().try_into().unwrap();
+        if !valid_signature {
+            // Flip the first byte of the signature to make it invalid
+            signature_bytes[0] ^= 0xff;
+        }
+
+        let instruction_data = Secp256r1InstructionData {
+            header,
+            signature: signature_bytes.into(),
+            pubkey: self.get_pubkey(),
+            message,
+        };
+
+        Instruction {
+            program_id: SECP256R1_ID,
+            accounts:   vec![],
+            data:       instruction_data.try_to_vec().unwrap(),
+        }
+    }
+
+    pub fn as_proof_of_identity(&self, verification_instruction_index: u8) -> IdentityCertificate {
+        IdentityCertificate::Passkey {
+            pubkey: self.get_pubkey(),
+            authenticator_data: self.message.get_authenticator_data().to_vec(),
+            client_data_json: self.message.get_client_data_json().to_vec(),
+            verification_instruction_index,
+        }
+    }
+}
+
+impl From<PasskeyTestIdentityCertificate> for Identity {
+    fn from(val: PasskeyTestIdentityCertificate) -> Self {
+        Identity::Passkey {
+            pubkey: val.get_pubkey(),
+        }
+    }
+}
+
+/**
+ * The secp256r1 precompile doesn't exist in the Solana version of `solana-program-test`, so the simulator
+ * registers this program at `SECP256R1_ID` instead. Like the precompile, it reads the signature, the public key
+ * and the message at the offsets of the header, hashes the message with sha256 and rejects high-S signatures.
+ */
+pub fn process_secp256r1_instruction(
+    _program_id: &Pubkey,
+    _accounts: &[AccountInfo],
+    instruction_data: &[u8],
+) -> ProgramResult {
+    let get_slice = |offset: usize, length: usize| {
+        instruction_data
+            .get(offset..offset + length)
+            .ok_or(ProgramError::InvalidInstructionData)
+    };
+    let get_offset = |offset: usize| -> Result<usize, ProgramError> {
+        Ok(u16::from_le_bytes(get_slice(offset, 2)?.try_into().unwrap()) as usize)
+    };
+
+    if get_slice(0, 1)?[0] != 1 {
+        return Err(ProgramError::InvalidInstructionData);
+    }
+    let signature = Signature::from_slice(get_slice(get_offset(2)?, Secp256r1Signature::LEN)?)
+        .map_err(|_| ProgramError::InvalidArgument)?;
+    let verifying_key =
+        VerifyingKey::from_sec1_bytes(get_slice(get_offset(6)?, Secp256r1Pubkey::LEN)?)
+            .map_err(|_| ProgramError::InvalidArgument)?;
+    let message = get_slice(get_offset(10)?, get_offset(12)?)?;
+
+    if signature.normalize_s().is_some() {
+        return Err(ProgramError::InvalidArgument);
+    }
+    verifying_key
+        .verify(message, &signature)
+        .map_err(|_| ProgramError::InvalidArgument)
+}
+
+#[tokio::test]
+pub async fn test_verify_signed_message_onchain() {
+    let signed_message = PasskeyTestIdentityCertificate::random(&Pubkey::new_unique());
+
+    let mut simulator = DispenserSimulator::new().await;
+
+    assert!(signed_message
+        .verifying_key
+        .verify(
+            &signed_message.message.get_signed_message(),
+            &signed_message.signature
+        )
+        .is_ok());
+
+    assert!(simulator
+        .process_ix(&[signed_message.as_instruction(0, true)], &vec![])
+        .await
+        .is_ok());
+
+    assert!(simulator
+        .process_ix(&[signed_message.as_instruction(0, false)], &vec![])
+        .await
+        .is_err());
+}
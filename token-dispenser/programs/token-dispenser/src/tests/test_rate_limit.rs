@@ -0,0 +1,192 @@
+use {
+    super::{
+        dispenser_simulator::{
+            copy_keypair,
+            DispenserSimulator,
+            IntoTransactionError,
+        },
+        merkleize,
+        test_happy_path::TestClaimCertificate,
+    },
+    crate::{
+        get_config_pda,
+        ClaimInfo,
+        Config,
+        ErrorCode,
+        RateLimit,
+    },
+    solana_program_test::tokio,
+    solana_sdk::signer::{
+        keypair::Keypair,
+        Signer,
+    },
+};
+
+#[test]
+pub fn test_rate_limit_window() {
+    let mut rate_limit = RateLimit {
+        max_amount: 100,
+        window_seconds: 3600,
+        ..RateLimit::default()
+    };
+
+    // Windows are aligned on multiples of `window_seconds`
+    rate_limit.checked_record_outflow(60, 10_800).unwrap();
+    assert_eq!(rate_limit.window_start, 10_800);
+    rate_limit.checked_record_outflow(40, 14_399).unwrap();
+    assert!(rate_limit.checked_record_outflow(1, 14_399).is_err());
+
+    // Halfway through the next window, half of the previous one still counts
+    let mut rate_limit = RateLimit {
+        window_claimed: 100,
+        ..rate_limit
+    };
+    rate_limit.checked_record_outflow(50, 16_200).unwrap();
+    assert_eq!(rate_limit.window_start, 14_400);
+    assert_eq!(rate_limit.previous_window_claimed, 100);
+    assert!(rate_limit.checked_record_outflow(1, 16_200).is_err());
+
+    // Windows older than the previous one don't count anymore
+    let mut rate_limit = RateLimit {
+        window_claimed: 50,
+        ..rate_limit
+    };
+    rate_limit.checked_record_outflow(100, 21_600).unwrap();
+    assert_eq!(rate_limit.window_start, 21_600);
+    assert_eq!(rate_limit.previous_window_claimed, 0);
+    assert_eq!(rate_limit.window_claimed, 100);
+}
+
+#[test]
+pub fn test_rate_limit_across_window_boundary() {
+    let mut rate_limit = RateLimit {
+        max_amount: 100,
+        window_seconds: 3600,
+        ..RateLimit::default()
+    };
+
+    // A burst right before the boundary still counts right after it
+    rate_limit.checked_record_outflow(100, 14_399).unwrap();
+    let mut after_boundary = rate_limit;
+    assert!(after_boundary.checked_record_outflow(1, 14_400).is_err());
+    let mut after_boundary = rate_limit;
+    assert!(after_boundary.checked_record_outflow(1, 14_401).is_err());
+
+    // The previous window fades out as the sliding window moves past it
+    let mut after_boundary = rate_limit;
+    after_boundary.checked_record_outflow(25, 15_300).unwrap();
+    assert_eq!(after_boundary.window_claimed, 25);
+    assert_eq!(after_boundary.previous_window_claimed, 100);
+    assert!(after_boundary.checked_record_outflow(1, 15_300).is_err());
+
+    let mut after_boundary = rate_limit;
+    assert!(after_boundary.checked_record_outflow(100, 17_999).is_err());
+    let mut after_boundary = rate_limit;
+    after_boundary.checked_record_outflow(99, 17_999).unwrap();
+}
+
+#[tokio::test]
+pub async fn test_claim_fails_over_rate_limit() {
+    let dispenser_guard = Keypair::new();
+    let mut simulator = DispenserSimulator::new().await;
+    let admin = copy_keypair(&simulator.genesis_keypair);
+
+    let claimant = Keypair::new();
+    let solana_certificate = TestClaimCertificate {
+        amount: 100,
+        ..TestClaimCertificate::random_solana(&claimant.pubkey())
+    };
+    let evm_certificate = TestClaimCertificate {
+        amount: 100,
+        ..TestClaimCertificate::random_evm(&claimant.pubkey())
+    };
+    let (merkle_tree, _) = merkleize(vec![
+        ClaimInfo::from(solana_certificate.clone()),
+        ClaimInfo::from(evm_certificate.clone()),
+    ]);
+
+    let address_lookup_table = simulator.init_lookup_table().await.unwrap();
+    simulator
+        .initialize(
+            merkle_tree.root.clone(),
+            dispenser_guard.pubkey(),
+            address_lookup_table,
+            None,
+            None,
+            None,
+        )
+        .await
+        .unwrap();
+    simulator.setup_treasury(200).await.unwrap();
+
+    // Only the admin can set the rate limit
+    assert_eq!(
+        simulator
+            .set_rate_limit(&Keypair::new(), Some(150), 3600)
+            .await
+            .unwrap_err()
+            .unwrap(),
+        anchor_lang::error::ErrorCode::ConstraintHasOne.into_transaction_error(0)
+    );
+    assert_eq!(
+        simulator
+            .set_rate_limit(&admin, Some(150), 0)
+            .await
+            .unwrap_err()
+            .unwrap(),
+        ErrorCode::InvalidRateLimit.into_transaction_error(0)
+    );
+    simulator
+        .set_rate_limit(&admin, Some(150), 3600)
+        .await
+        .unwrap();
+
+    simulator
+        .claim(
+            &claimant,
+            &solana_certificate,
+            &merkle_tree,
+            None,
+            None,
+            None,
+        )
+        .await
+        .unwrap();
+    assert_eq!(
+        simulator
+            .claim(&claimant, &evm_certificate, &merkle_tree, None, None, None)
+            .await
+            .unwrap_err()
+            .unwrap(),
+        ErrorCode::RateLimitExceeded
+            .into_transaction_error(evm_certificate.as_instruction_error_index(&merkle_tree))
+    );
+
+    // Raising the limit keeps the amount claimed in the current window
+    simulator
+        .set_rate_limit(&admin, Some(200), 3600)
+        .await
+        .unwrap();
+    simulator
+        .claim(&claimant, &evm_certificate, &merkle_tree, None, None, None)
+        .await
+        .unwrap();
+    let rate_limit = simulator
+        .get_account_data::<Config>(get_config_pda(simulator.campaign_id).0)
+        .await
+        .unwrap()
+        .rate_limit
+        .unwrap();
+    assert_eq!(rate_limit.max_amount, 200);
+    assert_eq!(rate_limit.window_claimed, 200);
+
+    simulator.set_rate_limit(&admin, None, 0).await.unwrap();
+    assert_eq!(
+        simulator
+            .get_account_data::<Config>(get_config_pda(simulator.campaign_id).0)
+            .await
+            .unwrap()
+            .rate_limit,
+        None
+    );
+}
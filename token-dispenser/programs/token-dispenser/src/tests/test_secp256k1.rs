@@ -2,17 +2,21 @@ use {
     super::dispenser_simulator::DispenserSimulator,
     crate::{
         ecosystems::{
+            check_payload,
             evm::EvmPrefixedMessage,
             get_expected_payload,
+            get_expected_payload_v2,
             secp256k1::{
                 EvmPubkey,
                 Secp256k1InstructionData,
                 Secp256k1InstructionHeader,
                 Secp256k1TestMessage,
             },
+            Authorization,
         },
         Identity,
         IdentityCertificate,
+        DEFAULT_CAMPAIGN_ID,
     },
     anchor_lang::{
         prelude::Pubkey,
@@ -114,9 +118,22 @@ impl Secp256k1TestIdentityCertificate<EvmPrefixedMessage, Keccak256> {
 }
 impl Secp256k1TestIdentityCertificate<EvmPrefixedMessage, Keccak256> {
     pub fn random(claimant: &Pubkey) -> Self {
-        let message = EvmPrefixedMessage::from(get_expected_payload(claimant).as_str());
-        let secret = libsecp256k1::SecretKey::random(&mut rand::thread_rng());
-        let (signature, recovery_id) = libsecp256k1::sign(&Self::hash_message(&message), &secret);
+        Self::random_with_payload(&get_expected_payload(DEFAULT_CAMPAIGN_ID, claimant))
+    }
+
+    pub fn random_with_payload(payload: &str) -> Self {
+        Self::new_with_payload(
+            &libsecp256k1::SecretKey::random(&mut rand::thread_rng()),
+            payload,
+        )
+    }
+
+    /**
+     * Signs `payload` with `secret`, so that the same identity can sign several payloads.
+     */
+    pub fn new_with_payload(secret: &libsecp256k1::SecretKey, payload: &str) -> Self {
+        let message = EvmPrefixedMessage::from(payload);
+        let (signature, recovery_id) = libsecp256k1::sign(&Self::hash_message(&message), secret);
         Self {
             message,
             signature,
@@ -146,3 +163,16 @@ pub async fn test_verify_signed_message_onchain() {
         .await
         .is_err());
 }
+
+#[tokio::test]
+pub async fn test_evm_message_v2() {
+    let authorization = Authorization::for_claimant(DEFAULT_CAMPAIGN_ID, &Pubkey::new_unique());
+    let payload = get_expected_payload_v2(&authorization);
+    let message = EvmPrefixedMessage::parse(
+        &EvmPrefixedMessage::from(payload.as_str()).get_message_with_metadata(),
+    )
+    .unwrap();
+
+    assert_eq!(message.get_payload(), payload.as_bytes());
+    assert!(check_payload(message.get_payload(), &authorization).is_ok());
+}
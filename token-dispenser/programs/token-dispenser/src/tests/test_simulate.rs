@@ -0,0 +1,374 @@
+use {
+    super::{
+        dispenser_simulator::DispenserSimulator,
+        test_happy_path::{
+            TestClaimCertificate,
+            TestIdentityCertificate,
+        },
+        test_secp256k1::Secp256k1TestIdentityCertificate,
+    },
+    crate::{
+        client::simulate::{
+            simulate_claim,
+            ClaimStage,
+        },
+        ecosystems::{
+            evm::EvmPrefixedMessage,
+            get_expected_payload,
+            secp256k1::EvmPubkey,
+        },
+        tests::merkleize,
+        ClaimInfo,
+        Config,
+        ErrorCode,
+        IdentityCertificate,
+        Receipt,
+        CONFIG_VERSION,
+        DEFAULT_CAMPAIGN_ID,
+        IDENTITY_VARIANTS,
+    },
+    anchor_lang::{
+        error,
+        prelude::Pubkey,
+        AnchorSerialize,
+    },
+    pythnet_sdk::hashers::keccak256::Keccak256,
+    solana_sdk::{
+        signature::Keypair,
+        signer::Signer,
+    },
+};
+
+fn get_config(dispenser_guard: &Keypair, claim_certificates: &[TestClaimCertificate]) -> Config {
+    Config {
+        bump:                 0,
+        merkle_root:          merkleize(
+            claim_certificates
+                .iter()
+                .map(|item| item.clone().into())
+                .collect(),
+        )
+        .0
+        .root
+        .into(),
+        dispenser_guard:      dispenser_guard.pubkey(),
+        mint:                 Pubkey::new_unique(),
+        treasury:             Pubkey::new_unique(),
+        address_lookup_table: Pubkey::new_unique(),
+        funder:               Pubkey::new_unique(),
+        max_transfer:         u64::MAX,
+        escrow:               false,
+        total_deposited:      0,
+        total_claimed:        0,
+        claim_count:          0,
+        claimed_per_identity: [0; IDENTITY_VARIANTS],
+        global_cap:           None,
+        max_per_claimant:     None,
+        admin:                Pubkey::new_unique(),
+        rate_limit:           None,
+        denylist_len:         0,
+        guard_policy:         0,
+        legacy_receipts:      0,
+        version:              CONFIG_VERSION,
+    }
+}
+
+#[test]
+pub fn test_simulate_claim_succeeds() {
+    let dispenser_guard = Keypair::new();
+    let claimant = Keypair::new();
+    let claim_certificates =
+        DispenserSimulator::generate_test_claim_certs(&claimant.pubkey(), &dispenser_guard);
+    let config = get_config(&dispenser_guard, &claim_certificates);
+    let merkle_tree = merkleize(
+        claim_certificates
+            .iter()
+            .map(|item| item.clone().into())
+            .collect(),
+    )
+    .0;
+
+    for offchain_claim_certificate in &claim_certificates {
+        let (claim_certificate, option_instruction) =
+            offchain_claim_certificate.as_claim_certificate(&merkle_tree, 0, None);
+        let instructions = option_instruction.into_iter().collect::<Vec<_>>();
+
+        let report = simulate_claim(
+            &config,
+            DEFAULT_CAMPAIGN_ID,
+            &claimant.pubkey(),
+            &claim_certificate,
+            &instructions,
+            None,
+            0,
+        );
+        assert!(report.is_success());
+        assert_eq!(
+            report.claim_info.unwrap().try_to_vec().unwrap(),
+            Into::<ClaimInfo>::into(offchain_claim_certificate.clone())
+                .try_to_vec()
+                .unwrap()
+        );
+        assert!(report.receipt.is_some());
+    }
+}
+
+#[test]
+pub fn test_simulate_claim_reports_failing_stage() {
+    let dispenser_guard = Keypair::new();
+    let claimant = Keypair::new();
+    let evm = TestClaimCertificate {
+        amount: 500,
+        ..TestClaimCertificate::random_evm(&claimant.pubkey())
+    };
+    let cosmos = TestClaimCertificate::random_cosmos(&claimant.pubkey());
+    let claim_certificates = vec![evm.clone(), cosmos.clone()];
+    let config = get_config(&dispenser_guard, &claim_certificates);
+    let merkle_tree = merkleize(
+        claim_certificates
+            .iter()
+            .map(|item| item.clone().into())
+            .collect(),
+    )
+    .0;
+
+    let (claim_certificate, option_instruction) = evm.as_claim_certificate(&merkle_tree, 0, None);
+    let instructions = vec![option_instruction.unwrap()];
+
+    // Missing precompile instruction
+    let report = simulate_claim(
+        &config,
+        DEFAULT_CAMPAIGN_ID,
+        &claimant.pubkey(),
+        &claim_certificate,
+        &[],
+        None,
+        0,
+    );
+    assert_eq!(report.failure.unwrap().stage, ClaimStage::PrecompileHeader);
+
+    // Wrong signer
+    let mut wrong_signer = claim_certificate.clone();
+    if let IdentityCertificate::Evm { pubkey, .. } = &mut wrong_signer.proof_of_identity {
+        *pubkey = EvmPubkey::from([0u8; EvmPubkey::LEN]);
+    }
+    let failure = simulate_claim(
+        &config,
+        DEFAULT_CAMPAIGN_ID,
+        &claimant.pubkey(),
+        &wrong_signer,
+        &instructions,
+        None,
+        0,
+    )
+    .failure
+    .unwrap();
+    assert_eq!(failure.stage, ClaimStage::Signer);
+    assert_eq!(
+        failure.error,
+        error!(ErrorCode::SignatureVerificationWrongSigner)
+    );
+    assert_eq!(failure.expected, Some(hex::encode([0u8; EvmPubkey::LEN])));
+    assert_ne!(failure.actual, failure.expected);
+
+    // Wrong claimant
+    let other_claimant = Pubkey::new_unique();
+    let report = simulate_claim(
+        &config,
+        DEFAULT_CAMPAIGN_ID,
+        &other_claimant,
+        &claim_certificate,
+        &instructions,
+        None,
+        0,
+    );
+    let failure = report.failure.unwrap();
+    assert_eq!(failure.stage, ClaimStage::Payload);
+    assert_eq!(
+        failure.expected,
+        Some(get_expected_payload(DEFAULT_CAMPAIGN_ID, &other_claimant))
+    );
+    assert!(report
+        .decoded_message
+        .unwrap()
+        .contains(&get_expected_payload(
+            DEFAULT_CAMPAIGN_ID,
+            &claimant.pubkey()
+        )));
+
+    // Signed for another campaign
+    let failure = simulate_claim(
+        &config,
+        1,
+        &claimant.pubkey(),
+        &claim_certificate,
+        &instructions,
+        None,
+        0,
+    )
+    .failure
+    .unwrap();
+    assert_eq!(failure.stage, ClaimStage::Payload);
+    assert_eq!(
+        failure.expected,
+        Some(get_expected_payload(1, &claimant.pubkey()))
+    );
+
+    // Unauthorized chain id
+    let (mut cosmos_claim_certificate, _) = cosmos.as_claim_certificate(&merkle_tree, 0, None);
+    if let IdentityCertificate::Cosmwasm { chain_id, .. } =
+        &mut cosmos_claim_certificate.proof_of_identity
+    {
+        *chain_id = "cosmos".to_string();
+    }
+    let failure = simulate_claim(
+        &config,
+        DEFAULT_CAMPAIGN_ID,
+        &claimant.pubkey(),
+        &cosmos_claim_certificate,
+        &[],
+        None,
+        0,
+    )
+    .failure
+    .unwrap();
+    assert_eq!(failure.stage, ClaimStage::ChainId);
+    assert_eq!(failure.actual, Some("cosmos".to_string()));
+
+    // Wrong root
+    let other_config = get_config(
+        &dispenser_guard,
+        &[TestClaimCertificate::random_evm(&claimant.pubkey())],
+    );
+    let failure = simulate_claim(
+        &other_config,
+        DEFAULT_CAMPAIGN_ID,
+        &claimant.pubkey(),
+        &claim_certificate,
+        &instructions,
+        None,
+        0,
+    )
+    .failure
+    .unwrap();
+    assert_eq!(failure.stage, ClaimStage::Inclusion);
+    assert_eq!(failure.error, error!(ErrorCode::InvalidInclusionProof));
+
+    // Already claimed
+    let report = simulate_claim(
+        &config,
+        DEFAULT_CAMPAIGN_ID,
+        &claimant.pubkey(),
+        &claim_certificate,
+        &instructions,
+        Some(&Receipt {
+            amount_claimed: evm.amount,
+            ..Receipt::default()
+        }),
+        0,
+    );
+    let failure = report.failure.unwrap();
+    assert_eq!(failure.stage, ClaimStage::Receipt);
+    assert_eq!(
+        failure.actual,
+        report.receipt.map(|receipt| receipt.to_string())
+    );
+
+    // Transfer exceeds max
+    let failure = simulate_claim(
+        &Config {
+            max_transfer: 499,
+            ..config.clone()
+        },
+        DEFAULT_CAMPAIGN_ID,
+        &claimant.pubkey(),
+        &claim_certificate,
+        &instructions,
+        None,
+        0,
+    )
+    .failure
+    .unwrap();
+    assert_eq!(failure.stage, ClaimStage::MaxTransfer);
+    assert_eq!(failure.expected, Some("499".to_string()));
+    assert_eq!(failure.actual, Some("500".to_string()));
+
+    // Only the top-up counts towards max_transfer
+    let report = simulate_claim(
+        &Config {
+            max_transfer: 100,
+            ..config
+        },
+        DEFAULT_CAMPAIGN_ID,
+        &claimant.pubkey(),
+        &claim_certificate,
+        &instructions,
+        Some(&Receipt {
+            amount_claimed: 400,
+            ..Receipt::default()
+        }),
+        0,
+    );
+    assert!(report.is_success());
+}
+
+#[test]
+pub fn test_simulate_claim_reports_nonce_mismatch() {
+    let dispenser_guard = Keypair::new();
+    let claimant = Keypair::new();
+    let payload = get_expected_payload(DEFAULT_CAMPAIGN_ID, &claimant.pubkey()) + "Nonce: 1\n";
+    let evm = TestClaimCertificate {
+        amount:                      500,
+        off_chain_proof_of_identity: TestIdentityCertificate::Evm(
+            Secp256k1TestIdentityCertificate::<EvmPrefixedMessage, Keccak256>::random_with_payload(
+                &payload,
+            ),
+        ),
+        expires_at:                  None,
+        nonce:                       Some(1),
+    };
+    let config = get_config(&dispenser_guard, &[evm.clone()]);
+    let merkle_tree = merkleize(vec![evm.clone().into()]).0;
+    let (claim_certificate, option_instruction) = evm.as_claim_certificate(&merkle_tree, 0, None);
+    let instructions = vec![option_instruction.unwrap()];
+
+    let report = simulate_claim(
+        &config,
+        DEFAULT_CAMPAIGN_ID,
+        &claimant.pubkey(),
+        &claim_certificate,
+        &instructions,
+        Some(&Receipt {
+            amount_claimed: 100,
+            nonce: 1,
+            ..Receipt::default()
+        }),
+        0,
+    );
+    assert!(report.is_success());
+
+    // The receipt has been used by another claim since the payload was signed
+    for receipt in [
+        None,
+        Some(&Receipt {
+            amount_claimed: 200,
+            nonce: 2,
+            ..Receipt::default()
+        }),
+    ] {
+        let failure = simulate_claim(
+            &config,
+            DEFAULT_CAMPAIGN_ID,
+            &claimant.pubkey(),
+            &claim_certificate,
+            &instructions,
+            receipt,
+            0,
+        )
+        .failure
+        .unwrap();
+        assert_eq!(failure.stage, ClaimStage::Nonce);
+        assert_eq!(failure.error, error!(ErrorCode::NonceMismatch));
+        assert_eq!(failure.actual, Some("1".to_string()));
+    }
+}
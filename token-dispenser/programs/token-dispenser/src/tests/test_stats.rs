@@ -0,0 +1,144 @@
+use {
+    super::{
+        dispenser_simulator::{
+            copy_keypair,
+            DispenserSimulator,
+            IntoTransactionError,
+        },
+        merkleize,
+        test_happy_path::TestClaimCertificate,
+    },
+    crate::{
+        get_config_pda,
+        ClaimInfo,
+        Config,
+        ErrorCode,
+        IDENTITY_VARIANTS,
+    },
+    solana_program_test::tokio,
+    solana_sdk::signer::{
+        keypair::Keypair,
+        Signer,
+    },
+};
+
+#[tokio::test]
+pub async fn test_claim_stats() {
+    let dispenser_guard = Keypair::new();
+    let mut simulator = DispenserSimulator::new().await;
+    let claimant_1 = Keypair::new();
+
+    let (merkle_tree, mock_offchain_certificates_and_claimants) = simulator
+        .initialize_with_claimants(
+            vec![
+                copy_keypair(&simulator.genesis_keypair),
+                copy_keypair(&claimant_1),
+            ],
+            &dispenser_guard,
+            None,
+        )
+        .await
+        .unwrap();
+
+    let mut expected_claim_count = 0;
+    let mut expected_claimed_per_identity = [0u64; IDENTITY_VARIANTS];
+    for (claimant, offchain_claim_certificates, _) in &mock_offchain_certificates_and_claimants {
+        for offchain_claim_certificate in offchain_claim_certificates {
+            simulator
+                .claim(
+                    &copy_keypair(claimant),
+                    offchain_claim_certificate,
+                    &merkle_tree,
+                    None,
+                    None,
+                    None,
+                )
+                .await
+                .unwrap();
+
+            expected_claim_count += 1;
+            expected_claimed_per_identity[ClaimInfo::from(offchain_claim_certificate.clone())
+                .identity
+                .get_variant_index()] += offchain_claim_certificate.amount;
+        }
+    }
+
+    let config = simulator
+        .get_account_data::<Config>(get_config_pda(simulator.campaign_id).0)
+        .await
+        .unwrap();
+    assert_eq!(config.claim_count, expected_claim_count);
+    assert_eq!(config.claimed_per_identity, expected_claimed_per_identity);
+    assert_eq!(
+        config.total_claimed,
+        mock_offchain_certificates_and_claimants
+            .iter()
+            .map(|(_, _, amount)| amount)
+            .sum::<u64>()
+    );
+    assert_eq!(config.global_cap, None);
+}
+
+#[tokio::test]
+pub async fn test_claim_fails_over_global_cap() {
+    let dispenser_guard = Keypair::new();
+    let mut simulator = DispenserSimulator::new().await;
+    simulator.global_cap = Some(150);
+
+    let claimant = Keypair::new();
+    let solana_certificate = TestClaimCertificate {
+        amount: 100,
+        ..TestClaimCertificate::random_solana(&claimant.pubkey())
+    };
+    let evm_certificate = TestClaimCertificate {
+        amount: 100,
+        ..TestClaimCertificate::random_evm(&claimant.pubkey())
+    };
+    let (merkle_tree, _) = merkleize(vec![
+        ClaimInfo::from(solana_certificate.clone()),
+        ClaimInfo::from(evm_certificate.clone()),
+    ]);
+
+    let address_lookup_table = simulator.init_lookup_table().await.unwrap();
+    simulator
+        .initialize(
+            merkle_tree.root.clone(),
+            dispenser_guard.pubkey(),
+            address_lookup_table,
+            None,
+            None,
+            None,
+        )
+        .await
+        .unwrap();
+    simulator.setup_treasury(200).await.unwrap();
+
+    simulator
+        .claim(
+            &claimant,
+            &solana_certificate,
+            &merkle_tree,
+            None,
+            None,
+            None,
+        )
+        .await
+        .unwrap();
+    assert_eq!(
+        simulator
+            .claim(&claimant, &evm_certificate, &merkle_tree, None, None, None)
+            .await
+            .unwrap_err()
+            .unwrap(),
+        ErrorCode::GlobalCapExceeded
+            .into_transaction_error(evm_certificate.as_instruction_error_index(&merkle_tree))
+    );
+
+    let config = simulator
+        .get_account_data::<Config>(get_config_pda(simulator.campaign_id).0)
+        .await
+        .unwrap();
+    assert_eq!(config.total_claimed, 100);
+    assert_eq!(config.claim_count, 1);
+    assert_eq!(config.global_cap, Some(150));
+}
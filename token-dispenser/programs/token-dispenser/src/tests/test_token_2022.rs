@@ -0,0 +1,160 @@
+use {
+    super::{
+        dispenser_simulator::{
+            copy_keypair,
+            DispenserSimulator,
+            IntoTransactionError,
+        },
+        merkleize,
+        test_happy_path::TestClaimCertificate,
+    },
+    crate::{
+        get_config_pda,
+        ClaimInfo,
+    },
+    anchor_lang::solana_program::program_option::COption,
+    anchor_spl::token::spl_token,
+    solana_program_test::tokio,
+    solana_sdk::signer::{
+        keypair::Keypair,
+        Signer,
+    },
+    spl_associated_token_account::get_associated_token_address_with_program_id,
+};
+
+#[tokio::test]
+pub async fn test_claim_token_2022() {
+    let dispenser_guard = Keypair::new();
+    let mut simulator = DispenserSimulator::new_token_2022(None).await;
+    let claimant_1 = Keypair::new();
+
+    let (merkle_tree, mock_offchain_certificates_and_claimants) = simulator
+        .initialize_with_claimants(
+            vec![
+                copy_keypair(&simulator.genesis_keypair),
+                copy_keypair(&claimant_1),
+            ],
+            &dispenser_guard,
+            None,
+        )
+        .await
+        .unwrap();
+
+    for (claimant, offchain_claim_certificates, amount) in &mock_offchain_certificates_and_claimants
+    {
+        for offchain_claim_certificate in offchain_claim_certificates {
+            simulator
+                .claim(
+                    &copy_keypair(claimant),
+                    offchain_claim_certificate,
+                    &merkle_tree,
+                    None,
+                    None,
+                    None,
+                )
+                .await
+                .unwrap();
+        }
+
+        // The claimant fund was created under token-2022
+        let claimant_fund = get_associated_token_address_with_program_id(
+            &claimant.pubkey(),
+            &simulator.mint_keypair.pubkey(),
+            &spl_token_2022::id(),
+        );
+        assert_eq!(
+            simulator.get_account(claimant_fund).await.unwrap().owner,
+            spl_token_2022::id()
+        );
+        simulator
+            .verify_token_account_data(claimant_fund, *amount, COption::None, 0)
+            .await
+            .unwrap();
+    }
+
+    simulator
+        .verify_token_account_data(simulator.pyth_treasury, 0, COption::None, 0)
+        .await
+        .unwrap();
+}
+
+#[tokio::test]
+pub async fn test_claim_token_2022_with_transfer_fee() {
+    let dispenser_guard = Keypair::new();
+    // 1% transfer fee
+    let mut simulator = DispenserSimulator::new_token_2022(Some(100)).await;
+
+    let claimant = Keypair::new();
+    let offchain_claim_certificate = TestClaimCertificate {
+        amount: 10_000,
+        ..TestClaimCertificate::random_solana(&claimant.pubkey())
+    };
+    let (merkle_tree, _) = merkleize(vec![ClaimInfo::from(offchain_claim_certificate.clone())]);
+
+    let address_lookup_table = simulator.init_lookup_table().await.unwrap();
+    simulator
+        .initialize(
+            merkle_tree.root.clone(),
+            dispenser_guard.pubkey(),
+            address_lookup_table,
+            None,
+            None,
+            None,
+        )
+        .await
+        .unwrap();
+    simulator.setup_treasury(15_000).await.unwrap();
+
+    // The token program has to be the owner of the mint
+    simulator.token_program = spl_token::id();
+    assert_eq!(
+        simulator
+            .claim(
+                &claimant,
+                &offchain_claim_certificate,
+                &merkle_tree,
+                None,
+                None,
+                None
+            )
+            .await
+            .unwrap_err()
+            .unwrap(),
+        anchor_lang::error::ErrorCode::ConstraintOwner.into_transaction_error(
+            offchain_claim_certificate.as_instruction_error_index(&merkle_tree)
+        )
+    );
+    simulator.token_program = spl_token_2022::id();
+
+    simulator
+        .claim(
+            &claimant,
+            &offchain_claim_certificate,
+            &merkle_tree,
+            None,
+            None,
+            None,
+        )
+        .await
+        .unwrap();
+
+    // The treasury pays the whole amount and the fee is withheld in the claimant fund
+    let claimant_fund = get_associated_token_address_with_program_id(
+        &claimant.pubkey(),
+        &simulator.mint_keypair.pubkey(),
+        &spl_token_2022::id(),
+    );
+    simulator
+        .verify_token_account_data(claimant_fund, 9_900, COption::None, 0)
+        .await
+        .unwrap();
+    simulator
+        .verify_token_account_data(
+            simulator.pyth_treasury,
+            5_000,
+            COption::Some(get_config_pda(simulator.campaign_id).0),
+            5_000,
+        )
+        .await
+        .unwrap();
+}
@@ -0,0 +1,283 @@
+use {
+    super::{
+        dispenser_simulator::{
+            copy_keypair,
+            DispenserSimulator,
+            IntoTransactionError,
+        },
+        merkleize,
+        test_happy_path::TestClaimCertificate,
+    },
+    crate::{
+        get_legacy_receipt_pda,
+        get_receipt_pda,
+        ClaimInfo,
+        ErrorCode,
+        Receipt,
+    },
+    anchor_lang::{
+        prelude::Rent,
+        solana_program::program_option::COption,
+        AccountDeserialize,
+        AnchorSerialize,
+    },
+    anchor_spl::associated_token::get_associated_token_address,
+    solana_program_test::tokio,
+    solana_sdk::{
+        account::Account,
+        signer::{
+            keypair::Keypair,
+            Signer,
+        },
+    },
+};
+
+async fn get_amount_claimed(
+    simulator: &mut DispenserSimulator,
+    offchain_claim_certificate: &TestClaimCertificate,
+) -> u64 {
+    simulator
+        .get_account_data::<Receipt>(
+            get_receipt_pda(
+                simulator.campaign_id,
+                &ClaimInfo::from(offchain_claim_certificate.clone()).identity,
+            )
+            .0,
+        )
+        .await
+        .unwrap()
+        .amount_claimed
+}
+
+#[tokio::test]
+pub async fn test_claim_top_up() {
+    let dispenser_guard = Keypair::new();
+    let mut simulator = DispenserSimulator::new().await;
+
+    let claimant = Keypair::new();
+    // The allocation of the identity is amended from 100 to 150 in a new tree
+    let initial = TestClaimCertificate {
+        amount: 100,
+        ..TestClaimCertificate::random_solana(&claimant.pubkey())
+    };
+    let amended = TestClaimCertificate {
+        amount: 150,
+        ..initial.clone()
+    };
+    let other = TestClaimCertificate {
+        amount: 200,
+        ..TestClaimCertificate::random_evm(&claimant.pubkey())
+    };
+    let (merkle_tree, _) = merkleize(
+        [&initial, &other]
+            .iter()
+            .map(|item| (*item).clone().into())
+            .collect::<Vec<ClaimInfo>>(),
+    );
+    let (amended_merkle_tree, _) = merkleize(
+        [&amended, &other]
+            .iter()
+            .map(|item| (*item).clone().into())
+            .collect::<Vec<ClaimInfo>>(),
+    );
+
+    let address_lookup_table = simulator.init_lookup_table().await.unwrap();
+    simulator
+        .initialize(
+            merkle_tree.root.clone(),
+            dispenser_guard.pubkey(),
+            address_lookup_table,
+            None,
+            None,
+            None,
+        )
+        .await
+        .unwrap();
+    simulator.setup_treasury(1000).await.unwrap();
+    let claimant_fund =
+        get_associated_token_address(&claimant.pubkey(), &simulator.mint_keypair.pubkey());
+
+    simulator
+        .claim(&claimant, &initial, &merkle_tree, None, None, None)
+        .await
+        .unwrap();
+    simulator
+        .verify_token_account_data(claimant_fund, 100, COption::None, 0)
+        .await
+        .unwrap();
+
+    // Only the admin can replace the tree
+    assert_eq!(
+        simulator
+            .update_merkle_root(&Keypair::new(), amended_merkle_tree.root.clone())
+            .await
+            .unwrap_err()
+            .unwrap(),
+        anchor_lang::error::ErrorCode::ConstraintHasOne.into_transaction_error(0)
+    );
+    // The amended leaf isn't in the current tree
+    assert_eq!(
+        simulator
+            .claim(&claimant, &amended, &amended_merkle_tree, None, None, None)
+            .await
+            .unwrap_err()
+            .unwrap(),
+        ErrorCode::InvalidInclusionProof.into_transaction_error(0)
+    );
+    let admin = copy_keypair(&simulator.genesis_keypair);
+    simulator
+        .update_merkle_root(&admin, amended_merkle_tree.root.clone())
+        .await
+        .unwrap();
+
+    // Only the difference is paid
+    simulator
+        .claim(&claimant, &amended, &amended_merkle_tree, None, None, None)
+        .await
+        .unwrap();
+    simulator
+        .verify_token_account_data(claimant_fund, 150, COption::None, 0)
+        .await
+        .unwrap();
+    assert_eq!(get_amount_claimed(&mut simulator, &amended).await, 150);
+
+    // The initial leaf isn't in the tree anymore
+    assert_eq!(
+        simulator
+            .claim(&claimant, &initial, &merkle_tree, None, None, None)
+            .await
+            .unwrap_err()
+            .unwrap(),
+        ErrorCode::InvalidInclusionProof.into_transaction_error(0)
+    );
+    assert_eq!(
+        simulator
+            .claim(&claimant, &amended, &amended_merkle_tree, None, None, None)
+            .await
+            .unwrap_err()
+            .unwrap(),
+        ErrorCode::AlreadyClaimed.into_transaction_error(0)
+    );
+    // Leaves that weren't amended can still be claimed
+    simulator
+        .claim(&claimant, &other, &amended_merkle_tree, None, None, None)
+        .await
+        .unwrap();
+    simulator
+        .verify_token_account_data(claimant_fund, 350, COption::None, 0)
+        .await
+        .unwrap();
+}
+
+#[tokio::test]
+pub async fn test_claim_with_legacy_receipt() {
+    let dispenser_guard = Keypair::new();
+    let claimant = Keypair::new();
+    let claimed = TestClaimCertificate {
+        amount: 100,
+        ..TestClaimCertificate::random_solana(&claimant.pubkey())
+    };
+    let initial = TestClaimCertificate {
+        amount: 100,
+        ..TestClaimCertificate::random_evm(&claimant.pubkey())
+    };
+    let amended = TestClaimCertificate {
+        amount: 150,
+        ..initial.clone()
+    };
+    let unclaimed = TestClaimCertificate {
+        amount: 100,
+        ..TestClaimCertificate::random_evm(&claimant.pubkey())
+    };
+
+    // The previous version of the program created an empty receipt keyed by the leaf for each claim
+    let legacy_receipts = [&claimed, &initial]
+        .iter()
+        .map(|item| {
+            (
+                get_legacy_receipt_pda(&ClaimInfo::from((*item).clone()).try_to_vec().unwrap()).0,
+                Account {
+                    lamports:   Rent::default().minimum_balance(0),
+                    data:       vec![],
+                    owner:      crate::id(),
+                    executable: false,
+                    rent_epoch: 0,
+                },
+            )
+        })
+        .collect();
+    let mut simulator = DispenserSimulator::new_with_accounts(legacy_receipts).await;
+
+    let (merkle_tree, _) = merkleize(
+        [&claimed, &amended, &unclaimed]
+            .iter()
+            .map(|item| (*item).clone().into())
+            .collect::<Vec<ClaimInfo>>(),
+    );
+    let address_lookup_table = simulator.init_lookup_table().await.unwrap();
+    simulator
+        .initialize(
+            merkle_tree.root.clone(),
+            dispenser_guard.pubkey(),
+            address_lookup_table,
+            None,
+            None,
+            None,
+        )
+        .await
+        .unwrap();
+    simulator.setup_treasury(1000).await.unwrap();
+    let claimant_fund =
+        get_associated_token_address(&claimant.pubkey(), &simulator.mint_keypair.pubkey());
+
+    // The leaf was claimed before receipts were keyed by identity
+    assert_eq!(
+        simulator
+            .claim(&claimant, &claimed, &merkle_tree, None, None, None)
+            .await
+            .unwrap_err()
+            .unwrap(),
+        ErrorCode::AlreadyClaimed.into_transaction_error(0)
+    );
+
+    // Receipts can only be migrated if the legacy receipt exists
+    assert_eq!(
+        simulator
+            .migrate_receipt(ClaimInfo::from(unclaimed.clone()))
+            .await
+            .unwrap_err()
+            .unwrap(),
+        ErrorCode::LegacyReceiptNotFound.into_transaction_error(0)
+    );
+    // The legacy receipt of the initial leaf doesn't match the amended leaf, so it is migrated first
+    simulator
+        .migrate_receipt(ClaimInfo::from(initial.clone()))
+        .await
+        .unwrap();
+    assert_eq!(get_amount_claimed(&mut simulator, &initial).await, 100);
+    simulator
+        .claim(&claimant, &amended, &merkle_tree, None, None, None)
+        .await
+        .unwrap();
+    simulator
+        .verify_token_account_data(claimant_fund, 50, COption::None, 0)
+        .await
+        .unwrap();
+    assert_eq!(get_amount_claimed(&mut simulator, &amended).await, 150);
+
+    // Migrating again doesn't lower the amount claimed
+    simulator
+        .migrate_receipt(ClaimInfo::from(initial.clone()))
+        .await
+        .unwrap();
+    assert_eq!(get_amount_claimed(&mut simulator, &amended).await, 150);
+
+    simulator
+        .claim(&claimant, &unclaimed, &merkle_tree, None, None, None)
+        .await
+        .unwrap();
+    simulator
+        .verify_token_account_data(claimant_fund, 150, COption::None, 0)
+        .await
+        .unwrap();
+}
@@ -0,0 +1,145 @@
+use {
+    anchor_lang::{
+        error::ErrorCode as AnchorErrorCode,
+        prelude::*,
+        solana_program::program::{
+            invoke,
+            invoke_signed,
+        },
+    },
+    anchor_spl::token::spl_token,
+    spl_associated_token_account::{
+        get_associated_token_address_with_program_id,
+        instruction::create_associated_token_account_idempotent,
+    },
+    spl_token_2022::{
+        extension::StateWithExtensions,
+        state::{
+            Account as TokenAccount,
+            Mint,
+        },
+    },
+};
+
+/**
+ * The mint, the treasury and the claimant fund can belong to either spl-token or spl-token-2022.
+ * Token-2022 implements the spl-token instructions and its accounts start with the spl-token layout,
+ * so the instructions are built and the accounts are parsed with spl-token-2022 for both programs.
+ */
+pub fn is_token_program(key: &Pubkey) -> bool {
+    *key == spl_token::ID || *key == spl_token_2022::ID
+}
+
+/**
+ * Same checks as deserializing an `Account` of anchor-spl, except that both token programs are accepted.
+ */
+fn check_token_program_owner(account_info: &AccountInfo) -> Result<()> {
+    if account_info.owner == &System::id() && account_info.lamports() == 0 {
+        return Err(AnchorErrorCode::AccountNotInitialized.into());
+    }
+    if !is_token_program(account_info.owner) {
+        return Err(AnchorErrorCode::AccountOwnedByWrongProgram.into());
+    }
+    Ok(())
+}
+
+pub fn unpack_mint(account_info: &AccountInfo) -> Result<Mint> {
+    check_token_program_owner(account_info)?;
+    Ok(
+        StateWithExtensions::<Mint>::unpack(&account_info.try_borrow_data()?)
+            .map_err(|_| AnchorErrorCode::AccountDidNotDeserialize)?
+            .base,
+    )
+}
+
+pub fn unpack_token_account(account_info: &AccountInfo) -> Result<TokenAccount> {
+    check_token_program_owner(account_info)?;
+    Ok(
+        StateWithExtensions::<TokenAccount>::unpack(&account_info.try_borrow_data()?)
+            .map_err(|_| AnchorErrorCode::AccountDidNotDeserialize)?
+            .base,
+    )
+}
+
+#[allow(clippy::too_many_arguments)]
+pub fn transfer_checked<'info>(
+    token_program: &AccountInfo<'info>,
+    from: &AccountInfo<'info>,
+    mint: &AccountInfo<'info>,
+    to: &AccountInfo<'info>,
+    authority: &AccountInfo<'info>,
+    signer_seeds: &[&[&[u8]]],
+    amount: u64,
+    decimals: u8,
+) -> Result<()> {
+    let instruction = spl_token_2022::instruction::transfer_checked(
+        token_program.key,
+        from.key,
+        mint.key,
+        to.key,
+        authority.key,
+        &[],
+        amount,
+        decimals,
+    )?;
+    invoke_signed(
+        &instruction,
+        &[
+            from.clone(),
+            mint.clone(),
+            to.clone(),
+            authority.clone(),
+            token_program.clone(),
+        ],
+        signer_seeds,
+    )?;
+    Ok(())
+}
+
+/**
+ * Creates the associated token account of `authority` under the token program of `mint` if it doesn't exist yet,
+ * otherwise checks it the way anchor checks an `init_if_needed` associated token account.
+ */
+pub fn checked_create_associated_token_account<'info>(
+    associated_token: &AccountInfo<'info>,
+    payer: &AccountInfo<'info>,
+    authority: &AccountInfo<'info>,
+    mint: &AccountInfo<'info>,
+    system_program: &AccountInfo<'info>,
+    token_program: &AccountInfo<'info>,
+    associated_token_program: &AccountInfo<'info>,
+) -> Result<()> {
+    if associated_token.owner == &System::id() {
+        invoke(
+            &create_associated_token_account_idempotent(
+                payer.key,
+                authority.key,
+                mint.key,
+                token_program.key,
+            ),
+            &[
+                payer.clone(),
+                associated_token.clone(),
+                authority.clone(),
+                mint.clone(),
+                system_program.clone(),
+                token_program.clone(),
+                associated_token_program.clone(),
+            ],
+        )?;
+    }
+
+    let token_account = unpack_token_account(associated_token)?;
+    if token_account.mint != mint.key() {
+        return Err(AnchorErrorCode::ConstraintTokenMint.into());
+    }
+    if token_account.owner != authority.key() {
+        return Err(AnchorErrorCode::ConstraintTokenOwner.into());
+    }
+    if associated_token.key()
+        != get_associated_token_address_with_program_id(authority.key, mint.key, token_program.key)
+    {
+        return Err(AnchorErrorCode::AccountNotAssociatedTokenAccount.into());
+    }
+    Ok(())
+}
@@ -11,9 +11,17 @@ jq '(..|objects| select(.type? and .type.defined? and .type.defined == "Ed25519P
 jq '(..|objects| select(.type? and .type.defined? and .type.defined == "Ed25519Signature")).type |= {"array": ["u8", 64]}' |
 jq '(..|objects| select(.type? and .type.defined? and .type.defined == "SuiAddress")).type |= {"array": ["u8", 32]}' |
 jq '(..|objects| select(.type? and .type.defined? and .type.defined == "AptosAddress")).type |= {"array": ["u8", 32]}' |
+jq '(..|objects| select(.type? and .type.defined? and .type.defined == "Secp256r1Pubkey")).type |= {"array": ["u8", 33]}' |
+jq '(..|objects| select(.type? and .type.defined? and .type.defined == "Secp256r1Signature")).type |= {"array": ["u8", 64]}' |
+jq '(..|objects| select(.type? and .type.defined? and .type.defined == "StarknetAddress")).type |= {"array": ["u8", 32]}' |
+jq '(..|objects| select(.type? and .type.defined? and .type.defined == "CardanoKeyHash")).type |= {"array": ["u8", 28]}' |
 # ADD EXTERNAL STRUCTS
-jq '(..|objects| select(.type? and .type.defined? and .type.defined == "MerklePath<SolanaHasher>")).type |= {"vec":{"array":["u8",20]}}' |
-jq '(..|objects| select(.type? and .type.defined? and .type.defined == "MerkleRoot<SolanaHasher>")).type |= {"array": ["u8", 20]}' |
+jq '(..|objects| select(.defined? == "MerklePath<SolanaHasher>")) |= {"vec":{"array":["u8",20]}}' |
+jq '(..|objects| select(.defined? == "MerkleRoot<SolanaHasher>")) |= {"array": ["u8", 20]}' |
+jq '(..|objects| select(.defined? == "MerklePath<SolanaHasher32>")) |= {"vec":{"array":["u8",32]}}' |
+jq '(..|objects| select(.defined? == "MerkleRoot<SolanaHasher32>")) |= {"array": ["u8", 32]}' |
+# NAME TUPLE VARIANT FIELDS, anchor 0.27 only encodes named fields of enum variants in typescript
+jq '(.types[] | select(.type.kind == "enum") | .type.variants[] | select(.fields? and (.fields | any(type == "string" or (has("name") | not))))).fields |= (to_entries | map({"name": (.key | tostring), "type": .value}))' |
 # DELETE TEST STRUCTS
 jq '.types |= map(select(.name != "TestIdentityCertificate"))' > ../frontend/claim_sdk/idl/token_dispenser.json
 
@@ -41,3 +49,6 @@ generate_declaration_file() {
 }
 
 generate_declaration_file ../frontend/claim_sdk/idl/token_dispenser.json ../frontend/claim_sdk/idl
+
+# The authorization payload constants are shared with the frontend
+cp programs/token-dispenser/authorization_payload.json ../frontend/claim_sdk/idl/authorization_payload.json